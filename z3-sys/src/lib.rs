@@ -275,6 +275,17 @@ pub struct _Z3_tactic {
 /// problem domains.
 pub type Z3_tactic = NonNull<_Z3_tactic>;
 
+#[doc(hidden)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _Z3_simplifier {
+    _unused: [u8; 0],
+}
+/// Incremental preprocessing step applied to the assertions of a solver.
+///
+/// Requires Z3 4.12.0 or later.
+pub type Z3_simplifier = NonNull<_Z3_simplifier>;
+
 #[doc(hidden)]
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -305,6 +316,16 @@ pub struct _Z3_solver {
 /// tactic or logic.
 pub type Z3_solver = NonNull<_Z3_solver>;
 
+#[doc(hidden)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _Z3_solver_callback {
+    _unused: [u8; 0],
+}
+/// Callback context passed to user propagator callbacks, used to
+/// propagate consequences.
+pub type Z3_solver_callback = NonNull<_Z3_solver_callback>;
+
 #[doc(hidden)]
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -627,6 +648,11 @@ pub enum DeclKind {
     /// produces an index, such that the arrays
     /// are different if they are different on the index.
     ARRAY_EXT = generated::Z3_decl_kind::Z3_OP_ARRAY_EXT as u32,
+    /// Predicate stating that a set has the given finite size. The relation
+    /// is binary.
+    SET_HAS_SIZE = generated::Z3_decl_kind::Z3_OP_SET_HAS_SIZE as u32,
+    /// Cardinality of a finite set. The function is unary.
+    SET_CARD = generated::Z3_decl_kind::Z3_OP_SET_CARD as u32,
     /// Bit-vector numeral.
     BNUM = generated::Z3_decl_kind::Z3_OP_BNUM as u32,
     /// One bit bit-vector.
@@ -1085,6 +1111,14 @@ pub enum DeclKind {
     /// unfolding the Boolean connectives in the axioms a small
     /// bounded number of steps `(=3)`.
     PR_DEF_AXIOM = generated::Z3_decl_kind::Z3_OP_PR_DEF_AXIOM as u32,
+    /// Clausal proof adding axiom.
+    PR_ASSUMPTION_ADD = generated::Z3_decl_kind::Z3_OP_PR_ASSUMPTION_ADD as u32,
+    /// Clausal proof lemma addition.
+    PR_LEMMA_ADD = generated::Z3_decl_kind::Z3_OP_PR_LEMMA_ADD as u32,
+    /// Clausal proof lemma deletion.
+    PR_REDUNDANT_DEL = generated::Z3_decl_kind::Z3_OP_PR_REDUNDANT_DEL as u32,
+    /// Clausal proof trail of additions and deletions.
+    PR_CLAUSE_TRAIL = generated::Z3_decl_kind::Z3_OP_PR_CLAUSE_TRAIL as u32,
     /// Introduces a name for a formula/term.
     ///
     /// Suppose `e` is an expression with free variables `x`, and
@@ -1345,12 +1379,16 @@ pub enum DeclKind {
     SEQ_EXTRACT = generated::Z3_decl_kind::Z3_OP_SEQ_EXTRACT as u32,
     SEQ_REPLACE = generated::Z3_decl_kind::Z3_OP_SEQ_REPLACE as u32,
     SEQ_AT = generated::Z3_decl_kind::Z3_OP_SEQ_AT as u32,
+    SEQ_NTH = generated::Z3_decl_kind::Z3_OP_SEQ_NTH as u32,
     SEQ_LENGTH = generated::Z3_decl_kind::Z3_OP_SEQ_LENGTH as u32,
     SEQ_INDEX = generated::Z3_decl_kind::Z3_OP_SEQ_INDEX as u32,
+    SEQ_LAST_INDEX = generated::Z3_decl_kind::Z3_OP_SEQ_LAST_INDEX as u32,
     SEQ_TO_RE = generated::Z3_decl_kind::Z3_OP_SEQ_TO_RE as u32,
     SEQ_IN_RE = generated::Z3_decl_kind::Z3_OP_SEQ_IN_RE as u32,
     STR_TO_INT = generated::Z3_decl_kind::Z3_OP_STR_TO_INT as u32,
     INT_TO_STR = generated::Z3_decl_kind::Z3_OP_INT_TO_STR as u32,
+    STRING_LT = generated::Z3_decl_kind::Z3_OP_STRING_LT as u32,
+    STRING_LE = generated::Z3_decl_kind::Z3_OP_STRING_LE as u32,
     RE_PLUS = generated::Z3_decl_kind::Z3_OP_RE_PLUS as u32,
     RE_STAR = generated::Z3_decl_kind::Z3_OP_RE_STAR as u32,
     RE_OPTION = generated::Z3_decl_kind::Z3_OP_RE_OPTION as u32,
@@ -1402,6 +1440,18 @@ pub enum DeclKind {
     ///
     /// Example: `2*x + 1*y + 2*z + 1*u = 4`
     PB_EQ = generated::Z3_decl_kind::Z3_OP_PB_EQ as u32,
+    /// A relation that is a total linear order.
+    SPECIAL_RELATION_LO = generated::Z3_decl_kind::Z3_OP_SPECIAL_RELATION_LO as u32,
+    /// A relation that is a partial order.
+    SPECIAL_RELATION_PO = generated::Z3_decl_kind::Z3_OP_SPECIAL_RELATION_PO as u32,
+    /// A relation that is a piecewise linear order.
+    SPECIAL_RELATION_PLO = generated::Z3_decl_kind::Z3_OP_SPECIAL_RELATION_PLO as u32,
+    /// A relation that is a tree order.
+    SPECIAL_RELATION_TO = generated::Z3_decl_kind::Z3_OP_SPECIAL_RELATION_TO as u32,
+    /// Transitive closure of a relation.
+    SPECIAL_RELATION_TC = generated::Z3_decl_kind::Z3_OP_SPECIAL_RELATION_TC as u32,
+    /// Transitive reflexive closure of a relation.
+    SPECIAL_RELATION_TRC = generated::Z3_decl_kind::Z3_OP_SPECIAL_RELATION_TRC as u32,
     /// Floating-point rounding mode RNE
     FPA_RM_NEAREST_TIES_TO_EVEN = generated::Z3_decl_kind::Z3_OP_FPA_RM_NEAREST_TIES_TO_EVEN as u32,
     /// Floating-point rounding mode RNA
@@ -4605,6 +4655,21 @@ unsafe extern "C" {
         to: *const Z3_ast,
     ) -> Option<Z3_ast>;
 
+    /// Substitute every application of `from[i]` in `a` with `to[i]`, for
+    /// `i` smaller than `num_funs`.
+    ///
+    /// The free variable with de-Bruijn index `j` in `to[i]` refers to the
+    /// `j`-th argument of the application of `from[i]`.
+    ///
+    /// Requires Z3 4.12.0 or later.
+    pub fn Z3_substitute_funs(
+        c: Z3_context,
+        a: Z3_ast,
+        num_funs: ::core::ffi::c_uint,
+        from: *const Z3_func_decl,
+        to: *const Z3_ast,
+    ) -> Option<Z3_ast>;
+
     /// Translate/Copy the AST `a` from context `source` to context `target`.
     ///
     /// AST `a` must have been created using context `source`.
@@ -5288,7 +5353,9 @@ unsafe extern "C" {
     /// converted to Booleans either, so the if caller intends to
     /// preserve satisfiability, it should apply bit-blasting tactics.
     /// Quantifiers and theory atoms will not be encoded.
-    pub fn Z3_goal_to_dimacs_string(c: Z3_context, g: Z3_goal) -> Z3_string;
+    /// If `include_names` is true, comment lines map the variables to the
+    /// names of the Boolean constants.
+    pub fn Z3_goal_to_dimacs_string(c: Z3_context, g: Z3_goal, include_names: bool) -> Z3_string;
 
     /// Return a tactic associated with the given name.
     ///
@@ -5760,6 +5827,22 @@ unsafe extern "C" {
     /// Return the set of non units in the solver state.
     pub fn Z3_solver_get_non_units(c: Z3_context, s: Z3_solver) -> Option<Z3_ast_vector>;
 
+    /// Return the trail modulo model conversion, in order of decision level.
+    /// The decision level can be retrieved using [`Z3_solver_get_levels`]
+    /// based on the trail.
+    pub fn Z3_solver_get_trail(c: Z3_context, s: Z3_solver) -> Option<Z3_ast_vector>;
+
+    /// Retrieve the decision depth of Boolean literals (variables or their
+    /// negations). Assumes a check-sat call and no other calls (to extract
+    /// models) have been invoked.
+    pub fn Z3_solver_get_levels(
+        c: Z3_context,
+        s: Z3_solver,
+        literals: Z3_ast_vector,
+        sz: ::core::ffi::c_uint,
+        levels: *mut ::core::ffi::c_uint,
+    );
+
     /// Check whether the assertions in a given solver are consistent or not.
     ///
     /// The function [`Z3_solver_get_model`]
@@ -5798,6 +5881,25 @@ unsafe extern "C" {
         assumptions: *const Z3_ast,
     ) -> Z3_lbool;
 
+    /// Provide an initial value hint for the variable `v`, which the solver
+    /// tries first when it branches on `v`.
+    ///
+    /// Requires Z3 4.13.1 or later.
+    #[cfg(feature = "z3_4_13_1")]
+    pub fn Z3_solver_set_initial_value(c: Z3_context, s: Z3_solver, v: Z3_ast, val: Z3_ast);
+
+    /// Retrieve the representative of the congruence class of `a` in the
+    /// solver state after the last check.
+    ///
+    /// Requires Z3 4.12.0 or later.
+    pub fn Z3_solver_congruence_root(c: Z3_context, s: Z3_solver, a: Z3_ast) -> Option<Z3_ast>;
+
+    /// Retrieve the next term in the congruence class of `a`, which lists
+    /// the class as a cycle.
+    ///
+    /// Requires Z3 4.12.0 or later.
+    pub fn Z3_solver_congruence_next(c: Z3_context, s: Z3_solver, a: Z3_ast) -> Option<Z3_ast>;
+
     /// Retrieve congruence class representatives for terms.
     ///
     /// The function can be used for relying on Z3 to identify equal terms under the current
@@ -6694,6 +6796,351 @@ unsafe extern "C" {
     );
 }
 
+// The user propagator API of Z3 4.8.12 to 4.8.14, which identifies the
+// registered terms by unsigned ids. Z3 4.8.15 passes the terms themselves
+// and adds a callback argument to `push_eh` and `pop_eh`; that API is bound
+// below under the `z3_4_8_15` feature.
+#[cfg(not(feature = "z3_4_8_15"))]
+pub type Z3_push_eh = ::core::option::Option<unsafe extern "C" fn(ctx: *mut ::core::ffi::c_void)>;
+#[cfg(not(feature = "z3_4_8_15"))]
+pub type Z3_pop_eh = ::core::option::Option<
+    unsafe extern "C" fn(ctx: *mut ::core::ffi::c_void, num_scopes: ::core::ffi::c_uint),
+>;
+#[cfg(not(feature = "z3_4_8_15"))]
+pub type Z3_fresh_eh = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::core::ffi::c_void,
+        new_context: Z3_context,
+    ) -> *mut ::core::ffi::c_void,
+>;
+#[cfg(not(feature = "z3_4_8_15"))]
+pub type Z3_fixed_eh = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::core::ffi::c_void,
+        cb: Z3_solver_callback,
+        id: ::core::ffi::c_uint,
+        value: Z3_ast,
+    ),
+>;
+#[cfg(not(feature = "z3_4_8_15"))]
+pub type Z3_eq_eh = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::core::ffi::c_void,
+        cb: Z3_solver_callback,
+        x: ::core::ffi::c_uint,
+        y: ::core::ffi::c_uint,
+    ),
+>;
+#[cfg(not(feature = "z3_4_8_15"))]
+pub type Z3_final_eh = ::core::option::Option<
+    unsafe extern "C" fn(ctx: *mut ::core::ffi::c_void, cb: Z3_solver_callback),
+>;
+
+#[cfg(not(feature = "z3_4_8_15"))]
+unsafe extern "C" {
+    /// Register a user-propagator with the solver.
+    pub fn Z3_solver_propagate_init(
+        c: Z3_context,
+        s: Z3_solver,
+        user_context: *mut ::core::ffi::c_void,
+        push_eh: Z3_push_eh,
+        pop_eh: Z3_pop_eh,
+        fresh_eh: Z3_fresh_eh,
+    );
+
+    /// Register a callback for when an expression is bound to a fixed value.
+    /// The supported expression types are
+    /// - Booleans
+    /// - Bit-vectors
+    pub fn Z3_solver_propagate_fixed(c: Z3_context, s: Z3_solver, fixed_eh: Z3_fixed_eh);
+
+    /// Register a callback on final check.
+    /// This provides freedom to the propagator to delay actions or implement a branch-and bound solver.
+    ///
+    /// The `final_eh` callback takes as argument the original `user_context` that was used
+    /// when calling [`Z3_solver_propagate_init`], and it takes a callback context for propagations.
+    /// If may use the callback context to invoke the [`Z3_solver_propagate_consequence`] function.
+    /// If the callback context gets used, the solver continues.
+    pub fn Z3_solver_propagate_final(c: Z3_context, s: Z3_solver, final_eh: Z3_final_eh);
+
+    /// Register a callback on expression equalities.
+    pub fn Z3_solver_propagate_eq(c: Z3_context, s: Z3_solver, eq_eh: Z3_eq_eh);
+
+    /// Register a callback on expression dis-equalities.
+    pub fn Z3_solver_propagate_diseq(c: Z3_context, s: Z3_solver, eq_eh: Z3_eq_eh);
+
+    /// Register an expression to propagate on with the solver.
+    /// Only expressions of type Bool and type Bit-Vector can be registered for propagation.
+    ///
+    /// Returns the id identifying the expression in callbacks.
+    pub fn Z3_solver_propagate_register(
+        c: Z3_context,
+        s: Z3_solver,
+        e: Z3_ast,
+    ) -> ::core::ffi::c_uint;
+
+    /// Propagate a consequence based on fixed values.
+    /// This is a callback a client may invoke during the `fixed_eh` callback.
+    /// The callback adds a propagation consequence based on the fixed values of the
+    /// `fixed_ids` and the equalities between `eq_lhs` and `eq_rhs`.
+    pub fn Z3_solver_propagate_consequence(
+        c: Z3_context,
+        cb: Z3_solver_callback,
+        num_fixed: ::core::ffi::c_uint,
+        fixed_ids: *const ::core::ffi::c_uint,
+        num_eqs: ::core::ffi::c_uint,
+        eq_lhs: *const ::core::ffi::c_uint,
+        eq_rhs: *const ::core::ffi::c_uint,
+        conseq: Z3_ast,
+    );
+}
+
+// The user propagator API of Z3 4.8.15 and later, which identifies the
+// registered terms by the terms themselves.
+#[cfg(feature = "z3_4_8_15")]
+pub type Z3_push_eh = ::core::option::Option<
+    unsafe extern "C" fn(ctx: *mut ::core::ffi::c_void, cb: Z3_solver_callback),
+>;
+#[cfg(feature = "z3_4_8_15")]
+pub type Z3_pop_eh = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::core::ffi::c_void,
+        cb: Z3_solver_callback,
+        num_scopes: ::core::ffi::c_uint,
+    ),
+>;
+#[cfg(feature = "z3_4_8_15")]
+pub type Z3_fresh_eh = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::core::ffi::c_void,
+        new_context: Z3_context,
+    ) -> *mut ::core::ffi::c_void,
+>;
+#[cfg(feature = "z3_4_8_15")]
+pub type Z3_fixed_eh = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::core::ffi::c_void,
+        cb: Z3_solver_callback,
+        t: Z3_ast,
+        value: Z3_ast,
+    ),
+>;
+#[cfg(feature = "z3_4_8_15")]
+pub type Z3_eq_eh = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::core::ffi::c_void,
+        cb: Z3_solver_callback,
+        s: Z3_ast,
+        t: Z3_ast,
+    ),
+>;
+#[cfg(feature = "z3_4_8_15")]
+pub type Z3_final_eh = ::core::option::Option<
+    unsafe extern "C" fn(ctx: *mut ::core::ffi::c_void, cb: Z3_solver_callback),
+>;
+#[cfg(feature = "z3_4_8_15")]
+pub type Z3_created_eh = ::core::option::Option<
+    unsafe extern "C" fn(ctx: *mut ::core::ffi::c_void, cb: Z3_solver_callback, t: Z3_ast),
+>;
+#[cfg(feature = "z3_4_12_0")]
+pub type Z3_decide_eh = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::core::ffi::c_void,
+        cb: Z3_solver_callback,
+        t: Z3_ast,
+        idx: ::core::ffi::c_uint,
+        phase: bool,
+    ),
+>;
+
+#[cfg(feature = "z3_4_8_15")]
+unsafe extern "C" {
+    /// Register a user-propagator with the solver.
+    pub fn Z3_solver_propagate_init(
+        c: Z3_context,
+        s: Z3_solver,
+        user_context: *mut ::core::ffi::c_void,
+        push_eh: Z3_push_eh,
+        pop_eh: Z3_pop_eh,
+        fresh_eh: Z3_fresh_eh,
+    );
+
+    /// Register a callback for when an expression is bound to a fixed value.
+    /// The supported expression types are
+    /// - Booleans
+    /// - Bit-vectors
+    pub fn Z3_solver_propagate_fixed(c: Z3_context, s: Z3_solver, fixed_eh: Z3_fixed_eh);
+
+    /// Register a callback on final check.
+    /// This provides freedom to the propagator to delay actions or implement a branch-and bound solver.
+    ///
+    /// The `final_eh` callback takes as argument the original `user_context` that was used
+    /// when calling [`Z3_solver_propagate_init`], and it takes a callback context for propagations.
+    /// If may use the callback context to invoke the [`Z3_solver_propagate_consequence`] function.
+    /// If the callback context gets used, the solver continues.
+    pub fn Z3_solver_propagate_final(c: Z3_context, s: Z3_solver, final_eh: Z3_final_eh);
+
+    /// Register a callback on expression equalities.
+    pub fn Z3_solver_propagate_eq(c: Z3_context, s: Z3_solver, eq_eh: Z3_eq_eh);
+
+    /// Register a callback on expression dis-equalities.
+    pub fn Z3_solver_propagate_diseq(c: Z3_context, s: Z3_solver, eq_eh: Z3_eq_eh);
+
+    /// Register a callback when a new expression with a registered function is used by the solver.
+    /// The registered function appears at the top level and is created using
+    /// [`Z3_solver_propagate_declare`].
+    pub fn Z3_solver_propagate_created(c: Z3_context, s: Z3_solver, created_eh: Z3_created_eh);
+
+    /// Create an uninterpreted function declaration for the user propagator.
+    /// When expressions using the function are created by the solver, the
+    /// `created_eh` callback is invoked with the expression, which is then
+    /// registered for propagation.
+    pub fn Z3_solver_propagate_declare(
+        c: Z3_context,
+        name: Z3_symbol,
+        n: ::core::ffi::c_uint,
+        domain: *const Z3_sort,
+        range: Z3_sort,
+    ) -> Option<Z3_func_decl>;
+
+    /// Register an expression to propagate on with the solver.
+    /// Only expressions of type Bool and type Bit-Vector can be registered for propagation.
+    pub fn Z3_solver_propagate_register(c: Z3_context, s: Z3_solver, e: Z3_ast);
+
+    /// Register an expression to propagate on with the solver from within a callback.
+    /// Only expressions of type Bool and type Bit-Vector can be registered for propagation.
+    pub fn Z3_solver_propagate_register_cb(c: Z3_context, cb: Z3_solver_callback, e: Z3_ast);
+
+    /// Propagate a consequence based on fixed values.
+    /// This is a callback a client may invoke during the `fixed_eh` callback.
+    /// The callback adds a propagation consequence based on the fixed values of the
+    /// registered expressions `fixed` and the equalities between `eq_lhs` and `eq_rhs`.
+    ///
+    /// Later versions of Z3 return whether the consequence was propagated;
+    /// the result is left unbound, which is compatible with the C calling
+    /// convention.
+    pub fn Z3_solver_propagate_consequence(
+        c: Z3_context,
+        cb: Z3_solver_callback,
+        num_fixed: ::core::ffi::c_uint,
+        fixed: *const Z3_ast,
+        num_eqs: ::core::ffi::c_uint,
+        eq_lhs: *const Z3_ast,
+        eq_rhs: *const Z3_ast,
+        conseq: Z3_ast,
+    );
+}
+
+#[cfg(feature = "z3_4_12_0")]
+unsafe extern "C" {
+    /// Register a callback when the solver decides to split on a registered expression.
+    /// The callback may change the split by invoking [`Z3_solver_next_split`].
+    ///
+    /// Requires Z3 4.12.0 or later.
+    pub fn Z3_solver_propagate_decide(c: Z3_context, s: Z3_solver, decide_eh: Z3_decide_eh);
+
+    /// Make the solver split next on bit `idx` of the registered expression
+    /// `t`, `0` for a Boolean, assigning it `phase`, or a phase of the
+    /// solver's choice for `Z3_L_UNDEF`. This is a callback a client may
+    /// invoke during the `decide_eh` callback.
+    ///
+    /// Returns `false` if the solver rejects the split.
+    ///
+    /// Requires Z3 4.12.0 or later.
+    pub fn Z3_solver_next_split(
+        c: Z3_context,
+        cb: Z3_solver_callback,
+        t: Z3_ast,
+        idx: ::core::ffi::c_uint,
+        phase: Z3_lbool,
+    ) -> bool;
+}
+
+unsafe extern "C" {
+    /// Return a simplifier associated with the given name.
+    /// The complete list of simplifiers may be obtained using the procedures
+    /// [`Z3_get_num_simplifiers`] and [`Z3_get_simplifier_name`].
+    ///
+    /// Requires Z3 4.12.0 or later.
+    pub fn Z3_mk_simplifier(c: Z3_context, name: Z3_string) -> Option<Z3_simplifier>;
+
+    /// Increment the reference counter of the given simplifier.
+    pub fn Z3_simplifier_inc_ref(c: Z3_context, t: Z3_simplifier);
+
+    /// Decrement the reference counter of the given simplifier.
+    pub fn Z3_simplifier_dec_ref(c: Z3_context, g: Z3_simplifier);
+
+    /// Attach `simplifier` to `solver`, returning a solver that
+    /// preprocesses its assertions incrementally with it.
+    pub fn Z3_solver_add_simplifier(
+        c: Z3_context,
+        solver: Z3_solver,
+        simplifier: Z3_simplifier,
+    ) -> Option<Z3_solver>;
+
+    /// Return a simplifier that applies `t1` and then `t2`.
+    pub fn Z3_simplifier_and_then(
+        c: Z3_context,
+        t1: Z3_simplifier,
+        t2: Z3_simplifier,
+    ) -> Option<Z3_simplifier>;
+
+    /// Return a simplifier that applies `t` using the given set of parameters.
+    pub fn Z3_simplifier_using_params(
+        c: Z3_context,
+        t: Z3_simplifier,
+        p: Z3_params,
+    ) -> Option<Z3_simplifier>;
+
+    /// Return the number of builtin simplifiers available in Z3.
+    pub fn Z3_get_num_simplifiers(c: Z3_context) -> ::core::ffi::c_uint;
+
+    /// Return the name of the `i`-th simplifier.
+    ///
+    /// # Preconditions:
+    ///
+    /// - `i < Z3_get_num_simplifiers(c)`
+    pub fn Z3_get_simplifier_name(c: Z3_context, i: ::core::ffi::c_uint) -> Z3_string;
+
+    /// Return a string containing a description of parameters accepted by
+    /// the given simplifier.
+    pub fn Z3_simplifier_get_help(c: Z3_context, t: Z3_simplifier) -> Z3_string;
+
+    /// Return the parameter description set for the given simplifier object.
+    pub fn Z3_simplifier_get_param_descrs(
+        c: Z3_context,
+        s: Z3_simplifier,
+    ) -> Option<Z3_param_descrs>;
+
+    /// Return a string containing a description of the simplifier with the
+    /// given name.
+    pub fn Z3_simplifier_get_descr(c: Z3_context, name: Z3_string) -> Z3_string;
+}
+
+pub type Z3_on_clause_eh = ::core::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::core::ffi::c_void,
+        proof_hint: Option<Z3_ast>,
+        literals: Z3_ast_vector,
+    ),
+>;
+
+unsafe extern "C" {
+    /// Register a callback invoked with the clauses inferred by the solver.
+    ///
+    /// The callback receives the proof hint of the clause and its literals.
+    ///
+    /// Requires Z3 4.12.0 or later.
+    pub fn Z3_solver_register_on_clause(
+        c: Z3_context,
+        s: Z3_solver,
+        user_context: *mut ::core::ffi::c_void,
+        on_clause_eh: Z3_on_clause_eh,
+    );
+}
+
+pub type Z3_model_eh = ::core::option::Option<unsafe extern "C" fn(ctx: *mut ::core::ffi::c_void)>;
+
 pub type Z3_fixedpoint_new_lemma_eh = ::core::option::Option<
     unsafe extern "C" fn(
         state: *mut ::core::ffi::c_void,
@@ -7005,6 +7452,18 @@ unsafe extern "C" {
     /// function is always returned as a minimization objective.
     pub fn Z3_optimize_get_objectives(c: Z3_context, o: Z3_optimize) -> Option<Z3_ast_vector>;
 
+    /// Register a model event handler for new models.
+    ///
+    /// Whenever the optimizer finds an improved model, the contents of `m`
+    /// are replaced by it and `model_eh` is called with `ctx`.
+    pub fn Z3_optimize_register_model_eh(
+        c: Z3_context,
+        o: Z3_optimize,
+        m: Z3_model,
+        ctx: *mut ::core::ffi::c_void,
+        model_eh: Z3_model_eh,
+    );
+
     /// Create the `RoundingMode` sort.
     ///
     /// - `c`: logical context
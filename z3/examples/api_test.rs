@@ -14,8 +14,8 @@ fn main() {
 
     // Test Algebraic operations
     println!("\n✓ Testing Algebraic operations...");
-    let a = Real::from_real(3, 2);
-    let b = Real::from_real(5, 3);
+    let a = Real::from_rational(3, 2);
+    let b = Real::from_rational(5, 3);
     println!("a = {}, b = {}", a, b);
     
     let sum = Algebraic::add(&a, &b);
@@ -23,8 +23,8 @@ fn main() {
 
     // Test enhanced Float operations
     println!("\n✓ Testing enhanced Float operations...");
-    let f1 = Float::from_f64(3.14159);
-    let f2 = Float::from_f64(2.71828);
+    let f1 = Float::from_f64(3.5);
+    let f2 = Float::from_f64(2.25);
     
     println!("f1 = {}", f1);
     println!("f1 is positive: {}", f1.is_positive());
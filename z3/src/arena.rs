@@ -0,0 +1,282 @@
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_uint;
+
+use z3_sys::*;
+
+use crate::ast::{Ast, Dynamic};
+use crate::{Context, FuncDecl, Sort, Symbol};
+
+/// An arena owning terms of the thread-local [`Context`], handed out as
+/// [`ArenaAst`] handles which borrow from it.
+///
+/// Owned terms such as [`Dynamic`] each hold a reference to their context
+/// and to their Z3 term, updated whenever they are cloned or dropped.
+/// Arena handles are plain copyable pointers instead: the arena references
+/// every term once and releases them all when it is dropped. This suits
+/// encoders building many short-lived intermediate terms, at the price of
+/// keeping them alive as long as the arena. Terms meant to outlive it are
+/// converted with [`ArenaAst::to_dynamic()`].
+///
+/// Operations panic if Z3 rejects their arguments, e.g. because their
+/// sorts do not match, or if they mix terms of different arenas.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{ContextArena, SatResult, Solver, Sort};
+/// let arena = ContextArena::new();
+/// let xs: Vec<_> = (0..100)
+///     .map(|i| arena.constant(format!("x{i}"), &Sort::int()))
+///     .collect();
+/// // x0 < x1 < ... < x99, with x0 >= 0.
+/// let mut constraints = vec![xs[0].ge(arena.int(0))];
+/// constraints.extend(xs.windows(2).map(|w| w[0].lt(w[1])));
+/// let formula = arena.and(&constraints);
+///
+/// let solver = Solver::new();
+/// solver.assert(formula.to_dynamic().as_bool().unwrap());
+/// solver.assert(xs[99].to_dynamic().as_int().unwrap().lt(99));
+/// assert_eq!(solver.check(), SatResult::Unsat);
+/// ```
+pub struct ContextArena {
+    ctx: Context,
+    asts: RefCell<Vec<Z3_ast>>,
+}
+
+/// A term owned by a [`ContextArena`].
+#[derive(Clone, Copy)]
+pub struct ArenaAst<'a> {
+    arena: &'a ContextArena,
+    z3_ast: Z3_ast,
+}
+
+impl ContextArena {
+    /// Create an empty arena for the thread-local context.
+    pub fn new() -> ContextArena {
+        ContextArena {
+            ctx: Context::thread_local(),
+            asts: RefCell::new(vec![]),
+        }
+    }
+
+    /// Returns the number of terms allocated in the arena, counting a term
+    /// built several times once per construction.
+    pub fn len(&self) -> usize {
+        self.asts.borrow().len()
+    }
+
+    /// Returns `true` if no term was allocated in the arena.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn alloc(&self, z3_ast: Option<Z3_ast>) -> ArenaAst<'_> {
+        let z3_ctx = self.ctx.z3_ctx.0;
+        let Some(z3_ast) = z3_ast else {
+            let msg =
+                unsafe { CStr::from_ptr(Z3_get_error_msg(z3_ctx, Z3_get_error_code(z3_ctx))) };
+            panic!("invalid term: {}", msg.to_string_lossy());
+        };
+        unsafe { Z3_inc_ref(z3_ctx, z3_ast) };
+        self.asts.borrow_mut().push(z3_ast);
+        ArenaAst {
+            arena: self,
+            z3_ast,
+        }
+    }
+
+    fn raw(&self, args: &[ArenaAst]) -> Vec<Z3_ast> {
+        args.iter()
+            .map(|a| {
+                assert!(std::ptr::eq(a.arena, self), "terms of different arenas");
+                a.z3_ast
+            })
+            .collect()
+    }
+
+    /// Copy `ast` into the arena.
+    pub fn import(&self, ast: &impl Ast) -> ArenaAst<'_> {
+        assert_eq!(ast.get_ctx(), &self.ctx, "term of another context");
+        self.alloc(Some(ast.get_z3_ast()))
+    }
+
+    /// Create a constant of the given sort.
+    pub fn constant<S: Into<Symbol>>(&self, name: S, sort: &Sort) -> ArenaAst<'_> {
+        let z3_ast = unsafe { crate::declarations::mk_const(&self.ctx, name.into(), sort.z3_sort) };
+        self.alloc(z3_ast)
+    }
+
+    /// Create an integer numeral.
+    pub fn int(&self, value: i64) -> ArenaAst<'_> {
+        let sort = Sort::int();
+        self.alloc(unsafe { Z3_mk_int64(self.ctx.z3_ctx.0, value, sort.z3_sort) })
+    }
+
+    /// Create a Boolean constant.
+    pub fn from_bool(&self, value: bool) -> ArenaAst<'_> {
+        let z3_ctx = self.ctx.z3_ctx.0;
+        self.alloc(unsafe {
+            if value {
+                Z3_mk_true(z3_ctx)
+            } else {
+                Z3_mk_false(z3_ctx)
+            }
+        })
+    }
+
+    /// Apply `decl` to `args`.
+    pub fn apply<'a>(&'a self, decl: &FuncDecl, args: &[ArenaAst<'a>]) -> ArenaAst<'a> {
+        let args = self.raw(args);
+        self.alloc(unsafe {
+            Z3_mk_app(
+                self.ctx.z3_ctx.0,
+                decl.z3_func_decl,
+                args.len() as c_uint,
+                args.as_ptr(),
+            )
+        })
+    }
+
+    fn varop<'a>(
+        &'a self,
+        op: unsafe extern "C" fn(Z3_context, c_uint, *const Z3_ast) -> Option<Z3_ast>,
+        args: &[ArenaAst<'a>],
+    ) -> ArenaAst<'a> {
+        let args = self.raw(args);
+        self.alloc(unsafe { op(self.ctx.z3_ctx.0, args.len() as c_uint, args.as_ptr()) })
+    }
+
+    /// Create the conjunction of `args`.
+    pub fn and<'a>(&'a self, args: &[ArenaAst<'a>]) -> ArenaAst<'a> {
+        self.varop(Z3_mk_and, args)
+    }
+
+    /// Create the disjunction of `args`.
+    pub fn or<'a>(&'a self, args: &[ArenaAst<'a>]) -> ArenaAst<'a> {
+        self.varop(Z3_mk_or, args)
+    }
+
+    /// Create the sum of `args`.
+    pub fn add<'a>(&'a self, args: &[ArenaAst<'a>]) -> ArenaAst<'a> {
+        self.varop(Z3_mk_add, args)
+    }
+
+    /// Create the product of `args`.
+    pub fn mul<'a>(&'a self, args: &[ArenaAst<'a>]) -> ArenaAst<'a> {
+        self.varop(Z3_mk_mul, args)
+    }
+}
+
+impl Default for ContextArena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ContextArena {
+    fn drop(&mut self) {
+        for z3_ast in self.asts.get_mut().drain(..) {
+            unsafe { Z3_dec_ref(self.ctx.z3_ctx.0, z3_ast) };
+        }
+    }
+}
+
+impl fmt::Debug for ContextArena {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("ContextArena")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+macro_rules! arena_binop {
+    ( $( $(#[$attr:meta])* $f:ident ( $z3fn:ident ); )* ) => {
+        $(
+            $(#[$attr])*
+            pub fn $f(self, other: ArenaAst<'a>) -> ArenaAst<'a> {
+                let args = self.arena.raw(&[self, other]);
+                self.arena
+                    .alloc(unsafe { $z3fn(self.arena.ctx.z3_ctx.0, args[0], args[1]) })
+            }
+        )*
+    };
+}
+
+impl<'a> ArenaAst<'a> {
+    arena_binop! {
+        /// Create `self == other`.
+        eq(Z3_mk_eq);
+        /// Create `self => other`.
+        implies(Z3_mk_implies);
+        /// Create `self < other`.
+        lt(Z3_mk_lt);
+        /// Create `self <= other`.
+        le(Z3_mk_le);
+        /// Create `self > other`.
+        gt(Z3_mk_gt);
+        /// Create `self >= other`.
+        ge(Z3_mk_ge);
+    }
+
+    /// Create `if self { then } else { otherwise }`.
+    pub fn ite(self, then: ArenaAst<'a>, otherwise: ArenaAst<'a>) -> ArenaAst<'a> {
+        let args = self.arena.raw(&[self, then, otherwise]);
+        self.arena
+            .alloc(unsafe { Z3_mk_ite(self.arena.ctx.z3_ctx.0, args[0], args[1], args[2]) })
+    }
+
+    /// Convert the term into an owned term, which may outlive the arena.
+    pub fn to_dynamic(self) -> Dynamic {
+        unsafe { Dynamic::wrap(&self.arena.ctx, self.z3_ast) }
+    }
+}
+
+impl fmt::Display for ArenaAst<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_ast_to_string(self.arena.ctx.z3_ctx.0, self.z3_ast) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{s}"),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
+impl fmt::Debug for ArenaAst<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        <Self as fmt::Display>::fmt(self, f)
+    }
+}
+
+impl<'a> std::ops::Not for ArenaAst<'a> {
+    type Output = ArenaAst<'a>;
+
+    fn not(self) -> ArenaAst<'a> {
+        self.arena
+            .alloc(unsafe { Z3_mk_not(self.arena.ctx.z3_ctx.0, self.z3_ast) })
+    }
+}
+
+macro_rules! arena_arith {
+    ( $( $trait:ident $f:ident ( $z3fn:ident ); )* ) => {
+        $(
+            impl<'a> std::ops::$trait for ArenaAst<'a> {
+                type Output = ArenaAst<'a>;
+
+                fn $f(self, other: ArenaAst<'a>) -> ArenaAst<'a> {
+                    self.arena.varop($z3fn, &[self, other])
+                }
+            }
+        )*
+    };
+}
+
+arena_arith! {
+    Add add(Z3_mk_add);
+    Sub sub(Z3_mk_sub);
+    Mul mul(Z3_mk_mul);
+}
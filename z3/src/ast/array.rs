@@ -1,5 +1,5 @@
 use crate::ast::{Ast, Dynamic};
-use crate::{Context, Sort, Symbol};
+use crate::{Context, FuncDecl, Sort, Symbol};
 use std::ffi::CString;
 use z3_sys::*;
 
@@ -20,7 +20,7 @@ impl Array {
         let sort = Sort::array(domain, range);
         unsafe {
             Self::wrap(ctx, {
-                Z3_mk_const(ctx.z3_ctx.0, name.into().as_z3_symbol(), sort.z3_sort).unwrap()
+                crate::declarations::mk_const(ctx, name.into(), sort.z3_sort).unwrap()
             })
         }
     }
@@ -55,20 +55,20 @@ impl Array {
     ///
     /// Note that the `index` _must be_ of the array's `domain` sort.
     /// The return type will be of the array's `range` sort.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not of the array's `domain` sort.
     //
     // We avoid the binop! macro because the argument has a non-Self type
+    #[track_caller]
     pub fn select<A>(&self, index: &A) -> Dynamic
     where
         A: Ast,
     {
-        // TODO: We could validate here that the index is of the correct type.
-        // This would require us either to keep around the original `domain` argument
-        // from when the Array was constructed, or to do an additional Z3 query
-        // to find the domain sort first.
-        // But if we did this check ourselves, we'd just panic, so it doesn't seem
-        // like a huge advantage over just letting Z3 panic itself when it discovers the
-        // problem.
-        // This way we also avoid the redundant check every time this method is called.
+        if let Some(domain) = self.get_sort().array_domain() {
+            crate::ast::assert_sort("select", &domain, index);
+        }
         unsafe {
             Dynamic::wrap(&self.ctx, {
                 Z3_mk_select(self.ctx.z3_ctx.0, self.z3_ast, index.get_z3_ast()).unwrap()
@@ -98,13 +98,23 @@ impl Array {
     ///
     /// Note that the `index` _must be_ of the array's `domain` sort,
     /// and the `value` _must be_ of the array's `range` sort.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` or `value` are not of the expected sorts.
     //
     // We avoid the trinop! macro because the arguments have non-Self types
+    #[track_caller]
     pub fn store<A1, A2>(&self, index: &A1, value: &A2) -> Self
     where
         A1: Ast,
         A2: Ast,
     {
+        let sort = self.get_sort();
+        if let (Some(domain), Some(range)) = (sort.array_domain(), sort.array_range()) {
+            crate::ast::assert_sort("store", &domain, index);
+            crate::ast::assert_sort("store", &range, value);
+        }
         unsafe {
             Self::wrap(&self.ctx, {
                 Z3_mk_store(
@@ -118,6 +128,32 @@ impl Array {
         }
     }
 
+    /// Returns true if the array is an `(_ as-array f)` term, i.e. it is
+    /// defined by the function `f`.
+    ///
+    /// Models frequently use such terms for the values of array constants.
+    pub fn is_as_array(&self) -> bool {
+        unsafe { Z3_is_as_array(self.ctx.z3_ctx.0, self.z3_ast) }
+    }
+
+    /// Return the function `f` if the array is an `(_ as-array f)` term.
+    ///
+    /// The interpretation of the returned function can then be obtained from
+    /// a model with [`Model::get_func_interp()`](crate::Model::get_func_interp).
+    ///
+    /// # See also:
+    ///
+    /// - [`FuncDecl::as_array_term()`]
+    pub fn to_func_decl(&self) -> Option<FuncDecl> {
+        if !self.is_as_array() {
+            return None;
+        }
+        unsafe {
+            let f = Z3_get_as_array_func_decl(self.ctx.z3_ctx.0, self.z3_ast)?;
+            Some(FuncDecl::wrap(&self.ctx, f))
+        }
+    }
+
     /// Returns true if the array is a const array (i.e. `a.is_const_array() => exists v, forall i. select(a, i) == v`)
     ///
     /// # Examples
@@ -0,0 +1,114 @@
+use crate::ast::{Array, Ast, Bool, Dynamic, Int, Real, String, exists_const, forall_const};
+
+/// Terms which [`forall()`], [`exists()`] and [`lambda()`] can bind, of a
+/// sort determined by their type.
+pub trait Bindable: Ast + Sized {
+    /// Create a fresh constant standing for the bound variable.
+    fn fresh_bound() -> Self;
+}
+
+impl Bindable for Bool {
+    fn fresh_bound() -> Self {
+        Bool::fresh_const("b")
+    }
+}
+
+impl Bindable for Int {
+    fn fresh_bound() -> Self {
+        Int::fresh_const("x")
+    }
+}
+
+impl Bindable for Real {
+    fn fresh_bound() -> Self {
+        Real::fresh_const("r")
+    }
+}
+
+impl Bindable for String {
+    fn fresh_bound() -> Self {
+        String::fresh_const("s")
+    }
+}
+
+/// Closures whose arguments are [`Bindable`] terms, such as
+/// `|x: Int, y: Int| x.lt(&y)`, which [`forall()`], [`exists()`] and
+/// [`lambda()`] turn into binders.
+///
+/// `Args` is the tuple of the argument types.
+pub trait Binder<Args, Body> {
+    /// Call the closure on fresh constants, and return them with the body.
+    fn bind(self) -> (Vec<Dynamic>, Body);
+}
+
+macro_rules! impl_binder {
+    ( $( $arg:ident ),+ ) => {
+        impl<F, Body, $( $arg ),+> Binder<( $( $arg, )+ ), Body> for F
+        where
+            F: FnOnce( $( $arg ),+ ) -> Body,
+            $( $arg: Bindable, )+
+        {
+            #[allow(non_snake_case)]
+            fn bind(self) -> (Vec<Dynamic>, Body) {
+                $( let $arg = $arg::fresh_bound(); )+
+                let bounds = vec![ $( Dynamic::from_ast(&$arg) ),+ ];
+                (bounds, self( $( $arg ),+ ))
+            }
+        }
+    };
+}
+
+impl_binder!(A);
+impl_binder!(A, B);
+impl_binder!(A, B, C);
+impl_binder!(A, B, C, D);
+impl_binder!(A, B, C, D, E);
+impl_binder!(A, B, C, D, E, G);
+
+fn as_bounds(bounds: &[Dynamic]) -> Vec<&dyn Ast> {
+    bounds.iter().map(|b| b as &dyn Ast).collect()
+}
+
+/// Create a universal quantifier over the arguments of `body`, which are
+/// fresh constants closed over once `body` returns.
+///
+/// This spares building the bound constants and listing them separately,
+/// as [`forall_const()`] needs. Bound variables of sorts which the type
+/// does not determine, such as bit-vectors of some width, are left to
+/// [`forall_const()`].
+///
+/// # Example
+/// ```
+/// # use z3::{SatResult, Solver};
+/// # use z3::ast::{self, Int};
+/// let solver = Solver::new();
+/// solver.assert(ast::forall(|x: Int, y: Int| x.lt(&y).implies((&x + 1).le(&y))));
+/// solver.assert(ast::exists(|x: Int| (&x * &x).eq(2)).not());
+/// assert_eq!(solver.check(), SatResult::Sat);
+/// ```
+pub fn forall<Args>(body: impl Binder<Args, Bool>) -> Bool {
+    let (bounds, body) = body.bind();
+    forall_const(&as_bounds(&bounds), &[], &body)
+}
+
+/// Create an existential quantifier over the arguments of `body`, as
+/// [`forall()`] does for universal ones.
+pub fn exists<Args>(body: impl Binder<Args, Bool>) -> Bool {
+    let (bounds, body) = body.bind();
+    exists_const(&as_bounds(&bounds), &[], &body)
+}
+
+/// Create a lambda term over the arguments of `body`, as [`forall()`] does
+/// for quantifiers.
+///
+/// # Example
+/// ```
+/// # use z3::ast::{self, Ast, Int};
+/// let double = ast::lambda(|x: Int| &x + &x);
+/// let four = double.select(&Int::from_i64(2)).simplify();
+/// assert_eq!(four.as_int().unwrap().as_i64(), Some(4));
+/// ```
+pub fn lambda<Args, Body: Ast>(body: impl Binder<Args, Body>) -> Array {
+    let (bounds, body) = body.bind();
+    super::lambda_const(&as_bounds(&bounds), &Dynamic::from_ast(&body))
+}
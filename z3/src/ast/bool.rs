@@ -1,5 +1,6 @@
-use crate::ast::{Ast, binop, unop, varop};
-use crate::{Context, Sort, Symbol};
+use crate::ast::{Ast, Dynamic, binop, unop, varop};
+use crate::{Context, LogicFeatures, Pattern, Sort, Symbol};
+use std::collections::HashMap;
 use std::ffi::CString;
 use z3_sys::*;
 
@@ -14,7 +15,7 @@ impl Bool {
         let sort = Sort::bool();
         unsafe {
             Self::wrap(ctx, {
-                Z3_mk_const(ctx.z3_ctx.0, name.into().as_z3_symbol(), sort.z3_sort).unwrap()
+                crate::declarations::mk_const(ctx, name.into(), sort.z3_sort).unwrap()
             })
         }
     }
@@ -54,11 +55,176 @@ impl Bool {
         }
     }
 
+    /// Returns `true` if the formula contains no quantifiers.
+    pub fn is_quantifier_free(&self) -> bool {
+        let mut quantifier_free = true;
+        crate::logic::walk(&self.ctx, self.z3_ast, |_, kind| {
+            quantifier_free = kind != AstKind::Quantifier;
+            quantifier_free
+        });
+        quantifier_free
+    }
+
+    /// Returns `true` if the formula is a universal quantifier.
+    pub fn is_forall(&self) -> bool {
+        self.kind() == AstKind::Quantifier
+            && unsafe { Z3_is_quantifier_forall(self.ctx.z3_ctx.0, self.z3_ast) }
+    }
+
+    /// Returns `true` if the formula is an existential quantifier.
+    pub fn is_exists(&self) -> bool {
+        self.kind() == AstKind::Quantifier
+            && unsafe { Z3_is_quantifier_exists(self.ctx.z3_ctx.0, self.z3_ast) }
+    }
+
+    /// Returns the weight of the quantifier, or `None` if the formula is not
+    /// a quantifier.
+    ///
+    /// # See also:
+    ///
+    /// - [`quantifier_const()`](crate::ast::quantifier_const)
+    pub fn quantifier_weight(&self) -> Option<u32> {
+        (self.kind() == AstKind::Quantifier)
+            .then(|| unsafe { Z3_get_quantifier_weight(self.ctx.z3_ctx.0, self.z3_ast) })
+    }
+
+    /// Returns the patterns triggering the instantiation of the quantifier,
+    /// or an empty vector if the formula is not a quantifier.
+    ///
+    /// In the terms of the patterns, the bound constants are replaced by
+    /// bound variables.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{FuncDecl, Pattern, Sort};
+    /// # use z3::ast::{self, Ast, Int};
+    /// let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    /// let x = Int::new_const("x");
+    /// let f_x = f.apply(&[&x]);
+    /// let forall = ast::quantifier_const(
+    ///     true,
+    ///     5,
+    ///     "f_pos",
+    ///     "sk_f_pos",
+    ///     &[&x],
+    ///     &[&Pattern::new(&[&f_x])],
+    ///     &[],
+    ///     &f_x.as_int().unwrap().gt(0),
+    /// );
+    /// assert!(forall.is_forall());
+    /// assert_eq!(forall.quantifier_weight(), Some(5));
+    /// let [pattern] = &forall.quantifier_patterns()[..] else { panic!() };
+    /// assert_eq!(pattern.terms()[0].decl(), f);
+    /// assert!(forall.to_string().contains(":qid f_pos"));
+    /// ```
+    pub fn quantifier_patterns(&self) -> Vec<Pattern> {
+        if self.kind() != AstKind::Quantifier {
+            return vec![];
+        }
+        let z3_ctx = self.ctx.z3_ctx.0;
+        let n = unsafe { Z3_get_quantifier_num_patterns(z3_ctx, self.z3_ast) };
+        (0..n)
+            .map(|i| unsafe {
+                Pattern::wrap(
+                    &self.ctx,
+                    Z3_get_quantifier_pattern_ast(z3_ctx, self.z3_ast, i).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the terms excluded from the patterns inferred for the
+    /// quantifier, or an empty vector if the formula is not a quantifier.
+    /// As for [`Bool::quantifier_patterns()`], they refer to bound variables.
+    pub fn quantifier_no_patterns(&self) -> Vec<Dynamic> {
+        if self.kind() != AstKind::Quantifier {
+            return vec![];
+        }
+        let z3_ctx = self.ctx.z3_ctx.0;
+        let n = unsafe { Z3_get_quantifier_num_no_patterns(z3_ctx, self.z3_ast) };
+        (0..n)
+            .map(|i| unsafe {
+                Dynamic::wrap(
+                    &self.ctx,
+                    Z3_get_quantifier_no_pattern_ast(z3_ctx, self.z3_ast, i).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    /// Detect the theories and features used by the formula.
+    ///
+    /// # See also:
+    ///
+    /// - [`LogicFeatures`]
+    /// - [`Logic::includes()`](crate::Logic::includes)
+    pub fn detect_logic(&self) -> LogicFeatures {
+        LogicFeatures::of(&self.ctx, self.z3_ast)
+    }
+
+    /// Rewrite the formula into a disjunction of guarded cases without `ite`
+    /// terms, or return `None` if there would be more than `max_cases`
+    /// cases.
+    ///
+    /// Each `ite(c, a, b)` splits the cases in two: one guarded by `c`, where
+    /// it is replaced by `a`, and one guarded by `!c`, where it is replaced by
+    /// `b`. Cases with contradicting guards are dropped, so `ite` terms
+    /// sharing a condition split only once. The conditions themselves and
+    /// the bodies of quantifiers are left as they are.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::ast::{Bool, Int};
+    /// let c = Bool::new_const("c");
+    /// let x = Int::new_const("x");
+    /// let y = c.ite(&Int::from_i64(1), &Int::from_i64(2));
+    /// let z = c.ite(&x, &Int::from_i64(3));
+    /// let formula = (&y + &z).gt(&x);
+    ///
+    /// let flattened = formula.flatten_ite_cases(2).unwrap();
+    /// assert_eq!(
+    ///     flattened,
+    ///     Bool::or(&[
+    ///         Bool::and(&[c.clone(), (Int::from_i64(1) + &x).gt(&x)]),
+    ///         Bool::and(&[c.not(), (Int::from_i64(2) + Int::from_i64(3)).gt(&x)]),
+    ///     ])
+    /// );
+    /// assert!(formula.flatten_ite_cases(1).is_none());
+    /// ```
+    pub fn flatten_ite_cases(&self, max_cases: usize) -> Option<Bool> {
+        let mut flattener = IteFlattener {
+            max_cases,
+            cases: HashMap::new(),
+        };
+        let cases = flattener.cases(&Dynamic::from_ast(self))?;
+        if let [(guard, body)] = &cases[..] {
+            if guard.is_empty() {
+                return body.as_bool();
+            }
+        }
+        let cases: Vec<Bool> = cases
+            .iter()
+            .map(|(guard, body)| {
+                let mut conjuncts: Vec<Bool> = guard
+                    .iter()
+                    .map(|(c, polarity)| if *polarity { c.clone() } else { c.not() })
+                    .collect();
+                conjuncts.push(body.as_bool().unwrap());
+                Bool::and(&conjuncts)
+            })
+            .collect();
+        Some(Bool::or(&cases))
+    }
+
     // This doesn't quite fit the trinop! macro because of the generic argty
+    #[track_caller]
     pub fn ite<T>(&self, a: &T, b: &T) -> T
     where
         T: Ast,
     {
+        crate::ast::assert_same_sort("ite", a, b);
         unsafe {
             T::wrap(&self.ctx, {
                 Z3_mk_ite(
@@ -77,9 +243,9 @@ impl Bool {
         or(Z3_mk_or, Self);
     }
     binop! {
-        xor(Z3_mk_xor, Self);
-        iff(Z3_mk_iff, Self);
-        implies(Z3_mk_implies, Self);
+        xor, try_xor(Z3_mk_xor, Self);
+        iff, try_iff(Z3_mk_iff, Self);
+        implies, try_implies(Z3_mk_implies, Self);
     }
     unop! {
         not(Z3_mk_not, Self);
@@ -148,3 +314,80 @@ impl Bool {
         }
     }
 }
+
+/// The conditions guarding a case, with the polarity each must have.
+type Guard = Vec<(Bool, bool)>;
+
+struct IteFlattener {
+    max_cases: usize,
+    /// The cases of each visited term.
+    cases: HashMap<Dynamic, Vec<(Guard, Dynamic)>>,
+}
+
+impl IteFlattener {
+    fn cases(&mut self, term: &Dynamic) -> Option<Vec<(Guard, Dynamic)>> {
+        if let Some(cases) = self.cases.get(term) {
+            return Some(cases.clone());
+        }
+        let cases = if !term.is_app() || term.num_children() == 0 {
+            vec![(vec![], term.clone())]
+        } else if term.decl().kind() == DeclKind::ITE {
+            let condition = term.nth_child(0)?.as_bool()?;
+            let mut cases = vec![];
+            for (branch, polarity) in [(1, true), (2, false)] {
+                for (guard, body) in self.cases(&term.nth_child(branch)?)? {
+                    if let Some(guard) = extend(&guard, &[(condition.clone(), polarity)]) {
+                        cases.push((guard, body));
+                    }
+                }
+            }
+            cases
+        } else {
+            let mut combined: Vec<(Guard, Vec<Dynamic>)> = vec![(vec![], vec![])];
+            for child in term.children() {
+                let child_cases = self.cases(&child)?;
+                let mut next = vec![];
+                for (guard, args) in &combined {
+                    for (child_guard, body) in &child_cases {
+                        if let Some(guard) = extend(guard, child_guard) {
+                            let mut args = args.clone();
+                            args.push(body.clone());
+                            next.push((guard, args));
+                        }
+                    }
+                }
+                if next.len() > self.max_cases {
+                    return None;
+                }
+                combined = next;
+            }
+            let decl = term.decl();
+            combined
+                .into_iter()
+                .map(|(guard, args)| {
+                    let args: Vec<&dyn Ast> = args.iter().map(|a| a as &dyn Ast).collect();
+                    (guard, decl.apply(&args))
+                })
+                .collect()
+        };
+        if cases.len() > self.max_cases {
+            return None;
+        }
+        self.cases.insert(term.clone(), cases.clone());
+        Some(cases)
+    }
+}
+
+/// Add the conditions of `other` to `guard`, or return `None` if they
+/// contradict it.
+fn extend(guard: &Guard, other: &[(Bool, bool)]) -> Option<Guard> {
+    let mut guard = guard.clone();
+    for (condition, polarity) in other {
+        match guard.iter().find(|(c, _)| c == condition) {
+            Some((_, p)) if p != polarity => return None,
+            Some(_) => {}
+            None => guard.push((condition.clone(), *polarity)),
+        }
+    }
+    Some(guard)
+}
@@ -1,6 +1,7 @@
 use crate::ast::IntoAst;
 use crate::ast::{Ast, Bool, Int, binop, unop};
 use crate::{Context, Sort, Symbol};
+use std::cmp::Ordering;
 use std::ffi::CString;
 use z3_sys::*;
 
@@ -62,7 +63,7 @@ impl BV {
         let sort = Sort::bitvector(sz);
         unsafe {
             Self::wrap(ctx, {
-                Z3_mk_const(ctx.z3_ctx.0, name.into().as_z3_symbol(), sort.z3_sort).unwrap()
+                crate::declarations::mk_const(ctx, name.into(), sort.z3_sort).unwrap()
             })
         }
     }
@@ -173,76 +174,83 @@ impl BV {
     }
     binop! {
         /// Bitwise and
-        bvand(Z3_mk_bvand, Self);
+        bvand, try_bvand(Z3_mk_bvand, Self);
         /// Bitwise or
-        bvor(Z3_mk_bvor, Self);
+        bvor, try_bvor(Z3_mk_bvor, Self);
         /// Bitwise exclusive-or
-        bvxor(Z3_mk_bvxor, Self);
+        bvxor, try_bvxor(Z3_mk_bvxor, Self);
         /// Bitwise nand
-        bvnand(Z3_mk_bvnand, Self);
+        bvnand, try_bvnand(Z3_mk_bvnand, Self);
         /// Bitwise nor
-        bvnor(Z3_mk_bvnor, Self);
+        bvnor, try_bvnor(Z3_mk_bvnor, Self);
         /// Bitwise xnor
-        bvxnor(Z3_mk_bvxnor, Self);
+        bvxnor, try_bvxnor(Z3_mk_bvxnor, Self);
     }
 
     // Arithmetic ops
     binop! {
         /// Addition
-        bvadd(Z3_mk_bvadd, Self);
+        bvadd, try_bvadd(Z3_mk_bvadd, Self);
         /// Subtraction
-        bvsub(Z3_mk_bvsub, Self);
+        bvsub, try_bvsub(Z3_mk_bvsub, Self);
         /// Multiplication
-        bvmul(Z3_mk_bvmul, Self);
+        bvmul, try_bvmul(Z3_mk_bvmul, Self);
         /// Unsigned division
-        bvudiv(Z3_mk_bvudiv, Self);
+        bvudiv, try_bvudiv(Z3_mk_bvudiv, Self);
         /// Signed division
-        bvsdiv(Z3_mk_bvsdiv, Self);
+        bvsdiv, try_bvsdiv(Z3_mk_bvsdiv, Self);
         /// Unsigned remainder
-        bvurem(Z3_mk_bvurem, Self);
+        bvurem, try_bvurem(Z3_mk_bvurem, Self);
         /// Signed remainder (sign follows dividend)
-        bvsrem(Z3_mk_bvsrem, Self);
+        bvsrem, try_bvsrem(Z3_mk_bvsrem, Self);
         /// Signed remainder (sign follows divisor)
-        bvsmod(Z3_mk_bvsmod, Self);
+        bvsmod, try_bvsmod(Z3_mk_bvsmod, Self);
     }
 
     // Comparison ops
     binop! {
         /// Unsigned less than
-        bvult(Z3_mk_bvult, Bool);
+        bvult, try_bvult(Z3_mk_bvult, Bool);
         /// Signed less than
-        bvslt(Z3_mk_bvslt, Bool);
+        bvslt, try_bvslt(Z3_mk_bvslt, Bool);
         /// Unsigned less than or equal
-        bvule(Z3_mk_bvule, Bool);
+        bvule, try_bvule(Z3_mk_bvule, Bool);
         /// Signed less than or equal
-        bvsle(Z3_mk_bvsle, Bool);
+        bvsle, try_bvsle(Z3_mk_bvsle, Bool);
         /// Unsigned greater or equal
-        bvuge(Z3_mk_bvuge, Bool);
+        bvuge, try_bvuge(Z3_mk_bvuge, Bool);
         /// Signed greater or equal
-        bvsge(Z3_mk_bvsge, Bool);
+        bvsge, try_bvsge(Z3_mk_bvsge, Bool);
         /// Unsigned greater than
-        bvugt(Z3_mk_bvugt, Bool);
+        bvugt, try_bvugt(Z3_mk_bvugt, Bool);
         /// Signed greater than
-        bvsgt(Z3_mk_bvsgt, Bool);
+        bvsgt, try_bvsgt(Z3_mk_bvsgt, Bool);
     }
 
     // Shift ops
     binop! {
         /// Shift left
-        bvshl(Z3_mk_bvshl, Self);
+        bvshl, try_bvshl(Z3_mk_bvshl, Self);
         /// Logical shift right (add zeroes in the high bits)
-        bvlshr(Z3_mk_bvlshr, Self);
+        bvlshr, try_bvlshr(Z3_mk_bvlshr, Self);
         /// Arithmetic shift right (sign-extend in the high bits)
-        bvashr(Z3_mk_bvashr, Self);
+        bvashr, try_bvashr(Z3_mk_bvashr, Self);
         /// Rotate left
-        bvrotl(Z3_mk_ext_rotate_left, Self);
+        bvrotl, try_bvrotl(Z3_mk_ext_rotate_left, Self);
         /// Rotate right
-        bvrotr(Z3_mk_ext_rotate_right, Self);
+        bvrotr, try_bvrotr(Z3_mk_ext_rotate_right, Self);
     }
 
-    binop! {
-        /// Concatenate two bitvectors
-        concat(Z3_mk_concat, Self);
+    /// Concatenate two bitvectors
+    //
+    // We avoid the binop! macro because the arguments may have different widths
+    pub fn concat<T: IntoAst<Self>>(&self, other: T) -> Self {
+        let other = other.into_ast(self);
+        unsafe {
+            Self::wrap(&self.ctx, {
+                Z3_mk_concat(self.ctx.z3_ctx.0, self.z3_ast, other.z3_ast).unwrap()
+            })
+        }
     }
 
     // overflow checks
@@ -260,13 +268,13 @@ impl BV {
     }
     binop! {
         /// Check if addition underflows
-        bvadd_no_underflow(Z3_mk_bvadd_no_underflow, Bool);
+        bvadd_no_underflow, try_bvadd_no_underflow(Z3_mk_bvadd_no_underflow, Bool);
         /// Check if subtraction overflows
-        bvsub_no_overflow(Z3_mk_bvsub_no_overflow, Bool);
+        bvsub_no_overflow, try_bvsub_no_overflow(Z3_mk_bvsub_no_overflow, Bool);
         /// Check if signed division overflows
-        bvsdiv_no_overflow(Z3_mk_bvsdiv_no_overflow, Bool);
+        bvsdiv_no_overflow, try_bvsdiv_no_overflow(Z3_mk_bvsdiv_no_overflow, Bool);
         /// Check if multiplication underflows
-        bvmul_no_underflow(Z3_mk_bvmul_no_underflow, Bool);
+        bvmul_no_underflow, try_bvmul_no_underflow(Z3_mk_bvmul_no_underflow, Bool);
     }
 
     /// Extract the bits `high` down to `low` from the bitvector.
@@ -298,6 +306,73 @@ impl BV {
             })
         }
     }
+
+    /// Resize the bitvector to `width` bits, sign-extending it if `signed`
+    /// and zero-extending it otherwise, or keeping its `width` lowest bits if
+    /// it is wider.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is 0.
+    ///
+    /// ```
+    /// # use z3::ast::{Ast, BV};
+    /// let x = BV::from_i64(-2, 8);
+    /// assert_eq!(x.resize_to(16, true).simplify(), BV::from_i64(-2, 16));
+    /// assert_eq!(x.resize_to(16, false).simplify(), BV::from_u64(254, 16));
+    /// assert_eq!(x.resize_to(4, true).simplify(), BV::from_i64(-2, 4));
+    /// ```
+    pub fn resize_to(&self, width: u32, signed: bool) -> Self {
+        assert!(width > 0, "Bitvectors must have at least one bit");
+        let size = self.get_size();
+        match width.cmp(&size) {
+            Ordering::Equal => self.clone(),
+            Ordering::Greater if signed => self.sign_ext(width - size),
+            Ordering::Greater => self.zero_ext(width - size),
+            Ordering::Less => self.extract(width - 1, 0),
+        }
+    }
+
+    /// Like [`BV::resize_to()`], but also returns the side condition under
+    /// which the resized bitvector has the same signed (if `signed`) or
+    /// unsigned value, which only fails when truncating.
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::BV;
+    /// let x = BV::new_const("x", 16);
+    /// let (y, fits) = x.resize_to_checked(8, false);
+    /// let solver = Solver::new();
+    /// solver.assert(&fits);
+    /// solver.assert(x.bvugt(255));
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// assert_eq!(y.get_size(), 8);
+    /// ```
+    pub fn resize_to_checked(&self, width: u32, signed: bool) -> (Self, Bool) {
+        let resized = self.resize_to(width, signed);
+        let fits = if width < self.get_size() {
+            resized.resize_to(self.get_size(), signed).eq(self)
+        } else {
+            Bool::from_bool(true)
+        };
+        (resized, fits)
+    }
+
+    /// Extend the narrower of `a` and `b` to the width of the other, with
+    /// [`BV::resize_to()`], so that they can be combined by operations which
+    /// require operands of the same width.
+    ///
+    /// ```
+    /// # use z3::ast::{Ast, BV};
+    /// let a = BV::from_u64(200, 8);
+    /// let b = BV::from_u64(1000, 16);
+    /// let (a, b) = BV::common_width(&a, &b, false);
+    /// assert_eq!(a.bvadd(&b).simplify().as_u64(), Some(1200));
+    /// ```
+    pub fn common_width(a: &BV, b: &BV, signed: bool) -> (BV, BV) {
+        let width = a.get_size().max(b.get_size());
+        (a.resize_to(width, signed), b.resize_to(width, signed))
+    }
 }
 
 macro_rules! into_bv {
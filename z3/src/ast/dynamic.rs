@@ -19,7 +19,7 @@ impl Dynamic {
         unsafe {
             Self::wrap(
                 ctx,
-                Z3_mk_const(ctx.z3_ctx.0, name.into().as_z3_symbol(), sort.z3_sort).unwrap(),
+                crate::declarations::mk_const(ctx, name.into(), sort.z3_sort).unwrap(),
             )
         }
     }
@@ -35,6 +35,17 @@ impl Dynamic {
         }
     }
 
+    /// Create the free variable of de Bruijn index `index` and sort `sort`,
+    /// as occurs in the body of a quantifier.
+    ///
+    /// # See also
+    ///
+    /// - [`Ast::substitute_bound_vars()`]
+    pub fn bound_var(index: u32, sort: &Sort) -> Self {
+        let ctx = &sort.ctx;
+        unsafe { Self::wrap(ctx, Z3_mk_bound(ctx.z3_ctx.0, index, sort.z3_sort).unwrap()) }
+    }
+
     pub fn sort_kind(&self) -> SortKind {
         unsafe {
             Z3_get_sort_kind(
@@ -98,7 +98,7 @@ impl Float {
         let sort = Sort::float(ebits, sbits);
         unsafe {
             Self::wrap(ctx, {
-                Z3_mk_const(ctx.z3_ctx.0, name.into().as_z3_symbol(), sort.z3_sort).unwrap()
+                crate::declarations::mk_const(ctx, name.into(), sort.z3_sort).unwrap()
             })
         }
     }
@@ -109,7 +109,7 @@ impl Float {
         let sort = Sort::float32();
         unsafe {
             Self::wrap(ctx, {
-                Z3_mk_const(ctx.z3_ctx.0, name.into().as_z3_symbol(), sort.z3_sort).unwrap()
+                crate::declarations::mk_const(ctx, name.into(), sort.z3_sort).unwrap()
             })
         }
     }
@@ -120,7 +120,7 @@ impl Float {
         let sort = Sort::double();
         unsafe {
             Self::wrap(ctx, {
-                Z3_mk_const(ctx.z3_ctx.0, name.into().as_z3_symbol(), sort.z3_sort).unwrap()
+                crate::declarations::mk_const(ctx, name.into(), sort.z3_sort).unwrap()
             })
         }
     }
@@ -228,14 +228,14 @@ impl Float {
         is_positive(Z3_mk_fpa_is_positive, Bool);
     }
     binop! {
-        lt(Z3_mk_fpa_lt, Bool);
-        le(Z3_mk_fpa_leq, Bool);
-        gt(Z3_mk_fpa_gt, Bool);
-        ge(Z3_mk_fpa_geq, Bool);
-        eq_fpa(Z3_mk_fpa_eq, Bool);
-        min(Z3_mk_fpa_min, Self);
-        max(Z3_mk_fpa_max, Self);
-        rem(Z3_mk_fpa_rem, Self);
+        lt, try_lt(Z3_mk_fpa_lt, Bool);
+        le, try_le(Z3_mk_fpa_leq, Bool);
+        gt, try_gt(Z3_mk_fpa_gt, Bool);
+        ge, try_ge(Z3_mk_fpa_geq, Bool);
+        eq_fpa, try_eq_fpa(Z3_mk_fpa_eq, Bool);
+        min, try_min(Z3_mk_fpa_min, Self);
+        max, try_max(Z3_mk_fpa_max, Self);
+        rem, try_rem(Z3_mk_fpa_rem, Self);
     }
 
     /// Square root with default rounding mode (nearest ties to even).
@@ -286,7 +286,8 @@ impl Float {
                     self.z3_ast,
                     y.z3_ast,
                     z.z3_ast,
-                ).unwrap(),
+                )
+                .unwrap(),
             )
         }
     }
@@ -326,6 +327,55 @@ impl Float {
         }
     }
 
+    /// Convert float to real number, along with the condition under which the
+    /// conversion is exact: the float is neither NaN nor infinite.
+    ///
+    /// The value of [`Float::to_real()`] is unspecified on NaN and the
+    /// infinities, so constraints relating a float to its real value should
+    /// be guarded by the condition.
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::{Ast, Float, Real};
+    /// let x = Float::new_const_double("x");
+    /// let (real, finite) = x.to_real_exact();
+    /// let solver = Solver::new();
+    /// solver.assert(&finite);
+    /// solver.assert(x.eq(Float::from_f64(0.1)));
+    /// // The double closest to 0.1 is slightly larger.
+    /// solver.assert(real.gt(Real::from_rational(1, 10)));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// ```
+    pub fn to_real_exact(&self) -> (crate::ast::Real, Bool) {
+        let finite = Bool::and(&[self.is_nan().not(), self.is_infinite().not()]);
+        (self.to_real(), finite)
+    }
+
+    /// Returns the exact value of a finite float numeral, such as a float
+    /// from a model, as a rational.
+    ///
+    /// Returns `None` for NaN, the infinities, and terms that are not
+    /// numerals.
+    ///
+    /// ```
+    /// # use z3::ast::Float;
+    /// # use num::BigRational;
+    /// let tenth = Float::from_f64(0.1).as_big_rational().unwrap();
+    /// assert_ne!(tenth, BigRational::new(1.into(), 10.into()));
+    /// assert_eq!(Some(tenth), BigRational::from_float(0.1));
+    /// ```
+    pub fn as_big_rational(&self) -> Option<num::BigRational> {
+        unsafe {
+            if !Z3_is_numeral_ast(self.ctx.z3_ctx.0, self.z3_ast)
+                || Z3_fpa_is_numeral_nan(self.ctx.z3_ctx.0, self.z3_ast)
+                || Z3_fpa_is_numeral_inf(self.ctx.z3_ctx.0, self.z3_ast)
+            {
+                return None;
+            }
+        }
+        self.to_real().simplify().as_big_rational()
+    }
+
     /// Convert float to another floating-point sort with specified rounding mode.
     pub fn to_fp_with_rounding_mode(&self, rm: &RoundingMode, target_sort: &crate::Sort) -> Float {
         assert!(matches!(target_sort.kind(), crate::SortKind::FloatingPoint));
@@ -337,7 +387,8 @@ impl Float {
                     rm.z3_ast,
                     self.z3_ast,
                     target_sort.z3_sort,
-                ).unwrap(),
+                )
+                .unwrap(),
             )
         }
     }
@@ -23,7 +23,7 @@ impl Int {
         let sort = Sort::int();
         unsafe {
             Self::wrap(ctx, {
-                Z3_mk_const(ctx.z3_ctx.0, name.into().as_z3_symbol(), sort.z3_sort).unwrap()
+                crate::declarations::mk_const(ctx, name.into(), sort.z3_sort).unwrap()
             })
         }
     }
@@ -138,14 +138,14 @@ impl Int {
         unary_minus(Z3_mk_unary_minus, Self);
     }
     binop! {
-        div(Z3_mk_div, Self);
-        rem(Z3_mk_rem, Self);
-        modulo(Z3_mk_mod, Self);
-        power(Z3_mk_power, Real);
-        lt(Z3_mk_lt, Bool);
-        le(Z3_mk_le, Bool);
-        gt(Z3_mk_gt, Bool);
-        ge(Z3_mk_ge, Bool);
+        div, try_div(Z3_mk_div, Self);
+        rem, try_rem(Z3_mk_rem, Self);
+        modulo, try_modulo(Z3_mk_mod, Self);
+        power, try_power(Z3_mk_power, Real);
+        lt, try_lt(Z3_mk_lt, Bool);
+        le, try_le(Z3_mk_le, Bool);
+        gt, try_gt(Z3_mk_gt, Bool);
+        ge, try_ge(Z3_mk_ge, Bool);
     }
     // Z3 does support mixing ints and reals in add(), sub(), mul(), div(), and power()
     //   (but not rem(), modulo(), lt(), le(), gt(), or ge()).
@@ -10,9 +10,12 @@ use std::hash::{Hash, Hasher};
 pub use z3_sys::AstKind;
 use z3_sys::*;
 
-use crate::{Context, FuncDecl, IsNotApp, Model, Pattern, Solvable, Sort, SortDiffers, Symbol};
+use crate::{
+    Context, FuncDecl, IsNotApp, Model, Params, Pattern, Solvable, Sort, SortDiffers, Symbol,
+};
 
 mod array;
+mod binder;
 mod bool;
 mod bv;
 mod datatype;
@@ -31,6 +34,7 @@ pub mod algebraic;
 pub mod polynomial;
 
 pub use array::Array;
+pub use binder::{Bindable, Binder, exists, forall, lambda};
 pub use bool::Bool;
 pub use bv::BV;
 pub use datatype::Datatype;
@@ -70,18 +74,35 @@ macro_rules! unop {
 macro_rules! binop {
     (
         $(
-            $( #[ $attr:meta ] )* $f:ident ( $z3fn:ident, $retty:ty ) ;
+            $( #[ $attr:meta ] )* $f:ident, $try_f:ident ( $z3fn:ident, $retty:ty ) ;
         )*
     ) => {
         $(
             $( #[ $attr ] )*
+            ///
+            /// # Panics
+            ///
+            /// Panics if the operands have different sorts.
+            #[track_caller]
             pub fn $f<T: crate::ast::IntoAst<Self>>(&self, other: T) -> $retty {
+                match self.$try_f(other) {
+                    Ok(ast) => ast,
+                    Err(e) => panic!("{e}"),
+                }
+            }
+
+            #[doc = concat!("Like [`", stringify!($f), "()`](Self::", stringify!($f), "), but returns an error if the operands have different sorts.")]
+            pub fn $try_f<T: crate::ast::IntoAst<Self>>(
+                &self,
+                other: T,
+            ) -> Result<$retty, crate::SortDiffers> {
                 let ast = other.into_ast(self);
-                unsafe {
+                crate::ast::check_same_sort(stringify!($f), self, &ast)?;
+                Ok(unsafe {
                     <$retty>::wrap(&self.ctx, {
                         $z3fn(self.ctx.z3_ctx.0, self.z3_ast, ast.z3_ast).unwrap()
                     })
-                }
+                })
             }
         )*
     };
@@ -90,19 +111,37 @@ macro_rules! binop {
 macro_rules! trinop {
     (
         $(
-            $( #[ $attr:meta ] )* $f:ident ( $z3fn:ident, $retty:ty ) ;
+            $( #[ $attr:meta ] )* $f:ident, $try_f:ident ( $z3fn:ident, $retty:ty ) ;
         )*
     ) => {
         $(
             $( #[ $attr ] )*
+            ///
+            /// # Panics
+            ///
+            /// Panics if the operands have different sorts.
+            #[track_caller]
             pub fn $f<A: Into<$retty>, B: crate::ast::IntoAst<$retty>>(&self, a: A, b: B) -> $retty {
+                match self.$try_f(a, b) {
+                    Ok(ast) => ast,
+                    Err(e) => panic!("{e}"),
+                }
+            }
+
+            #[doc = concat!("Like [`", stringify!($f), "()`](Self::", stringify!($f), "), but returns an error if the operands have different sorts.")]
+            pub fn $try_f<A: Into<$retty>, B: crate::ast::IntoAst<$retty>>(
+                &self,
+                a: A,
+                b: B,
+            ) -> Result<$retty, crate::SortDiffers> {
                 let a = a.into();
                 let b = b.into_ast(&a);
-                unsafe {
+                crate::ast::check_same_sort(stringify!($f), &a, &b)?;
+                Ok(unsafe {
                     <$retty>::wrap(&self.ctx, {
                         $z3fn(self.ctx.z3_ctx.0, self.z3_ast, a.z3_ast, b.z3_ast).unwrap()
                     })
-                }
+                })
             }
         )*
     };
@@ -131,6 +170,61 @@ macro_rules! varop {
     };
 }
 
+/// Returns a [`SortDiffers`] naming `op` if `left` and `right` have different sorts.
+///
+/// Operations call this before handing their arguments to Z3, so that a sort
+/// mismatch is reported where it is made instead of as an opaque Z3 failure.
+pub(crate) fn check_same_sort(
+    op: &'static str,
+    left: &dyn Ast,
+    right: &dyn Ast,
+) -> Result<(), SortDiffers> {
+    let ctx = left.get_ctx().z3_ctx.0;
+    let (l, r) = unsafe {
+        (
+            Z3_get_sort(ctx, left.get_z3_ast()).unwrap(),
+            Z3_get_sort(ctx, right.get_z3_ast()).unwrap(),
+        )
+    };
+    if l != r {
+        return Err(SortDiffers::in_operation(
+            op,
+            left.get_sort(),
+            right.get_sort(),
+        ));
+    }
+    Ok(())
+}
+
+/// Panics with a [`SortDiffers`] naming `op` if `left` and `right` have different sorts.
+#[track_caller]
+pub(crate) fn assert_same_sort(op: &'static str, left: &dyn Ast, right: &dyn Ast) {
+    if let Err(e) = check_same_sort(op, left, right) {
+        panic!("{e}");
+    }
+}
+
+/// Returns a [`SortDiffers`] naming `op` if `actual` is not of sort `expected`.
+pub(crate) fn check_sort(
+    op: &'static str,
+    expected: &Sort,
+    actual: &dyn Ast,
+) -> Result<(), SortDiffers> {
+    let sort = actual.get_sort();
+    if *expected != sort {
+        return Err(SortDiffers::in_operation(op, expected.clone(), sort));
+    }
+    Ok(())
+}
+
+/// Panics with a [`SortDiffers`] naming `op` if `actual` is not of sort `expected`.
+#[track_caller]
+pub(crate) fn assert_sort(op: &'static str, expected: &Sort, actual: &dyn Ast) {
+    if let Err(e) = check_sort(op, expected, actual) {
+        panic!("{e}");
+    }
+}
+
 /// Abstract syntax tree (AST) nodes represent terms, constants, or expressions.
 /// The `Ast` trait contains methods common to all AST subtypes.
 pub trait Ast: fmt::Debug {
@@ -200,6 +294,10 @@ pub trait Ast: fmt::Debug {
     /// Simplify the `Ast`. Returns a new `Ast` which is equivalent,
     /// but simplified using algebraic simplification rules, such as
     /// constant propagation.
+    ///
+    /// # See also:
+    ///
+    /// - [`Ast::simplify_with_params()`]
     fn simplify(&self) -> Self
     where
         Self: Sized,
@@ -211,6 +309,73 @@ pub trait Ast: fmt::Debug {
         }
     }
 
+    /// Simplify the `Ast` like [`Ast::simplify()`], configuring the
+    /// simplification rules with `params`.
+    ///
+    /// The accepted parameters are listed by
+    /// [`HelpEntry::simplifier()`](crate::HelpEntry::simplifier)
+    /// and [`ParamDescrs::simplifier()`](crate::ParamDescrs::simplifier).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `params` sets an unknown parameter, or one of the wrong
+    /// kind. Use [`Ast::try_simplify_with_params()`] to handle this as an
+    /// error instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::Params;
+    /// # use z3::ast::{Ast, Bool};
+    /// let (a, b) = (Bool::new_const("a"), Bool::new_const("b"));
+    /// let t = &a & &b;
+    /// assert_eq!(t.simplify().to_string(), "(and a b)");
+    ///
+    /// let mut params = Params::new();
+    /// params.set_bool("elim_and", true);
+    /// assert_eq!(
+    ///     t.simplify_with_params(&params).to_string(),
+    ///     "(not (or (not a) (not b)))"
+    /// );
+    /// ```
+    fn simplify_with_params(&self, params: &Params) -> Self
+    where
+        Self: Sized,
+    {
+        self.try_simplify_with_params(params)
+            .unwrap_or_else(|message| panic!("invalid simplifier parameters: {message}"))
+    }
+
+    /// Like [`Ast::simplify_with_params()`], but return Z3's error message
+    /// if `params` sets an unknown parameter, or one of the wrong kind.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::Params;
+    /// # use z3::ast::{Ast, Bool};
+    /// let mut params = Params::new();
+    /// params.set_bool("no_such_param", true);
+    /// assert!(Bool::new_const("a").try_simplify_with_params(&params).is_err());
+    /// ```
+    fn try_simplify_with_params(&self, params: &Params) -> Result<Self, String>
+    where
+        Self: Sized,
+    {
+        let ctx = self.get_ctx();
+        unsafe {
+            Z3_set_error(ctx.z3_ctx.0, ErrorCode::OK);
+            match Z3_simplify_ex(ctx.z3_ctx.0, self.get_z3_ast(), params.z3_params) {
+                Some(z3_ast) => Ok(Self::wrap(ctx, z3_ast)),
+                None => {
+                    let code = Z3_get_error_code(ctx.z3_ctx.0);
+                    let msg = CStr::from_ptr(Z3_get_error_msg(ctx.z3_ctx.0, code));
+                    Err(msg.to_string_lossy().into_owned())
+                }
+            }
+        }
+    }
+
     fn eq<T: IntoAst<Self>>(&self, other: T) -> Bool
     where
         Self: Sized;
@@ -249,10 +414,96 @@ pub trait Ast: fmt::Debug {
         }
     }
 
+    /// Replaces the free variables of the `Ast`: the variable with de Bruijn
+    /// index `i` is replaced by `to[i]`, for each `i` smaller than `to.len()`.
+    ///
+    /// Free variables occur in the bodies of quantifiers taken apart, or are
+    /// created with [`Dynamic::bound_var()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::Sort;
+    /// # use z3::ast::{Ast, Dynamic, Int};
+    /// let v = Dynamic::bound_var(0, &Sort::int()).as_int().unwrap();
+    /// let t = &v + 1;
+    /// let u = t.substitute_bound_vars(&[&Int::from_i64(2)]);
+    /// assert_eq!(u.simplify().as_i64(), Some(3));
+    /// ```
+    fn substitute_bound_vars(&self, to: &[&dyn Ast]) -> Self
+    where
+        Self: Sized,
+    {
+        let tos: Vec<Z3_ast> = to.iter().map(|t| t.get_z3_ast()).collect();
+        unsafe {
+            Self::wrap(
+                self.get_ctx(),
+                Z3_substitute_vars(
+                    self.get_ctx().z3_ctx.0,
+                    self.get_z3_ast(),
+                    tos.len() as ::std::os::raw::c_uint,
+                    tos.as_ptr(),
+                )
+                .unwrap(),
+            )
+        }
+    }
+
+    /// Replaces every application of a function of `substitutions` by the
+    /// associated term, in which the free variable with de Bruijn index `j`
+    /// stands for the `j`-th argument of the application.
+    ///
+    /// Requires Z3 4.12.0 or later.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{FuncDecl, Sort};
+    /// # use z3::ast::{Ast, Dynamic, Int};
+    /// let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    /// let x = Int::new_const("x");
+    /// let f_of = |a: &Int| f.apply(&[a]).as_int().unwrap();
+    /// let t = f_of(&x) + f_of(&Int::from_i64(3));
+    ///
+    /// // f(a) := a * 2
+    /// let body = Dynamic::bound_var(0, &Sort::int()).as_int().unwrap() * 2;
+    /// let u = t.substitute_funcs(&[(&f, &body)]);
+    /// assert!(!u.to_string().contains('f'));
+    ///
+    /// // 5 * 2 + 3 * 2
+    /// let u = u.substitute(&[(&x, &Int::from_i64(5))]);
+    /// assert_eq!(u.simplify().as_i64(), Some(16));
+    /// ```
+    #[cfg(feature = "z3_4_12_0")]
+    fn substitute_funcs(&self, substitutions: &[(&FuncDecl, &dyn Ast)]) -> Self
+    where
+        Self: Sized,
+    {
+        let froms: Vec<Z3_func_decl> = substitutions.iter().map(|(f, _)| f.z3_func_decl).collect();
+        let tos: Vec<Z3_ast> = substitutions.iter().map(|(_, t)| t.get_z3_ast()).collect();
+        unsafe {
+            Self::wrap(
+                self.get_ctx(),
+                Z3_substitute_funs(
+                    self.get_ctx().z3_ctx.0,
+                    self.get_z3_ast(),
+                    froms.len() as ::std::os::raw::c_uint,
+                    froms.as_ptr(),
+                    tos.as_ptr(),
+                )
+                .unwrap(),
+            )
+        }
+    }
+
     /// Return the number of children of this `Ast`.
     ///
-    /// Leaf nodes (eg `Bool` consts) will return 0.
+    /// Leaf nodes (eg `Bool` consts) will return 0, and so do nodes which are
+    /// not function applications, such as quantifiers and bound variables.
     fn num_children(&self) -> usize {
+        if !self.is_app() {
+            return 0;
+        }
         let this_ctx = self.get_ctx().z3_ctx.0;
         unsafe {
             let this_app = Z3_to_app(this_ctx, self.get_z3_ast()).unwrap();
@@ -314,6 +565,24 @@ pub trait Ast: fmt::Debug {
         self.safe_decl().expect("Ast is not an app")
     }
 
+    /// Return the kind of the `FuncDecl` of the `Ast`, e.g.
+    /// [`DeclKind::ADD`] for a sum, or `None` if the `Ast` is not an app.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::DeclKind;
+    /// # use z3::ast::{Ast, Int};
+    /// let x = Int::new_const("x");
+    /// let t = (&x + 1).gt(&x);
+    /// assert_eq!(t.decl_kind(), Some(DeclKind::GT));
+    /// assert_eq!(t.children()[0].decl_kind(), Some(DeclKind::ADD));
+    /// assert_eq!(x.decl_kind(), Some(DeclKind::UNINTERPRETED));
+    /// ```
+    fn decl_kind(&self) -> Option<DeclKind> {
+        self.safe_decl().ok().map(|decl| decl.kind())
+    }
+
     fn safe_decl(&self) -> Result<FuncDecl, IsNotApp> {
         if !self.is_app() {
             Err(IsNotApp::new(self.kind()))
@@ -441,11 +710,12 @@ macro_rules! impl_ast {
             /// `Ast`s being compared must be the same type.
             //
             // Note that we can't use the binop! macro because of the `pub` keyword on it
+            #[track_caller]
             pub fn eq<T: IntoAst<Self>>(&self, other: T) -> Bool
             where
                 Self: Sized,
             {
-                self.safe_eq(other).unwrap()
+                self.safe_eq(other).unwrap_or_else(|e| panic!("{e}"))
             }
 
             #[deprecated = "Please use safe_eq instead"]
@@ -477,7 +747,7 @@ macro_rules! impl_ast {
                             .unwrap()
                         })
                     }),
-                    false => Err(SortDiffers::new(left_sort, right_sort)),
+                    false => Err(SortDiffers::in_operation("eq", left_sort, right_sort)),
                 }
             }
         }
@@ -545,7 +815,14 @@ macro_rules! impl_ast {
 
         impl fmt::Display for $ast {
             fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-                <Self as fmt::Debug>::fmt(self, f)
+                match crate::numeral_format::get(&self.ctx) {
+                    Some(formatter) => write!(
+                        f,
+                        "{}",
+                        crate::numeral_format::ast_to_string(&self.ctx, self.z3_ast, &*formatter)
+                    ),
+                    None => <Self as fmt::Debug>::fmt(self, f),
+                }
             }
         }
 
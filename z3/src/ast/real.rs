@@ -1,7 +1,7 @@
 use crate::ast::Ast;
-use crate::ast::{Bool, Int, binop, unop, varop};
+use crate::ast::{Bool, Float, Int, RoundingMode, binop, unop, varop};
 use crate::{Context, Sort, Symbol};
-use num::BigRational;
+use num::{BigRational, ToPrimitive};
 use std::ffi::{CStr, CString};
 use z3_sys::*;
 
@@ -38,7 +38,7 @@ impl Real {
         let sort = Sort::real();
         unsafe {
             Self::wrap(ctx, {
-                Z3_mk_const(ctx.z3_ctx.0, name.into().as_z3_symbol(), sort.z3_sort).unwrap()
+                crate::declarations::mk_const(ctx, name.into(), sort.z3_sort).unwrap()
             })
         }
     }
@@ -107,6 +107,62 @@ impl Real {
         self.approx(17).parse().unwrap() // 17 decimal digits needed to get full f64 precision
     }
 
+    /// Returns the exact value of a rational numeral, without the size limit
+    /// of [`Real::as_rational()`].
+    pub fn as_big_rational(&self) -> Option<BigRational> {
+        if !unsafe { Z3_is_numeral_ast(self.ctx.z3_ctx.0, self.z3_ast) } {
+            return None;
+        }
+        let s = unsafe { CStr::from_ptr(Z3_get_numeral_string(self.ctx.z3_ctx.0, self.z3_ast)) }
+            .to_str()
+            .ok()?;
+        match s.split_once('/') {
+            Some((num, den)) => Some(BigRational::new(num.parse().ok()?, den.parse().ok()?)),
+            None => Some(BigRational::from_integer(s.parse().ok()?)),
+        }
+    }
+
+    /// Returns the value of a rational numeral as an [`f64`], if it is
+    /// exactly representable as one.
+    ///
+    /// Unlike [`Real::approx_f64()`], this never rounds.
+    ///
+    /// ```
+    /// # use z3::ast::Real;
+    /// assert_eq!(Real::from_rational(3, 4).as_f64_exact(), Some(0.75));
+    /// assert_eq!(Real::from_rational(1, 10).as_f64_exact(), None);
+    /// ```
+    pub fn as_f64_exact(&self) -> Option<f64> {
+        let value = self.as_big_rational()?;
+        let f = value.to_f64()?;
+        (BigRational::from_float(f)? == value).then_some(f)
+    }
+
+    /// Convert to the floating-point [`Sort`] `sort`, rounding with `rm`.
+    ///
+    /// ```
+    /// # use z3::Sort;
+    /// # use z3::ast::{Ast, Real, RoundingMode};
+    /// let rne = RoundingMode::round_nearest_ties_to_even();
+    /// let quarter = Real::from_rational(1, 4).to_float(&Sort::double(), &rne);
+    /// assert_eq!(quarter.simplify().as_f64(), 0.25);
+    ///
+    /// let third = Real::from_rational(1, 3);
+    /// let down = third.to_float(&Sort::double(), &RoundingMode::round_towards_negative());
+    /// let up = third.to_float(&Sort::double(), &RoundingMode::round_towards_positive());
+    /// assert!(down.simplify().as_f64() < up.simplify().as_f64());
+    /// ```
+    pub fn to_float(&self, sort: &Sort, rm: &RoundingMode) -> Float {
+        assert!(matches!(sort.kind(), SortKind::FloatingPoint));
+        unsafe {
+            Float::wrap(
+                &self.ctx,
+                Z3_mk_fpa_to_fp_real(self.ctx.z3_ctx.0, rm.z3_ast, self.z3_ast, sort.z3_sort)
+                    .unwrap(),
+            )
+        }
+    }
+
     pub fn from_int(ast: &Int) -> Real {
         unsafe {
             Self::wrap(
@@ -136,12 +192,12 @@ impl Real {
         unary_minus(Z3_mk_unary_minus, Self);
     }
     binop! {
-        div(Z3_mk_div, Self);
-        power(Z3_mk_power, Self);
-        lt(Z3_mk_lt, Bool);
-        le(Z3_mk_le, Bool);
-        gt(Z3_mk_gt, Bool);
-        ge(Z3_mk_ge, Bool);
+        div, try_div(Z3_mk_div, Self);
+        power, try_power(Z3_mk_power, Self);
+        lt, try_lt(Z3_mk_lt, Bool);
+        le, try_le(Z3_mk_le, Bool);
+        gt, try_gt(Z3_mk_gt, Bool);
+        ge, try_ge(Z3_mk_ge, Bool);
     }
 }
 
@@ -146,7 +146,7 @@ impl Regexp {
     crate::ast::binop! {
         /// Creates a difference regular expression
         /// Requires Z3 4.8.14 or later.
-        diff(Z3_mk_re_diff, Self);
+        diff, try_diff(Z3_mk_re_diff, Self);
     }
     crate::ast::varop! {
        /// Concatenates regular expressions
@@ -49,9 +49,9 @@ impl RoundingMode {
     }
 
     trinop! {
-        add(Z3_mk_fpa_add, Float);
-        sub(Z3_mk_fpa_sub, Float);
-        mul(Z3_mk_fpa_mul, Float);
-        div(Z3_mk_fpa_div, Float);
+        add, try_add(Z3_mk_fpa_add, Float);
+        sub, try_sub(Z3_mk_fpa_sub, Float);
+        mul, try_mul(Z3_mk_fpa_mul, Float);
+        div, try_div(Z3_mk_fpa_div, Float);
     }
 }
@@ -15,7 +15,7 @@ impl Set {
         let sort = Sort::set(eltype);
         unsafe {
             Self::wrap(ctx, {
-                Z3_mk_const(ctx.z3_ctx.0, name.into().as_z3_symbol(), sort.z3_sort).unwrap()
+                crate::declarations::mk_const(ctx, name.into(), sort.z3_sort).unwrap()
             })
         }
     }
@@ -38,15 +38,28 @@ impl Set {
         unsafe { Self::wrap(ctx, Z3_mk_empty_set(ctx.z3_ctx.0, domain.z3_sort).unwrap()) }
     }
 
+    #[track_caller]
+    fn assert_element_sort(&self, op: &'static str, element: &dyn Ast) {
+        if let Some(eltype) = self.get_sort().array_domain() {
+            crate::ast::assert_sort(op, &eltype, element);
+        }
+    }
+
     /// Add an element to the set.
     ///
     /// Note that the `element` _must be_ of the `Set`'s `eltype` sort.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `element` is not of the `Set`'s `eltype` sort.
     //
     // We avoid the binop! macro because the argument has a non-Self type
+    #[track_caller]
     pub fn add<A>(&self, element: &A) -> Set
     where
         A: Ast,
     {
+        self.assert_element_sort("add", element);
         unsafe {
             Self::wrap(&self.ctx, {
                 Z3_mk_set_add(self.ctx.z3_ctx.0, self.z3_ast, element.get_z3_ast()).unwrap()
@@ -57,12 +70,18 @@ impl Set {
     /// Remove an element from the set.
     ///
     /// Note that the `element` _must be_ of the `Set`'s `eltype` sort.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `element` is not of the `Set`'s `eltype` sort.
     //
     // We avoid the binop! macro because the argument has a non-Self type
+    #[track_caller]
     pub fn del<A>(&self, element: &A) -> Set
     where
         A: Ast,
     {
+        self.assert_element_sort("del", element);
         unsafe {
             Self::wrap(&self.ctx, {
                 Z3_mk_set_del(self.ctx.z3_ctx.0, self.z3_ast, element.get_z3_ast()).unwrap()
@@ -73,12 +92,18 @@ impl Set {
     /// Check if an item is a member of the set.
     ///
     /// Note that the `element` _must be_ of the `Set`'s `eltype` sort.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `element` is not of the `Set`'s `eltype` sort.
     //
     // We avoid the binop! macro because the argument has a non-Self type
+    #[track_caller]
     pub fn member<A>(&self, element: &A) -> Bool
     where
         A: Ast,
     {
+        self.assert_element_sort("member", element);
         unsafe {
             Bool::wrap(&self.ctx, {
                 Z3_mk_set_member(self.ctx.z3_ctx.0, element.get_z3_ast(), self.z3_ast).unwrap()
@@ -98,8 +123,8 @@ impl Set {
     }
     binop! {
         /// Check if the set is a subset of another set.
-        set_subset(Z3_mk_set_subset, Bool);
+        set_subset, try_set_subset(Z3_mk_set_subset, Bool);
         /// Take the set difference between two sets.
-        difference(Z3_mk_set_difference, Self);
+        difference, try_difference(Z3_mk_set_difference, Self);
     }
 }
@@ -18,7 +18,7 @@ impl String {
         let sort = Sort::string();
         unsafe {
             Self::wrap(ctx, {
-                Z3_mk_const(ctx.z3_ctx.0, name.into().as_z3_symbol(), sort.z3_sort).unwrap()
+                crate::declarations::mk_const(ctx, name.into(), sort.z3_sort).unwrap()
             })
         }
     }
@@ -194,15 +194,15 @@ impl String {
 
     binop! {
         /// Checks whether `Self` contains a substring
-        contains(Z3_mk_seq_contains, Bool);
+        contains, try_contains(Z3_mk_seq_contains, Bool);
         /// Checks whether `Self` is a prefix of the argument
-        prefix(Z3_mk_seq_prefix, Bool);
+        prefix, try_prefix(Z3_mk_seq_prefix, Bool);
         /// Checks whether `Self` is a suffix of the argument
-        suffix(Z3_mk_seq_suffix, Bool);
+        suffix, try_suffix(Z3_mk_seq_suffix, Bool);
         /// Checks whether `Self` is less than the argument in lexicographic order (str.<  s1 s2)
-        str_lt(Z3_mk_str_lt, Bool);
+        str_lt, try_str_lt(Z3_mk_str_lt, Bool);
         /// Checks whether `Self` is less than or equal to the argument in lexicographic order (str.<= s1 s2)
-        str_le(Z3_mk_str_le, Bool);
+        str_le, try_str_le(Z3_mk_str_le, Bool);
     }
 }
 
@@ -1,15 +1,20 @@
-use crate::ast::Ast;
+use std::cell::Cell;
+
 use crate::Context;
+use crate::ast::Ast;
 use z3_sys::*;
 
 /// Vector of Z3 AST nodes.
-/// 
+///
 /// Provides a container for managing collections of Z3 AST objects
 /// with proper reference counting and memory management.
 #[derive(Debug)]
 pub struct AstVector {
     pub(crate) ctx: Context,
     pub(crate) z3_ast_vector: Z3_ast_vector,
+    /// Whether [`AstVector::for_each_ref()`] is running, during which the
+    /// vector must not change.
+    inspecting: Cell<bool>,
 }
 
 impl Drop for AstVector {
@@ -30,6 +35,7 @@ impl AstVector {
             AstVector {
                 ctx: ctx.clone(),
                 z3_ast_vector: av,
+                inspecting: Cell::new(false),
             }
         }
     }
@@ -42,6 +48,7 @@ impl AstVector {
         AstVector {
             ctx: ctx.clone(),
             z3_ast_vector,
+            inspecting: Cell::new(false),
         }
     }
 
@@ -56,7 +63,7 @@ impl AstVector {
     }
 
     /// Get the element at the specified index.
-    /// 
+    ///
     /// # Panics
     /// Panics if the index is out of bounds.
     pub fn get(&self, index: usize) -> crate::ast::Dynamic {
@@ -70,10 +77,11 @@ impl AstVector {
     }
 
     /// Set the element at the specified index.
-    /// 
+    ///
     /// # Panics
     /// Panics if the index is out of bounds.
     pub fn set(&self, index: usize, ast: &impl Ast) {
+        self.assert_not_inspecting();
         assert!(index < self.len(), "Index {} out of bounds", index);
         unsafe {
             Z3_ast_vector_set(
@@ -87,6 +95,7 @@ impl AstVector {
 
     /// Push an element to the end of the vector.
     pub fn push(&self, ast: &impl Ast) {
+        self.assert_not_inspecting();
         unsafe {
             Z3_ast_vector_push(self.ctx.z3_ctx.0, self.z3_ast_vector, ast.get_z3_ast());
         }
@@ -95,16 +104,108 @@ impl AstVector {
     /// Resize the vector to the specified size.
     /// If the new size is larger, the new elements are uninitialized.
     pub fn resize(&self, new_size: usize) {
+        self.assert_not_inspecting();
         unsafe {
             Z3_ast_vector_resize(self.ctx.z3_ctx.0, self.z3_ast_vector, new_size as u32);
         }
     }
 
+    /// Remove the element at the specified index and return it, shifting
+    /// the following elements to the left.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds.
+    pub fn remove(&self, index: usize) -> crate::ast::Dynamic {
+        let removed = self.get(index);
+        let len = self.len();
+        for i in index + 1..len {
+            self.set(i - 1, &self.get(i));
+        }
+        self.resize(len - 1);
+        removed
+    }
+
+    /// Swap the elements at the specified indices.
+    ///
+    /// # Panics
+    /// Panics if either index is out of bounds.
+    pub fn swap(&self, i: usize, j: usize) {
+        let a = self.get(i);
+        let b = self.get(j);
+        self.set(i, &b);
+        self.set(j, &a);
+    }
+
+    /// Remove all elements from the vector.
+    pub fn clear(&self) {
+        self.resize(0);
+    }
+
+    /// Shorten the vector to `len` elements.
+    /// Has no effect if the vector is not longer than `len`.
+    pub fn truncate(&self, len: usize) {
+        if len < self.len() {
+            self.resize(len);
+        }
+    }
+
+    fn assert_not_inspecting(&self) {
+        assert!(
+            !self.inspecting.get(),
+            "AstVector modified during for_each_ref"
+        );
+    }
+
+    /// Call `f` on each element, borrowed rather than wrapped as a
+    /// [`Dynamic`](crate::ast::Dynamic).
+    ///
+    /// Iterating with [`IntoIterator`] takes a reference to each element
+    /// and releases it when the element is dropped. Borrowing skips this,
+    /// which speeds up inspecting large vectors, e.g. those returned by
+    /// parsers. Elements to keep are converted with [`AstRef::to_dynamic()`].
+    ///
+    /// # Panics
+    /// Panics if `f` modifies the vector.
+    ///
+    /// # Example
+    /// ```
+    /// # use z3::AstVector;
+    /// # use z3::ast::{Bool, Int};
+    /// # use z3::SortKind;
+    /// let (x, p) = (Int::new_const("x"), Bool::new_const("p"));
+    /// let vector = AstVector::from_slice(&[&x, &Int::from_i64(1), &x]);
+    /// vector.push(&p);
+    ///
+    /// let mut ints = 0;
+    /// vector.for_each_ref(|a| {
+    ///     if a.sort_kind() == SortKind::Int {
+    ///         ints += 1;
+    ///     }
+    /// });
+    /// assert_eq!(ints, 3);
+    /// ```
+    pub fn for_each_ref(&self, mut f: impl FnMut(AstRef<'_>)) {
+        struct Reset<'a>(&'a Cell<bool>, bool);
+        impl Drop for Reset<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.1);
+            }
+        }
+        let _reset = Reset(&self.inspecting, self.inspecting.replace(true));
+        for index in 0..self.len() {
+            let z3_ast = unsafe {
+                Z3_ast_vector_get(self.ctx.z3_ctx.0, self.z3_ast_vector, index as u32).unwrap()
+            };
+            f(AstRef {
+                ctx: &self.ctx,
+                z3_ast,
+            });
+        }
+    }
+
     /// Convert the vector to a Rust Vec.
     pub fn to_vec(&self) -> Vec<crate::ast::Dynamic> {
-        (0..self.len())
-            .map(|i| self.get(i))
-            .collect()
+        (0..self.len()).map(|i| self.get(i)).collect()
     }
 
     /// Create an AST vector from a slice of AST objects.
@@ -121,33 +222,74 @@ impl AstVector {
         unsafe {
             AstVector::wrap(
                 target_ctx,
-                Z3_ast_vector_translate(
-                    self.ctx.z3_ctx.0,
-                    self.z3_ast_vector,
-                    target_ctx.z3_ctx.0,
-                ).unwrap(),
+                Z3_ast_vector_translate(self.ctx.z3_ctx.0, self.z3_ast_vector, target_ctx.z3_ctx.0)
+                    .unwrap(),
             )
         }
     }
+}
 
-    /// Convert the vector to a string representation.
-    pub fn to_string(&self) -> String {
-        unsafe {
+impl Default for AstVector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for AstVector {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let s = unsafe {
             let s = Z3_ast_vector_to_string(self.ctx.z3_ctx.0, self.z3_ast_vector);
             std::ffi::CStr::from_ptr(s).to_string_lossy().into_owned()
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// An element of an [`AstVector`] borrowed by [`AstVector::for_each_ref()`].
+#[derive(Clone, Copy)]
+pub struct AstRef<'a> {
+    ctx: &'a Context,
+    z3_ast: Z3_ast,
+}
+
+impl AstRef<'_> {
+    /// Returns the kind of the element.
+    pub fn kind(&self) -> AstKind {
+        unsafe { Z3_get_ast_kind(self.ctx.z3_ctx.0, self.z3_ast) }
+    }
+
+    /// Returns the kind of the sort of the element.
+    pub fn sort_kind(&self) -> SortKind {
+        unsafe {
+            let sort = Z3_get_sort(self.ctx.z3_ctx.0, self.z3_ast).unwrap();
+            Z3_get_sort_kind(self.ctx.z3_ctx.0, sort)
         }
     }
+
+    /// Returns the raw Z3 AST, valid while the element is borrowed.
+    pub fn get_z3_ast(&self) -> Z3_ast {
+        self.z3_ast
+    }
+
+    /// Wrap the element as a term which may outlive the borrow.
+    pub fn to_dynamic(&self) -> crate::ast::Dynamic {
+        unsafe { crate::ast::Dynamic::wrap(self.ctx, self.z3_ast) }
+    }
 }
 
-impl Default for AstVector {
-    fn default() -> Self {
-        Self::new()
+impl std::fmt::Display for AstRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let s = unsafe {
+            let s = Z3_ast_to_string(self.ctx.z3_ctx.0, self.z3_ast);
+            std::ffi::CStr::from_ptr(s).to_string_lossy().into_owned()
+        };
+        write!(f, "{}", s)
     }
 }
 
-impl std::fmt::Display for AstVector {
+impl std::fmt::Debug for AstRef<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        write!(f, "{}", self.to_string())
+        <Self as std::fmt::Display>::fmt(self, f)
     }
 }
 
@@ -182,4 +324,4 @@ impl<'a> IntoIterator for &'a AstVector {
             index: 0,
         }
     }
-}
\ No newline at end of file
+}
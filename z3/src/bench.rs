@@ -0,0 +1,406 @@
+//! Benchmarking solver configurations on a directory of SMT-LIB problems.
+//!
+//! A [`Bench`] loads the `.smt2` files below a directory and checks each of
+//! them with every [`Setup`], a solver configuration such as a tactic and
+//! parameters. The resulting [`Report`] holds one [`Record`] per file and
+//! setup, with the answer, the time and the resource units taken, and is
+//! written out as CSV or JSON to compare runs over time.
+//!
+//! Each check runs in a fresh [`Context`](crate::Context), so the results
+//! and resource counts of a file do not depend on the files checked before.
+//! To time a single problem with a benchmarking framework such as
+//! `criterion`, call [`Setup::run()`] in the measured closure.
+//!
+//! # Example
+//!
+//! ```
+//! # use z3::SatResult;
+//! # use z3::bench::{Bench, Problem, Setup};
+//! let bench = Bench::from_problems(vec![
+//!     Problem::new("sat.smt2", "(declare-const x Int) (assert (> x 2))"),
+//!     Problem::new("unsat.smt2", "(declare-const x Int) (assert (> x 2)) (assert (< x 1))"),
+//! ])
+//! .setup(Setup::new("default"))
+//! .setup(Setup::new("lia").tactic("qflia").u32_param("random_seed", 7));
+//!
+//! let report = bench.run();
+//! assert_eq!(report.records().len(), 4);
+//! assert_eq!(report.records()[1].result, SatResult::Sat);
+//! assert_eq!(report.records()[3].result, SatResult::Unsat);
+//! assert!(report.conflicts().is_empty());
+//!
+//! let csv = report.to_csv();
+//! assert!(csv.starts_with("file,setup,result,seconds,rlimit_count,reason_unknown,error\n"));
+//! assert!(csv.contains("\nunsat.smt2,lia,unsat,"));
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::params::ParamValue;
+use crate::{Config, Params, SatResult, Solver, Tactic, with_z3_config};
+
+/// An SMT-LIB problem of a [`Bench`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    path: PathBuf,
+    smtlib: String,
+}
+
+impl Problem {
+    /// Create a problem named `path` from its SMT-LIB source.
+    pub fn new(path: impl Into<PathBuf>, smtlib: impl Into<String>) -> Problem {
+        Problem {
+            path: path.into(),
+            smtlib: smtlib.into(),
+        }
+    }
+
+    /// Read the problem from the file at `path`.
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Problem> {
+        let path = path.into();
+        let smtlib = fs::read_to_string(&path)?;
+        Ok(Problem { path, smtlib })
+    }
+
+    /// Returns the path of the problem.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the SMT-LIB source of the problem.
+    pub fn smtlib(&self) -> &str {
+        &self.smtlib
+    }
+}
+
+/// A solver configuration of a [`Bench`].
+///
+/// By default, this is the general-purpose solver of [`Solver::new()`] with
+/// the default parameters and no timeout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setup {
+    name: String,
+    tactic: Option<String>,
+    params: Vec<(String, ParamValue)>,
+    timeout: Option<Duration>,
+}
+
+impl Setup {
+    /// Create a setup named `name` in the reports, using the
+    /// general-purpose solver.
+    pub fn new(name: &str) -> Setup {
+        Setup {
+            name: name.to_owned(),
+            tactic: None,
+            params: vec![],
+            timeout: None,
+        }
+    }
+
+    /// Use a solver built from the tactic named `name` instead, see
+    /// [`Tactic::new()`].
+    pub fn tactic(mut self, name: &str) -> Setup {
+        self.tactic = Some(name.to_owned());
+        self
+    }
+
+    /// Set the boolean solver parameter `key`, see [`Params::set_bool()`].
+    pub fn bool_param(mut self, key: &str, value: bool) -> Setup {
+        self.params.push((key.to_owned(), ParamValue::Bool(value)));
+        self
+    }
+
+    /// Set the unsigned solver parameter `key`, see [`Params::set_u32()`].
+    pub fn u32_param(mut self, key: &str, value: u32) -> Setup {
+        self.params.push((key.to_owned(), ParamValue::U32(value)));
+        self
+    }
+
+    /// Set the floating-point solver parameter `key`, see
+    /// [`Params::set_f64()`].
+    pub fn f64_param(mut self, key: &str, value: f64) -> Setup {
+        self.params.push((key.to_owned(), ParamValue::F64(value)));
+        self
+    }
+
+    /// Set the symbol solver parameter `key`, see [`Params::set_symbol()`].
+    pub fn symbol_param(mut self, key: &str, value: &str) -> Setup {
+        let value = ParamValue::Symbol(value.into());
+        self.params.push((key.to_owned(), value));
+        self
+    }
+
+    /// Give each check at most `timeout`, after which it answers
+    /// [`SatResult::Unknown`].
+    pub fn timeout(mut self, timeout: Duration) -> Setup {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the name of the setup.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check the assertions of `problem` in a fresh
+    /// [`Context`](crate::Context) configured by this setup.
+    ///
+    /// Only the check is timed, not the parsing of the problem. If the
+    /// setup names an invalid tactic or the problem cannot be parsed, the
+    /// result is [`SatResult::Unknown`] and the error is recorded in
+    /// [`Record::error`].
+    pub fn run(&self, problem: &Problem) -> Record {
+        let mut cfg = Config::new();
+        if let Some(timeout) = self.timeout {
+            cfg.set_timeout_msec(timeout.as_millis().try_into().unwrap_or(u64::MAX));
+        }
+        let mut record = Record {
+            file: problem.path.clone(),
+            setup: self.name.clone(),
+            result: SatResult::Unknown,
+            time: Duration::ZERO,
+            rlimit_count: None,
+            reason_unknown: None,
+            error: None,
+        };
+        with_z3_config(&cfg, || {
+            let solver = match &self.tactic {
+                Some(name) if !Tactic::list_all().contains(&Ok(name.clone())) => {
+                    record.error = Some(format!("{name} is an invalid tactic"));
+                    return;
+                }
+                Some(name) => Tactic::new(name).solver(),
+                None => Solver::new(),
+            };
+            if !self.params.is_empty() {
+                solver.set_params(&self.params());
+            }
+            if let Err(e) = solver.from_string(problem.smtlib.as_str()) {
+                record.error = Some(e.to_string());
+                return;
+            }
+
+            let start = Instant::now();
+            record.result = solver.check();
+            record.time = start.elapsed();
+
+            record.rlimit_count = solver.get_statistics().rlimit_count();
+            if record.result == SatResult::Unknown {
+                record.reason_unknown = solver.get_reason_unknown();
+            }
+        });
+        record
+    }
+
+    fn params(&self) -> Params {
+        let mut params = Params::new();
+        for (key, value) in &self.params {
+            params.set(key.as_str().into(), value.clone());
+        }
+        params
+    }
+}
+
+/// A set of problems checked with each of a list of [`Setup`]s, see the
+/// [module documentation](self).
+#[derive(Debug, Clone, Default)]
+pub struct Bench {
+    problems: Vec<Problem>,
+    setups: Vec<Setup>,
+}
+
+impl Bench {
+    /// Load the `.smt2` files below the directory `dir`, including its
+    /// subdirectories, ordered by path.
+    pub fn load(dir: impl AsRef<Path>) -> io::Result<Bench> {
+        let mut paths = vec![];
+        collect_smt2_files(dir.as_ref(), &mut paths)?;
+        paths.sort();
+        let problems = paths
+            .into_iter()
+            .map(Problem::load)
+            .collect::<io::Result<_>>()?;
+        Ok(Bench::from_problems(problems))
+    }
+
+    /// Create a bench of the given problems.
+    pub fn from_problems(problems: Vec<Problem>) -> Bench {
+        Bench {
+            problems,
+            setups: vec![],
+        }
+    }
+
+    /// Add `setup` to the setups checking every problem.
+    pub fn setup(mut self, setup: Setup) -> Bench {
+        self.setups.push(setup);
+        self
+    }
+
+    /// Returns the problems of the bench.
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
+    /// Returns the setups of the bench.
+    pub fn setups(&self) -> &[Setup] {
+        &self.setups
+    }
+
+    /// Check every problem with every setup, one after the other, see
+    /// [`Setup::run()`].
+    ///
+    /// The records are ordered by problem, then by setup.
+    pub fn run(&self) -> Report {
+        let records = self
+            .problems
+            .iter()
+            .flat_map(|problem| self.setups.iter().map(move |setup| setup.run(problem)))
+            .collect();
+        Report { records }
+    }
+}
+
+fn collect_smt2_files(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_smt2_files(&path, paths)?;
+        } else if path.extension().is_some_and(|e| e == "smt2") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The outcome of checking one problem with one [`Setup`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// The path of the problem.
+    pub file: PathBuf,
+    /// The name of the setup.
+    pub setup: String,
+    /// The answer of the check.
+    pub result: SatResult,
+    /// The time taken by the check.
+    pub time: Duration,
+    /// The resource units consumed, see
+    /// [`Statistics::rlimit_count()`](crate::Statistics::rlimit_count).
+    pub rlimit_count: Option<u64>,
+    /// Why the answer is [`SatResult::Unknown`], as reported by Z3.
+    pub reason_unknown: Option<String>,
+    /// The error which prevented the check, if any: an invalid tactic, or a
+    /// parse error of the problem.
+    pub error: Option<String>,
+}
+
+/// The records of a [`Bench::run()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    records: Vec<Record>,
+}
+
+impl Report {
+    /// Returns the records, ordered by problem, then by setup.
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Returns the files some setups find satisfiable and others
+    /// unsatisfiable, which points to a bug in one of them.
+    pub fn conflicts(&self) -> Vec<&Path> {
+        let mut files: Vec<&Path> = vec![];
+        for record in &self.records {
+            let file = record.file.as_path();
+            let disagrees = self.records.iter().any(|other| {
+                other.file == file
+                    && matches!(
+                        (record.result, other.result),
+                        (SatResult::Sat, SatResult::Unsat)
+                    )
+            });
+            if disagrees && !files.contains(&file) {
+                files.push(file);
+            }
+        }
+        files
+    }
+
+    /// Write the records as CSV with a header line, one record per line.
+    /// The time is in seconds, and the fields missing from a record are
+    /// left empty.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("file,setup,result,seconds,rlimit_count,reason_unknown,error\n");
+        for r in &self.records {
+            let fields = [
+                csv_field(&r.file.to_string_lossy()),
+                csv_field(&r.setup),
+                r.result.to_string(),
+                r.time.as_secs_f64().to_string(),
+                r.rlimit_count.map(|c| c.to_string()).unwrap_or_default(),
+                csv_field(r.reason_unknown.as_deref().unwrap_or_default()),
+                csv_field(r.error.as_deref().unwrap_or_default()),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Write the records as a JSON array of objects, with the same fields
+    /// as [`Report::to_csv()`]. Missing fields are `null`.
+    pub fn to_json(&self) -> String {
+        let records: Vec<String> = self
+            .records
+            .iter()
+            .map(|r| {
+                format!(
+                    "{{\"file\":{},\"setup\":{},\"result\":\"{}\",\"seconds\":{},\
+                     \"rlimit_count\":{},\"reason_unknown\":{},\"error\":{}}}",
+                    json_string(&r.file.to_string_lossy()),
+                    json_string(&r.setup),
+                    r.result,
+                    r.time.as_secs_f64(),
+                    r.rlimit_count
+                        .map_or_else(|| "null".to_owned(), |c| c.to_string()),
+                    r.reason_unknown
+                        .as_deref()
+                        .map_or_else(|| "null".to_owned(), json_string),
+                    r.error
+                        .as_deref()
+                        .map_or_else(|| "null".to_owned(), json_string),
+                )
+            })
+            .collect();
+        format!("[{}]", records.join(","))
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,200 @@
+use crate::ast::{Ast, Bool, Dynamic};
+use crate::{Model, SatResult, Solver, Trace};
+
+/// Incremental bounded model checking of a transition system.
+///
+/// The system is given by its state variables, their next-state copies, an
+/// initial condition over the state variables, a transition relation over
+/// both, and a bad-state condition over the state variables. Each step of
+/// the unrolling gets fresh copies of the state variables.
+///
+/// Deepening the unrolling only asserts the new transition; earlier steps
+/// stay in the solver. The bad-state condition of each step is guarded by
+/// an activation literal, so [`Bmc::check()`] checks a step by assuming its
+/// literal instead of pushing and popping, and the solver keeps everything
+/// it learnt about the shallower steps.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{Bmc, SatResult};
+/// # use z3::ast::{Ast, Int};
+/// // A counter starting at 0 and incrementing by 1 or 2 at each step.
+/// let (x, x_next) = (Int::new_const("x"), Int::new_const("x'"));
+/// let mut bmc = Bmc::new(
+///     &[&x],
+///     &[&x_next],
+///     &x.eq(0),
+///     &(x_next.eq(&x + 1) | x_next.eq(&x + 2)),
+///     &x.eq(5),
+/// );
+/// assert_eq!(bmc.find(10), Some(3));
+///
+/// let trace = bmc.trace(&bmc.solver().get_model().unwrap());
+/// assert_eq!(trace.len(), 4);
+/// assert_eq!(trace.value(3, "x").unwrap().to_string(), "5");
+/// ```
+#[derive(Debug)]
+pub struct Bmc {
+    solver: Solver,
+    vars: Vec<Dynamic>,
+    next: Vec<Dynamic>,
+    trans: Bool,
+    bad: Bool,
+    /// The copies of the state variables at each step.
+    states: Vec<Vec<Dynamic>>,
+    /// The activation literal guarding the bad-state condition of each step.
+    activations: Vec<Bool>,
+}
+
+impl Bmc {
+    /// Create a bounded model checker for the transition system with state
+    /// variables `vars`, next-state variables `next`, initial condition
+    /// `init`, transition relation `trans` and bad-state condition `bad`.
+    ///
+    /// The unrolling starts with the initial step only.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `vars` and `next` have different lengths.
+    pub fn new(vars: &[&dyn Ast], next: &[&dyn Ast], init: &Bool, trans: &Bool, bad: &Bool) -> Bmc {
+        assert_eq!(
+            vars.len(),
+            next.len(),
+            "every state variable needs a next-state copy"
+        );
+        let mut bmc = Bmc {
+            solver: Solver::new(),
+            vars: vars.iter().map(|v| Dynamic::from_ast(*v)).collect(),
+            next: next.iter().map(|v| Dynamic::from_ast(*v)).collect(),
+            trans: trans.clone(),
+            bad: bad.clone(),
+            states: vec![],
+            activations: vec![],
+        };
+        let state = bmc.new_state();
+        bmc.solver.assert(bmc.at(init, &state));
+        bmc.add_step(state);
+        bmc
+    }
+
+    /// Returns the number of transitions unrolled so far.
+    pub fn depth(&self) -> usize {
+        self.states.len() - 1
+    }
+
+    /// Returns the solver holding the unrolling.
+    ///
+    /// After a [`SatResult::Sat`] result, its model is a counterexample.
+    pub fn solver(&self) -> &Solver {
+        &self.solver
+    }
+
+    /// Returns the copies of the state variables at `step`, in the order
+    /// they were given, or `None` if the unrolling is not that deep yet.
+    pub fn state(&self, step: usize) -> Option<&[Dynamic]> {
+        self.states.get(step).map(|s| &s[..])
+    }
+
+    /// Returns the activation literal guarding the bad-state condition at
+    /// `step`, or `None` if the unrolling is not that deep yet.
+    pub fn activation(&self, step: usize) -> Option<&Bool> {
+        self.activations.get(step)
+    }
+
+    /// Unroll the transition relation up to `depth` transitions.
+    ///
+    /// Only the missing steps are asserted; does nothing if the unrolling
+    /// is already deep enough.
+    pub fn unroll(&mut self, depth: usize) {
+        while self.depth() < depth {
+            let state = self.new_state();
+            let trans = self.step(&self.trans, &self.states[self.depth()], &state);
+            self.solver.assert(trans);
+            self.add_step(state);
+        }
+    }
+
+    /// Check whether a bad state is reachable in exactly `step` transitions,
+    /// unrolling the transition relation as needed.
+    ///
+    /// Once a step is shown to be unreachable, its activation literal is
+    /// asserted false so the solver can discard the step's bad-state
+    /// condition.
+    pub fn check(&mut self, step: usize) -> SatResult {
+        self.unroll(step);
+        let activation = &self.activations[step];
+        let result = self
+            .solver
+            .check_assumptions(std::slice::from_ref(activation));
+        if result == SatResult::Unsat {
+            self.solver.assert(activation.not());
+        }
+        result
+    }
+
+    /// Find the smallest number of transitions, up to `max_depth`, in which a
+    /// bad state is reachable.
+    ///
+    /// Returns `None` if no bad state is reachable within the bound, or if
+    /// the solver gives up on a step. The solver's model is a shortest
+    /// counterexample when a depth is returned.
+    pub fn find(&mut self, max_depth: usize) -> Option<usize> {
+        for step in 0..=max_depth {
+            match self.check(step) {
+                SatResult::Sat => return Some(step),
+                SatResult::Unsat => {}
+                SatResult::Unknown => return None,
+            }
+        }
+        None
+    }
+
+    /// Extract the values of the state variables at every unrolled step
+    /// from `model`.
+    ///
+    /// The columns are named after the state variables.
+    pub fn trace(&self, model: &Model) -> Trace {
+        let names: Vec<String> = self.vars.iter().map(|v| v.decl().name()).collect();
+        Trace::from_model(
+            model,
+            self.states
+                .iter()
+                .map(|state| names.iter().cloned().zip(state.iter().cloned())),
+        )
+    }
+
+    fn new_state(&self) -> Vec<Dynamic> {
+        let step = self.states.len();
+        self.vars
+            .iter()
+            .map(|v| Dynamic::fresh_const(&format!("{}@{step}", v.decl().name()), &v.get_sort()))
+            .collect()
+    }
+
+    fn add_step(&mut self, state: Vec<Dynamic>) {
+        let step = self.states.len();
+        let activation = Bool::fresh_const(&format!("bad@{step}"));
+        self.solver
+            .assert(activation.implies(self.at(&self.bad, &state)));
+        self.states.push(state);
+        self.activations.push(activation);
+    }
+
+    /// Instantiate a condition over the state variables at `state`.
+    fn at(&self, cond: &Bool, state: &[Dynamic]) -> Bool {
+        let subst: Vec<_> = self.vars.iter().zip(state).collect();
+        cond.substitute(&subst)
+    }
+
+    /// Instantiate the transition relation from `current` to `next`.
+    fn step(&self, trans: &Bool, current: &[Dynamic], next: &[Dynamic]) -> Bool {
+        let subst: Vec<_> = self
+            .vars
+            .iter()
+            .zip(current)
+            .chain(self.next.iter().zip(next))
+            .collect();
+        trans.substitute(&subst)
+    }
+}
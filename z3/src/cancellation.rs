@@ -0,0 +1,131 @@
+use std::sync::{Arc, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+use z3_sys::{Z3_context, Z3_interrupt};
+
+/// A handle for cancelling checks from another thread.
+///
+/// Pass the token to [`Solver::check_cancellable()`](crate::Solver::check_cancellable),
+/// and call [`CancellationToken::cancel()`] on a clone of it from any thread
+/// to interrupt the check, which then returns
+/// [`SatResult::Unknown`](crate::SatResult::Unknown). Unlike
+/// [`ContextHandle`](crate::ContextHandle), the token is not tied to the
+/// lifetime of a [`Context`](crate::Context), and cancelling it also stops
+/// the checks that have not started yet.
+///
+/// # Example
+///
+/// ```
+/// # use std::time::Duration;
+/// # use z3::{CancellationToken, SatResult, Solver};
+/// # use z3::ast::Int;
+/// let token = CancellationToken::new();
+/// let canceller = token.clone();
+/// std::thread::spawn(move || {
+///     std::thread::sleep(Duration::from_millis(100));
+///     canceller.cancel();
+/// });
+///
+/// // Three cubes summing to 33 are hard to find.
+/// let v: Vec<Int> = (0..3).map(|i| Int::new_const(format!("v{i}"))).collect();
+/// let solver = Solver::new();
+/// solver.assert((&v[0] * &v[0] * &v[0] + &v[1] * &v[1] * &v[1] + &v[2] * &v[2] * &v[2]).eq(33));
+/// assert_eq!(solver.check_cancellable(&token), SatResult::Unknown);
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    cancelled: bool,
+    /// The contexts of the checks in progress.
+    running: Vec<RunningContext>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RunningContext(Z3_context);
+
+// A context is only interrupted while it is registered in `State`, and the
+// thread running the check unregisters it before the context can be dropped.
+unsafe impl Send for RunningContext {}
+
+impl CancellationToken {
+    /// Create a token which is not cancelled.
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Cancel the checks using this token, now and in the future.
+    ///
+    /// Returns once all checks in progress have stopped. Z3 forgets an
+    /// interrupt arriving just before a check starts, so the checks are
+    /// interrupted repeatedly until they are done.
+    ///
+    /// On `wasm32`, where there is no other thread to run the checks, they
+    /// are interrupted once and this returns immediately.
+    pub fn cancel(&self) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let mut state = self.state.lock().unwrap();
+            state.cancelled = true;
+            for ctx in &state.running {
+                unsafe { Z3_interrupt(ctx.0) };
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                state.cancelled = true;
+                if state.running.is_empty() {
+                    return;
+                }
+                for ctx in &state.running {
+                    unsafe { Z3_interrupt(ctx.0) };
+                }
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Returns `true` if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.lock().unwrap().cancelled
+    }
+
+    /// Run `check` for the context `ctx`, unless the token is already
+    /// cancelled, in which case `None` is returned.
+    pub(crate) fn run<R>(&self, ctx: Z3_context, check: impl FnOnce() -> R) -> Option<R> {
+        let ctx = RunningContext(ctx);
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.cancelled {
+                return None;
+            }
+            state.running.push(ctx);
+        }
+        let _registration = Registration { token: self, ctx };
+        Some(check())
+    }
+}
+
+/// Unregisters a running context, also when the check panics.
+struct Registration<'a> {
+    token: &'a CancellationToken,
+    ctx: RunningContext,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        let mut state = self.token.state.lock().unwrap();
+        if let Some(idx) = state.running.iter().position(|c| *c == self.ctx) {
+            state.running.swap_remove(idx);
+        }
+    }
+}
@@ -3,21 +3,50 @@ use std::cell::RefCell;
 use std::clone::Clone;
 use std::ffi::CString;
 use std::rc::Rc;
+use std::sync::Mutex;
 use z3_sys::*;
 
-use crate::{Config, ContextHandle};
+use crate::ast::Dynamic;
+use crate::{Config, ContextHandle, Declarations};
 
 /// A wrapper around [`Z3_context`] that enforces proper dropping behavior.
 /// All high-level code should instead use [`Context`]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct ContextInternal(pub(crate) Z3_context);
 
+impl ContextInternal {
+    /// Take ownership of `z3_ctx`, counting it in `live`, the locked
+    /// [`LIVE_CONTEXTS`].
+    fn new(z3_ctx: Z3_context, live: &mut usize) -> ContextInternal {
+        *live += 1;
+        ContextInternal(z3_ctx)
+    }
+
+    /// Delete the context, counting it out of `live`, the locked
+    /// [`LIVE_CONTEXTS`].
+    fn delete(self, live: &mut usize) {
+        crate::numeral_format::forget(self.0);
+        crate::declarations::forget(self.0);
+        unsafe { Z3_del_context(self.0) };
+        *live -= 1;
+        std::mem::forget(self);
+    }
+}
+
 impl Drop for ContextInternal {
     fn drop(&mut self) {
+        crate::numeral_format::forget(self.0);
+        crate::declarations::forget(self.0);
+        let mut live = LIVE_CONTEXTS.lock().unwrap();
         unsafe { Z3_del_context(self.0) };
+        *live -= 1;
     }
 }
 
+/// The number of contexts alive in all threads. Holding the lock prevents
+/// contexts from being created by this crate or deleted.
+static LIVE_CONTEXTS: Mutex<usize> = Mutex::new(0);
+
 /// Manager of all other Z3 objects, global configuration options, etc.
 ///
 /// An application may use multiple Z3 contexts. Objects created in one context
@@ -58,7 +87,16 @@ impl Context {
     /// # See also:
     /// - [`with_z3_config`](crate::with_z3_config)
     pub fn thread_local() -> Context {
-        DEFAULT_CONTEXT.with(|f| f.borrow().clone())
+        DEFAULT_CONTEXT.with(|f| {
+            f.borrow_mut()
+                .get_or_insert_with(|| {
+                    DEFAULT_CONFIG.with(|cfg| match &*cfg.borrow() {
+                        Some(cfg) => Context::new(cfg),
+                        None => Context::new(&Config::new()),
+                    })
+                })
+                .clone()
+        })
     }
 
     /// _Replaces_ the thread-local [`Context`] with a new one created from the given [`Config`].
@@ -74,18 +112,19 @@ impl Context {
     /// /// - [`Context::thread_local()`]
     pub(crate) fn set_thread_local(ctx: &Context) {
         DEFAULT_CONTEXT.with(|f| {
-            *f.borrow_mut() = ctx.clone();
+            *f.borrow_mut() = Some(ctx.clone());
         });
     }
 
     /// Creates a new Z3 Context using the given configuration.
     pub(crate) fn new(cfg: &Config) -> Context {
+        let mut live = LIVE_CONTEXTS.lock().unwrap();
         Context {
             z3_ctx: unsafe {
                 let p = Z3_mk_context_rc(cfg.z3_cfg).unwrap();
                 debug!("new context {p:p}");
                 Z3_set_error_handler(p, None);
-                Rc::new(ContextInternal(p))
+                Rc::new(ContextInternal::new(p, &mut live))
             },
         }
     }
@@ -117,7 +156,10 @@ impl Context {
     pub unsafe fn from_raw(z3_ctx: Z3_context) -> Context {
         debug!("from_raw context {z3_ctx:p}");
         Context {
-            z3_ctx: Rc::new(ContextInternal(z3_ctx)),
+            z3_ctx: Rc::new(ContextInternal::new(
+                z3_ctx,
+                &mut LIVE_CONTEXTS.lock().unwrap(),
+            )),
         }
     }
 
@@ -161,6 +203,94 @@ impl Context {
     pub fn update_bool_param_value(&mut self, k: &str, v: bool) {
         self.update_param_value(k, if v { "true" } else { "false" });
     }
+
+    /// Register a hook rendering numerals in the [`Display`](std::fmt::Display)
+    /// output of terms created in this context.
+    ///
+    /// `formatter` is called on every numeral of a printed term and returns
+    /// its text, or `None` to keep Z3's default rendering. This replaces any
+    /// hook registered before. [`Debug`](std::fmt::Debug) output is unaffected.
+    ///
+    /// While a hook is registered, subterms shared in the DAG are named by
+    /// `let` bindings placed at the top of the printed term. Terms printed by
+    /// the hook itself keep Z3's rendering.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::Context;
+    /// # use z3::ast::BV;
+    /// let ctx = Context::thread_local();
+    /// let x = BV::new_const("x", 16);
+    /// let t = x.bvadd(BV::from_u64(255, 16));
+    /// assert_eq!(t.to_string(), "(bvadd x #x00ff)");
+    ///
+    /// ctx.set_numeral_formatter(|n| {
+    ///     let bv = n.as_bv()?;
+    ///     Some(format!("(_ bv{} {})", bv.as_u64()?, bv.get_size()))
+    /// });
+    /// assert_eq!(t.to_string(), "(bvadd x (_ bv255 16))");
+    ///
+    /// ctx.clear_numeral_formatter();
+    /// assert_eq!(t.to_string(), "(bvadd x #x00ff)");
+    /// ```
+    pub fn set_numeral_formatter(&self, formatter: impl Fn(&Dynamic) -> Option<String> + 'static) {
+        crate::numeral_format::set(self, Some(Rc::new(formatter)));
+    }
+
+    /// Remove the hook registered with [`Context::set_numeral_formatter()`].
+    pub fn clear_numeral_formatter(&self) {
+        crate::numeral_format::set(self, None);
+    }
+
+    /// Start recording the constants, functions and sorts declared in this
+    /// context, see [`Context::declarations()`].
+    ///
+    /// Recorded are the declarations created through this crate, such as
+    /// by [`Int::new_const()`](crate::ast::Int::new_const),
+    /// [`FuncDecl::new()`](crate::FuncDecl::new) or
+    /// [`Sort::uninterpreted()`](crate::Sort::uninterpreted), and those
+    /// occurring in input parsed by
+    /// [`Solver::from_string()`](crate::Solver::from_string). Declarations
+    /// created before recording started are not included, nor are fresh
+    /// constants, such as by
+    /// [`Int::fresh_const()`](crate::ast::Int::fresh_const).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{Context, FuncDecl, Sort, Symbol};
+    /// # use z3::ast::Int;
+    /// let ctx = Context::thread_local();
+    /// ctx.enable_declarations();
+    ///
+    /// let x = Int::new_const("x");
+    /// let s = Sort::uninterpreted(Symbol::from("S"));
+    /// let f = FuncDecl::new("f", &[&s], &Sort::int());
+    ///
+    /// let decls = ctx.declarations().unwrap();
+    /// let names: Vec<String> = decls.func_decls().iter().map(|d| d.name()).collect();
+    /// assert_eq!(names, ["x", "f"]);
+    /// assert_eq!(decls.sorts(), [s]);
+    /// assert_eq!(decls.fresh_name("x"), "x0");
+    ///
+    /// ctx.disable_declarations();
+    /// assert!(ctx.declarations().is_none());
+    /// ```
+    pub fn enable_declarations(&self) {
+        crate::declarations::enable(self);
+    }
+
+    /// Stop recording declarations, and forget those recorded so far.
+    pub fn disable_declarations(&self) {
+        crate::declarations::forget(self.z3_ctx.0);
+    }
+
+    /// Returns the declarations recorded since
+    /// [`Context::enable_declarations()`], or `None` if recording is off.
+    pub fn declarations(&self) -> Option<Declarations> {
+        crate::declarations::get(self)
+    }
 }
 
 impl ContextHandle<'_> {
@@ -176,5 +306,112 @@ unsafe impl Sync for ContextHandle<'_> {}
 unsafe impl Send for ContextHandle<'_> {}
 
 thread_local! {
-    static DEFAULT_CONTEXT: RefCell<Context> = RefCell::new(Context::new(&Config::new()));
+    // Created on first use, and released by `reset_memory()`.
+    static DEFAULT_CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+    static DEFAULT_CONFIG: RefCell<Option<Config>> = const { RefCell::new(None) };
+}
+
+/// Set the configuration of the thread-local [`Context`] of the current
+/// thread, e.g. to enable proof generation for all z3 operations.
+///
+/// The thread-local context is created with this configuration on first use.
+/// If it already exists, it is _replaced_ by a new one: like with
+/// [`with_z3_config`](crate::with_z3_config), existing [`Ast`](crate::ast::Ast)s
+/// and other Z3 objects remain valid, but cannot be used with the new context
+/// without translation. Call this before creating any Z3 objects to avoid
+/// confusion.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{Config, Context, SatResult, Solver};
+/// # use z3::ast::Bool;
+/// let mut cfg = Config::new();
+/// cfg.set_proof_generation(true);
+/// z3::set_thread_local_config(cfg);
+///
+/// let solver = Solver::new();
+/// let a = Bool::new_const("a");
+/// solver.assert(&a);
+/// solver.assert(a.not());
+/// assert_eq!(solver.check(), SatResult::Unsat);
+/// assert!(solver.get_proof().is_some());
+/// ```
+pub fn set_thread_local_config(cfg: Config) {
+    DEFAULT_CONTEXT.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        if slot.is_some() {
+            *slot = Some(Context::new(&cfg));
+        }
+    });
+    DEFAULT_CONFIG.with(|f| *f.borrow_mut() = Some(cfg));
+}
+
+/// If no context remains but the thread-local context, which nothing else
+/// refers to, release it and call `f` while holding the lock on
+/// [`LIVE_CONTEXTS`], so that no context is created in the meantime.
+fn with_no_live_contexts(f: impl FnOnce()) -> bool {
+    DEFAULT_CONTEXT.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        if slot
+            .as_ref()
+            .is_some_and(|ctx| Rc::strong_count(&ctx.z3_ctx) > 1)
+        {
+            return false;
+        }
+        let mut live = LIVE_CONTEXTS.lock().unwrap();
+        if *live > usize::from(slot.is_some()) {
+            return false;
+        }
+        if let Some(ctx) = slot.take() {
+            // Dropping the context would take the lock again.
+            let Ok(ctx) = Rc::try_unwrap(ctx.z3_ctx) else {
+                unreachable!("the thread-local context is not shared");
+            };
+            ctx.delete(&mut live);
+        }
+        f();
+        true
+    })
+}
+
+/// Free the memory held by Z3's internal caches and global tables, such as
+/// the symbol table, so that long-running processes can reclaim it between
+/// large jobs.
+///
+/// This is only done when no Z3 object remains, i.e. when no [`Context`]
+/// exists other than the thread-local context of the calling thread, and
+/// that context is not used by any live object. The thread-local context is
+/// then released, and created anew on its next use. Returns `false` without
+/// doing anything otherwise.
+///
+/// Global parameters set with [`set_global_param()`](crate::set_global_param)
+/// are reset as well.
+///
+/// # Example
+///
+/// ```
+/// # use z3::ast::Int;
+/// let x = Int::new_const("x");
+/// assert!(!z3::reset_memory());
+/// drop(x);
+/// assert!(z3::reset_memory());
+/// assert_eq!(Int::from_i64(1).as_i64(), Some(1));
+/// ```
+pub fn reset_memory() -> bool {
+    with_no_live_contexts(|| unsafe { Z3_reset_memory() })
+}
+
+/// Destroy all resources allocated by Z3, e.g. before checking a process for
+/// memory leaks.
+///
+/// Like [`reset_memory()`], this is only done when no Z3 object remains, and
+/// returns `false` without doing anything otherwise.
+///
+/// # Safety
+///
+/// Z3 must not be used again by this process, in any thread, after this
+/// function returned `true`.
+pub unsafe fn finalize_memory() -> bool {
+    with_no_live_contexts(|| unsafe { Z3_finalize_memory() })
 }
@@ -0,0 +1,304 @@
+//! A small Datalog DSL on top of [`Fixedpoint`].
+//!
+//! Relations are declared with [`Relation::new`], and rules are assembled from
+//! [`Atom`]s whose arguments are either named variables or values. Variables
+//! are universally quantified over each rule and their sorts are inferred from
+//! the relation arguments they appear in, so no quantifiers have to be built
+//! by hand.
+//!
+//! # Example
+//!
+//! ```rust
+//! use z3::{Fixedpoint, Params, SatResult, Sort};
+//! use z3::ast::BV;
+//! use z3::datalog::{Relation, Rule, Term};
+//!
+//! let fp = Fixedpoint::new();
+//! let mut params = Params::new();
+//! params.set_symbol("engine", "datalog");
+//! fp.set_params(&params);
+//!
+//! let node = Sort::bitvector(4);
+//! let edge = Relation::new("edge", &[&node, &node]);
+//! let path = Relation::new("path", &[&node, &node]);
+//! let n = |v: u64| Term::value(BV::from_u64(v, 4));
+//!
+//! Rule::fact(edge.atom([n(1), n(2)])).add_to(&fp);
+//! Rule::fact(edge.atom([n(2), n(3)])).add_to(&fp);
+//! Rule::new(path.atom(["x", "y"])).when(edge.atom(["x", "y"])).add_to(&fp);
+//! Rule::new(path.atom(["x", "z"]))
+//!     .when(edge.atom(["x", "y"]))
+//!     .when(path.atom(["y", "z"]))
+//!     .add_to(&fp);
+//!
+//! assert_eq!(path.atom([n(1), n(3)]).query(&fp), SatResult::Sat);
+//! assert_eq!(path.atom([n(3), n(1)]).query(&fp), SatResult::Unsat);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::ast::{self, Ast, Bool, Dynamic};
+use crate::{Fixedpoint, FuncDecl, SatResult, Sort, Symbol};
+
+/// A relation (predicate) over the given argument sorts.
+#[derive(Debug, Clone)]
+pub struct Relation {
+    decl: FuncDecl,
+    domain: Vec<Sort>,
+}
+
+impl Relation {
+    pub fn new<S: Into<Symbol>>(name: S, domain: &[&Sort]) -> Relation {
+        Relation {
+            decl: FuncDecl::new(name, domain, &Sort::bool()),
+            domain: domain.iter().map(|&s| s.clone()).collect(),
+        }
+    }
+
+    /// Returns the declaration of the relation.
+    pub fn decl(&self) -> &FuncDecl {
+        &self.decl
+    }
+
+    /// Returns the sorts of the arguments of the relation.
+    pub fn domain(&self) -> &[Sort] {
+        &self.domain
+    }
+
+    /// Apply the relation to `args`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of arguments does not match the arity of the
+    /// relation, or if a value does not have the sort of its argument.
+    pub fn atom<T: Into<Term>>(&self, args: impl IntoIterator<Item = T>) -> Atom {
+        let args: Vec<Term> = args.into_iter().map(Into::into).collect();
+        assert_eq!(
+            args.len(),
+            self.domain.len(),
+            "Relation {} expects {} arguments",
+            self.decl.name(),
+            self.domain.len()
+        );
+        for (arg, sort) in args.iter().zip(&self.domain) {
+            if let Term::Value(v) = arg {
+                assert!(
+                    v.get_sort() == *sort,
+                    "Relation {} expects an argument of sort {sort}, got {v}",
+                    self.decl.name()
+                );
+            }
+        }
+        Atom {
+            relation: self.clone(),
+            args,
+        }
+    }
+}
+
+/// An argument of an [`Atom`]: either a named variable or a value.
+#[derive(Debug, Clone)]
+pub enum Term {
+    Var(String),
+    Value(Dynamic),
+}
+
+impl Term {
+    /// A variable, quantified over the rule it appears in.
+    pub fn var(name: impl Into<String>) -> Term {
+        Term::Var(name.into())
+    }
+
+    /// A fixed value.
+    pub fn value(value: impl Into<Dynamic>) -> Term {
+        Term::Value(value.into())
+    }
+}
+
+impl From<&str> for Term {
+    fn from(name: &str) -> Self {
+        Term::var(name)
+    }
+}
+
+impl From<String> for Term {
+    fn from(name: String) -> Self {
+        Term::var(name)
+    }
+}
+
+impl From<Dynamic> for Term {
+    fn from(value: Dynamic) -> Self {
+        Term::Value(value)
+    }
+}
+
+/// A relation applied to arguments.
+#[derive(Debug, Clone)]
+pub struct Atom {
+    relation: Relation,
+    args: Vec<Term>,
+}
+
+impl Atom {
+    /// Returns the relation of the atom.
+    pub fn relation(&self) -> &Relation {
+        &self.relation
+    }
+
+    /// Returns the arguments of the atom.
+    pub fn args(&self) -> &[Term] {
+        &self.args
+    }
+
+    /// Check whether the atom is derivable in `fp`, for some values of its
+    /// variables.
+    pub fn query(&self, fp: &Fixedpoint) -> SatResult {
+        let mut vars = Variables::default();
+        vars.bind(self);
+        let query = ast::exists_const(&vars.bounds(), &[], &vars.apply(self));
+        fp.register_relation(self.relation.decl());
+        fp.query(&query)
+    }
+}
+
+/// A Horn clause `head :- body, constraints`.
+///
+/// Every variable used in a constraint must occur in the head or in a body
+/// atom, from which its sort is inferred.
+pub struct Rule {
+    head: Atom,
+    body: Vec<Atom>,
+    constraints: Vec<Box<Constraint>>,
+    name: Option<String>,
+}
+
+type Constraint = dyn Fn(&HashMap<String, Dynamic>) -> Bool;
+
+impl Rule {
+    /// A rule deriving `head`, with an initially empty body.
+    pub fn new(head: Atom) -> Rule {
+        Rule {
+            head,
+            body: vec![],
+            constraints: vec![],
+            name: None,
+        }
+    }
+
+    /// A rule stating that `head` holds unconditionally.
+    pub fn fact(head: Atom) -> Rule {
+        Self::new(head)
+    }
+
+    /// Add an atom to the body of the rule.
+    pub fn when(mut self, atom: Atom) -> Self {
+        self.body.push(atom);
+        self
+    }
+
+    /// Add a constraint to the body of the rule.
+    ///
+    /// `constraint` receives the constants standing for the variables of the
+    /// rule, by name.
+    pub fn constraint(
+        mut self,
+        constraint: impl Fn(&HashMap<String, Dynamic>) -> Bool + 'static,
+    ) -> Self {
+        self.constraints.push(Box::new(constraint));
+        self
+    }
+
+    /// Name the rule, see [`Fixedpoint::add_rule()`].
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Lower the rule to a universally quantified implication.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a variable is used at arguments of different sorts.
+    pub fn to_bool(&self) -> Bool {
+        let mut vars = Variables::default();
+        for atom in std::iter::once(&self.head).chain(&self.body) {
+            vars.bind(atom);
+        }
+        let mut body: Vec<Bool> = self.body.iter().map(|a| vars.apply(a)).collect();
+        body.extend(self.constraints.iter().map(|c| c(&vars.consts)));
+        let head = vars.apply(&self.head);
+        let clause = if body.is_empty() {
+            head
+        } else {
+            Bool::and(&body).implies(&head)
+        };
+        ast::forall_const(&vars.bounds(), &[], &clause)
+    }
+
+    /// Register the relations of the rule with `fp` and add the rule.
+    pub fn add_to(&self, fp: &Fixedpoint) {
+        for atom in std::iter::once(&self.head).chain(&self.body) {
+            fp.register_relation(atom.relation.decl());
+        }
+        fp.add_rule(&self.to_bool(), self.name.as_deref());
+    }
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Rule")
+            .field("head", &self.head)
+            .field("body", &self.body)
+            .field("constraints", &self.constraints.len())
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// The constants standing for the variables of a rule or query.
+#[derive(Default)]
+struct Variables {
+    order: Vec<String>,
+    consts: HashMap<String, Dynamic>,
+}
+
+impl Variables {
+    fn bind(&mut self, atom: &Atom) {
+        for (arg, sort) in atom.args.iter().zip(&atom.relation.domain) {
+            let Term::Var(name) = arg else { continue };
+            match self.consts.get(name) {
+                Some(c) => assert!(
+                    c.get_sort() == *sort,
+                    "Variable {name} is used with sorts {} and {sort}",
+                    c.get_sort()
+                ),
+                None => {
+                    self.order.push(name.clone());
+                    self.consts
+                        .insert(name.clone(), Dynamic::fresh_const(name, sort));
+                }
+            }
+        }
+    }
+
+    fn bounds(&self) -> Vec<&dyn Ast> {
+        self.order
+            .iter()
+            .map(|name| &self.consts[name] as &dyn Ast)
+            .collect()
+    }
+
+    fn apply(&self, atom: &Atom) -> Bool {
+        let args: Vec<&dyn Ast> = atom
+            .args
+            .iter()
+            .map(|arg| match arg {
+                Term::Var(name) => &self.consts[name] as &dyn Ast,
+                Term::Value(v) => v as &dyn Ast,
+            })
+            .collect();
+        atom.relation.decl.apply(&args).as_bool().unwrap()
+    }
+}
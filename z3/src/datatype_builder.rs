@@ -172,6 +172,7 @@ pub fn create_datatypes(datatype_builders: Vec<DatatypeBuilder>) -> Vec<Datatype
             ctx: ctx.clone(),
             z3_sort,
         };
+        crate::declarations::record_sort(&ctx, z3_sort);
 
         let mut variants: Vec<DatatypeVariant> = Vec::with_capacity(num_cs);
 
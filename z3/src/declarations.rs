@@ -0,0 +1,219 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use z3_sys::*;
+
+use crate::ast::Ast;
+use crate::{Context, FuncDecl, Sort, Symbol};
+
+/// The declarations recorded in a [`Context`], see
+/// [`Context::enable_declarations()`].
+#[derive(Debug, Clone)]
+pub struct Declarations {
+    func_decls: Vec<FuncDecl>,
+    sorts: Vec<Sort>,
+}
+
+impl Declarations {
+    /// Returns the declared constants and functions, in order of creation.
+    pub fn func_decls(&self) -> &[FuncDecl] {
+        &self.func_decls
+    }
+
+    /// Returns the declared sorts, in order of creation.
+    ///
+    /// These are the sorts which must be declared before use: uninterpreted,
+    /// enumeration, finite domain and datatype sorts.
+    pub fn sorts(&self) -> &[Sort] {
+        &self.sorts
+    }
+
+    /// Returns `true` if a constant, function or sort is named `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.func_decls.iter().any(|d| d.name() == name)
+            || self.sorts.iter().any(|s| s.to_string() == name)
+    }
+
+    /// Returns a name starting with `prefix` which is not declared yet:
+    /// `prefix` itself if it is free, otherwise `prefix` followed by the
+    /// smallest free number.
+    pub fn fresh_name(&self, prefix: &str) -> String {
+        let taken: HashSet<String> = self
+            .func_decls
+            .iter()
+            .map(|d| d.name())
+            .chain(self.sorts.iter().map(|s| s.to_string()))
+            .collect();
+        if !taken.contains(prefix) {
+            return prefix.to_owned();
+        }
+        (0..)
+            .map(|i| format!("{prefix}{i}"))
+            .find(|name| !taken.contains(name))
+            .unwrap()
+    }
+}
+
+/// The declarations of a context, as referenced raw handles. Storing wrapped
+/// objects would keep the context alive.
+#[derive(Default)]
+struct Registry {
+    func_decls: Vec<Z3_func_decl>,
+    sorts: Vec<Z3_sort>,
+    seen: HashSet<u32>,
+}
+
+thread_local! {
+    // Contexts are not `Send`, so the registry of a context always lives in
+    // the thread owning it. They are keyed by the address of the context.
+    static REGISTRIES: RefCell<HashMap<usize, Registry>> = RefCell::new(HashMap::new());
+}
+
+fn key(z3_ctx: Z3_context) -> usize {
+    z3_ctx.as_ptr() as usize
+}
+
+pub(crate) fn enable(ctx: &Context) {
+    REGISTRIES.with(|r| {
+        r.borrow_mut().entry(key(ctx.z3_ctx.0)).or_default();
+    });
+}
+
+pub(crate) fn get(ctx: &Context) -> Option<Declarations> {
+    REGISTRIES.with(|r| {
+        let registries = r.borrow();
+        let registry = registries.get(&key(ctx.z3_ctx.0))?;
+        Some(Declarations {
+            func_decls: registry
+                .func_decls
+                .iter()
+                .map(|&d| unsafe { FuncDecl::wrap(ctx, d) })
+                .collect(),
+            sorts: registry
+                .sorts
+                .iter()
+                .map(|&s| unsafe { Sort::wrap(ctx, s) })
+                .collect(),
+        })
+    })
+}
+
+/// Drop the registry of a context, which may be being deleted.
+pub(crate) fn forget(z3_ctx: Z3_context) {
+    // The table may already be gone if the thread is exiting.
+    let registry = REGISTRIES
+        .try_with(|r| r.borrow_mut().remove(&key(z3_ctx)))
+        .ok()
+        .flatten();
+    for ast in registry.iter().flat_map(|r| r.asts(z3_ctx)) {
+        unsafe { Z3_dec_ref(z3_ctx, ast) };
+    }
+}
+
+impl Registry {
+    fn asts(&self, z3_ctx: Z3_context) -> impl Iterator<Item = Z3_ast> + '_ {
+        let decls = self
+            .func_decls
+            .iter()
+            .map(move |&d| unsafe { Z3_func_decl_to_ast(z3_ctx, d).unwrap() });
+        let sorts = self
+            .sorts
+            .iter()
+            .map(move |&s| unsafe { Z3_sort_to_ast(z3_ctx, s).unwrap() });
+        decls.chain(sorts)
+    }
+
+    /// Add `ast` to the registry unless it is already in it, and return
+    /// whether it was added.
+    fn insert(&mut self, z3_ctx: Z3_context, ast: Z3_ast) -> bool {
+        let id = unsafe { Z3_get_ast_id(z3_ctx, ast) };
+        let added = self.seen.insert(id);
+        if added {
+            unsafe { Z3_inc_ref(z3_ctx, ast) };
+        }
+        added
+    }
+
+    fn add_decl(&mut self, z3_ctx: Z3_context, decl: Z3_func_decl) {
+        if self.insert(z3_ctx, unsafe {
+            Z3_func_decl_to_ast(z3_ctx, decl).unwrap()
+        }) {
+            self.func_decls.push(decl);
+        }
+    }
+
+    fn add_sort(&mut self, z3_ctx: Z3_context, sort: Z3_sort) {
+        if self.insert(z3_ctx, unsafe { Z3_sort_to_ast(z3_ctx, sort).unwrap() }) {
+            self.sorts.push(sort);
+        }
+    }
+}
+
+fn with_registry(ctx: &Context, f: impl FnOnce(&mut Registry)) {
+    REGISTRIES.with(|r| {
+        if let Some(registry) = r.borrow_mut().get_mut(&key(ctx.z3_ctx.0)) {
+            f(registry);
+        }
+    });
+}
+
+/// Record a constant or function declaration.
+pub(crate) fn record_decl(ctx: &Context, decl: Z3_func_decl) {
+    with_registry(ctx, |registry| registry.add_decl(ctx.z3_ctx.0, decl));
+}
+
+/// Create the constant `name` of sort `sort`, and record its declaration.
+///
+/// Fresh constants are not recorded: their names are unique already, and they
+/// are mostly auxiliary or bound variables.
+pub(crate) unsafe fn mk_const(ctx: &Context, name: Symbol, sort: Z3_sort) -> Option<Z3_ast> {
+    let z3_ctx = ctx.z3_ctx.0;
+    let ast = unsafe { Z3_mk_const(z3_ctx, name.as_z3_symbol(), sort)? };
+    with_registry(ctx, |registry| {
+        let decl = unsafe { Z3_get_app_decl(z3_ctx, Z3_to_app(z3_ctx, ast).unwrap()).unwrap() };
+        registry.add_decl(z3_ctx, decl);
+    });
+    Some(ast)
+}
+
+/// Record a sort which needs a declaration.
+pub(crate) fn record_sort(ctx: &Context, sort: Z3_sort) {
+    with_registry(ctx, |registry| registry.add_sort(ctx.z3_ctx.0, sort));
+}
+
+/// Record the uninterpreted declarations and declared sorts occurring in
+/// `asts`, e.g. after parsing them.
+pub(crate) fn record_asts(ctx: &Context, asts: &[impl Ast]) {
+    with_registry(ctx, |registry| {
+        let z3_ctx = ctx.z3_ctx.0;
+        let mut visited = HashSet::new();
+        let mut todo: Vec<Z3_ast> = asts.iter().map(|a| a.get_z3_ast()).collect();
+        while let Some(ast) = todo.pop() {
+            if !visited.insert(unsafe { Z3_get_ast_id(z3_ctx, ast) }) {
+                continue;
+            }
+            unsafe {
+                let sort = Z3_get_sort(z3_ctx, ast).unwrap();
+                if matches!(
+                    Z3_get_sort_kind(z3_ctx, sort),
+                    SortKind::Uninterpreted | SortKind::Datatype | SortKind::FiniteDomain
+                ) {
+                    registry.add_sort(z3_ctx, sort);
+                }
+                match Z3_get_ast_kind(z3_ctx, ast) {
+                    AstKind::App => {
+                        let app = Z3_to_app(z3_ctx, ast).unwrap();
+                        let decl = Z3_get_app_decl(z3_ctx, app).unwrap();
+                        if Z3_get_decl_kind(z3_ctx, decl) == DeclKind::UNINTERPRETED {
+                            registry.add_decl(z3_ctx, decl);
+                        }
+                        for i in 0..Z3_get_app_num_args(z3_ctx, app) {
+                            todo.push(Z3_get_app_arg(z3_ctx, app, i).unwrap());
+                        }
+                    }
+                    AstKind::Quantifier => todo.push(Z3_get_quantifier_body(z3_ctx, ast).unwrap()),
+                    _ => {}
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,160 @@
+use std::collections::{HashMap, HashSet};
+use z3_sys::*;
+
+use crate::ast::{Ast, Bool};
+use crate::logic::walk;
+use crate::{Context, FuncDecl};
+
+/// A graph over the assertions of a [`Solver`](crate::Solver), connecting
+/// assertions which share an uninterpreted constant or function.
+///
+/// Assertions in different connected components have no symbols in common,
+/// so each component can be solved independently of the others.
+///
+/// # Example
+///
+/// ```
+/// # use z3::Solver;
+/// # use z3::ast::Int;
+/// let solver = Solver::new();
+/// let (x, y, z) = (Int::new_const("x"), Int::new_const("y"), Int::new_const("z"));
+/// solver.assert(x.gt(0));
+/// solver.assert(z.lt(0));
+/// solver.assert(x.lt(&y));
+///
+/// let graph = solver.dependency_graph();
+/// assert_eq!(graph.neighbors(0), &[2]);
+/// assert_eq!(graph.components(), vec![vec![0, 2], vec![1]]);
+/// ```
+///
+/// # See also:
+///
+/// - [`Solver::dependency_graph()`](crate::Solver::dependency_graph)
+/// - [`Solver::split_independent()`](crate::Solver::split_independent)
+#[derive(Debug, Clone)]
+pub struct DependencyGraph {
+    assertions: Vec<Bool>,
+    symbols: Vec<Vec<FuncDecl>>,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl DependencyGraph {
+    /// Build the dependency graph of `assertions`.
+    pub fn new(assertions: Vec<Bool>) -> DependencyGraph {
+        let symbols: Vec<Vec<FuncDecl>> = assertions.iter().map(uninterpreted_symbols).collect();
+
+        let mut occurrences: HashMap<&FuncDecl, Vec<usize>> = HashMap::new();
+        for (i, decls) in symbols.iter().enumerate() {
+            for decl in decls {
+                occurrences.entry(decl).or_default().push(i);
+            }
+        }
+        let mut neighbors: Vec<Vec<usize>> = vec![vec![]; assertions.len()];
+        for (i, decls) in symbols.iter().enumerate() {
+            let mut adjacent: Vec<usize> = decls
+                .iter()
+                .flat_map(|decl| &occurrences[decl])
+                .copied()
+                .filter(|&j| j != i)
+                .collect();
+            adjacent.sort_unstable();
+            adjacent.dedup();
+            neighbors[i] = adjacent;
+        }
+
+        DependencyGraph {
+            assertions,
+            symbols,
+            neighbors,
+        }
+    }
+
+    /// Returns the number of assertions in the graph.
+    pub fn len(&self) -> usize {
+        self.assertions.len()
+    }
+
+    /// Returns `true` if the graph has no assertions.
+    pub fn is_empty(&self) -> bool {
+        self.assertions.is_empty()
+    }
+
+    /// Returns the assertions, indexed by node.
+    pub fn assertions(&self) -> &[Bool] {
+        &self.assertions
+    }
+
+    /// Returns the uninterpreted constants and functions occurring in the
+    /// assertion `node`.
+    pub fn symbols(&self, node: usize) -> &[FuncDecl] {
+        &self.symbols[node]
+    }
+
+    /// Returns the assertions sharing a symbol with the assertion `node`,
+    /// in increasing order.
+    pub fn neighbors(&self, node: usize) -> &[usize] {
+        &self.neighbors[node]
+    }
+
+    /// Iterate over the edges `(i, j)` of the graph, with `i < j`.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors
+            .iter()
+            .enumerate()
+            .flat_map(|(i, adj)| adj.iter().filter(move |&&j| i < j).map(move |&j| (i, j)))
+    }
+
+    /// Returns the connected components of the graph.
+    ///
+    /// Each component lists its nodes in increasing order, and components are
+    /// ordered by their smallest node.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.len()];
+        let mut components = vec![];
+        for start in 0..self.len() {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut component = vec![];
+            let mut stack = vec![start];
+            while let Some(node) = stack.pop() {
+                component.push(node);
+                for &next in &self.neighbors[node] {
+                    if !seen[next] {
+                        seen[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            component.sort_unstable();
+            components.push(component);
+        }
+        components
+    }
+}
+
+/// Collect the uninterpreted constants and functions occurring in `ast`,
+/// in order of first occurrence, including those under quantifiers.
+fn uninterpreted_symbols(ast: &Bool) -> Vec<FuncDecl> {
+    let ctx: &Context = ast.get_ctx();
+    let z3_ctx = ctx.z3_ctx.0;
+    let mut symbols = vec![];
+    let mut seen_decls = HashSet::new();
+    walk(ctx, ast.get_z3_ast(), |node, kind| {
+        if kind == AstKind::App {
+            unsafe {
+                let app = Z3_to_app(z3_ctx, node).unwrap();
+                let decl = Z3_get_app_decl(z3_ctx, app).unwrap();
+                if Z3_get_decl_kind(z3_ctx, decl) == DeclKind::UNINTERPRETED {
+                    let decl = FuncDecl::wrap(ctx, decl);
+                    if seen_decls.insert(decl.clone()) {
+                        symbols.push(decl);
+                    }
+                }
+            }
+        }
+        true
+    });
+    symbols
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::DeclKind;
+use crate::ast::{Ast, Bool};
+
+/// A propositional formula in conjunctive normal form, numbered for export
+/// in the DIMACS format. Created by [`Goal::to_dimacs()`](crate::Goal::to_dimacs).
+///
+/// Variables are numbered from 1. The atoms passed as `first` to
+/// [`Goal::to_dimacs()`](crate::Goal::to_dimacs) come first, in order,
+/// followed by the other atoms of the goal in order of first occurrence.
+///
+/// [`Display`](fmt::Display) prints the problem in DIMACS format, with a
+/// comment line `c <var> <atom>` naming each variable.
+#[derive(Debug, Clone)]
+pub struct Dimacs {
+    atoms: Vec<Bool>,
+    vars: HashMap<Bool, i32>,
+    clauses: Vec<Vec<i32>>,
+}
+
+impl Dimacs {
+    pub(crate) fn new(formulas: &[Bool], first: &[&Bool]) -> Result<Dimacs, String> {
+        let mut dimacs = Dimacs {
+            atoms: vec![],
+            vars: HashMap::new(),
+            clauses: vec![],
+        };
+        for &atom in first {
+            if !is_atom(atom) {
+                return Err(format!("Term {atom} to number first is not an atom"));
+            }
+            dimacs.var(atom);
+        }
+        for formula in formulas {
+            let disjuncts = if formula.is_app() && formula.decl().kind() == DeclKind::OR {
+                formula
+                    .children()
+                    .iter()
+                    .map(|c| c.as_bool().unwrap())
+                    .collect()
+            } else {
+                vec![formula.clone()]
+            };
+            let mut clause = vec![];
+            let mut tautology = false;
+            for disjunct in &disjuncts {
+                let (atom, positive) = split_literal(disjunct);
+                match atom.as_bool() {
+                    Some(value) => tautology |= value == positive,
+                    None => clause.push(dimacs.add_literal(&atom, positive, formula)?),
+                }
+            }
+            if !tautology {
+                dimacs.clauses.push(clause);
+            }
+        }
+        Ok(dimacs)
+    }
+
+    fn var(&mut self, atom: &Bool) -> i32 {
+        if let Some(&var) = self.vars.get(atom) {
+            return var;
+        }
+        self.atoms.push(atom.clone());
+        let var = self.atoms.len() as i32;
+        self.vars.insert(atom.clone(), var);
+        var
+    }
+
+    fn add_literal(&mut self, atom: &Bool, positive: bool, formula: &Bool) -> Result<i32, String> {
+        if !is_atom(atom) {
+            return Err(format!("Formula {formula} is not a clause"));
+        }
+        let var = self.var(atom);
+        Ok(if positive { var } else { -var })
+    }
+
+    /// Returns the number of variables.
+    pub fn num_vars(&self) -> usize {
+        self.atoms.len()
+    }
+
+    /// Returns the clauses, as lists of non-zero literals.
+    pub fn clauses(&self) -> &[Vec<i32>] {
+        &self.clauses
+    }
+
+    /// Returns the atom of the variable `var`, if it exists.
+    pub fn atom(&self, var: u32) -> Option<&Bool> {
+        self.atoms.get((var as usize).checked_sub(1)?)
+    }
+
+    /// Returns the DIMACS literal of `literal`, an atom or its negation, if
+    /// its atom has a variable.
+    pub fn literal(&self, literal: &Bool) -> Option<i32> {
+        if let Some(&var) = self.vars.get(literal) {
+            return Some(var);
+        }
+        if literal.is_app() && literal.decl().kind() == DeclKind::NOT {
+            let atom = literal.nth_child(0)?.as_bool()?;
+            return self.vars.get(&atom).map(|&var| -var);
+        }
+        None
+    }
+}
+
+/// Split a literal into its atom and whether it is positive.
+fn split_literal(literal: &Bool) -> (Bool, bool) {
+    if literal.is_app() && literal.decl().kind() == DeclKind::NOT {
+        (literal.nth_child(0).unwrap().as_bool().unwrap(), false)
+    } else {
+        (literal.clone(), true)
+    }
+}
+
+/// Returns `true` if `b` is not built with a Boolean connective.
+fn is_atom(b: &Bool) -> bool {
+    if !b.is_app() {
+        return false;
+    }
+    match b.decl().kind() {
+        DeclKind::AND
+        | DeclKind::OR
+        | DeclKind::NOT
+        | DeclKind::IMPLIES
+        | DeclKind::IFF
+        | DeclKind::XOR
+        | DeclKind::ITE
+        | DeclKind::TRUE
+        | DeclKind::FALSE => false,
+        DeclKind::EQ | DeclKind::DISTINCT => b.nth_child(0).unwrap().as_bool().is_none(),
+        _ => true,
+    }
+}
+
+impl fmt::Display for Dimacs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        writeln!(f, "p cnf {} {}", self.atoms.len(), self.clauses.len())?;
+        for (i, atom) in self.atoms.iter().enumerate() {
+            writeln!(f, "c {} {atom}", i + 1)?;
+        }
+        for clause in &self.clauses {
+            for lit in clause {
+                write!(f, "{lit} ")?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+}
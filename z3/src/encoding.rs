@@ -0,0 +1,194 @@
+//! Boolean encodings of bounded integers.
+//!
+//! An integer known to lie in a small range can be represented by Boolean
+//! literals, so that constraints over it are solved by the SAT core instead
+//! of the arithmetic solver. The encodings here come with channeling
+//! constraints tying the literals to the original [`Int`], so constraints
+//! over the integer and over its literals can be mixed freely.
+
+use crate::ast::{self, Bool, Int};
+
+/// The order encoding of a bounded integer `x` in `lo..=hi`: one literal
+/// per value `k` in `lo + 1..=hi`, true if and only if `x >= k`.
+///
+/// Bounds on `x` are single literals, which suits problems made of many
+/// inequalities.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{SatResult, Solver};
+/// # use z3::ast::Int;
+/// # use z3::encoding::OrderEncoding;
+/// let x = Int::new_const("x");
+/// let order = OrderEncoding::new(&x, 0, 9);
+/// let solver = Solver::new();
+/// for constraint in order.constraints() {
+///     solver.assert(constraint);
+/// }
+/// solver.assert(order.ge(4));
+/// solver.assert(order.le(4));
+/// assert_eq!(solver.check(), SatResult::Sat);
+/// assert_eq!(solver.get_model().unwrap().eval(&x, true).unwrap().as_i64(), Some(4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderEncoding {
+    lo: i64,
+    /// `literals[i]` holds if and only if `x >= lo + 1 + i`.
+    literals: Vec<Bool>,
+    constraints: Vec<Bool>,
+}
+
+impl OrderEncoding {
+    /// Encode `x`, which must lie in `lo..=hi`.
+    ///
+    /// The constraints bound `x` to the range, order the literals so that
+    /// `x >= k + 1` implies `x >= k`, and define `x` as `lo` plus the number
+    /// of true literals.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lo > hi`.
+    pub fn new(x: &Int, lo: i64, hi: i64) -> OrderEncoding {
+        assert!(lo <= hi, "empty range {lo}..={hi}");
+        let literals: Vec<Bool> = (lo + 1..=hi)
+            .map(|k| Bool::fresh_const(&format!("{x}>={k}")))
+            .collect();
+        let mut constraints = vec![x.ge(lo), x.le(hi)];
+        constraints.extend(literals.windows(2).map(|w| w[1].implies(&w[0])));
+        let (zero, one) = (Int::from_i64(0), Int::from_i64(1));
+        let count = literals.iter().map(|l| l.ite(&one, &zero));
+        constraints.push(
+            x.eq(Int::add(
+                &std::iter::once(Int::from_i64(lo))
+                    .chain(count)
+                    .collect::<Vec<_>>(),
+            )),
+        );
+        OrderEncoding {
+            lo,
+            literals,
+            constraints,
+        }
+    }
+
+    /// Returns the literals, where the `i`-th one stands for `x >= lo + 1 + i`.
+    pub fn literals(&self) -> &[Bool] {
+        &self.literals
+    }
+
+    /// Returns the channeling constraints, which must be asserted for the
+    /// literals to have their meaning.
+    pub fn constraints(&self) -> &[Bool] {
+        &self.constraints
+    }
+
+    /// Returns the literal for `x >= k`, or a constant outside the range.
+    pub fn ge(&self, k: i64) -> Bool {
+        if k <= self.lo {
+            Bool::from_bool(true)
+        } else {
+            let i = usize::try_from(k - self.lo - 1).unwrap();
+            self.literals
+                .get(i)
+                .cloned()
+                .unwrap_or_else(|| Bool::from_bool(false))
+        }
+    }
+
+    /// Returns the literal for `x <= k`, or a constant outside the range.
+    pub fn le(&self, k: i64) -> Bool {
+        if k < self.lo {
+            Bool::from_bool(false)
+        } else {
+            let i = usize::try_from(k - self.lo).unwrap();
+            self.literals
+                .get(i)
+                .map_or_else(|| Bool::from_bool(true), |l| l.not())
+        }
+    }
+}
+
+/// The one-hot encoding of a bounded integer `x` in `lo..=hi`: one literal
+/// per value `k` in the range, true if and only if `x == k`.
+///
+/// Exactly one literal holds, which suits problems where `x` selects among
+/// cases, such as table lookups.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{SatResult, Solver};
+/// # use z3::ast::Int;
+/// # use z3::encoding::OneHotEncoding;
+/// let x = Int::new_const("x");
+/// let one_hot = OneHotEncoding::new(&x, 1, 3);
+/// let solver = Solver::new();
+/// for constraint in one_hot.constraints() {
+///     solver.assert(constraint);
+/// }
+/// solver.assert(one_hot.eq(1).not());
+/// solver.assert(one_hot.eq(2).not());
+/// assert_eq!(solver.check(), SatResult::Sat);
+/// assert_eq!(solver.get_model().unwrap().eval(&x, true).unwrap().as_i64(), Some(3));
+/// ```
+#[derive(Debug, Clone)]
+pub struct OneHotEncoding {
+    lo: i64,
+    /// `literals[i]` holds if and only if `x == lo + i`.
+    literals: Vec<Bool>,
+    constraints: Vec<Bool>,
+}
+
+impl OneHotEncoding {
+    /// Encode `x`, which must lie in `lo..=hi`.
+    ///
+    /// The constraints state that exactly one literal holds and define `x`
+    /// as the value of that literal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lo > hi`.
+    pub fn new(x: &Int, lo: i64, hi: i64) -> OneHotEncoding {
+        assert!(lo <= hi, "empty range {lo}..={hi}");
+        let literals: Vec<Bool> = (lo..=hi)
+            .map(|k| Bool::fresh_const(&format!("{x}=={k}")))
+            .collect();
+        let zero = Int::from_i64(0);
+        let values: Vec<Int> = literals
+            .iter()
+            .zip(lo..)
+            .map(|(l, k)| l.ite(&Int::from_i64(k), &zero))
+            .collect();
+        let constraints = vec![
+            Bool::or(&literals),
+            ast::atmost(&literals, 1),
+            x.eq(Int::add(&values)),
+        ];
+        OneHotEncoding {
+            lo,
+            literals,
+            constraints,
+        }
+    }
+
+    /// Returns the literals, where the `i`-th one stands for `x == lo + i`.
+    pub fn literals(&self) -> &[Bool] {
+        &self.literals
+    }
+
+    /// Returns the channeling constraints, which must be asserted for the
+    /// literals to have their meaning.
+    pub fn constraints(&self) -> &[Bool] {
+        &self.constraints
+    }
+
+    /// Returns the literal for `x == k`, or `false` outside the range.
+    pub fn eq(&self, k: i64) -> Bool {
+        usize::try_from(k - self.lo)
+            .ok()
+            .and_then(|i| self.literals.get(i))
+            .cloned()
+            .unwrap_or_else(|| Bool::from_bool(false))
+    }
+}
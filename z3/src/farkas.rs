@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use z3_sys::{DeclKind, SortKind};
+
+use crate::ast::{Ast, Bool, Dynamic, Int};
+use crate::{Optimize, SatResult};
+
+/// A certificate that linear integer constraints are infeasible, computed by
+/// [`farkas_certificate()`].
+///
+/// Each constraint is written `a·x <= b`, and multiplied by its multiplier.
+/// The products add up to `0 <= bound`, with a negative `bound`, which is
+/// impossible. Strict inequalities are strengthened first, e.g. `x < y`
+/// becomes `x - y <= -1`, which holds only because the constants are
+/// integers.
+#[derive(Debug, Clone)]
+pub struct FarkasCertificate {
+    multipliers: Vec<(Bool, i64)>,
+    bound: i64,
+}
+
+impl FarkasCertificate {
+    /// Returns the constraints taking part in the certificate, with their
+    /// multipliers, in the order they were given.
+    ///
+    /// Multipliers of inequalities are positive, those of equalities may be
+    /// negative.
+    pub fn multipliers(&self) -> &[(Bool, i64)] {
+        &self.multipliers
+    }
+
+    /// Returns the negative constant `bound` of the sum `0 <= bound`.
+    pub fn bound(&self) -> i64 {
+        self.bound
+    }
+}
+
+impl fmt::Display for FarkasCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        for (constraint, multiplier) in &self.multipliers {
+            writeln!(f, "{multiplier} * {constraint}")?;
+        }
+        write!(f, "sum: 0 <= {}", self.bound)
+    }
+}
+
+/// Explain why linear integer constraints are infeasible, such as those of
+/// an unsat core, by a combination of them with integer multipliers.
+///
+/// Each constraint must be a comparison (`<=`, `<`, `>=`, `>`, `=`) of
+/// linear integer terms, or the negation of an inequality. Other integer
+/// terms, such as products of constants, are treated as opaque variables.
+/// The multipliers are found with [`Optimize`], keeping their sum minimal,
+/// so constraints which are not needed are left out.
+///
+/// Returns `None` if a constraint is not of that form, or if no combination
+/// certifies infeasibility. The latter happens when the constraints have
+/// rational solutions but no integer one, e.g. `2 * x = 1`.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{SatResult, Solver, farkas_certificate};
+/// # use z3::ast::{Bool, Int};
+/// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+/// let constraints = [x.ge(&y + 2), y.ge(0), x.le(1), y.le(5)];
+/// let solver = Solver::new();
+/// assert_eq!(solver.check_assumptions(&constraints), SatResult::Unsat);
+///
+/// let certificate = farkas_certificate(&solver.get_unsat_core()).unwrap();
+/// let multipliers: Vec<i64> = certificate.multipliers().iter().map(|(_, m)| *m).collect();
+/// assert_eq!(multipliers, [1, 1, 1]);
+/// assert_eq!(certificate.bound(), -1);
+/// ```
+pub fn farkas_certificate(constraints: &[Bool]) -> Option<FarkasCertificate> {
+    let rows = constraints
+        .iter()
+        .map(Row::new)
+        .collect::<Option<Vec<Row>>>()?;
+
+    let optimize = Optimize::new();
+    let zero = Int::from_i64(0);
+    let mut multipliers = vec![];
+    let mut magnitudes = vec![];
+    for (i, row) in rows.iter().enumerate() {
+        let positive = Int::fresh_const(&format!("farkas_{i}"));
+        optimize.assert(&positive.ge(&zero));
+        magnitudes.push(positive.clone());
+        if row.equality {
+            let negative = Int::fresh_const(&format!("farkas_{i}"));
+            optimize.assert(&negative.ge(&zero));
+            magnitudes.push(negative.clone());
+            multipliers.push(positive - negative);
+        } else {
+            multipliers.push(positive);
+        }
+    }
+    let scaled = |coefficient: &dyn Fn(&Row) -> i64| {
+        let products: Vec<Int> = rows
+            .iter()
+            .zip(&multipliers)
+            .map(|(row, m)| m * coefficient(row))
+            .collect();
+        Int::add(&products)
+    };
+    let terms: Vec<&Dynamic> = rows
+        .iter()
+        .flat_map(|row| row.coefficients.keys())
+        .collect();
+    for term in terms {
+        optimize.assert(&scaled(&|row| row.coefficients.get(term).copied().unwrap_or(0)).eq(&zero));
+    }
+    let bound = scaled(&|row| row.bound);
+    optimize.assert(&bound.lt(&zero));
+    optimize.minimize(&Int::add(&magnitudes));
+    if optimize.check(&[]) != SatResult::Sat {
+        return None;
+    }
+
+    let model = optimize.get_model()?;
+    let value = |t: &Int| model.eval(t, true).and_then(|v| v.as_i64());
+    let mut certificate = vec![];
+    for (constraint, multiplier) in constraints.iter().zip(&multipliers) {
+        match value(multiplier)? {
+            0 => {}
+            m => certificate.push((constraint.clone(), m)),
+        }
+    }
+    Some(FarkasCertificate {
+        multipliers: certificate,
+        bound: value(&bound)?,
+    })
+}
+
+/// A constraint in the form `a·x <= b`, or `a·x = b`.
+struct Row {
+    coefficients: HashMap<Dynamic, i64>,
+    bound: i64,
+    equality: bool,
+}
+
+impl Row {
+    fn new(constraint: &Bool) -> Option<Row> {
+        let (constraint, negated) = match constraint.safe_decl().ok()?.kind() {
+            DeclKind::NOT => (constraint.nth_child(0)?, true),
+            _ => (Dynamic::from_ast(constraint), false),
+        };
+        if !constraint.is_app() || constraint.num_children() != 2 {
+            return None;
+        }
+        let (lhs, rhs) = (constraint.nth_child(0)?, constraint.nth_child(1)?);
+        if lhs.sort_kind() != SortKind::Int {
+            return None;
+        }
+        // Normalize `lhs op rhs` to `lhs - rhs op 0`, with `op` one of `<=`,
+        // `<` and `=`, by swapping the sides of `>=` and `>`.
+        let (kind, swapped) = match (constraint.decl().kind(), negated) {
+            (DeclKind::LE, false) | (DeclKind::GT, true) => (DeclKind::LE, false),
+            (DeclKind::LT, false) | (DeclKind::GE, true) => (DeclKind::LT, false),
+            (DeclKind::GE, false) | (DeclKind::LT, true) => (DeclKind::LE, true),
+            (DeclKind::GT, false) | (DeclKind::LE, true) => (DeclKind::LT, true),
+            (DeclKind::EQ, false) => (DeclKind::EQ, false),
+            _ => return None,
+        };
+        let mut row = Row {
+            coefficients: HashMap::new(),
+            bound: 0,
+            equality: kind == DeclKind::EQ,
+        };
+        let sign = if swapped { -1 } else { 1 };
+        row.add(&lhs, sign)?;
+        row.add(&rhs, -sign)?;
+        if kind == DeclKind::LT {
+            row.bound = row.bound.checked_sub(1)?;
+        }
+        row.coefficients.retain(|_, c| *c != 0);
+        Some(row)
+    }
+
+    /// Add `factor * term` to the left-hand side.
+    fn add(&mut self, term: &Dynamic, factor: i64) -> Option<()> {
+        if let Some(value) = term.as_int().and_then(|t| t.as_i64()) {
+            self.bound = self.bound.checked_sub(value.checked_mul(factor)?)?;
+            return Some(());
+        }
+        if !term.is_app() {
+            return self.add_opaque(term, factor);
+        }
+        let children = term.children();
+        match term.decl().kind() {
+            DeclKind::ADD => {
+                for child in &children {
+                    self.add(child, factor)?;
+                }
+            }
+            DeclKind::SUB => {
+                self.add(&children[0], factor)?;
+                for child in &children[1..] {
+                    self.add(child, factor.checked_neg()?)?;
+                }
+            }
+            DeclKind::UMINUS => self.add(&children[0], factor.checked_neg()?)?,
+            DeclKind::MUL => {
+                let (numerals, others): (Vec<_>, Vec<_>) = children
+                    .iter()
+                    .partition(|c| c.as_int().and_then(|c| c.as_i64()).is_some());
+                let mut scaled = factor;
+                for numeral in numerals {
+                    scaled = scaled.checked_mul(numeral.as_int()?.as_i64()?)?;
+                }
+                match &others[..] {
+                    [] => self.bound = self.bound.checked_sub(scaled)?,
+                    [other] => self.add(other, scaled)?,
+                    _ => self.add_opaque(term, factor)?,
+                }
+            }
+            _ => self.add_opaque(term, factor)?,
+        }
+        Some(())
+    }
+
+    fn add_opaque(&mut self, term: &Dynamic, factor: i64) -> Option<()> {
+        let coefficient = self.coefficients.entry(term.clone()).or_insert(0);
+        *coefficient = coefficient.checked_add(factor)?;
+        Some(())
+    }
+}
@@ -1,171 +1,647 @@
-use crate::ast::{Ast, Bool};
-use crate::{Context, Statistics, Params};
-use std::ffi::CString;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString, c_uint, c_void};
+use std::fmt;
 use z3_sys::*;
 
+use crate::ast::{self, Ast, Bool, Dynamic};
+use crate::{
+    AstKind, AstVector, Context, DeclKind, FuncDecl, ParamDescrs, Params, ParseError, SatResult,
+    Sort, SortKind, Statistics, Symbol,
+};
+
 /// Fixedpoint context for Horn clause solving.
-/// 
+///
 /// Fixedpoint provides facilities for solving Horn clauses and recursive predicates.
 /// It supports both bottom-up (Datalog) and top-down (PDR/IC3) solving strategies.
-#[derive(Debug)]
+//
+// Note for in-crate users: Never construct a `Fixedpoint` directly; only use
+// `Fixedpoint::new()` which handles Z3 refcounting properly.
 pub struct Fixedpoint {
     pub(crate) ctx: Context,
     pub(crate) z3_fp: Z3_fixedpoint,
-}
-
-impl Drop for Fixedpoint {
-    fn drop(&mut self) {
-        unsafe {
-            Z3_fixedpoint_dec_ref(self.ctx.z3_ctx.0, self.z3_fp);
-        }
-    }
+    // Registered callbacks must live as long as the Z3 object that calls them,
+    // and must not move since Z3 holds pointers to them.
+    #[allow(clippy::vec_box)]
+    callbacks: RefCell<Vec<Box<CallbackState>>>,
+    relations: RefCell<Vec<FuncDecl>>,
 }
 
 impl Fixedpoint {
     /// Create a new fixedpoint context.
     pub fn new() -> Fixedpoint {
-        let ctx = Context::thread_local();
+        let ctx = &Context::thread_local();
         unsafe {
-            let fp = Z3_mk_fixedpoint(ctx.z3_ctx.0);
+            let fp = Z3_mk_fixedpoint(ctx.z3_ctx.0).unwrap();
             Z3_fixedpoint_inc_ref(ctx.z3_ctx.0, fp);
             Fixedpoint {
                 ctx: ctx.clone(),
                 z3_fp: fp,
+                callbacks: RefCell::new(vec![]),
+                relations: RefCell::new(vec![]),
             }
         }
     }
 
+    pub fn get_context(&self) -> &Context {
+        &self.ctx
+    }
+
+    fn lbool_to_sat_result(res: Z3_lbool) -> SatResult {
+        match res {
+            Z3_L_FALSE => SatResult::Unsat,
+            Z3_L_UNDEF => SatResult::Unknown,
+            Z3_L_TRUE => SatResult::Sat,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Register a relation (predicate) with the fixedpoint context, so that
+    /// it is treated as recursive.
+    pub fn register_relation(&self, pred: &FuncDecl) {
+        unsafe {
+            Z3_fixedpoint_register_relation(self.ctx.z3_ctx.0, self.z3_fp, pred.z3_func_decl);
+        }
+        let mut relations = self.relations.borrow_mut();
+        if !relations.contains(pred) {
+            relations.push(pred.clone());
+        }
+    }
+
     /// Add a Horn clause rule to the fixedpoint context.
-    /// 
+    ///
+    /// Rules that are not given a name are named by the integer symbol `0`.
+    ///
     /// # Example
-    /// ```ignore
+    /// ```
+    /// # use z3::{Fixedpoint, FuncDecl, SatResult, Sort};
+    /// # use z3::ast::Bool;
     /// let fp = Fixedpoint::new();
-    /// let p = Bool::new_const("p");
-    /// let q = Bool::new_const("q");
-    /// 
-    /// // Add rule: p => q
-    /// fp.add_rule(&p.implies(&q), None);
+    /// let p = FuncDecl::new("p", &[], &Sort::bool());
+    /// let q = FuncDecl::new("q", &[], &Sort::bool());
+    /// fp.register_relation(&p);
+    /// fp.register_relation(&q);
+    ///
+    /// let p_app = p.apply(&[]).as_bool().unwrap();
+    /// let q_app = q.apply(&[]).as_bool().unwrap();
+    /// fp.add_rule(&p_app, Some("p_holds"));
+    /// fp.add_rule(&p_app.implies(&q_app), None);
+    /// assert_eq!(fp.query(&q_app), SatResult::Sat);
     /// ```
-    pub fn add_rule(&self, rule: &impl Ast, name: Option<&str>) {
+    pub fn add_rule(&self, rule: &Bool, name: Option<&str>) {
+        let name = name.map_or(Symbol::Int(0), Symbol::from);
         unsafe {
-            let name_sym = match name {
-                Some(n) => {
-                    let cname = CString::new(n).unwrap();
-                    Z3_mk_string_symbol(self.ctx.z3_ctx.0, cname.as_ptr())
-                },
-                None => std::ptr::null_mut(),
-            };
-            Z3_fixedpoint_add_rule(self.ctx.z3_ctx.0, self.z3_fp, rule.get_z3_ast(), name_sym);
+            Z3_fixedpoint_add_rule(
+                self.ctx.z3_ctx.0,
+                self.z3_fp,
+                rule.z3_ast,
+                name.as_z3_symbol(),
+            );
         }
     }
 
-    /// Add a fact (ground assertion) to the fixedpoint context.
-    pub fn add_fact(&self, pred: &impl Ast, args: &[&dyn Ast]) {
-        let args_z3: Vec<Z3_ast> = args.iter().map(|a| a.get_z3_ast()).collect();
+    /// Add a table fact to the fixedpoint context.
+    ///
+    /// The predicate must be a relation over finite domain sorts, and the
+    /// arguments are the indices of the elements of those sorts.
+    pub fn add_fact(&self, pred: &FuncDecl, args: &[u32]) {
+        let mut args = args.to_vec();
         unsafe {
             Z3_fixedpoint_add_fact(
                 self.ctx.z3_ctx.0,
                 self.z3_fp,
-                pred.get_z3_ast(),
-                args_z3.len() as u32,
-                args_z3.as_ptr(),
+                pred.z3_func_decl,
+                args.len() as u32,
+                args.as_mut_ptr(),
             );
         }
     }
 
-    /// Assert a formula in the fixedpoint context.
-    pub fn assert(&self, axiom: &impl Ast) {
-        unsafe {
-            Z3_fixedpoint_assert(self.ctx.z3_ctx.0, self.z3_fp, axiom.get_z3_ast());
+    /// Add the rules `target(xs) :- source(xs)` for every relation in `sources`,
+    /// making `target` the union of the `sources`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signature of a source differs from that of `target`.
+    pub fn add_union(&self, target: &FuncDecl, sources: &[&FuncDecl]) {
+        let sorts = self.relation_domain(target);
+        for source in sources {
+            assert!(
+                sorts == self.relation_domain(source),
+                "Can not add union rule, signature of {} does not match {}",
+                source.name(),
+                target.name()
+            );
+            let xs = Self::fresh_columns(&sorts);
+            self.add_relational_rule(&xs, &[Self::apply_relation(source, &xs)], target, &xs);
         }
     }
 
-    /// Query the fixedpoint context for satisfiability.
-    /// 
-    /// Returns the result of the query (satisfiable, unsatisfiable, or unknown).
-    pub fn query(&self, query: &impl Ast) -> Z3_lbool {
-        unsafe { Z3_fixedpoint_query(self.ctx.z3_ctx.0, self.z3_fp, query.get_z3_ast()) }
+    /// Add the rule `target(xs) :- source(xs), filter(xs)`, selecting the
+    /// tuples of `source` for which `filter` holds.
+    ///
+    /// `filter` is called once with the columns of the relation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signature of `source` differs from that of `target`.
+    pub fn add_filter(
+        &self,
+        target: &FuncDecl,
+        source: &FuncDecl,
+        filter: impl FnOnce(&[Dynamic]) -> Bool,
+    ) {
+        let sorts = self.relation_domain(target);
+        assert!(
+            sorts == self.relation_domain(source),
+            "Can not add filter rule, signature of {} does not match {}",
+            source.name(),
+            target.name()
+        );
+        let xs = Self::fresh_columns(&sorts);
+        let cond = filter(&xs);
+        self.add_relational_rule(&xs, &[Self::apply_relation(source, &xs), cond], target, &xs);
     }
 
-    /// Query the fixedpoint context with multiple relations.
-    pub fn query_relations(&self, relations: &[&dyn Ast]) -> Z3_lbool {
-        let relations_z3: Vec<Z3_ast> = relations.iter().map(|r| r.get_z3_ast()).collect();
+    /// Add the rule `target(x_c0, .., x_ck) :- source(x_0, .., x_n)`, keeping
+    /// the given `columns` of `source` in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a column is out of range or the projected columns do not
+    /// match the signature of `target`.
+    pub fn add_projection(&self, target: &FuncDecl, source: &FuncDecl, columns: &[usize]) {
+        let xs = Self::fresh_columns(&self.relation_domain(source));
+        let projected: Vec<Dynamic> = columns.iter().map(|&c| xs[c].clone()).collect();
+        let sorts: Vec<Sort> = projected.iter().map(|x| x.get_sort()).collect();
+        assert!(
+            sorts == self.relation_domain(target),
+            "Can not add projection rule, projected columns do not match {}",
+            target.name()
+        );
+        self.add_relational_rule(
+            &xs,
+            &[Self::apply_relation(source, &xs)],
+            target,
+            &projected,
+        );
+    }
+
+    /// Add the rule `target(xs, ys) :- left(xs), right(ys), x_i = y_j` for every
+    /// pair `(i, j)` in `on`.
+    ///
+    /// The columns of `target` are the columns of `left` followed by those of
+    /// `right`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a column is out of range or the signature of `target` is not
+    /// the concatenation of those of `left` and `right`.
+    pub fn add_join(
+        &self,
+        target: &FuncDecl,
+        left: &FuncDecl,
+        right: &FuncDecl,
+        on: &[(usize, usize)],
+    ) {
+        let xs = Self::fresh_columns(&self.relation_domain(left));
+        let ys = Self::fresh_columns(&self.relation_domain(right));
+        let columns: Vec<Dynamic> = xs.iter().chain(&ys).cloned().collect();
+        let sorts: Vec<Sort> = columns.iter().map(|x| x.get_sort()).collect();
+        assert!(
+            sorts == self.relation_domain(target),
+            "Can not add join rule, signature of {} does not match {} and {}",
+            target.name(),
+            left.name(),
+            right.name()
+        );
+        let mut body = vec![
+            Self::apply_relation(left, &xs),
+            Self::apply_relation(right, &ys),
+        ];
+        body.extend(on.iter().map(|&(i, j)| xs[i].eq(&ys[j])));
+        self.add_relational_rule(&columns, &body, target, &columns);
+    }
+
+    fn relation_domain(&self, pred: &FuncDecl) -> Vec<Sort> {
+        (0..pred.arity())
+            .map(|i| unsafe {
+                Sort::wrap(
+                    &self.ctx,
+                    Z3_get_domain(self.ctx.z3_ctx.0, pred.z3_func_decl, i as c_uint).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn fresh_columns(sorts: &[Sort]) -> Vec<Dynamic> {
+        sorts.iter().map(|s| Dynamic::fresh_const("x", s)).collect()
+    }
+
+    fn apply_relation(pred: &FuncDecl, args: &[Dynamic]) -> Bool {
+        let args: Vec<&dyn Ast> = args.iter().map(|a| a as &dyn Ast).collect();
+        pred.apply(&args).as_bool().unwrap()
+    }
+
+    // Register all relations involved and add `forall vars. body => head(args)`.
+    fn add_relational_rule(
+        &self,
+        vars: &[Dynamic],
+        body: &[Bool],
+        head: &FuncDecl,
+        args: &[Dynamic],
+    ) {
+        self.register_relation(head);
+        for atom in body {
+            let decl = atom.decl();
+            if decl.kind() == DeclKind::UNINTERPRETED && decl.range() == SortKind::Bool {
+                self.register_relation(&decl);
+            }
+        }
+        let rule = Bool::and(body).implies(Self::apply_relation(head, args));
+        let vars: Vec<&dyn Ast> = vars.iter().map(|v| v as &dyn Ast).collect();
+        self.add_rule(&ast::forall_const(&vars, &[], &rule), None);
+    }
+
+    /// Assert a constraint in the fixedpoint context.
+    ///
+    /// Constraints are used as background axioms when solving queries.
+    pub fn assert(&self, axiom: &Bool) {
         unsafe {
+            Z3_fixedpoint_assert(self.ctx.z3_ctx.0, self.z3_fp, axiom.z3_ast);
+        }
+    }
+
+    /// Pose a query against the asserted rules.
+    ///
+    /// Returns [`SatResult::Sat`] if the query is derivable,
+    /// [`SatResult::Unsat`] if it is not, and [`SatResult::Unknown`] otherwise.
+    pub fn query(&self, query: &Bool) -> SatResult {
+        Self::lbool_to_sat_result(unsafe {
+            Z3_fixedpoint_query(self.ctx.z3_ctx.0, self.z3_fp, query.z3_ast)
+        })
+    }
+
+    /// Pose a query against the asserted rules, starting the search at
+    /// unrolling level `lvl` instead of level 0.
+    ///
+    /// This is only supported by the Spacer engine, and is useful to resume a
+    /// bounded search once the lower levels are known to be exhausted.
+    ///
+    /// # See also:
+    ///
+    /// - [`Fixedpoint::query()`]
+    /// - [`SpacerConfig::max_level()`]
+    pub fn query_from_lvl(&self, query: &Bool, lvl: u32) -> SatResult {
+        Self::lbool_to_sat_result(unsafe {
+            Z3_fixedpoint_query_from_lvl(self.ctx.z3_ctx.0, self.z3_fp, query.z3_ast, lvl)
+        })
+    }
+
+    /// Pose a query against the asserted rules, checking whether any of the
+    /// given relations is non-empty.
+    pub fn query_relations(&self, relations: &[&FuncDecl]) -> SatResult {
+        let relations: Vec<Z3_func_decl> = relations.iter().map(|r| r.z3_func_decl).collect();
+        Self::lbool_to_sat_result(unsafe {
             Z3_fixedpoint_query_relations(
                 self.ctx.z3_ctx.0,
                 self.z3_fp,
-                relations_z3.len() as u32,
-                relations_z3.as_ptr(),
+                relations.len() as u32,
+                relations.as_ptr(),
             )
-        }
+        })
     }
 
-    /// Get the answer substitution after a successful query.
-    /// This provides the concrete values that make the query satisfiable.
+    /// Retrieve a formula that encodes satisfying answers to the last query.
+    ///
+    /// When the engine is PDR/Spacer and the query is unsatisfiable, this is
+    /// an inductive invariant; when it is satisfiable, it is a derivation.
     pub fn get_answer(&self) -> Option<Bool> {
         unsafe {
-            let answer = Z3_fixedpoint_get_answer(self.ctx.z3_ctx.0, self.z3_fp);
-            if answer.is_some() {
-                Some(Bool::wrap(&self.ctx, answer.unwrap()))
-            } else {
-                None
+            let answer = Z3_fixedpoint_get_answer(self.ctx.z3_ctx.0, self.z3_fp)?;
+            Some(Bool::wrap(&self.ctx, answer))
+        }
+    }
+
+    /// Decode the answer of the last [`Fixedpoint::query_relations()`] with
+    /// the Datalog engine into the tuples of each queried relation.
+    ///
+    /// `relations` must be the relations passed to the query, in the same
+    /// order. Returns `None` if there is no answer, or if it does not have the
+    /// shape of an explicit enumeration of tuples (e.g. because a relation
+    /// holds for all values of an argument).
+    pub fn get_answer_tuples(
+        &self,
+        relations: &[&FuncDecl],
+    ) -> Option<HashMap<FuncDecl, Vec<Vec<Dynamic>>>> {
+        let answer = Dynamic::from_ast(&self.get_answer()?);
+        let parts = if relations.len() == 1 {
+            vec![answer]
+        } else if answer.decl().kind() == DeclKind::AND && answer.num_children() == relations.len()
+        {
+            answer.children()
+        } else {
+            return None;
+        };
+        relations
+            .iter()
+            .zip(parts)
+            .map(|(pred, part)| Some(((*pred).clone(), self.decode_tuples(&part, pred.arity())?)))
+            .collect()
+    }
+
+    // Decode a disjunction of tuples `(and (= (:var 0) v0) .. (= (:var n) vn))`.
+    fn decode_tuples(&self, answer: &Dynamic, arity: usize) -> Option<Vec<Vec<Dynamic>>> {
+        match answer.safe_decl().ok()?.kind() {
+            DeclKind::FALSE => Some(vec![]),
+            DeclKind::TRUE if arity == 0 => Some(vec![vec![]]),
+            DeclKind::OR => answer
+                .children()
+                .iter()
+                .map(|t| self.decode_tuple(t, arity))
+                .collect(),
+            _ => Some(vec![self.decode_tuple(answer, arity)?]),
+        }
+    }
+
+    fn decode_tuple(&self, tuple: &Dynamic, arity: usize) -> Option<Vec<Dynamic>> {
+        let eqs = if arity == 1 {
+            vec![tuple.clone()]
+        } else if tuple.safe_decl().ok()?.kind() == DeclKind::AND {
+            tuple.children()
+        } else {
+            return None;
+        };
+        let mut values = vec![None; arity];
+        for eq in eqs {
+            if eq.safe_decl().ok()?.kind() != DeclKind::EQ {
+                return None;
+            }
+            let (var, value) = (eq.nth_child(0)?, eq.nth_child(1)?);
+            if var.kind() != AstKind::Var {
+                return None;
+            }
+            let idx = unsafe { Z3_get_index_value(self.ctx.z3_ctx.0, var.z3_ast) } as usize;
+            match values.get_mut(idx) {
+                Some(slot @ None) => *slot = Some(value),
+                _ => return None,
             }
         }
+        values.into_iter().collect()
+    }
+
+    /// Retrieve a ground instance of the counterexample found by the last
+    /// satisfiable query.
+    ///
+    /// This is only supported by the Spacer engine.
+    pub fn get_ground_sat_answer(&self) -> Option<Bool> {
+        unsafe {
+            let answer = Z3_fixedpoint_get_ground_sat_answer(self.ctx.z3_ctx.0, self.z3_fp)?;
+            Some(Bool::wrap(&self.ctx, answer))
+        }
+    }
+
+    /// Retrieve the rules used along the counterexample trace of the last
+    /// satisfiable query.
+    ///
+    /// This is only supported by the Spacer engine.
+    ///
+    /// # See also:
+    ///
+    /// - [`Fixedpoint::get_rule_names_along_trace()`]
+    /// - [`Fixedpoint::get_derivation()`]
+    pub fn get_rules_along_trace(&self) -> AstVector {
+        unsafe {
+            AstVector::wrap(
+                &self.ctx,
+                Z3_fixedpoint_get_rules_along_trace(self.ctx.z3_ctx.0, self.z3_fp).unwrap(),
+            )
+        }
+    }
+
+    /// Retrieve the names of the rules used along the counterexample trace of
+    /// the last satisfiable query, in the same order as
+    /// [`Fixedpoint::get_rules_along_trace()`].
+    ///
+    /// The list may start with additional entries for the query itself.
+    ///
+    /// This is only supported by the Spacer engine.
+    pub fn get_rule_names_along_trace(&self) -> Vec<String> {
+        let names = unsafe {
+            let symbol =
+                Z3_fixedpoint_get_rule_names_along_trace(self.ctx.z3_ctx.0, self.z3_fp).unwrap();
+            CStr::from_ptr(Z3_get_symbol_string(self.ctx.z3_ctx.0, symbol))
+                .to_string_lossy()
+                .into_owned()
+        };
+        // Z3 joins the rule names with ';'.
+        names.split(';').map(str::to_string).collect()
+    }
+
+    /// Retrieve the counterexample of the last satisfiable query as a
+    /// structured [`Derivation`].
+    ///
+    /// The steps name the applied rules but do not bind their variables,
+    /// as Z3 does not expose the instantiation of each step; see
+    /// [`DerivationStep`].
+    ///
+    /// Returns `None` if the engine did not produce a trace.
+    ///
+    /// This is only supported by the Spacer engine.
+    ///
+    /// # Example
+    /// ```
+    /// # use z3::{Fixedpoint, FuncDecl, Params, SatResult, Sort};
+    /// let fp = Fixedpoint::new();
+    /// let mut params = Params::new();
+    /// params.set_symbol("engine", "spacer");
+    /// fp.set_params(&params);
+    /// fp.from_string(
+    ///     "(declare-rel inv (Int))
+    ///      (declare-rel err ())
+    ///      (declare-var x Int)
+    ///      (rule (=> (= x 0) (inv x)) base)
+    ///      (rule (=> (and (inv x) (< x 2)) (inv (+ x 1))) step)
+    ///      (rule (=> (and (inv x) (= x 2)) err) bad)",
+    /// )
+    /// .unwrap();
+    /// let err = FuncDecl::new("err", &[], &Sort::bool());
+    /// assert_eq!(fp.query(&err.apply(&[]).as_bool().unwrap()), SatResult::Sat);
+    ///
+    /// let derivation = fp.get_derivation().unwrap();
+    /// let names: Vec<&str> = derivation.steps().iter().map(|s| s.name.as_str()).collect();
+    /// assert_eq!(names, ["bad", "step", "step", "base"]);
+    /// ```
+    pub fn get_derivation(&self) -> Option<Derivation> {
+        let rules = self.get_rules_along_trace();
+        if rules.is_empty() {
+            return None;
+        }
+        let names = self.get_rule_names_along_trace();
+        // Skip the names of the query, which has no rule on the trace.
+        let skip = names.len().saturating_sub(rules.len());
+        let steps = (&rules)
+            .into_iter()
+            .enumerate()
+            .map(|(i, rule)| DerivationStep {
+                name: names.get(skip + i).cloned().unwrap_or_default(),
+                rule: rule.as_bool().unwrap(),
+            })
+            .collect();
+        Some(Derivation {
+            steps,
+            ground_answer: self.get_ground_sat_answer(),
+        })
     }
 
-    /// Get the reason (core) for unsatisfiability after an unsuccessful query.
-    pub fn get_reason_unknown(&self) -> String {
+    /// Register closures that the Spacer engine invokes while solving.
+    ///
+    /// Callbacks accumulate: registering a second set does not remove the
+    /// first. They stay alive as long as this `Fixedpoint`.
+    ///
+    /// Spacer only reports lemmas when lemma sharing is enabled, i.e. when
+    /// the `spacer.p3.share_lemmas` (and, for invariants,
+    /// `spacer.p3.share_invariants`) parameters are set.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::cell::RefCell;
+    /// # use std::rc::Rc;
+    /// # use z3::{Fixedpoint, FixedpointCallbacks, FuncDecl, Params, SatResult, Sort};
+    /// let fp = Fixedpoint::new();
+    /// let mut params = Params::new();
+    /// params.set_symbol("engine", "spacer");
+    /// params.set_bool("spacer.p3.share_lemmas", true);
+    /// params.set_bool("spacer.p3.share_invariants", true);
+    /// fp.set_params(&params);
+    ///
+    /// let lemmas = Rc::new(RefCell::new(vec![]));
+    /// let sink = lemmas.clone();
+    /// fp.add_callbacks(
+    ///     FixedpointCallbacks::new()
+    ///         .on_lemma(move |lemma, level| sink.borrow_mut().push((lemma.to_string(), level))),
+    /// );
+    /// fp.from_string(
+    ///     "(declare-rel inv (Int))
+    ///      (declare-rel err ())
+    ///      (declare-var x Int)
+    ///      (rule (=> (= x 0) (inv x)))
+    ///      (rule (=> (and (inv x) (< x 10)) (inv (+ x 1))))
+    ///      (rule (=> (and (inv x) (< x 0)) err))",
+    /// )
+    /// .unwrap();
+    /// let err = FuncDecl::new("err", &[], &Sort::bool());
+    /// assert_eq!(fp.query(&err.apply(&[]).as_bool().unwrap()), SatResult::Unsat);
+    /// assert!(!lemmas.borrow().is_empty());
+    /// ```
+    pub fn add_callbacks(&self, callbacks: FixedpointCallbacks) {
+        let new_lemma_eh: Z3_fixedpoint_new_lemma_eh = callbacks
+            .on_lemma
+            .as_ref()
+            .map(|_| new_lemma_trampoline as _);
+        let predecessor_eh: Z3_fixedpoint_predecessor_eh = callbacks
+            .on_predecessor
+            .as_ref()
+            .map(|_| predecessor_trampoline as _);
+        let unfold_eh: Z3_fixedpoint_unfold_eh =
+            callbacks.on_unfold.as_ref().map(|_| unfold_trampoline as _);
+        let mut state = Box::new(CallbackState {
+            ctx: self.ctx.clone(),
+            callbacks,
+        });
         unsafe {
-            let reason = Z3_fixedpoint_get_reason_unknown(self.ctx.z3_ctx.0, self.z3_fp);
-            std::ffi::CStr::from_ptr(reason).to_string_lossy().into_owned()
+            Z3_fixedpoint_add_callback(
+                self.ctx.z3_ctx.0,
+                self.z3_fp,
+                &mut *state as *mut CallbackState as *mut c_void,
+                new_lemma_eh,
+                predecessor_eh,
+                unfold_eh,
+            );
+        }
+        self.callbacks.borrow_mut().push(state);
+    }
+
+    /// Retrieve a string that describes the last status returned by [`Fixedpoint::query()`].
+    pub fn get_reason_unknown(&self) -> Option<String> {
+        let p = unsafe { Z3_fixedpoint_get_reason_unknown(self.ctx.z3_ctx.0, self.z3_fp) };
+        if p.is_null() {
+            return None;
         }
+        unsafe { CStr::from_ptr(p) }
+            .to_str()
+            .ok()
+            .map(|s| s.to_string())
     }
 
-    /// Update a named parameter in the fixedpoint context.
-    pub fn update_rule(&self, rule: &impl Ast, name: &str) {
+    /// Update a named rule. A rule with the same name must have been
+    /// previously added.
+    pub fn update_rule(&self, rule: &Bool, name: &str) {
+        let name = Symbol::from(name);
         unsafe {
-            let cname = CString::new(name).unwrap();
-            let name_sym = Z3_mk_string_symbol(self.ctx.z3_ctx.0, cname.as_ptr());
-            Z3_fixedpoint_update_rule(self.ctx.z3_ctx.0, self.z3_fp, rule.get_z3_ast(), name_sym);
+            Z3_fixedpoint_update_rule(
+                self.ctx.z3_ctx.0,
+                self.z3_fp,
+                rule.z3_ast,
+                name.as_z3_symbol(),
+            );
         }
     }
 
-    /// Get the number of levels explored during the last query.
-    pub fn get_num_levels(&self) -> u32 {
-        unsafe { Z3_fixedpoint_get_num_levels(self.ctx.z3_ctx.0, self.z3_fp) }
+    /// Query the PDR engine for the maximal level where a non-trivial
+    /// inductive property for `pred` was found.
+    pub fn get_num_levels(&self, pred: &FuncDecl) -> u32 {
+        unsafe { Z3_fixedpoint_get_num_levels(self.ctx.z3_ctx.0, self.z3_fp, pred.z3_func_decl) }
     }
 
-    /// Get the cover (approximation) at a given level.
-    pub fn get_cover_delta(&self, level: i32, predicate: &impl Ast) -> Option<Bool> {
+    /// Retrieve the property for `pred` at the given `level`. A `level` of
+    /// `-1` refers to the property at infinity.
+    pub fn get_cover_delta(&self, level: i32, pred: &FuncDecl) -> Option<Bool> {
         unsafe {
             let delta = Z3_fixedpoint_get_cover_delta(
                 self.ctx.z3_ctx.0,
                 self.z3_fp,
                 level,
-                predicate.get_z3_ast(),
-            );
-            if delta.is_some() {
-                Some(Bool::wrap(&self.ctx, delta.unwrap()))
-            } else {
-                None
-            }
+                pred.z3_func_decl,
+            )?;
+            Some(Bool::wrap(&self.ctx, delta))
         }
     }
 
-    /// Add a cover for a predicate at a given level.
-    pub fn add_cover(&self, level: i32, predicate: &impl Ast, property: &impl Ast) {
+    /// Retrieve the inductive invariant found for every relation registered
+    /// with [`Fixedpoint::register_relation()`].
+    ///
+    /// This is meaningful after a [`Fixedpoint::query()`] returned
+    /// [`SatResult::Unsat`], in which case the invariants together form a
+    /// certificate of safety. Each invariant is the cover delta at infinity
+    /// and is a formula over the de Bruijn indexed arguments of its relation.
+    ///
+    /// Relations declared by [`Fixedpoint::from_string()`] must be registered
+    /// as well to be included.
+    pub fn get_invariants(&self) -> HashMap<FuncDecl, Bool> {
+        self.relations
+            .borrow()
+            .iter()
+            .filter_map(|pred| Some((pred.clone(), self.get_cover_delta(-1, pred)?)))
+            .collect()
+    }
+
+    /// Add a property about `pred` at the given `level`.
+    ///
+    /// The property is a formula over the de Bruijn indexed arguments of `pred`.
+    pub fn add_cover(&self, level: i32, pred: &FuncDecl, property: &Bool) {
         unsafe {
             Z3_fixedpoint_add_cover(
                 self.ctx.z3_ctx.0,
                 self.z3_fp,
                 level,
-                predicate.get_z3_ast(),
-                property.get_z3_ast(),
+                pred.z3_func_decl,
+                property.z3_ast,
             );
         }
     }
 
-    /// Get statistics about the last query.
+    /// Retrieve statistics information from the last call to [`Fixedpoint::query()`].
     pub fn get_statistics(&self) -> Statistics {
         unsafe {
             Statistics::wrap(
@@ -182,53 +658,387 @@ impl Fixedpoint {
         }
     }
 
-    /// Get the help string for fixedpoint parameters.
-    pub fn get_help() -> String {
-        let ctx = Context::thread_local();
-        let fp = unsafe { Z3_mk_fixedpoint(ctx.z3_ctx.0) };
+    /// Select the Spacer engine and apply the options in `config`.
+    ///
+    /// Options that were not set in `config` keep their current values.
+    pub fn set_spacer_config(&self, config: &SpacerConfig) {
+        self.set_params(&config.to_params());
+    }
+
+    /// Return the descriptions of all available parameters for fixedpoint contexts.
+    ///
+    /// ```
+    /// # use z3::{Fixedpoint, ParamKind};
+    /// let fp = Fixedpoint::new();
+    /// let descrs = fp.get_param_descrs();
+    /// assert_eq!(descrs.get_kind("engine"), Some(ParamKind::Symbol));
+    /// assert_eq!(descrs.get_kind("no_such_option"), None);
+    /// ```
+    pub fn get_param_descrs(&self) -> ParamDescrs {
         unsafe {
-            let help = Z3_fixedpoint_get_help(ctx.z3_ctx.0, fp);
-            std::ffi::CStr::from_ptr(help).to_string_lossy().into_owned()
+            ParamDescrs::wrap(
+                &self.ctx,
+                Z3_fixedpoint_get_param_descrs(self.ctx.z3_ctx.0, self.z3_fp).unwrap(),
+            )
         }
     }
 
-    /// Convert the fixedpoint context to a string representation.
-    /// This includes all rules, facts, and assertions.
-    pub fn to_string(&self) -> String {
+    /// Return a string describing all available parameters for fixedpoint
+    /// contexts, see [`HelpEntry::parse()`](crate::HelpEntry::parse).
+    pub fn get_help(&self) -> String {
         unsafe {
-            let s = Z3_fixedpoint_to_string(self.ctx.z3_ctx.0, self.z3_fp, 0, std::ptr::null_mut());
-            std::ffi::CStr::from_ptr(s).to_string_lossy().into_owned()
+            let help = Z3_fixedpoint_get_help(self.ctx.z3_ctx.0, self.z3_fp);
+            CStr::from_ptr(help).to_string_lossy().into_owned()
         }
     }
 
-    /// Parse a fixedpoint problem from a string in SMT-LIB format.
-    pub fn from_string(&self, s: &str) -> Result<(), String> {
-        let cs = CString::new(s).map_err(|_| "String contains null byte")?;
+    /// Parse an SMT-LIB2 string with fixedpoint rules, adding the rules
+    /// and assertions to this context.
+    ///
+    /// On success, returns the queries contained in the string.
+    pub fn from_string(&self, source: &str) -> Result<AstVector, ParseError> {
+        let source = CString::new(source).map_err(|e| ParseError::new(e.to_string()))?;
+        let queries = ParseError::catch(&self.ctx, || unsafe {
+            Z3_fixedpoint_from_string(self.ctx.z3_ctx.0, self.z3_fp, source.as_ptr())
+        })?;
+        queries
+            .map(|v| unsafe { AstVector::wrap(&self.ctx, v) })
+            .ok_or_else(|| ParseError::new("Failed to parse fixedpoint from string"))
+    }
+
+    /// Parse an SMT-LIB2 file with fixedpoint rules, adding the rules
+    /// and assertions to this context.
+    ///
+    /// On success, returns the queries contained in the file.
+    pub fn from_file(&self, filename: &str) -> Result<AstVector, ParseError> {
+        let filename = CString::new(filename).map_err(|e| ParseError::new(e.to_string()))?;
+        let queries = ParseError::catch(&self.ctx, || unsafe {
+            Z3_fixedpoint_from_file(self.ctx.z3_ctx.0, self.z3_fp, filename.as_ptr())
+        })?;
+        queries
+            .map(|v| unsafe { AstVector::wrap(&self.ctx, v) })
+            .ok_or_else(|| ParseError::new("Failed to parse fixedpoint from file"))
+    }
+
+    /// Retrieve the rules added to the fixedpoint context, including those
+    /// loaded with [`Fixedpoint::from_string()`] or [`Fixedpoint::from_file()`].
+    ///
+    /// # Example
+    /// ```
+    /// # use z3::Fixedpoint;
+    /// let fp = Fixedpoint::new();
+    /// fp.from_string(
+    ///     "(declare-rel p (Int))
+    ///      (declare-var x Int)
+    ///      (rule (=> (< x 3) (p x)))
+    ///      (declare-const y Int)
+    ///      (assert (> y 0))",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(fp.get_rules().len(), 1);
+    /// assert_eq!(fp.get_assertions().len(), 1);
+    /// ```
+    pub fn get_rules(&self) -> AstVector {
         unsafe {
-            let result = Z3_fixedpoint_from_string(self.ctx.z3_ctx.0, self.z3_fp, cs.as_ptr());
-            match result {
-                Some(_) => Ok(()),
-                None => Err("Failed to parse fixedpoint from string".to_string()),
-            }
+            AstVector::wrap(
+                &self.ctx,
+                Z3_fixedpoint_get_rules(self.ctx.z3_ctx.0, self.z3_fp).unwrap(),
+            )
         }
     }
 
-    /// Parse a file containing a fixedpoint problem.
-    pub fn from_file(&self, filename: &str) -> Result<(), String> {
-        let cs = CString::new(filename).map_err(|_| "Filename contains null byte")?;
+    /// Retrieve the background assertions added to the fixedpoint context.
+    ///
+    /// # See also:
+    ///
+    /// - [`Fixedpoint::assert()`]
+    pub fn get_assertions(&self) -> AstVector {
         unsafe {
-            let result = Z3_fixedpoint_from_file(self.ctx.z3_ctx.0, self.z3_fp, cs.as_ptr());
-            match result {
-                Some(_) => Ok(()),
-                None => Err("Failed to parse fixedpoint from file".to_string()),
+            AstVector::wrap(
+                &self.ctx,
+                Z3_fixedpoint_get_assertions(self.ctx.z3_ctx.0, self.z3_fp).unwrap(),
+            )
+        }
+    }
+
+    /// Convert the fixedpoint context to a string in SMT-LIB2 format,
+    /// including the given `queries`.
+    pub fn to_smt2(&self, queries: &[&Bool]) -> String {
+        let mut queries: Vec<Z3_ast> = queries.iter().map(|q| q.z3_ast).collect();
+        unsafe {
+            let s = Z3_fixedpoint_to_string(
+                self.ctx.z3_ctx.0,
+                self.z3_fp,
+                queries.len() as u32,
+                queries.as_mut_ptr(),
+            );
+            CStr::from_ptr(s).to_string_lossy().into_owned()
+        }
+    }
+}
+
+/// Closures invoked by the Spacer engine during solving.
+///
+/// A callback that panics aborts the process, since it is invoked from Z3.
+///
+/// # See also:
+///
+/// - [`Fixedpoint::add_callbacks()`]
+#[derive(Default)]
+pub struct FixedpointCallbacks {
+    on_lemma: Option<LemmaCallback>,
+    on_predecessor: Option<Box<dyn FnMut()>>,
+    on_unfold: Option<Box<dyn FnMut()>>,
+}
+
+impl FixedpointCallbacks {
+    pub fn new() -> FixedpointCallbacks {
+        Self::default()
+    }
+
+    /// Called with every new lemma and the level it was learned at.
+    /// Lemmas that are inductive invariants have level `u32::MAX`.
+    pub fn on_lemma(mut self, f: impl FnMut(&Bool, u32) + 'static) -> Self {
+        self.on_lemma = Some(Box::new(f));
+        self
+    }
+
+    /// Called whenever Spacer computes a predecessor.
+    pub fn on_predecessor(mut self, f: impl FnMut() + 'static) -> Self {
+        self.on_predecessor = Some(Box::new(f));
+        self
+    }
+
+    /// Called whenever Spacer unfolds the transition relation one more level.
+    pub fn on_unfold(mut self, f: impl FnMut() + 'static) -> Self {
+        self.on_unfold = Some(Box::new(f));
+        self
+    }
+}
+
+impl fmt::Debug for FixedpointCallbacks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("FixedpointCallbacks")
+            .field("on_lemma", &self.on_lemma.is_some())
+            .field("on_predecessor", &self.on_predecessor.is_some())
+            .field("on_unfold", &self.on_unfold.is_some())
+            .finish()
+    }
+}
+
+/// Typed options for the Spacer engine of a [`Fixedpoint`] context.
+///
+/// Every option is left at the Z3 default unless set explicitly.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{Fixedpoint, SpacerConfig};
+/// let fp = Fixedpoint::new();
+/// fp.set_spacer_config(
+///     &SpacerConfig::new()
+///         .max_level(20)
+///         .push_pob(true)
+///         .quantified_lemmas(false),
+/// );
+/// ```
+///
+/// # See also:
+///
+/// - [`Fixedpoint::set_spacer_config()`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpacerConfig {
+    push_pob: Option<bool>,
+    push_pob_max_depth: Option<u32>,
+    gpdr: Option<bool>,
+    max_level: Option<u32>,
+    quantified_lemmas: Option<bool>,
+    share_lemmas: Option<bool>,
+    share_invariants: Option<bool>,
+    random_seed: Option<u32>,
+}
+
+impl SpacerConfig {
+    pub fn new() -> SpacerConfig {
+        Self::default()
+    }
+
+    /// Push blocked proof obligations to higher levels (`spacer.push_pob`).
+    pub fn push_pob(mut self, enable: bool) -> Self {
+        self.push_pob = Some(enable);
+        self
+    }
+
+    /// Maximum depth at which proof obligations are pushed
+    /// (`spacer.push_pob_max_depth`).
+    pub fn push_pob_max_depth(mut self, depth: u32) -> Self {
+        self.push_pob_max_depth = Some(depth);
+        self
+    }
+
+    /// Use the GPDR strategy for non-linear CHCs (`spacer.gpdr`).
+    pub fn gpdr(mut self, enable: bool) -> Self {
+        self.gpdr = Some(enable);
+        self
+    }
+
+    /// Maximum level to explore (`spacer.max_level`).
+    pub fn max_level(mut self, level: u32) -> Self {
+        self.max_level = Some(level);
+        self
+    }
+
+    /// Allow quantified lemmas in frames (`spacer.q3`).
+    pub fn quantified_lemmas(mut self, enable: bool) -> Self {
+        self.quantified_lemmas = Some(enable);
+        self
+    }
+
+    /// Share frame lemmas between parallel workers (`spacer.p3.share_lemmas`).
+    pub fn share_lemmas(mut self, enable: bool) -> Self {
+        self.share_lemmas = Some(enable);
+        self
+    }
+
+    /// Share invariants between parallel workers (`spacer.p3.share_invariants`).
+    pub fn share_invariants(mut self, enable: bool) -> Self {
+        self.share_invariants = Some(enable);
+        self
+    }
+
+    /// Random seed of the underlying SMT solver (`spacer.random_seed`).
+    pub fn random_seed(mut self, seed: u32) -> Self {
+        self.random_seed = Some(seed);
+        self
+    }
+
+    /// Build the [`Params`] selecting the Spacer engine with these options.
+    pub fn to_params(&self) -> Params {
+        let mut params = Params::new();
+        params.set_symbol("engine", "spacer");
+        let bools = [
+            ("spacer.push_pob", self.push_pob),
+            ("spacer.gpdr", self.gpdr),
+            ("spacer.q3", self.quantified_lemmas),
+            ("spacer.p3.share_lemmas", self.share_lemmas),
+            ("spacer.p3.share_invariants", self.share_invariants),
+        ];
+        for (k, v) in bools {
+            if let Some(v) = v {
+                params.set_bool(k, v);
+            }
+        }
+        let u32s = [
+            ("spacer.push_pob_max_depth", self.push_pob_max_depth),
+            ("spacer.max_level", self.max_level),
+            ("spacer.random_seed", self.random_seed),
+        ];
+        for (k, v) in u32s {
+            if let Some(v) = v {
+                params.set_u32(k, v);
             }
         }
+        params
     }
+}
+
+type LemmaCallback = Box<dyn FnMut(&Bool, u32)>;
+
+struct CallbackState {
+    ctx: Context,
+    callbacks: FixedpointCallbacks,
+}
+
+unsafe extern "C" fn new_lemma_trampoline(state: *mut c_void, lemma: Z3_ast, level: c_uint) {
+    let state = unsafe { &mut *(state as *mut CallbackState) };
+    if let Some(f) = state.callbacks.on_lemma.as_mut() {
+        let lemma = unsafe { Bool::wrap(&state.ctx, lemma) };
+        f(&lemma, level);
+    }
+}
+
+unsafe extern "C" fn predecessor_trampoline(state: *mut c_void) {
+    let state = unsafe { &mut *(state as *mut CallbackState) };
+    if let Some(f) = state.callbacks.on_predecessor.as_mut() {
+        f();
+    }
+}
+
+unsafe extern "C" fn unfold_trampoline(state: *mut c_void) {
+    let state = unsafe { &mut *(state as *mut CallbackState) };
+    if let Some(f) = state.callbacks.on_unfold.as_mut() {
+        f();
+    }
+}
+
+/// A single rule application along a counterexample [`Derivation`].
+///
+/// The step holds the rule as declared, with its variables unbound: Z3
+/// reports the rules along the trace, but not the values their variables
+/// take in each application. The ground instance of the counterexample as
+/// a whole is available from [`Derivation::ground_answer()`].
+#[derive(Debug, Clone)]
+pub struct DerivationStep {
+    /// The name of the applied rule.
+    pub name: String,
+    /// The applied rule, universally quantified over its variables.
+    pub rule: Bool,
+}
 
+/// The refutation trace produced by the Spacer engine for a satisfiable query.
+///
+/// # See also:
+///
+/// - [`Fixedpoint::get_derivation()`]
+#[derive(Debug, Clone)]
+pub struct Derivation {
+    steps: Vec<DerivationStep>,
+    ground_answer: Option<Bool>,
+}
+
+impl Derivation {
+    /// The rules applied along the trace.
+    pub fn steps(&self) -> &[DerivationStep] {
+        &self.steps
+    }
+
+    /// A ground instance of the counterexample, if the engine provided one.
+    pub fn ground_answer(&self) -> Option<&Bool> {
+        self.ground_answer.as_ref()
+    }
+}
+
+impl fmt::Display for Derivation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        for step in &self.steps {
+            writeln!(f, "{}: {}", step.name, step.rule)?;
+        }
+        Ok(())
+    }
 }
 
 impl Default for Fixedpoint {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+impl fmt::Display for Fixedpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.to_smt2(&[]))
+    }
+}
+
+impl fmt::Debug for Fixedpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        <Self as fmt::Display>::fmt(self, f)
+    }
+}
+
+impl Drop for Fixedpoint {
+    fn drop(&mut self) {
+        unsafe {
+            Z3_fixedpoint_dec_ref(self.ctx.z3_ctx.0, self.z3_fp);
+        }
+    }
+}
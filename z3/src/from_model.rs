@@ -0,0 +1,158 @@
+use z3_sys::*;
+
+use crate::ast::{Ast, Dynamic};
+use crate::{FuncDecl, Model, SortKind};
+
+/// A Rust value which can be decoded from the interpretation of a term in a
+/// [`Model`].
+///
+/// Implementations are provided for the primitive types matching Z3's sorts,
+/// [`Vec`] (from sequences) and tuples (from single-constructor datatypes,
+/// field by field). Implement it for your own types to decode datatypes with
+/// [`Model::get()`] in one call.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{SatResult, Solver};
+/// # use z3::ast::{Bool, Int};
+/// let solver = Solver::new();
+/// let x = Int::new_const("x");
+/// let b = Bool::new_const("b");
+/// solver.assert(x.eq(-7));
+/// solver.assert(b.not());
+/// assert_eq!(solver.check(), SatResult::Sat);
+///
+/// let model = solver.get_model().unwrap();
+/// assert_eq!(model.get::<i64>(&x), Some(-7));
+/// assert_eq!(model.get::<bool>(&b), Some(false));
+/// assert_eq!(model.get::<u64>(&x), None);
+/// ```
+pub trait FromModel: Sized {
+    /// Decode the value of `term` in `model`, or return `None` if it has no
+    /// value of this type.
+    fn from_model(model: &Model, term: &Dynamic) -> Option<Self>;
+}
+
+/// The value of `term` in `model`, with model completion.
+fn value(model: &Model, term: &Dynamic) -> Option<Dynamic> {
+    model.eval(term, true)
+}
+
+fn is_numeral(value: &Dynamic) -> bool {
+    let z3_ctx = value.get_ctx().z3_ctx.0;
+    unsafe {
+        Z3_is_numeral_ast(z3_ctx, value.get_z3_ast())
+            || Z3_is_algebraic_number(z3_ctx, value.get_z3_ast())
+    }
+}
+
+impl FromModel for Dynamic {
+    fn from_model(model: &Model, term: &Dynamic) -> Option<Self> {
+        value(model, term)
+    }
+}
+
+impl FromModel for bool {
+    fn from_model(model: &Model, term: &Dynamic) -> Option<Self> {
+        value(model, term)?.as_bool()?.as_bool()
+    }
+}
+
+impl FromModel for i64 {
+    fn from_model(model: &Model, term: &Dynamic) -> Option<Self> {
+        let value = value(model, term)?;
+        match value.sort_kind() {
+            SortKind::Int => value.as_int()?.as_i64(),
+            SortKind::BV => value.as_bv()?.as_i64(),
+            _ => None,
+        }
+    }
+}
+
+impl FromModel for u64 {
+    fn from_model(model: &Model, term: &Dynamic) -> Option<Self> {
+        let value = value(model, term)?;
+        match value.sort_kind() {
+            SortKind::Int => value.as_int()?.as_u64(),
+            SortKind::BV => value.as_bv()?.as_u64(),
+            _ => None,
+        }
+    }
+}
+
+impl FromModel for f64 {
+    fn from_model(model: &Model, term: &Dynamic) -> Option<Self> {
+        let value = value(model, term)?;
+        if !is_numeral(&value) {
+            return None;
+        }
+        match value.sort_kind() {
+            SortKind::FloatingPoint => Some(value.as_float()?.as_f64()),
+            SortKind::Real => value.as_real()?.approx(17).parse().ok(),
+            SortKind::Int => value.as_int()?.as_i64().map(|i| i as f64),
+            _ => None,
+        }
+    }
+}
+
+impl FromModel for String {
+    fn from_model(model: &Model, term: &Dynamic) -> Option<Self> {
+        value(model, term)?.as_string()?.as_string()
+    }
+}
+
+impl<T: FromModel> FromModel for Vec<T> {
+    fn from_model(model: &Model, term: &Dynamic) -> Option<Self> {
+        let seq = term.as_seq()?;
+        let len = model.eval(&seq.length(), true)?.as_u64()?;
+        (0..len)
+            .map(|i| T::from_model(model, &seq.nth(i)))
+            .collect()
+    }
+}
+
+/// The fields of `term`, whose sort must be a datatype with a single
+/// constructor of `arity` fields.
+fn fields(term: &Dynamic, arity: usize) -> Option<Vec<Dynamic>> {
+    let ctx = term.get_ctx();
+    let z3_ctx = ctx.z3_ctx.0;
+    let sort = term.get_sort();
+    if sort.kind() != SortKind::Datatype {
+        return None;
+    }
+    unsafe {
+        if Z3_get_datatype_sort_num_constructors(z3_ctx, sort.z3_sort) != 1 {
+            return None;
+        }
+        let constructor = Z3_get_datatype_sort_constructor(z3_ctx, sort.z3_sort, 0)?;
+        if Z3_get_arity(z3_ctx, constructor) as usize != arity {
+            return None;
+        }
+        (0..arity)
+            .map(|i| {
+                let accessor =
+                    Z3_get_datatype_sort_constructor_accessor(z3_ctx, sort.z3_sort, 0, i as u32)?;
+                Some(FuncDecl::wrap(ctx, accessor).apply(&[term]))
+            })
+            .collect()
+    }
+}
+
+macro_rules! impl_from_model_tuple {
+    ($n:expr; $($t:ident $i:tt),+) => {
+        impl<$($t: FromModel),+> FromModel for ($($t,)+) {
+            fn from_model(model: &Model, term: &Dynamic) -> Option<Self> {
+                let fields = fields(term, $n)?;
+                Some(($($t::from_model(model, &fields[$i])?,)+))
+            }
+        }
+    };
+}
+
+impl_from_model_tuple!(1; A 0);
+impl_from_model_tuple!(2; A 0, B 1);
+impl_from_model_tuple!(3; A 0, B 1, C 2);
+impl_from_model_tuple!(4; A 0, B 1, C 2, D 3);
+impl_from_model_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+impl_from_model_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
@@ -1,10 +1,11 @@
 use std::convert::TryInto;
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::{borrow::Borrow, ffi::c_uint};
 use z3_sys::*;
 
-use crate::{Context, FuncDecl, Sort, Symbol, Translate, ast, ast::Ast};
+use crate::{Context, FuncDecl, Pattern, Solver, Sort, Symbol, Translate, ast, ast::Ast};
 impl FuncDecl {
     pub(crate) unsafe fn wrap(ctx: &Context, z3_func_decl: Z3_func_decl) -> Self {
         unsafe {
@@ -25,7 +26,7 @@ impl FuncDecl {
         assert_eq!(ctx.z3_ctx, range.ctx.z3_ctx);
 
         let domain: Vec<_> = domain.iter().map(|s| s.z3_sort).collect();
-        unsafe {
+        let decl = unsafe {
             Self::wrap(
                 ctx,
                 Z3_mk_func_decl(
@@ -37,7 +38,9 @@ impl FuncDecl {
                 )
                 .unwrap(),
             )
-        }
+        };
+        crate::declarations::record_decl(ctx, decl.z3_func_decl);
+        decl
     }
 
     /// Create a partial order [`FuncDecl`] "Special Relation" over the given [`Sort`].
@@ -226,6 +229,33 @@ impl FuncDecl {
         }
     }
 
+    /// Return the array term `(_ as-array f)` that represents this function.
+    ///
+    /// The resulting [`Array`](ast::Array) maps the domain of the function to
+    /// its range. Returns `None` for constants, which take no arguments.
+    ///
+    /// # See also:
+    ///
+    /// - [`Array::to_func_decl()`](ast::Array::to_func_decl)
+    ///
+    /// ```
+    /// # use z3::{FuncDecl, Sort};
+    /// # use z3::ast::Ast;
+    /// let f = FuncDecl::new("f", &[&Sort::int()], &Sort::bool());
+    /// let arr = f.as_array_term().unwrap();
+    /// assert_eq!(arr.get_sort(), Sort::array(&Sort::int(), &Sort::bool()));
+    /// assert_eq!(arr.to_func_decl().unwrap().name(), "f");
+    /// ```
+    pub fn as_array_term(&self) -> Option<ast::Array> {
+        if self.arity() == 0 {
+            return None;
+        }
+        unsafe {
+            let arr = Z3_mk_as_array(self.ctx.z3_ctx.0, self.z3_func_decl)?;
+            Some(ast::Array::wrap(&self.ctx, arr))
+        }
+    }
+
     /// Return the `DeclKind` of this `FuncDecl`.
     pub fn kind(&self) -> DeclKind {
         unsafe { Z3_get_decl_kind(self.ctx.z3_ctx.0, self.z3_func_decl) }
@@ -278,6 +308,108 @@ impl FuncDecl {
             )
         }
     }
+
+    /// Assert in `solver` that this function is injective, and return the
+    /// inverse functions used to encode it, one per argument.
+    ///
+    /// The pairwise axiom `f(xs) = f(ys) => xs = ys` has no good pattern and
+    /// triggers quadratically many instantiations. Instead, for each argument
+    /// `i`, a fresh function `inv_i` from the range to the `i`-th domain is
+    /// declared, and `forall xs. inv_i(f(xs)) = x_i` is asserted with the
+    /// pattern `f(xs)`, so that each application of `f` is instantiated once.
+    ///
+    /// The axioms help refute spurious equalities between applications.
+    /// Showing satisfiability also requires models of the inverses, which
+    /// quantifier instantiation may fail to find.
+    ///
+    /// Constants are trivially injective: nothing is asserted for them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{FuncDecl, SatResult, Solver, Sort};
+    /// # use z3::ast::{Ast, Int};
+    /// let f = FuncDecl::new("f", &[&Sort::int(), &Sort::int()], &Sort::int());
+    /// let solver = Solver::new();
+    /// let inverses = f.assert_injective(&solver);
+    /// assert_eq!(inverses.len(), 2);
+    ///
+    /// let (a, b, c) = (Int::new_const("a"), Int::new_const("b"), Int::new_const("c"));
+    /// solver.assert(f.apply(&[&a, &b]).eq(f.apply(&[&a, &c])));
+    /// solver.assert(b.eq(&c).not());
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// ```
+    pub fn assert_injective(&self, solver: &Solver) -> Vec<FuncDecl> {
+        let z3_ctx = self.ctx.z3_ctx.0;
+        let (domain, range) = unsafe {
+            let domain: Vec<Sort> = (0..Z3_get_domain_size(z3_ctx, self.z3_func_decl))
+                .map(|i| {
+                    Sort::wrap(
+                        &self.ctx,
+                        Z3_get_domain(z3_ctx, self.z3_func_decl, i).unwrap(),
+                    )
+                })
+                .collect();
+            let range = Sort::wrap(&self.ctx, Z3_get_range(z3_ctx, self.z3_func_decl).unwrap());
+            (domain, range)
+        };
+
+        let bounds: Vec<ast::Dynamic> = domain
+            .iter()
+            .map(|sort| ast::Dynamic::fresh_const("x", sort))
+            .collect();
+        let bound_refs: Vec<&dyn Ast> = bounds.iter().map(|b| b as &dyn Ast).collect();
+        let app = self.apply(&bound_refs);
+        let pattern = Pattern::new(&[&app]);
+
+        let prefix = CString::new(format!("{}-inv", self.name())).unwrap();
+        domain
+            .iter()
+            .zip(&bounds)
+            .map(|(sort, bound)| {
+                let inverse = unsafe {
+                    Self::wrap(
+                        &self.ctx,
+                        Z3_mk_fresh_func_decl(
+                            z3_ctx,
+                            prefix.as_ptr(),
+                            1,
+                            &range.z3_sort,
+                            sort.z3_sort,
+                        )
+                        .unwrap(),
+                    )
+                };
+                crate::declarations::record_decl(&self.ctx, inverse.z3_func_decl);
+                let axiom = inverse.apply(&[&app]).eq(bound);
+                solver.assert(ast::forall_const(&bound_refs, &[&pattern], &axiom));
+                inverse
+            })
+            .collect()
+    }
+}
+
+impl Clone for FuncDecl {
+    fn clone(&self) -> Self {
+        unsafe { Self::wrap(&self.ctx, self.z3_func_decl) }
+    }
+}
+
+impl PartialEq for FuncDecl {
+    fn eq(&self, other: &FuncDecl) -> bool {
+        unsafe { Z3_is_eq_func_decl(self.ctx.z3_ctx.0, self.z3_func_decl, other.z3_func_decl) }
+    }
+}
+
+impl Eq for FuncDecl {}
+
+impl Hash for FuncDecl {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        unsafe {
+            let ast = Z3_func_decl_to_ast(self.ctx.z3_ctx.0, self.z3_func_decl).unwrap();
+            Z3_get_ast_hash(self.ctx.z3_ctx.0, ast).hash(state);
+        }
+    }
 }
 
 impl fmt::Display for FuncDecl {
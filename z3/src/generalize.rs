@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use crate::ast::{Ast, Dynamic};
+
+/// The least general generalization of two terms, computed by
+/// [`generalize()`].
+///
+/// The pattern agrees with both terms except at the positions where they
+/// differ, which hold placeholder constants. Each placeholder stands for
+/// the same pair of subterms wherever it occurs, so substituting the left
+/// (right) subterms for the placeholders gives back the left (right) term.
+#[derive(Debug, Clone)]
+pub struct Generalization {
+    pattern: Dynamic,
+    /// Each placeholder with the subterms of the left and right terms it
+    /// replaces.
+    placeholders: Vec<(Dynamic, Dynamic, Dynamic)>,
+}
+
+impl Generalization {
+    /// Returns the pattern, with placeholders where the terms differ.
+    pub fn pattern(&self) -> &Dynamic {
+        &self.pattern
+    }
+
+    /// Returns the placeholder constants, in order of first occurrence.
+    pub fn placeholders(&self) -> Vec<Dynamic> {
+        self.placeholders
+            .iter()
+            .map(|(p, _, _)| p.clone())
+            .collect()
+    }
+
+    /// Returns the substitution of the placeholders giving the left term.
+    pub fn left(&self) -> Vec<(Dynamic, Dynamic)> {
+        self.placeholders
+            .iter()
+            .map(|(p, l, _)| (p.clone(), l.clone()))
+            .collect()
+    }
+
+    /// Returns the substitution of the placeholders giving the right term.
+    pub fn right(&self) -> Vec<(Dynamic, Dynamic)> {
+        self.placeholders
+            .iter()
+            .map(|(p, _, r)| (p.clone(), r.clone()))
+            .collect()
+    }
+}
+
+/// Compute the least general generalization (anti-unification) of the terms
+/// `a` and `b`, with fresh placeholder constants where they differ.
+///
+/// Returns `None` if the terms have different sorts. Quantifiers and bound
+/// variables are compared as a whole.
+///
+/// # Example
+///
+/// ```
+/// # use z3::generalize;
+/// # use z3::ast::{Ast, Int};
+/// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+/// let a = (&x + 1) * (&x + 1);
+/// let b = (&y + 1) * (&y + 1);
+/// let g = generalize(&a, &b).unwrap();
+///
+/// // Both occurrences of the pair `(x, y)` share one placeholder.
+/// let [p] = &g.placeholders()[..] else { panic!() };
+/// assert_eq!(g.pattern().to_string(), format!("(* (+ {p} 1) (+ {p} 1))"));
+/// assert_eq!(g.left(), [(p.clone(), x.into())]);
+/// ```
+pub fn generalize(a: &impl Ast, b: &impl Ast) -> Option<Generalization> {
+    let (a, b) = (Dynamic::from_ast(a), Dynamic::from_ast(b));
+    if a.get_sort() != b.get_sort() {
+        return None;
+    }
+    let mut builder = Builder {
+        placeholders: vec![],
+        patterns: HashMap::new(),
+    };
+    let pattern = builder.generalize(&a, &b);
+    Some(Generalization {
+        pattern,
+        placeholders: builder.placeholders,
+    })
+}
+
+struct Builder {
+    placeholders: Vec<(Dynamic, Dynamic, Dynamic)>,
+    /// The pattern of each visited pair of terms.
+    patterns: HashMap<(Dynamic, Dynamic), Dynamic>,
+}
+
+impl Builder {
+    fn generalize(&mut self, a: &Dynamic, b: &Dynamic) -> Dynamic {
+        if a == b {
+            return a.clone();
+        }
+        let key = (a.clone(), b.clone());
+        if let Some(pattern) = self.patterns.get(&key) {
+            return pattern.clone();
+        }
+        let pattern = match self.generalize_args(a, b) {
+            Some(pattern) => pattern,
+            None => {
+                let placeholder = Dynamic::fresh_const("g", &a.get_sort());
+                self.placeholders
+                    .push((placeholder.clone(), a.clone(), b.clone()));
+                placeholder
+            }
+        };
+        self.patterns.insert(key, pattern.clone());
+        pattern
+    }
+
+    /// Generalize the arguments of `a` and `b` if they apply the same
+    /// declaration.
+    fn generalize_args(&mut self, a: &Dynamic, b: &Dynamic) -> Option<Dynamic> {
+        if !a.is_app() || !b.is_app() || a.decl() != b.decl() {
+            return None;
+        }
+        let (a_args, b_args) = (a.children(), b.children());
+        if a_args.len() != b_args.len() {
+            return None;
+        }
+        let args: Vec<Dynamic> = a_args
+            .iter()
+            .zip(&b_args)
+            .map(|(a, b)| self.generalize(a, b))
+            .collect();
+        let args: Vec<&dyn Ast> = args.iter().map(|a| a as &dyn Ast).collect();
+        Some(a.decl().apply(&args))
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt;
 use z3_sys::*;
 
 use crate::ast::Bool;
-use crate::{Context, Goal, Translate, ast, ast::Ast};
+use crate::{Context, Dimacs, Goal, Model, Translate, ast, ast::Ast};
 
 // todo: is this sound? This should be through `wrap`, no?
 impl Clone for Goal {
@@ -109,6 +109,126 @@ impl Goal {
         }
         formulas
     }
+
+    /// Number the atoms and clauses of the goal for export in the DIMACS
+    /// format.
+    ///
+    /// The goal must be in conjunctive normal form, e.g. after applying the
+    /// `tseitin-cnf` tactic, preceded by `simplify` and `bit-blast` for
+    /// bit-vectors. Atoms which are not Boolean constants, such as theory
+    /// atoms, are numbered as opaque variables.
+    ///
+    /// The atoms of `first` are numbered first, in order, whether or not they
+    /// still occur in the goal. They are only numbered, not protected from
+    /// preprocessing: to query an external SAT solver about a term `t`,
+    /// assert a definition `p == t` with a fresh constant `p` before
+    /// preprocessing, and pass `p` in `first`.
+    ///
+    /// Returns an error if the goal is not in conjunctive normal form, or a
+    /// term of `first` is not an atom.
+    ///
+    /// # Example
+    /// ```
+    /// # use z3::{Goal, Tactic};
+    /// # use z3::ast::{Ast, Bool, BV};
+    /// let x = BV::new_const("x", 2);
+    /// let (p, q) = (Bool::new_const("p"), Bool::new_const("q"));
+    /// let goal = Goal::new(false, false, false);
+    /// goal.assert(&p.iff(x.extract(0, 0).eq(BV::from_u64(1, 1))));
+    /// goal.assert(&q.iff(x.bvuge(BV::from_u64(2, 2))));
+    /// goal.assert(&x.bvugt(BV::from_u64(0, 2)));
+    ///
+    /// let cnf = Tactic::new("simplify")
+    ///     .and_then(&Tactic::new("bit-blast"))
+    ///     .and_then(&Tactic::new("tseitin-cnf"))
+    ///     .apply(&goal, None)
+    ///     .unwrap()
+    ///     .list_subgoals()
+    ///     .next()
+    ///     .unwrap();
+    /// let dimacs = cnf.to_dimacs(&[&p, &q]).unwrap();
+    /// assert_eq!(dimacs.literal(&p), Some(1));
+    /// assert_eq!(dimacs.literal(&q.not()), Some(-2));
+    /// assert!(dimacs.to_string().starts_with(&format!("p cnf {} ", dimacs.num_vars())));
+    /// ```
+    pub fn to_dimacs(&self, first: &[&Bool]) -> Result<Dimacs, String> {
+        Dimacs::new(&self.get_formulas(), first)
+    }
+
+    /// Convert the goal into a DIMACS string, with Z3's own numbering of the
+    /// atoms.
+    ///
+    /// The goal must be in conjunctive normal form, e.g. after applying the
+    /// `tseitin-cnf` tactic, preceded by `simplify` and `bit-blast` for
+    /// bit-vectors. If `include_names` is true, comment lines `c <var>
+    /// <name>` give the Boolean constant of each variable. Use
+    /// [`Goal::to_dimacs()`] to control the numbering.
+    ///
+    /// # Example
+    /// ```
+    /// # use z3::{Goal, Tactic};
+    /// # use z3::ast::{Ast, BV};
+    /// let x = BV::new_const("x", 2);
+    /// let goal = Goal::new(false, false, false);
+    /// goal.assert(&x.bvugt(BV::from_u64(1, 2)));
+    ///
+    /// let cnf = Tactic::new("simplify")
+    ///     .and_then(&Tactic::new("bit-blast"))
+    ///     .and_then(&Tactic::new("tseitin-cnf"))
+    ///     .apply(&goal, None)
+    ///     .unwrap()
+    ///     .subgoal(0)
+    ///     .unwrap();
+    /// assert!(cnf.to_dimacs_string(false).starts_with("p cnf "));
+    /// ```
+    pub fn to_dimacs_string(&self, include_names: bool) -> String {
+        unsafe {
+            CStr::from_ptr(Z3_goal_to_dimacs_string(
+                self.ctx.z3_ctx.0,
+                self.z3_goal,
+                include_names,
+            ))
+        }
+        .to_string_lossy()
+        .into_owned()
+    }
+
+    /// Convert a model of this goal, obtained from a tactic, into a model of
+    /// the goal the tactic was applied to.
+    ///
+    /// Tactics may eliminate or introduce constants, so a model of a subgoal
+    /// does not necessarily interpret the constants of the original goal.
+    /// The goal the tactic was applied to must have been created with
+    /// `models` enabled. Returns `None` if Z3 fails to convert the model.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{Goal, SatResult, Solver, Tactic};
+    /// # use z3::ast::Int;
+    /// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    /// let goal = Goal::new(true, false, false);
+    /// goal.assert(&x.eq(&y + 1));
+    /// goal.assert(&y.gt(3));
+    ///
+    /// let result = Tactic::new("solve-eqs").apply(&goal, None).unwrap();
+    /// let subgoal = result.subgoal(0).unwrap();
+    /// let solver = Solver::new();
+    /// for formula in subgoal.get_formulas() {
+    ///     solver.assert(&formula);
+    /// }
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let model = subgoal.convert_model(&solver.get_model().unwrap()).unwrap();
+    /// let (x, y) = (model.eval(&x, true).unwrap(), model.eval(&y, true).unwrap());
+    /// assert_eq!(x.as_i64().unwrap(), y.as_i64().unwrap() + 1);
+    /// ```
+    pub fn convert_model(&self, model: &Model) -> Option<Model> {
+        unsafe {
+            let z3_mdl = Z3_goal_convert_model(self.ctx.z3_ctx.0, self.z3_goal, model.z3_mdl)?;
+            Some(Model::wrap(&self.ctx, z3_mdl))
+        }
+    }
 }
 
 impl fmt::Display for Goal {
@@ -0,0 +1,92 @@
+use std::ffi::CStr;
+use z3_sys::*;
+
+use crate::Context;
+
+/// An option described in the help text of a component, such as
+/// [`Solver::get_help()`](crate::Solver::get_help).
+///
+/// # Example
+///
+/// ```
+/// # use z3::{HelpEntry, ParamKind, Tactic};
+/// let entries = HelpEntry::parse(&Tactic::new("simplify").get_help());
+/// let entry = entries.iter().find(|e| e.name == "arith_lhs").unwrap();
+/// assert_eq!(entry.kind, ParamKind::Bool);
+/// assert_eq!(entry.default.as_deref(), Some("false"));
+/// assert!(entry.doc.contains("left-hand-side"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpEntry {
+    pub name: String,
+    pub kind: ParamKind,
+    /// The default value as written in the help text, if given.
+    pub default: Option<String>,
+    pub doc: String,
+}
+
+impl HelpEntry {
+    /// Parse help text made of lines of the form
+    /// `name (kind) documentation (default: value)`.
+    ///
+    /// Lines which do not describe an option are skipped. Kinds other than
+    /// `bool`, `unsigned int`, `double`, `symbol` and `string` are reported
+    /// as [`ParamKind::Other`].
+    pub fn parse(help: &str) -> Vec<HelpEntry> {
+        help.lines().filter_map(parse_line).collect()
+    }
+
+    /// Return the options of the simplifier used by
+    /// [`Ast::simplify()`](crate::ast::Ast::simplify).
+    pub fn simplifier() -> Vec<HelpEntry> {
+        let ctx = &Context::thread_local();
+        let help = unsafe { CStr::from_ptr(Z3_simplify_get_help(ctx.z3_ctx.0)) };
+        HelpEntry::parse(&help.to_string_lossy())
+    }
+}
+
+fn parse_line(line: &str) -> Option<HelpEntry> {
+    let line = line.trim();
+    let (name, rest) = line.split_once(" (")?;
+    let (kind, rest) = rest.split_once(')')?;
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    let kind = match kind {
+        "bool" => ParamKind::Bool,
+        "unsigned int" => ParamKind::UInt,
+        "double" => ParamKind::Double,
+        "symbol" => ParamKind::Symbol,
+        "string" => ParamKind::String,
+        _ => ParamKind::Other,
+    };
+    let (doc, default) = match rest.find("(default: ") {
+        Some(start) => {
+            let value_start = start + "(default: ".len();
+            let end = closing_paren(&rest[value_start..])? + value_start;
+            let doc = format!("{} {}", rest[..start].trim(), rest[end + 1..].trim());
+            (doc, Some(rest[value_start..end].to_owned()))
+        }
+        None => (rest.to_owned(), None),
+    };
+    Some(HelpEntry {
+        name: name.to_owned(),
+        kind,
+        default,
+        doc: doc.trim().to_owned(),
+    })
+}
+
+/// Returns the position of the parenthesis closing an already opened one.
+fn closing_paren(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
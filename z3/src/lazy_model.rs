@@ -0,0 +1,110 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Model;
+use crate::ast::{Ast, Dynamic};
+
+/// A view of a [`Model`] which evaluates terms on demand and caches the results.
+///
+/// Extracting values for every declared constant of a large problem is expensive
+/// when only a handful of them are actually inspected. A `LazyModel` defers the
+/// call to `Z3_model_eval` until a value is requested through [`LazyModel::get()`],
+/// and remembers the result so that repeated lookups of the same term are free.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{LazyModel, SatResult, Solver};
+/// # use z3::ast::Int;
+/// let solver = Solver::new();
+/// let x = Int::new_const("x");
+/// solver.assert(x.eq(7));
+/// assert_eq!(solver.check(), SatResult::Sat);
+///
+/// let model = LazyModel::new(solver.get_model().unwrap(), true);
+/// assert_eq!(model.num_cached(), 0);
+/// assert_eq!(model.get(&x).and_then(|v| v.as_i64()), Some(7));
+/// assert_eq!(model.num_cached(), 1);
+/// ```
+///
+/// # See also:
+///
+/// - [`Model::eval()`]
+pub struct LazyModel {
+    model: Model,
+    model_completion: bool,
+    cache: RefCell<HashMap<Dynamic, Option<Dynamic>>>,
+}
+
+impl LazyModel {
+    /// Create a lazy view of `model`.
+    ///
+    /// `model_completion` is forwarded to every evaluation, see [`Model::eval()`].
+    pub fn new(model: Model, model_completion: bool) -> LazyModel {
+        LazyModel {
+            model,
+            model_completion,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the value of `ast` in the underlying model, evaluating it
+    /// only the first time it is requested.
+    ///
+    /// Returns `None` if the term could not be evaluated.
+    pub fn get<T: Ast>(&self, ast: &T) -> Option<T> {
+        let key = Dynamic::from_ast(ast);
+        if let Some(cached) = self.cache.borrow().get(&key) {
+            return cached
+                .as_ref()
+                .map(|v| unsafe { T::wrap(v.get_ctx(), v.get_z3_ast()) });
+        }
+        let value = self.model.eval(&key, self.model_completion);
+        let result = value
+            .as_ref()
+            .map(|v| unsafe { T::wrap(v.get_ctx(), v.get_z3_ast()) });
+        self.cache.borrow_mut().insert(key, value);
+        result
+    }
+
+    /// Returns `true` if the value of `ast` has already been evaluated.
+    pub fn is_cached(&self, ast: &impl Ast) -> bool {
+        self.cache.borrow().contains_key(&Dynamic::from_ast(ast))
+    }
+
+    /// Returns the number of terms evaluated so far.
+    pub fn num_cached(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
+    /// Forget all previously evaluated values.
+    pub fn clear_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Returns the underlying [`Model`].
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// Consume the view and return the underlying [`Model`].
+    pub fn into_model(self) -> Model {
+        self.model
+    }
+}
+
+impl From<Model> for LazyModel {
+    fn from(model: Model) -> Self {
+        LazyModel::new(model, false)
+    }
+}
+
+impl fmt::Debug for LazyModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("LazyModel")
+            .field("model_completion", &self.model_completion)
+            .field("num_cached", &self.num_cached())
+            .finish()
+    }
+}
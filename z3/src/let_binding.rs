@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use z3_sys::*;
+
+use crate::ast::{Ast, Bool, Dynamic};
+
+/// Print `(assert formula)`, binding with `let` the subterms of at least
+/// `min_size` nodes which occur more than once. The bound variables are
+/// named `{prefix}{i}`.
+pub(crate) fn assert_with_lets(formula: &Bool, min_size: usize, prefix: &str) -> String {
+    let mut counter = Counter {
+        sizes: HashMap::new(),
+        references: HashMap::new(),
+        order: vec![],
+    };
+    let formula = Dynamic::from_ast(formula);
+    counter.visit(&formula);
+
+    let shared: Vec<&Dynamic> = counter
+        .order
+        .iter()
+        .filter(|t| counter.references[*t] > 1 && counter.sizes[*t] >= min_size)
+        .collect();
+    let mut substitution: Vec<(Dynamic, Dynamic)> = vec![];
+    let mut text = String::from("(assert\n");
+    for term in &shared {
+        let definition = substitute(term, &substitution);
+        let variable = placeholder(&format!("{prefix}{}", substitution.len()), term);
+        writeln!(text, " (let (({variable} {definition}))").unwrap();
+        substitution.push(((*term).clone(), variable));
+    }
+    write!(text, " {}", substitute(&formula, &substitution)).unwrap();
+    text.push_str(&")".repeat(shared.len() + 1));
+    text.push('\n');
+    text
+}
+
+struct Counter {
+    /// The number of nodes of each visited term, as printed without sharing.
+    sizes: HashMap<Dynamic, usize>,
+    /// The number of occurrences of each visited term as an argument.
+    references: HashMap<Dynamic, usize>,
+    /// The visited applications, arguments first.
+    order: Vec<Dynamic>,
+}
+
+impl Counter {
+    fn visit(&mut self, term: &Dynamic) -> usize {
+        *self.references.entry(term.clone()).or_insert(0) += 1;
+        if let Some(size) = self.sizes.get(term) {
+            return *size;
+        }
+        let size = if term.is_app() && term.num_children() > 0 {
+            let size = term.children().iter().fold(1, |size: usize, child| {
+                size.saturating_add(self.visit(child))
+            });
+            self.order.push(term.clone());
+            size
+        } else {
+            1
+        };
+        self.sizes.insert(term.clone(), size);
+        size
+    }
+}
+
+fn substitute(term: &Dynamic, substitution: &[(Dynamic, Dynamic)]) -> Dynamic {
+    let pairs: Vec<(&Dynamic, &Dynamic)> = substitution.iter().map(|(a, b)| (a, b)).collect();
+    term.substitute(&pairs)
+}
+
+/// A constant standing for `term`, which is not recorded as a declaration.
+fn placeholder(name: &str, term: &Dynamic) -> Dynamic {
+    let ctx = term.get_ctx();
+    let name = std::ffi::CString::new(name).unwrap();
+    unsafe {
+        let symbol = Z3_mk_string_symbol(ctx.z3_ctx.0, name.as_ptr()).unwrap();
+        let sort = Z3_get_sort(ctx.z3_ctx.0, term.get_z3_ast()).unwrap();
+        Dynamic::wrap(ctx, Z3_mk_const(ctx.z3_ctx.0, symbol, sort).unwrap())
+    }
+}
@@ -82,46 +82,128 @@
 
 use std::ffi::CString;
 use z3_sys::*;
-pub use z3_sys::{AstKind, GoalPrec, SortKind};
+pub use z3_sys::{AstKind, GoalPrec, ParamKind, SortKind};
 
+mod arena;
 pub mod ast;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bench;
+mod bmc;
+mod cancellation;
 mod config;
 mod context;
+pub mod datalog;
 pub mod datatype_builder;
+mod declarations;
+mod dependency_graph;
+mod dimacs;
+pub mod encoding;
+mod farkas;
+mod fixedpoint;
+mod from_model;
 mod func_decl;
 mod func_entry;
 mod func_interp;
+mod generalize;
 mod goal;
+mod help;
+mod lazy_model;
+mod let_binding;
+mod logic;
+mod max_sat;
 mod model;
+mod modular;
+mod numeral_format;
 mod ops;
 mod optimize;
+mod param_descrs;
 mod params;
+mod parse_error;
 mod pattern;
+mod plugin;
+#[cfg(not(target_arch = "wasm32"))]
+mod portfolio;
 mod probe;
+mod proof;
 mod rec_func_decl;
+mod roundoff;
+mod scope;
+#[cfg(all(feature = "server", not(target_arch = "wasm32")))]
+pub mod server;
+#[cfg(feature = "z3_4_12_0")]
+mod simplifier;
+mod smt2_session;
 mod solver;
 mod sort;
 mod statistics;
 mod symbol;
+pub mod symmetry;
+mod synthesis;
 mod tactic;
+mod template;
+mod trace;
+pub mod transform;
 mod translate;
+pub mod unsat_core;
+mod user_propagator;
 mod version;
 
 // New modules for extended API coverage
 pub mod ast_vector;
-pub mod quantifier_elimination_simple;
+pub mod quantifier_elimination;
+#[deprecated = "Please use quantifier_elimination instead"]
+pub mod quantifier_elimination_simple {
+    //! Renamed to [`quantifier_elimination`](crate::quantifier_elimination).
+    pub use crate::quantifier_elimination::*;
+}
 
-pub use crate::params::{get_global_param, reset_all_global_params, set_global_param};
+pub use crate::param_descrs::ParamDescr;
+pub use crate::params::{
+    get_global_param, reset_all_global_params, set_global_param, set_single_threaded,
+};
+pub use crate::parse_error::ParseError;
 pub use crate::statistics::{StatisticsEntry, StatisticsValue};
+pub use crate::trace::Trace;
 pub use crate::translate::Translate;
 pub use crate::translate::synchronization::*;
 pub use crate::version::{Version, full_version, version};
-pub use context::Context;
+pub use arena::{ArenaAst, ContextArena};
+pub use bmc::Bmc;
+pub use cancellation::CancellationToken;
+pub use context::{Context, finalize_memory, reset_memory, set_thread_local_config};
 pub use datatype_builder::DatatypeAccessor;
-pub use solver::Solvable;
+pub use declarations::Declarations;
+pub use dependency_graph::DependencyGraph;
+pub use dimacs::Dimacs;
+pub use farkas::{FarkasCertificate, farkas_certificate};
+pub use fixedpoint::{Derivation, DerivationStep, Fixedpoint, FixedpointCallbacks, SpacerConfig};
+pub use from_model::FromModel;
+pub use generalize::{Generalization, generalize};
+pub use help::HelpEntry;
+pub use lazy_model::LazyModel;
+pub use logic::{Logic, LogicFeatures};
+pub use max_sat::{MaxSat, MaxSatSolution, SoftClause};
+pub use model::{ModelSummary, SortedModel};
+pub use modular::Mod;
+pub use optimize::{Objective, ObjectiveBound, ParetoIter};
+pub use plugin::{
+    DeclId, SortId, TERM_PROVIDER_ABI_VERSION, TermId, TermOp, TermProvider, TermRegistry,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use portfolio::{Portfolio, PortfolioResult, Strategy};
+pub use proof::Proof;
+pub use roundoff::Roundoff;
+pub use scope::{OptimizeScope, SolverScope};
+#[cfg(feature = "z3_4_12_0")]
+pub use simplifier::Simplifier;
+pub use smt2_session::{Smt2Response, Smt2Session};
+pub use solver::{DeepeningStep, Solvable, TrackId, Verdict};
+pub use synthesis::Grammar;
+pub use template::FormulaTemplate;
+pub use user_propagator::{PropagatorCallback, UserPropagator};
 
 // Export new modules for extended API coverage
-pub use ast_vector::AstVector;
+pub use ast_vector::{AstRef, AstVector};
 /// Configuration used to initialize [logical contexts](Context).
 ///
 /// # See also:
@@ -162,10 +244,14 @@ pub struct Sort {
 }
 
 /// A struct to represent when two sorts are of different types.
+///
+/// If the mismatch was detected while building a term, the name of the
+/// offending operation is available via [`SortDiffers::operation()`].
 #[derive(Debug)]
 pub struct SortDiffers {
     left: Sort,
     right: Sort,
+    operation: Option<&'static str>,
 }
 
 /// A struct to represent when an ast is not a function application.
@@ -181,6 +267,22 @@ pub struct IsNotApp {
 pub struct Solver {
     ctx: Context,
     z3_slv: Z3_solver,
+    // The user propagator must live as long as the Z3 object that calls it,
+    // and must not move since Z3 holds a pointer to it.
+    propagator: std::cell::RefCell<Option<Box<user_propagator::PropagatorState>>>,
+    // The clause callback, kept alive like the user propagator.
+    #[cfg(feature = "z3_4_12_0")]
+    on_clause: std::cell::RefCell<Option<Box<solver::OnClauseState>>>,
+    // The constraints tracked by `Solver::assert_and_track_auto()` and
+    // `Solver::assert_named()` with their answer literals, in assertion order.
+    tracked: std::cell::RefCell<Vec<solver::Tracked>>,
+    // The `TrackId` of the next tracked constraint.
+    next_track_id: std::cell::Cell<usize>,
+    // The logic set by `Solver::set_logic()`, which assertions must belong to.
+    logic: std::cell::Cell<Option<Logic>>,
+    // The parameters set by `Solver::set_params()`, restored after checks
+    // which change parameters temporarily.
+    params: std::cell::RefCell<Vec<(Symbol, params::ParamValue)>>,
 }
 
 /// Model for the constraints inserted into the logical context.
@@ -199,6 +301,9 @@ pub struct Model {
 pub struct Optimize {
     ctx: Context,
     z3_opt: Z3_optimize,
+    // The model callback must live as long as the Z3 object that calls it,
+    // and must not move since Z3 holds a pointer to it.
+    on_model: std::cell::RefCell<Option<Box<optimize::OnModelState>>>,
 }
 
 /// Function declaration. Every constant and function have an associated declaration.
@@ -309,6 +414,26 @@ pub struct DatatypeSort {
 pub struct Params {
     ctx: Context,
     z3_params: Z3_params,
+    // The values set so far, so that a solver can restore them.
+    entries: Vec<(Symbol, params::ParamValue)>,
+}
+
+/// Descriptions of the parameters accepted by a component, such as a
+/// [`Fixedpoint`] context.
+///
+/// # See also:
+///
+/// - [`Fixedpoint::get_param_descrs()`]
+/// - [`Optimize::get_param_descrs()`]
+/// - [`Solver::get_param_descrs()`]
+/// - [`Tactic::get_param_descrs()`]
+/// - [`ParamDescrs::simplifier()`]
+//
+// Note for in-crate users: Never construct a `ParamDescrs` directly; only use
+// `ParamDescrs::wrap()` which handles Z3 refcounting properly.
+pub struct ParamDescrs {
+    ctx: Context,
+    z3_param_descrs: Z3_param_descrs,
 }
 
 /// Result of a satisfiability query.
@@ -329,7 +454,7 @@ pub struct Pattern {
 }
 
 /// Collection of subgoals resulting from applying of a tactic to a goal.
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct ApplyResult {
     ctx: Context,
     z3_apply_result: Z3_apply_result,
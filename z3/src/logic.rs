@@ -0,0 +1,388 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use z3_sys::*;
+
+use crate::{Context, Symbol};
+
+/// The features of the SMT-LIB logic a formula belongs to, as detected by
+/// [`Bool::detect_logic()`](crate::ast::Bool::detect_logic).
+///
+/// Each field records whether the formula uses the corresponding feature.
+/// [`Display`](fmt::Display) prints the name of the smallest SMT-LIB style
+/// logic covering these features, such as `QF_LIA`, `QF_ABV` or `NRA`.
+///
+/// # Example
+///
+/// ```
+/// # use z3::ast::{Bool, Int};
+/// let x = Int::new_const("x");
+/// let y = Int::new_const("y");
+/// assert_eq!(x.lt(&y).detect_logic().to_string(), "QF_LIA");
+/// assert_eq!((&x * &y).eq(1).detect_logic().to_string(), "QF_NIA");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LogicFeatures {
+    pub quantifiers: bool,
+    pub arrays: bool,
+    pub uninterpreted_functions: bool,
+    pub bit_vectors: bool,
+    pub floating_point: bool,
+    pub datatypes: bool,
+    pub strings: bool,
+    pub integers: bool,
+    pub reals: bool,
+    pub nonlinear: bool,
+}
+
+impl LogicFeatures {
+    /// Detect the features used by the term `ast` in `ctx`.
+    pub(crate) fn of(ctx: &Context, ast: Z3_ast) -> LogicFeatures {
+        let mut logic = LogicFeatures::default();
+        walk(ctx, ast, |node, kind| {
+            logic.visit(ctx, node, kind);
+            true
+        });
+        logic
+    }
+
+    /// Returns `true` if the logic contains no feature of `other` that it
+    /// does not contain itself, i.e. if every formula of `other` is also a
+    /// formula of this logic.
+    pub fn includes(&self, other: &LogicFeatures) -> bool {
+        let LogicFeatures {
+            quantifiers,
+            arrays,
+            uninterpreted_functions,
+            bit_vectors,
+            floating_point,
+            datatypes,
+            strings,
+            integers,
+            reals,
+            nonlinear,
+        } = *other;
+        (!quantifiers || self.quantifiers)
+            && (!arrays || self.arrays)
+            && (!uninterpreted_functions || self.uninterpreted_functions)
+            && (!bit_vectors || self.bit_vectors)
+            && (!floating_point || self.floating_point)
+            && (!datatypes || self.datatypes)
+            && (!strings || self.strings)
+            && (!integers || self.integers)
+            && (!reals || self.reals)
+            && (!nonlinear || self.nonlinear)
+    }
+
+    fn visit(&mut self, ctx: &Context, node: Z3_ast, kind: AstKind) {
+        let z3_ctx = ctx.z3_ctx.0;
+        match kind {
+            AstKind::Quantifier => {
+                self.quantifiers = true;
+                return;
+            }
+            AstKind::Numeral | AstKind::App | AstKind::Var => {}
+            _ => return,
+        }
+        unsafe {
+            self.visit_sort(ctx, Z3_get_sort(z3_ctx, node).unwrap());
+        }
+        if kind != AstKind::App {
+            return;
+        }
+        unsafe {
+            let app = Z3_to_app(z3_ctx, node).unwrap();
+            let decl = Z3_get_app_decl(z3_ctx, app).unwrap();
+            let num_args = Z3_get_app_num_args(z3_ctx, app);
+            let non_numeral_args = (0..num_args)
+                .filter(|&i| {
+                    let arg = Z3_get_app_arg(z3_ctx, app, i).unwrap();
+                    Z3_get_ast_kind(z3_ctx, arg) != AstKind::Numeral
+                })
+                .count();
+            match Z3_get_decl_kind(z3_ctx, decl) {
+                DeclKind::UNINTERPRETED if num_args > 0 => self.uninterpreted_functions = true,
+                DeclKind::MUL if non_numeral_args > 1 => self.nonlinear = true,
+                DeclKind::DIV | DeclKind::IDIV | DeclKind::MOD | DeclKind::REM => {
+                    let divisor = Z3_get_app_arg(z3_ctx, app, num_args - 1).unwrap();
+                    if Z3_get_ast_kind(z3_ctx, divisor) != AstKind::Numeral {
+                        self.nonlinear = true;
+                    }
+                }
+                DeclKind::POWER if non_numeral_args > 0 => self.nonlinear = true,
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_sort(&mut self, ctx: &Context, sort: Z3_sort) {
+        let z3_ctx = ctx.z3_ctx.0;
+        match unsafe { Z3_get_sort_kind(z3_ctx, sort) } {
+            SortKind::Uninterpreted => self.uninterpreted_functions = true,
+            SortKind::Int => self.integers = true,
+            SortKind::Real => self.reals = true,
+            SortKind::BV => self.bit_vectors = true,
+            SortKind::FloatingPoint | SortKind::RoundingMode => self.floating_point = true,
+            SortKind::Datatype => self.datatypes = true,
+            SortKind::Seq | SortKind::RE => self.strings = true,
+            SortKind::Array => unsafe {
+                self.arrays = true;
+                self.visit_sort(ctx, Z3_get_array_sort_domain(z3_ctx, sort).unwrap());
+                self.visit_sort(ctx, Z3_get_array_sort_range(z3_ctx, sort).unwrap());
+            },
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for LogicFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let mut name = String::new();
+        if !self.quantifiers {
+            name.push_str("QF_");
+        }
+        if self.arrays {
+            name.push('A');
+        }
+        let arith = self.integers || self.reals;
+        let theories = self.arrays
+            || self.bit_vectors
+            || self.floating_point
+            || self.datatypes
+            || self.strings
+            || arith;
+        if self.uninterpreted_functions || !theories {
+            name.push_str("UF");
+        }
+        if self.bit_vectors {
+            name.push_str("BV");
+        }
+        if self.floating_point {
+            name.push_str("FP");
+        }
+        if self.datatypes {
+            name.push_str("DT");
+        }
+        if self.strings {
+            name.push('S');
+        }
+        if arith {
+            name.push(if self.nonlinear { 'N' } else { 'L' });
+            if self.integers {
+                name.push('I');
+            }
+            if self.reals {
+                name.push('R');
+            }
+            name.push('A');
+        }
+        write!(f, "{name}")
+    }
+}
+
+/// Parses the name of an SMT-LIB style logic, such as `QF_LIA`, `AUFNIRA`,
+/// `QF_IDL` or `ALL`, including combinations that are not standard
+/// [`Logic`]s, such as `QF_BVNIA`.
+///
+/// The difference logics `IDL` and `RDL` are read as linear integer and real
+/// arithmetic. `QF_UF` sets [`LogicFeatures::uninterpreted_functions`], so it
+/// does not give back the empty logic it is printed for.
+///
+/// ```
+/// # use z3::LogicFeatures;
+/// let logic: LogicFeatures = "QF_AUFBV".parse().unwrap();
+/// assert!(logic.arrays && logic.uninterpreted_functions && logic.bit_vectors);
+/// assert!(!logic.quantifiers);
+/// assert_eq!(logic.to_string(), "QF_AUFBV");
+/// assert!("QF_XYZ".parse::<LogicFeatures>().is_err());
+/// ```
+impl FromStr for LogicFeatures {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<LogicFeatures, String> {
+        if name == "ALL" {
+            return Ok(LogicFeatures {
+                quantifiers: true,
+                arrays: true,
+                uninterpreted_functions: true,
+                bit_vectors: true,
+                floating_point: true,
+                datatypes: true,
+                strings: true,
+                integers: true,
+                reals: true,
+                nonlinear: true,
+            });
+        }
+        let mut logic = LogicFeatures::default();
+        let rest = match name.strip_prefix("QF_") {
+            Some(rest) => rest,
+            None => {
+                logic.quantifiers = true;
+                name
+            }
+        };
+        if rest.is_empty() {
+            return Err(format!("unknown logic {name}"));
+        }
+        let mut rest = match rest.strip_prefix("AX") {
+            Some("") => {
+                logic.arrays = true;
+                ""
+            }
+            _ => rest,
+        };
+        let mut take = |prefix: &str| match rest.strip_prefix(prefix) {
+            Some(r) => {
+                rest = r;
+                true
+            }
+            None => false,
+        };
+        logic.arrays |= take("A");
+        logic.uninterpreted_functions = take("UF");
+        logic.bit_vectors = take("BV");
+        logic.floating_point = take("FP");
+        logic.datatypes = take("DT");
+        logic.strings = take("S");
+        let (integers, reals, nonlinear) = match rest {
+            "" => (false, false, false),
+            "IDL" | "LIA" => (true, false, false),
+            "RDL" | "LRA" => (false, true, false),
+            "LIRA" => (true, true, false),
+            "NIA" => (true, false, true),
+            "NRA" => (false, true, true),
+            "NIRA" => (true, true, true),
+            _ => return Err(format!("unknown logic {name}")),
+        };
+        logic.integers = integers;
+        logic.reals = reals;
+        logic.nonlinear = nonlinear;
+        Ok(logic)
+    }
+}
+
+macro_rules! smtlib_logics {
+    ($($name:ident),* $(,)?) => {
+        /// A logic of the SMT-LIB benchmark library, such as [`Logic::QF_LIA`].
+        ///
+        /// Use it with [`Solver::new_for_logic()`](crate::Solver::new_for_logic)
+        /// to get a solver specialized for the logic, and with
+        /// [`Solver::set_logic()`](crate::Solver::set_logic) to check that the
+        /// asserted formulas belong to it.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// # use z3::Logic;
+        /// # use z3::ast::Int;
+        /// let logic: Logic = "QF_LIA".parse().unwrap();
+        /// assert_eq!(logic, Logic::QF_LIA);
+        /// assert_eq!(logic.to_string(), "QF_LIA");
+        ///
+        /// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+        /// assert!(logic.includes(&x.lt(&y).detect_logic()));
+        /// assert!(!logic.includes(&(&x * &y).eq(1).detect_logic()));
+        /// ```
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Logic {
+            $($name,)*
+        }
+
+        impl Logic {
+            /// All the logics, in the order they are declared.
+            pub fn list_all() -> &'static [Logic] {
+                &[$(Logic::$name),*]
+            }
+
+            /// The SMT-LIB name of the logic.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Logic::$name => stringify!($name),)*
+                }
+            }
+        }
+    };
+}
+
+smtlib_logics! {
+    ALL,
+    QF_AX, QF_UF, QF_BV, QF_ABV, QF_UFBV, QF_AUFBV, QF_BVFP, QF_ABVFP, QF_FP, QF_FPLRA,
+    QF_IDL, QF_RDL, QF_LIA, QF_LRA, QF_LIRA, QF_NIA, QF_NRA, QF_NIRA,
+    QF_UFIDL, QF_UFLIA, QF_UFLRA, QF_UFNIA, QF_UFNRA,
+    QF_ALIA, QF_AUFLIA, QF_ANIA, QF_AUFNIA, QF_AUFLIRA,
+    QF_DT, QF_UFDT, QF_S, QF_SLIA,
+    UF, BV, ABV, UFBV, AUFBV, BVFP, FP, FPLRA,
+    LIA, LRA, NIA, NRA, UFLIA, UFLRA, UFNIA, UFNRA, UFIDL,
+    ALIA, AUFLIA, AUFLIRA, AUFNIA, AUFNIRA,
+    UFDT, UFDTLIA, UFDTLIRA, UFDTNIA, UFDTNIRA,
+}
+
+impl Logic {
+    /// The features that formulas of the logic may use.
+    pub fn features(&self) -> LogicFeatures {
+        self.name()
+            .parse()
+            .expect("every SMT-LIB logic name parses into features")
+    }
+
+    /// Returns `true` if every formula using the `features` belongs to
+    /// the logic, see [`LogicFeatures::includes()`].
+    pub fn includes(&self, features: &LogicFeatures) -> bool {
+        self.features().includes(features)
+    }
+}
+
+impl fmt::Display for Logic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Logic {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Logic, String> {
+        Logic::list_all()
+            .iter()
+            .find(|logic| logic.name() == name)
+            .copied()
+            .ok_or_else(|| format!("unknown logic {name}"))
+    }
+}
+
+impl From<Logic> for Symbol {
+    fn from(logic: Logic) -> Self {
+        Symbol::String(logic.name().to_owned())
+    }
+}
+
+/// Visit every node of the DAG `ast` once, including quantifier bodies,
+/// until `visit` returns `false`.
+pub(crate) fn walk(ctx: &Context, ast: Z3_ast, mut visit: impl FnMut(Z3_ast, AstKind) -> bool) {
+    let z3_ctx = ctx.z3_ctx.0;
+    let mut visited = HashSet::new();
+    let mut stack = vec![ast];
+    while let Some(node) = stack.pop() {
+        if !visited.insert(unsafe { Z3_get_ast_id(z3_ctx, node) }) {
+            continue;
+        }
+        let kind = unsafe { Z3_get_ast_kind(z3_ctx, node) };
+        if !visit(node, kind) {
+            return;
+        }
+        match kind {
+            AstKind::App => unsafe {
+                let app = Z3_to_app(z3_ctx, node).unwrap();
+                for i in (0..Z3_get_app_num_args(z3_ctx, app)).rev() {
+                    stack.push(Z3_get_app_arg(z3_ctx, app, i).unwrap());
+                }
+            },
+            AstKind::Quantifier => unsafe {
+                stack.push(Z3_get_quantifier_body(z3_ctx, node).unwrap());
+            },
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use num::{BigInt, BigRational, Zero};
+use std::collections::HashMap;
+
+use crate::ast::Bool;
+use crate::optimize::Weight;
+use crate::{Model, Optimize, SatResult, Symbol};
+
+/// Weighted maximum satisfiability on top of [`Optimize`].
+///
+/// Hard clauses must hold, while soft clauses may be violated at the cost of
+/// their weight. Soft clauses are organized in groups: the total penalty of
+/// each group is minimized separately, with the groups combined according to
+/// the `opt.priority` parameter (lexicographically in order of creation by
+/// default).
+///
+/// # Example
+///
+/// ```
+/// # use z3::{MaxSat, SatResult};
+/// # use z3::ast::Bool;
+/// let (a, b) = (Bool::new_const("a"), Bool::new_const("b"));
+/// let mut max_sat = MaxSat::new();
+/// max_sat.add_hard(&Bool::or(&[&a, &b]).not());
+/// let prefer_a = max_sat.add_soft(&a, 3, "prefs");
+/// let prefer_not_b = max_sat.add_soft(&b.not(), 1, "prefs");
+///
+/// assert_eq!(max_sat.check(), SatResult::Sat);
+/// let solution = max_sat.get_solution().unwrap();
+/// assert_eq!(solution.violated(), &[prefer_a]);
+/// assert!(!solution.is_violated(prefer_not_b));
+/// assert_eq!(solution.penalty().to_string(), "3");
+/// ```
+#[derive(Debug)]
+pub struct MaxSat {
+    opt: Optimize,
+    soft: Vec<SoftClause>,
+}
+
+/// A soft clause of a [`MaxSat`] problem.
+#[derive(Debug, Clone)]
+pub struct SoftClause {
+    clause: Bool,
+    weight: BigRational,
+    group: String,
+}
+
+impl SoftClause {
+    /// Returns the clause.
+    pub fn clause(&self) -> &Bool {
+        &self.clause
+    }
+
+    /// Returns the penalty for violating the clause.
+    pub fn weight(&self) -> &BigRational {
+        &self.weight
+    }
+
+    /// Returns the group of the clause.
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+}
+
+impl MaxSat {
+    /// Create an empty problem.
+    pub fn new() -> MaxSat {
+        MaxSat {
+            opt: Optimize::new(),
+            soft: vec![],
+        }
+    }
+
+    /// Returns the underlying optimizer, e.g. to set parameters.
+    pub fn optimize(&self) -> &Optimize {
+        &self.opt
+    }
+
+    /// Add a clause which must hold.
+    pub fn add_hard(&mut self, clause: &Bool) {
+        self.opt.assert(clause);
+    }
+
+    /// Add a clause violated at the cost of `weight` to `group`, and return
+    /// its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is a fraction with a zero denominator.
+    pub fn add_soft(&mut self, clause: &Bool, weight: impl Weight, group: &str) -> usize {
+        let weight = parse_weight(&weight.to_string());
+        self.opt.assert_soft(
+            clause,
+            weight.clone(),
+            Some(Symbol::String(group.to_owned())),
+        );
+        self.soft.push(SoftClause {
+            clause: clause.clone(),
+            weight,
+            group: group.to_owned(),
+        });
+        self.soft.len() - 1
+    }
+
+    /// Returns the soft clauses, by index.
+    pub fn soft_clauses(&self) -> &[SoftClause] {
+        &self.soft
+    }
+
+    /// Solve the problem.
+    ///
+    /// Returns [`SatResult::Unsat`] if the hard clauses are unsatisfiable.
+    pub fn check(&self) -> SatResult {
+        self.opt.check(&[])
+    }
+
+    /// Returns the solution found by the last [`MaxSat::check()`], if it
+    /// returned [`SatResult::Sat`].
+    pub fn get_solution(&self) -> Option<MaxSatSolution> {
+        let model = self.opt.get_model()?;
+        let violated = self
+            .soft
+            .iter()
+            .enumerate()
+            .filter(|(_, soft)| {
+                model.eval(&soft.clause, true).and_then(|c| c.as_bool()) != Some(true)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        Some(MaxSatSolution {
+            model,
+            soft: self.soft.clone(),
+            violated,
+        })
+    }
+}
+
+impl Default for MaxSat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The result of a successful [`MaxSat::check()`].
+#[derive(Debug)]
+pub struct MaxSatSolution {
+    model: Model,
+    soft: Vec<SoftClause>,
+    violated: Vec<usize>,
+}
+
+impl MaxSatSolution {
+    /// Returns a model satisfying the hard clauses.
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// Returns the indices of the violated soft clauses, in increasing order.
+    pub fn violated(&self) -> &[usize] {
+        &self.violated
+    }
+
+    /// Returns `true` if the soft clause `index` is violated.
+    pub fn is_violated(&self, index: usize) -> bool {
+        self.violated.binary_search(&index).is_ok()
+    }
+
+    /// Returns the total weight of the violated soft clauses.
+    pub fn penalty(&self) -> BigRational {
+        self.violated
+            .iter()
+            .map(|&i| &self.soft[i].weight)
+            .fold(BigRational::zero(), |acc, w| acc + w)
+    }
+
+    /// Returns the total weight of the violated soft clauses, by group.
+    ///
+    /// Groups without violated clauses are included with a penalty of zero.
+    pub fn group_penalties(&self) -> HashMap<&str, BigRational> {
+        let mut penalties: HashMap<&str, BigRational> = self
+            .soft
+            .iter()
+            .map(|soft| (soft.group.as_str(), BigRational::zero()))
+            .collect();
+        for &i in &self.violated {
+            let soft = &self.soft[i];
+            *penalties.get_mut(soft.group.as_str()).unwrap() += &soft.weight;
+        }
+        penalties
+    }
+}
+
+/// Parse the string form of a [`Weight`], `"n"` or `"n / d"`.
+fn parse_weight(weight: &str) -> BigRational {
+    let parse = |s: &str| s.trim().parse::<BigInt>().unwrap();
+    match weight.split_once('/') {
+        Some((numer, denom)) => {
+            let denom = parse(denom);
+            assert!(!denom.is_zero(), "weight {weight} has a zero denominator");
+            BigRational::new(parse(numer), denom)
+        }
+        None => BigRational::from_integer(parse(weight)),
+    }
+}
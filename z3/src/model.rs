@@ -1,11 +1,14 @@
+use std::collections::{BTreeMap, HashSet};
 use std::ffi::CStr;
 use std::fmt;
+use std::marker::PhantomData;
 use z3_sys::*;
 
-use crate::{Context, FuncDecl, FuncInterp, Model, Optimize, Solver, Translate, ast::Ast};
+use crate::ast::{Ast, Dynamic};
+use crate::{Context, FromModel, FuncDecl, FuncInterp, Model, Optimize, Solver, Sort, Translate};
 
 impl Model {
-    unsafe fn wrap(ctx: &Context, z3_mdl: Z3_model) -> Model {
+    pub(crate) unsafe fn wrap(ctx: &Context, z3_mdl: Z3_model) -> Model {
         unsafe {
             Z3_model_inc_ref(ctx.z3_ctx.0, z3_mdl);
         }
@@ -98,6 +101,62 @@ impl Model {
         }
     }
 
+    /// Decode the value of `ast` in the model, with model completion.
+    ///
+    /// Returns `None` if the value cannot be represented as a `T`.
+    ///
+    /// # See also:
+    ///
+    /// - [`FromModel`]
+    pub fn get<T: FromModel>(&self, ast: &impl Ast) -> Option<T> {
+        T::from_model(self, &Dynamic::from_ast(ast))
+    }
+
+    /// List the terms among `terms` whose values differ between this model
+    /// and `other`, each with its value in this model and in `other`.
+    ///
+    /// Values are evaluated with model completion and compared after
+    /// simplification, so structurally different values which simplify to
+    /// the same term, such as equal arithmetic numerals, count as equal.
+    /// Terms which cannot be evaluated in one of the models are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    /// let solver = Solver::new();
+    /// solver.assert(x.eq(1) & y.ge(0) & y.le(1));
+    ///
+    /// solver.push();
+    /// solver.assert(y.eq(0));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// let first = solver.get_model().unwrap();
+    /// solver.pop(1);
+    ///
+    /// solver.assert(y.eq(1));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// let second = solver.get_model().unwrap();
+    ///
+    /// let diff = first.diff(&second, &[&x, &y]);
+    /// assert_eq!(diff.len(), 1);
+    /// let (term, before, after) = &diff[0];
+    /// assert_eq!(term.to_string(), "y");
+    /// assert_eq!((before.to_string(), after.to_string()), ("0".into(), "1".into()));
+    /// ```
+    pub fn diff(&self, other: &Model, terms: &[&dyn Ast]) -> Vec<(Dynamic, Dynamic, Dynamic)> {
+        terms
+            .iter()
+            .filter_map(|term| {
+                let term = Dynamic::from_ast(*term);
+                let value = self.eval(&term, true)?.simplify();
+                let other_value = other.eval(&term, true)?.simplify();
+                (value != other_value).then_some((term, value, other_value))
+            })
+            .collect()
+    }
+
     fn len(&self) -> u32 {
         unsafe {
             Z3_model_get_num_consts(self.ctx.z3_ctx.0, self.z3_mdl)
@@ -105,9 +164,230 @@ impl Model {
         }
     }
 
+    /// Iterate over the declarations the model interprets: the constants
+    /// sorted by name, then the functions sorted by name.
+    ///
+    /// The order does not depend on the order in which Z3 lists them, which
+    /// changes between Z3 releases, so output built from it is stable.
+    ///
+    /// ```
+    /// # use z3::{FuncDecl, SatResult, Solver, Sort};
+    /// # use z3::ast::Int;
+    /// let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    /// let solver = Solver::new();
+    /// for name in ["z", "a", "m"] {
+    ///     let x = Int::new_const(name);
+    ///     solver.assert(f.apply(&[&x]).as_int().unwrap().eq(&x));
+    /// }
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// let model = solver.get_model().unwrap();
+    /// let names: Vec<String> = model.iter().map(|decl| decl.name()).collect();
+    /// assert_eq!(names, ["a", "m", "z", "f"]);
+    /// ```
     pub fn iter<'a>(&'a self) -> ModelIter<'a> {
         self.into_iter()
     }
+
+    /// Return the full SMT-LIB representation of the model, with every
+    /// interpretation of constants and functions, as printed by
+    /// [`Display`](fmt::Display).
+    ///
+    /// This can take a long time for models with many or large
+    /// interpretations, see [`Model::summary()`] for a bounded alternative.
+    pub fn dump(&self) -> String {
+        self.to_string()
+    }
+
+    /// Return a view of the model which prints at most `max_entries`
+    /// interpretations: the values of constants, and the number of entries
+    /// of functions.
+    ///
+    /// Use [`Model::dump()`] to print every interpretation in full.
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let solver = Solver::new();
+    /// for i in 0..10 {
+    ///     solver.assert(Int::new_const(format!("x{i}")).eq(i));
+    /// }
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// let model = solver.get_model().unwrap();
+    /// let summary = model.summary(2).to_string();
+    /// assert_eq!(summary.lines().count(), 3);
+    /// assert!(summary.ends_with("... 8 more\n"));
+    /// ```
+    pub fn summary(&self, max_entries: usize) -> ModelSummary<'_> {
+        ModelSummary {
+            model: self,
+            max_entries,
+        }
+    }
+
+    /// Return a view of the model which prints its interpretations in a
+    /// stable order, independent of the order in which Z3 lists them.
+    ///
+    /// Constants are grouped by sort, with the groups and the constants in
+    /// each group sorted by name. Functions follow, sorted by name, with
+    /// their interpretation tabulated one entry per line, sorted by the
+    /// arguments. Arrays given by a function interpretation are tabulated
+    /// the same way, in place of the name of Z3's auxiliary function.
+    ///
+    /// ```
+    /// # use z3::{FuncDecl, SatResult, Solver, Sort};
+    /// # use z3::ast::{Bool, Int};
+    /// let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    /// let (y, x, b) = (Int::new_const("y"), Int::new_const("x"), Bool::new_const("b"));
+    /// let solver = Solver::new();
+    /// solver.assert(y.eq(2) & x.eq(1) & b.not());
+    /// solver.assert(f.apply(&[&y]).as_int().unwrap().eq(4));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let model = solver.get_model().unwrap();
+    /// let sorted = model.sorted().to_string();
+    /// let lines: Vec<&str> = sorted.lines().collect();
+    /// assert_eq!(
+    ///     lines[..6],
+    ///     ["; Bool", "b -> false", "; Int", "x -> 1", "y -> 2", "f : Int -> Int"]
+    /// );
+    /// assert!(lines[6].starts_with("  "));
+    /// ```
+    pub fn sorted(&self) -> SortedModel<'_> {
+        SortedModel { model: self }
+    }
+}
+
+/// A view of a [`Model`] printed in a stable order, see [`Model::sorted()`].
+#[derive(Debug)]
+pub struct SortedModel<'a> {
+    model: &'a Model,
+}
+
+impl fmt::Display for SortedModel<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let model = self.model;
+        let ctx = &model.ctx;
+        let mut consts: BTreeMap<String, Vec<FuncDecl>> = BTreeMap::new();
+        let mut funcs = vec![];
+        for decl in model {
+            if decl.arity() == 0 {
+                let sort = unsafe {
+                    Sort::wrap(ctx, Z3_get_range(ctx.z3_ctx.0, decl.z3_func_decl).unwrap())
+                };
+                consts.entry(sort.to_string()).or_default().push(decl);
+            } else {
+                funcs.push(decl);
+            }
+        }
+
+        // The auxiliary functions interpreting arrays, which are shown in
+        // place of the arrays instead.
+        let mut hidden = HashSet::new();
+        for (sort, mut decls) in consts {
+            writeln!(f, "; {sort}")?;
+            decls.sort_by_key(|decl| decl.name());
+            for decl in decls {
+                let name = decl.name();
+                let value = unsafe {
+                    Z3_model_get_const_interp(ctx.z3_ctx.0, model.z3_mdl, decl.z3_func_decl)
+                };
+                let Some(value) = value else {
+                    writeln!(f, "{name} -> ?")?;
+                    continue;
+                };
+                if unsafe { Z3_is_as_array(ctx.z3_ctx.0, value) } {
+                    let func = unsafe {
+                        FuncDecl::wrap(ctx, Z3_get_as_array_func_decl(ctx.z3_ctx.0, value).unwrap())
+                    };
+                    if let Some(interp) = model.get_func_interp(&func) {
+                        writeln!(f, "{name} ->")?;
+                        write_table(f, &interp)?;
+                        hidden.insert(func.name());
+                        continue;
+                    }
+                }
+                let value = unsafe { Dynamic::wrap(ctx, value) };
+                writeln!(f, "{name} -> {value}")?;
+            }
+        }
+
+        funcs.retain(|decl| !hidden.contains(&decl.name()));
+        funcs.sort_by_key(|decl| decl.name());
+        for decl in funcs {
+            let (domain, range) = unsafe {
+                let domain: Vec<String> = (0..decl.arity())
+                    .map(|i| {
+                        let sort =
+                            Z3_get_domain(ctx.z3_ctx.0, decl.z3_func_decl, i as u32).unwrap();
+                        Sort::wrap(ctx, sort).to_string()
+                    })
+                    .collect();
+                let range = Sort::wrap(ctx, Z3_get_range(ctx.z3_ctx.0, decl.z3_func_decl).unwrap());
+                (domain, range)
+            };
+            writeln!(f, "{} : {} -> {range}", decl.name(), domain.join(" "))?;
+            if let Some(interp) = model.get_func_interp(&decl) {
+                write_table(f, &interp)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write the entries of `interp`, sorted by their arguments, and its else
+/// value, one per indented line.
+fn write_table(f: &mut fmt::Formatter, interp: &FuncInterp) -> Result<(), fmt::Error> {
+    let mut rows: Vec<(String, String)> = interp
+        .get_entries()
+        .iter()
+        .map(|entry| {
+            let args: Vec<String> = entry.get_args().iter().map(|a| a.to_string()).collect();
+            (args.join(", "), entry.get_value().to_string())
+        })
+        .collect();
+    rows.sort();
+    for (args, value) in rows {
+        writeln!(f, "  {args} -> {value}")?;
+    }
+    writeln!(f, "  else -> {}", interp.get_else())
+}
+
+/// A bounded view of a [`Model`], see [`Model::summary()`].
+#[derive(Debug)]
+pub struct ModelSummary<'a> {
+    model: &'a Model,
+    max_entries: usize,
+}
+
+impl fmt::Display for ModelSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let model = self.model;
+        for decl in model.iter().take(self.max_entries) {
+            let name = decl.name();
+            if decl.arity() == 0 {
+                let value = unsafe {
+                    Z3_model_get_const_interp(model.ctx.z3_ctx.0, model.z3_mdl, decl.z3_func_decl)
+                };
+                match value {
+                    Some(value) => {
+                        let value = unsafe { Dynamic::wrap(&model.ctx, value) };
+                        writeln!(f, "{name} -> {value}")?;
+                    }
+                    None => writeln!(f, "{name} -> ?")?,
+                }
+            } else {
+                let entries = model
+                    .get_func_interp(&decl)
+                    .map_or(0, |interp| interp.get_num_entries());
+                writeln!(f, "{name} -> function with {entries} entries")?;
+            }
+        }
+        let len = model.len() as usize;
+        if len > self.max_entries {
+            writeln!(f, "... {} more", len - self.max_entries)?;
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for Model {
@@ -129,18 +409,24 @@ impl fmt::Debug for Model {
     }
 }
 
+impl Clone for Model {
+    fn clone(&self) -> Self {
+        unsafe { Self::wrap(&self.ctx, self.z3_mdl) }
+    }
+}
+
 impl Drop for Model {
     fn drop(&mut self) {
         unsafe { Z3_model_dec_ref(self.ctx.z3_ctx.0, self.z3_mdl) };
     }
 }
 
+/// Iterator over the declarations interpreted by a [`Model`], see
+/// [`Model::iter()`].
 #[derive(Debug)]
-/// <https://z3prover.github.io/api/html/classz3py_1_1_model_ref.html#a7890b7c9bc70cf2a26a343c22d2c8367>
 pub struct ModelIter<'a> {
-    model: &'a Model,
-    idx: u32,
-    len: u32,
+    decls: std::vec::IntoIter<FuncDecl>,
+    model: PhantomData<&'a Model>,
 }
 
 impl<'a> IntoIterator for &'a Model {
@@ -148,10 +434,34 @@ impl<'a> IntoIterator for &'a Model {
     type IntoIter = ModelIter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
+        let z3_ctx = self.ctx.z3_ctx.0;
+        let sorted = |mut decls: Vec<FuncDecl>| {
+            // Declarations of the same name are overloads, told apart by
+            // their signature.
+            decls.sort_by_cached_key(|decl| (decl.name(), decl.to_string()));
+            decls
+        };
+        let consts = (0..unsafe { Z3_model_get_num_consts(z3_ctx, self.z3_mdl) })
+            .map(|i| unsafe {
+                FuncDecl::wrap(
+                    &self.ctx,
+                    Z3_model_get_const_decl(z3_ctx, self.z3_mdl, i).unwrap(),
+                )
+            })
+            .collect();
+        let funcs = (0..unsafe { Z3_model_get_num_funcs(z3_ctx, self.z3_mdl) })
+            .map(|i| unsafe {
+                FuncDecl::wrap(
+                    &self.ctx,
+                    Z3_model_get_func_decl(z3_ctx, self.z3_mdl, i).unwrap(),
+                )
+            })
+            .collect();
+        let mut decls = sorted(consts);
+        decls.extend(sorted(funcs));
         ModelIter {
-            model: self,
-            idx: 0,
-            len: self.len(),
+            decls: decls.into_iter(),
+            model: PhantomData,
         }
     }
 }
@@ -160,39 +470,16 @@ impl Iterator for ModelIter<'_> {
     type Item = FuncDecl;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.idx >= self.len {
-            None
-        } else {
-            let num_consts =
-                unsafe { Z3_model_get_num_consts(self.model.ctx.z3_ctx.0, self.model.z3_mdl) };
-            if self.idx < num_consts {
-                let const_decl = unsafe {
-                    Z3_model_get_const_decl(self.model.ctx.z3_ctx.0, self.model.z3_mdl, self.idx)
-                        .unwrap()
-                };
-                self.idx += 1;
-                Some(unsafe { FuncDecl::wrap(&self.model.ctx, const_decl) })
-            } else {
-                let func_decl = unsafe {
-                    Z3_model_get_func_decl(
-                        self.model.ctx.z3_ctx.0,
-                        self.model.z3_mdl,
-                        self.idx - num_consts,
-                    )
-                    .unwrap()
-                };
-                self.idx += 1;
-                Some(unsafe { FuncDecl::wrap(&self.model.ctx, func_decl) })
-            }
-        }
+        self.decls.next()
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = (self.len - self.idx) as usize;
-        (len, Some(len))
+        self.decls.size_hint()
     }
 }
 
+impl ExactSizeIterator for ModelIter<'_> {}
+
 unsafe impl Translate for Model {
     fn translate(&self, dest: &Context) -> Model {
         unsafe {
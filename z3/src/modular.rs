@@ -0,0 +1,186 @@
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::Symbol;
+use crate::ast::{Ast, Bool, Int};
+
+/// An integer modulo the constant `N`, layered on [`Int`].
+///
+/// The value is kept normalized in `0..N`: every arithmetic operation takes
+/// the result `mod N`, so encodings of modular arithmetic (as in hashing or
+/// cryptography) do not have to insert the reductions by hand.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{Mod, SatResult, Solver};
+/// # use z3::ast::Ast;
+/// type F7 = Mod<7>;
+/// let x = F7::new_const("x");
+/// let solver = Solver::new();
+/// solver.assert((&x * F7::from_u64(3) + F7::from_u64(5)).eq(&F7::from_u64(1)));
+/// assert_eq!(solver.check(), SatResult::Sat);
+///
+/// let model = solver.get_model().unwrap();
+/// assert_eq!(model.eval(x.value(), true).unwrap().as_u64(), Some(1));
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Mod<const N: u64> {
+    value: Int,
+}
+
+impl<const N: u64> Mod<N> {
+    /// Create the residue of `value` modulo `N`.
+    pub fn new(value: &Int) -> Mod<N> {
+        const { assert!(N > 0, "The modulus must be positive") };
+        Mod {
+            value: value.modulo(N),
+        }
+    }
+
+    /// Create the residue modulo `N` of a new integer constant.
+    pub fn new_const<S: Into<Symbol>>(name: S) -> Mod<N> {
+        Mod::new(&Int::new_const(name))
+    }
+
+    /// Create the residue of `value` modulo `N`, as a numeral.
+    pub fn from_u64(value: u64) -> Mod<N> {
+        const { assert!(N > 0, "The modulus must be positive") };
+        Mod {
+            value: Int::from_u64(value % N),
+        }
+    }
+
+    /// Returns the modulus `N`.
+    pub fn modulus() -> u64 {
+        N
+    }
+
+    /// Returns the normalized value, in `0..N`.
+    pub fn value(&self) -> &Int {
+        &self.value
+    }
+
+    /// Returns the value as a `u64`, if it is a numeral after
+    /// simplification.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.value.simplify().as_u64()
+    }
+
+    /// Returns a Boolean expressing the equality of the two residues.
+    pub fn eq(&self, other: &Mod<N>) -> Bool {
+        self.value.eq(&other.value)
+    }
+
+    /// Raise the value to the power `exp`, by repeated squaring.
+    pub fn pow(&self, mut exp: u64) -> Mod<N> {
+        let mut result = Mod::from_u64(1);
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// Returns the multiplicative inverse, with the condition under which
+    /// it exists, i.e. the value is coprime to `N`.
+    ///
+    /// A numeral is inverted directly, and the condition is `true` or
+    /// `false`. Otherwise the inverse is a fresh constant, which the
+    /// condition constrains: assert the condition wherever the inverse is
+    /// used.
+    ///
+    /// ```
+    /// # use z3::Mod;
+    /// # use z3::ast::Ast;
+    /// let (inv, exists) = Mod::<10>::from_u64(3).inverse();
+    /// assert_eq!(inv.as_u64(), Some(7));
+    /// assert_eq!(exists.as_bool(), Some(true));
+    ///
+    /// let (_, exists) = Mod::<10>::from_u64(4).inverse();
+    /// assert_eq!(exists.as_bool(), Some(false));
+    /// ```
+    pub fn inverse(&self) -> (Mod<N>, Bool) {
+        if let Some(value) = self.as_u64() {
+            return match inverse_u64(value, N) {
+                Some(inv) => (Mod::from_u64(inv), Bool::from_bool(true)),
+                None => (Mod::from_u64(0), Bool::from_bool(false)),
+            };
+        }
+        let inv = Int::fresh_const("inv");
+        let exists = Bool::and(&[
+            inv.ge(0),
+            inv.lt(N),
+            (&self.value * &inv).modulo(N).eq(Int::from_u64(1 % N)),
+        ]);
+        (Mod { value: inv }, exists)
+    }
+}
+
+/// The inverse of `value` modulo `n`, by the extended Euclidean algorithm.
+fn inverse_u64(value: u64, n: u64) -> Option<u64> {
+    let (mut r0, mut r1) = (n as i128, (value % n) as i128);
+    let (mut t0, mut t1) = (0i128, 1i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    (r0 == 1).then(|| t0.rem_euclid(n as i128) as u64)
+}
+
+macro_rules! impl_mod_op {
+    ($tr:ident::$trop:ident) => {
+        impl_mod_op!($tr::$trop, Mod<N>, Mod<N>);
+        impl_mod_op!($tr::$trop, Mod<N>, &Mod<N>);
+        impl_mod_op!($tr::$trop, &Mod<N>, Mod<N>);
+        impl_mod_op!($tr::$trop, &Mod<N>, &Mod<N>);
+    };
+    ($tr:ident::$trop:ident, $lhs:ty, $rhs:ty) => {
+        impl<const N: u64> $tr<$rhs> for $lhs {
+            type Output = Mod<N>;
+
+            fn $trop(self, rhs: $rhs) -> Mod<N> {
+                Mod::new(&(&self.value).$trop(&rhs.value))
+            }
+        }
+    };
+}
+
+impl_mod_op!(Add::add);
+impl_mod_op!(Sub::sub);
+impl_mod_op!(Mul::mul);
+
+impl<const N: u64> Neg for Mod<N> {
+    type Output = Mod<N>;
+
+    fn neg(self) -> Mod<N> {
+        -&self
+    }
+}
+
+impl<const N: u64> Neg for &Mod<N> {
+    type Output = Mod<N>;
+
+    fn neg(self) -> Mod<N> {
+        Mod::new(&-&self.value)
+    }
+}
+
+impl<const N: u64> fmt::Display for Mod<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl<const N: u64> fmt::Debug for Mod<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} (mod {N})", self.value)
+    }
+}
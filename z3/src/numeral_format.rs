@@ -0,0 +1,244 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::rc::Rc;
+use z3_sys::*;
+
+use crate::Context;
+use crate::ast::{Ast, Dynamic};
+
+/// A hook rendering numerals, see [`Context::set_numeral_formatter()`].
+pub(crate) type NumeralFormatter = dyn Fn(&Dynamic) -> Option<String>;
+
+thread_local! {
+    // Contexts are not `Send`, so the formatters of a context always live in
+    // the thread owning it. They are keyed by the address of the context.
+    static FORMATTERS: RefCell<HashMap<usize, Rc<NumeralFormatter>>> = RefCell::new(HashMap::new());
+    // Set while a term is printed with a formatter, so that a formatter
+    // printing terms itself gets Z3's rendering instead of recursing.
+    static FORMATTING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn key(z3_ctx: Z3_context) -> usize {
+    z3_ctx.as_ptr() as usize
+}
+
+pub(crate) fn set(ctx: &Context, formatter: Option<Rc<NumeralFormatter>>) {
+    FORMATTERS.with(|f| {
+        let mut formatters = f.borrow_mut();
+        match formatter {
+            Some(formatter) => formatters.insert(key(ctx.z3_ctx.0), formatter),
+            None => formatters.remove(&key(ctx.z3_ctx.0)),
+        }
+    });
+}
+
+pub(crate) fn get(ctx: &Context) -> Option<Rc<NumeralFormatter>> {
+    if FORMATTING.with(Cell::get) {
+        return None;
+    }
+    FORMATTERS.with(|f| f.borrow().get(&key(ctx.z3_ctx.0)).cloned())
+}
+
+/// Forget the formatter of a context which is being deleted.
+pub(crate) fn forget(z3_ctx: Z3_context) {
+    // The table may already be gone if the thread is exiting.
+    let _ = FORMATTERS.try_with(|f| f.borrow_mut().remove(&key(z3_ctx)));
+}
+
+/// Clears [`FORMATTING`] when printing ends, even if the formatter panics.
+struct Formatting;
+
+impl Drop for Formatting {
+    fn drop(&mut self) {
+        FORMATTING.with(|f| f.set(false));
+    }
+}
+
+/// How a node of the printed term is rendered.
+enum Shape {
+    /// Printed as a whole, by the formatter or by Z3.
+    Leaf(String),
+    /// An application printed as `(head args...)`.
+    App(String, Vec<Z3_ast>),
+}
+
+/// Print `ast` in SMT-LIB syntax, rendering numerals with `formatter`.
+///
+/// Applications shared in the DAG are named by `let` bindings `a!1`, `a!2`,
+/// ..., bound at the top of the term, so that the output stays linear in the
+/// size of the DAG. Terms which cannot be rendered structurally, such as
+/// quantifiers, are printed by Z3 as a whole.
+pub(crate) fn ast_to_string(ctx: &Context, ast: Z3_ast, formatter: &NumeralFormatter) -> String {
+    FORMATTING.with(|f| f.set(true));
+    let _formatting = Formatting;
+    let z3_ctx = ctx.z3_ctx.0;
+    let id = |node| unsafe { Z3_get_ast_id(z3_ctx, node) };
+
+    // Find the shape of every node, the number of its occurrences as an
+    // argument, and the order in which the shared ones can be bound.
+    let mut shapes = HashMap::new();
+    let mut occurrences = HashMap::<u32, usize>::new();
+    let mut post_order = vec![];
+    let mut stack = vec![(ast, false)];
+    while let Some((node, expanded)) = stack.pop() {
+        if expanded {
+            post_order.push(node);
+            continue;
+        }
+        *occurrences.entry(id(node)).or_default() += 1;
+        if shapes.contains_key(&id(node)) {
+            continue;
+        }
+        let shape = shape(ctx, node, formatter);
+        stack.push((node, true));
+        if let Shape::App(_, args) = &shape {
+            stack.extend(args.iter().rev().map(|&arg| (arg, false)));
+        }
+        shapes.insert(id(node), shape);
+    }
+    let mut names = HashMap::new();
+    let shared: Vec<Z3_ast> = post_order
+        .into_iter()
+        .filter(|&node| matches!(shapes[&id(node)], Shape::App(..)) && occurrences[&id(node)] > 1)
+        .collect();
+    for (i, &node) in shared.iter().enumerate() {
+        names.insert(id(node), format!("a!{}", i + 1));
+    }
+
+    // Print `node`, referring to the shared nodes below it by name.
+    let write = |out: &mut String, node: Z3_ast| {
+        enum Item {
+            Node(Z3_ast),
+            Text(&'static str),
+        }
+        let mut stack = vec![Item::Node(node)];
+        let mut top = true;
+        while let Some(item) = stack.pop() {
+            let node = match item {
+                Item::Text(text) => {
+                    out.push_str(text);
+                    continue;
+                }
+                Item::Node(node) => node,
+            };
+            if let Some(name) = names.get(&id(node)).filter(|_| !top) {
+                out.push_str(name);
+                continue;
+            }
+            top = false;
+            match &shapes[&id(node)] {
+                Shape::Leaf(text) => out.push_str(text),
+                Shape::App(head, args) => {
+                    out.push('(');
+                    out.push_str(head);
+                    stack.push(Item::Text(")"));
+                    for &arg in args.iter().rev() {
+                        stack.push(Item::Node(arg));
+                        stack.push(Item::Text(" "));
+                    }
+                }
+            }
+        }
+    };
+
+    let mut out = String::new();
+    for &node in &shared {
+        out.push_str("(let ((");
+        out.push_str(&names[&id(node)]);
+        out.push(' ');
+        write(&mut out, node);
+        out.push_str(")) ");
+    }
+    write(&mut out, ast);
+    out.extend(shared.iter().map(|_| ')'));
+    out
+}
+
+fn shape(ctx: &Context, ast: Z3_ast, formatter: &NumeralFormatter) -> Shape {
+    let z3_ctx = ctx.z3_ctx.0;
+    let kind = unsafe { Z3_get_ast_kind(z3_ctx, ast) };
+    let is_numeral = kind == AstKind::Numeral
+        || (kind == AstKind::App && unsafe { Z3_is_numeral_ast(z3_ctx, ast) });
+    if is_numeral {
+        return Shape::Leaf(
+            formatter(&unsafe { Dynamic::wrap(ctx, ast) })
+                .unwrap_or_else(|| raw_to_string(ctx, ast)),
+        );
+    }
+    if kind != AstKind::App {
+        return Shape::Leaf(raw_to_string(ctx, ast));
+    }
+    let (app, decl) = unsafe {
+        let app = Z3_to_app(z3_ctx, ast).unwrap();
+        (app, Z3_get_app_decl(z3_ctx, app).unwrap())
+    };
+    let num_args = unsafe { Z3_get_app_num_args(z3_ctx, app) };
+    let head = if num_args == 0 {
+        None
+    } else {
+        decl_head(ctx, decl)
+    };
+    let Some(head) = head else {
+        return Shape::Leaf(raw_to_string(ctx, ast));
+    };
+    let args = (0..num_args)
+        .map(|i| unsafe { Z3_get_app_arg(z3_ctx, app, i).unwrap() })
+        .collect();
+    Shape::App(head, args)
+}
+
+/// The head of an application of `decl`, e.g. `bvadd` or `(_ extract 7 0)`,
+/// or `None` if it has parameters that cannot be printed as indices.
+fn decl_head(ctx: &Context, decl: Z3_func_decl) -> Option<String> {
+    let z3_ctx = ctx.z3_ctx.0;
+    let name = unsafe { symbol_to_string(ctx, Z3_get_decl_name(z3_ctx, decl).unwrap()) };
+    let num_params = unsafe { Z3_get_decl_num_parameters(z3_ctx, decl) };
+    if num_params == 0 {
+        return Some(name);
+    }
+    let mut head = format!("(_ {name}");
+    for i in 0..num_params {
+        let param = unsafe {
+            match Z3_get_decl_parameter_kind(z3_ctx, decl, i) {
+                ParameterKind::Int => Z3_get_decl_int_parameter(z3_ctx, decl, i).to_string(),
+                ParameterKind::Symbol => {
+                    symbol_to_string(ctx, Z3_get_decl_symbol_parameter(z3_ctx, decl, i).unwrap())
+                }
+                _ => return None,
+            }
+        };
+        head.push(' ');
+        head.push_str(&param);
+    }
+    head.push(')');
+    Some(head)
+}
+
+unsafe fn symbol_to_string(ctx: &Context, symbol: Z3_symbol) -> String {
+    let z3_ctx = ctx.z3_ctx.0;
+    let name = unsafe {
+        match Z3_get_symbol_kind(z3_ctx, symbol) {
+            SymbolKind::String => CStr::from_ptr(Z3_get_symbol_string(z3_ctx, symbol))
+                .to_string_lossy()
+                .into_owned(),
+            SymbolKind::Int => return format!("k!{}", Z3_get_symbol_int(z3_ctx, symbol)),
+        }
+    };
+    let simple = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "~!@$%^&*_-+=<>.?/".contains(c));
+    if simple { name } else { format!("|{name}|") }
+}
+
+fn raw_to_string(ctx: &Context, ast: Z3_ast) -> String {
+    unsafe {
+        let p = Z3_ast_to_string(ctx.z3_ctx.0, ast);
+        if p.is_null() {
+            return String::new();
+        }
+        CStr::from_ptr(p).to_string_lossy().into_owned()
+    }
+}
@@ -1,16 +1,19 @@
 use log::debug;
+use std::cell::RefCell;
 use std::convert::TryInto;
-use std::ffi::{CStr, CString};
+use std::ffi::{CStr, CString, c_void};
 use std::fmt;
 
 use z3_sys::*;
 
 use crate::{
-    Context, Model, Optimize, Params, SatResult, Statistics, Symbol,
+    AstVector, Context, Model, Optimize, OptimizeScope, ParamDescrs, Params, ParseError, SatResult,
+    Statistics, Symbol,
     ast::{Ast, Bool, Dynamic},
 };
 
 use num::{
+    Signed, Zero,
     bigint::{BigInt, BigUint, Sign},
     rational::BigRational,
 };
@@ -23,6 +26,7 @@ impl Optimize {
         Optimize {
             ctx: ctx.clone(),
             z3_opt,
+            on_model: RefCell::new(None),
         }
     }
 
@@ -34,11 +38,47 @@ impl Optimize {
 
     /// Parse an SMT-LIB2 string with assertions, soft constraints and optimization objectives.
     /// Add the parsed constraints and objectives to the optimizer.
-    pub fn from_string<T: Into<Vec<u8>>>(&self, source_string: T) {
-        let source_cstring = CString::new(source_string).unwrap();
-        unsafe {
+    ///
+    /// The [`Display`](fmt::Display) output of an optimizer is in the same
+    /// format, so problems can be serialized and loaded again.
+    ///
+    /// # Example
+    /// ```
+    /// # use z3::{Optimize, SatResult};
+    /// let opt = Optimize::new();
+    /// opt.from_string(
+    ///     "(declare-const x Int)
+    ///      (assert (< x 10))
+    ///      (maximize x)",
+    /// )
+    /// .unwrap();
+    ///
+    /// let copy = Optimize::new();
+    /// copy.from_string(opt.to_string()).unwrap();
+    /// assert_eq!(copy.check(&[]), SatResult::Sat);
+    /// assert_eq!(copy.get_objectives()[0].to_string(), "(- x)");
+    ///
+    /// assert!(copy.from_string("(assert (< y 10))").is_err());
+    /// ```
+    pub fn from_string<T: Into<Vec<u8>>>(&self, source_string: T) -> Result<(), ParseError> {
+        let source_cstring =
+            CString::new(source_string).map_err(|e| ParseError::new(e.to_string()))?;
+        ParseError::catch(&self.ctx, || unsafe {
             Z3_optimize_from_string(self.ctx.z3_ctx.0, self.z3_opt, source_cstring.as_ptr());
-        }
+        })
+    }
+
+    /// Parse an SMT-LIB2 file with assertions, soft constraints and optimization objectives.
+    /// Add the parsed constraints and objectives to the optimizer.
+    ///
+    /// # See also:
+    ///
+    /// - [`Optimize::from_string()`]
+    pub fn from_file(&self, filename: &str) -> Result<(), ParseError> {
+        let filename = CString::new(filename).map_err(|e| ParseError::new(e.to_string()))?;
+        ParseError::catch(&self.ctx, || unsafe {
+            Z3_optimize_from_file(self.ctx.z3_ctx.0, self.z3_opt, filename.as_ptr());
+        })
     }
 
     /// Get this optimizers 's context.
@@ -154,24 +194,18 @@ impl Optimize {
     ///
     /// - [`Optimize::check`]
     pub fn get_unsat_core(&self) -> Vec<Bool> {
-        if let Some(z3_unsat_core) =
-            unsafe { Z3_optimize_get_unsat_core(self.ctx.z3_ctx.0, self.z3_opt) }
-        {
-            let len = unsafe { Z3_ast_vector_size(self.ctx.z3_ctx.0, z3_unsat_core) };
-
-            let mut unsat_core = Vec::with_capacity(len as usize);
-
-            for i in 0..len {
-                let elem =
-                    unsafe { Z3_ast_vector_get(self.ctx.z3_ctx.0, z3_unsat_core, i).unwrap() };
-                let elem = unsafe { Bool::wrap(&self.ctx, elem) };
-                unsat_core.push(elem);
-            }
-
-            unsat_core
-        } else {
-            vec![]
-        }
+        let Some(z3_unsat_core) =
+            (unsafe { Z3_optimize_get_unsat_core(self.ctx.z3_ctx.0, self.z3_opt) })
+        else {
+            return vec![];
+        };
+        // Keep the vector alive while its elements are wrapped.
+        let unsat_core = unsafe { AstVector::wrap(&self.ctx, z3_unsat_core) };
+        unsat_core
+            .to_vec()
+            .into_iter()
+            .map(|elem| elem.as_bool().unwrap())
+            .collect()
     }
 
     /// Create a backtracking point.
@@ -201,6 +235,23 @@ impl Optimize {
         unsafe { Z3_optimize_pop(self.ctx.z3_ctx.0, self.z3_opt) };
     }
 
+    /// Create a backtracking point, which is popped when the returned guard
+    /// is dropped.
+    ///
+    /// # See also:
+    ///
+    /// - [`Optimize::scope()`]
+    pub fn push_scoped(&self) -> OptimizeScope<'_> {
+        OptimizeScope::new(self)
+    }
+
+    /// Run `f` in a new backtracking point, which is popped afterwards,
+    /// also if `f` panics.
+    pub fn scope<R>(&self, f: impl FnOnce(&Optimize) -> R) -> R {
+        let scope = self.push_scoped();
+        f(&scope)
+    }
+
     /// Check consistency and produce optimal values.
     ///
     /// # See also:
@@ -235,22 +286,133 @@ impl Optimize {
     /// Retrieve the objectives for the last [`Optimize::check()`].
     ///
     /// This contains maximize/minimize objectives and grouped soft constraints.
+    ///
+    /// # See also:
+    ///
+    /// - [`Optimize::objectives()`]
     pub fn get_objectives(&self) -> Vec<Dynamic> {
-        let (z3_objectives, len) = unsafe {
-            let objectives = Z3_optimize_get_objectives(self.ctx.z3_ctx.0, self.z3_opt).unwrap();
-            let len = Z3_ast_vector_size(self.ctx.z3_ctx.0, objectives);
-            (objectives, len)
-        };
+        self.objectives().to_vec()
+    }
 
-        let mut objectives = Vec::with_capacity(len as usize);
+    /// Retrieve the objective terms as an [`AstVector`].
+    ///
+    /// Each minimize objective appears as its term, each maximize objective
+    /// as the term Z3 minimizes in its place (e.g. its negation), and each
+    /// group of soft constraints as the internally generated penalty term, in
+    /// the order in which they were added.
+    ///
+    /// ```
+    /// # use z3::Optimize;
+    /// # use z3::ast::{Bool, Int};
+    /// let opt = Optimize::new();
+    /// let x = Int::new_const("x");
+    /// opt.minimize(&x);
+    /// opt.assert_soft(&Bool::new_const("b"), 2, None);
+    /// let objectives = opt.objectives();
+    /// assert_eq!(objectives.len(), 2);
+    /// assert_eq!(objectives.get(0).to_string(), "x");
+    /// ```
+    pub fn objectives(&self) -> AstVector {
+        unsafe {
+            AstVector::wrap(
+                &self.ctx,
+                Z3_optimize_get_objectives(self.ctx.z3_ctx.0, self.z3_opt).unwrap(),
+            )
+        }
+    }
 
-        for i in 0..len {
-            let elem = unsafe { Z3_ast_vector_get(self.ctx.z3_ctx.0, z3_objectives, i).unwrap() };
-            let elem = unsafe { Dynamic::wrap(&self.ctx, elem) };
-            objectives.push(elem);
+    /// Return the objective with the given `index`, or `None` if there are
+    /// not that many objectives.
+    ///
+    /// Objectives are numbered in the order in which they were added by
+    /// [`Optimize::maximize()`], [`Optimize::minimize()`] and the first
+    /// [`Optimize::assert_soft()`] of each group.
+    ///
+    /// # See also:
+    ///
+    /// - [`Optimize::objectives()`]
+    pub fn objective(&self, index: usize) -> Option<Objective<'_>> {
+        (index < self.objectives().len()).then_some(Objective {
+            opt: self,
+            index: index.try_into().unwrap(),
+        })
+    }
+
+    /// Enumerate the Pareto front of the objectives.
+    ///
+    /// Every call to `next` checks the context again, yielding the next
+    /// Pareto-optimal model until the front is exhausted (or the check
+    /// returns [`SatResult::Unknown`]).
+    ///
+    /// This switches the context to Pareto mode: it sets the `priority`
+    /// parameter to `pareto`, which stays in effect for later checks after
+    /// the iterator is dropped. Set `priority` again with
+    /// [`Optimize::set_params()`] to return to another mode.
+    ///
+    /// ```
+    /// # use z3::Optimize;
+    /// # use z3::ast::Int;
+    /// let mut opt = Optimize::new();
+    /// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    /// opt.assert(&x.ge(0));
+    /// opt.assert(&y.ge(0));
+    /// opt.assert(&(&x + &y).le(2));
+    /// opt.maximize(&x);
+    /// opt.maximize(&y);
+    /// assert_eq!(opt.pareto_iter().count(), 3);
+    /// ```
+    pub fn pareto_iter(&mut self) -> ParetoIter<'_> {
+        let mut params = Params::new();
+        params.set_symbol("priority", "pareto");
+        self.set_params(&params);
+        ParetoIter {
+            opt: self,
+            done: false,
         }
+    }
 
-        objectives
+    /// Register a callback invoked with each improved model found during
+    /// [`Optimize::check()`], replacing any callback registered before.
+    ///
+    /// This allows observing the best solution so far while a long-running
+    /// optimization continues. The model passed to the callback is only
+    /// valid during the call; copy out the values you need.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::cell::RefCell;
+    /// # use std::rc::Rc;
+    /// # use z3::{Optimize, SatResult};
+    /// # use z3::ast::Int;
+    /// let opt = Optimize::new();
+    /// let x = Int::new_const("x");
+    /// opt.assert(&x.lt(100));
+    /// opt.maximize(&x);
+    ///
+    /// let best = Rc::new(RefCell::new(vec![]));
+    /// let sink = best.clone();
+    /// let y = x.clone();
+    /// opt.set_on_model(move |model| {
+    ///     sink.borrow_mut().push(model.eval(&y, true).unwrap().as_i64().unwrap());
+    /// });
+    /// assert_eq!(opt.check(&[]), SatResult::Sat);
+    /// assert_eq!(best.borrow().last(), Some(&99));
+    /// ```
+    pub fn set_on_model(&self, callback: impl FnMut(&Model) + 'static) {
+        let mut state = Box::new(OnModelState {
+            model: unsafe { Model::wrap(&self.ctx, Z3_mk_model(self.ctx.z3_ctx.0).unwrap()) },
+            callback: Box::new(callback),
+        });
+        unsafe {
+            Z3_optimize_register_model_eh(
+                self.ctx.z3_ctx.0,
+                self.z3_opt,
+                state.model.z3_mdl,
+                &mut *state as *mut OnModelState as *mut c_void,
+                Some(on_model_trampoline),
+            );
+        }
+        *self.on_model.borrow_mut() = Some(state);
     }
 
     /// Retrieve a string that describes the last status returned by [`Optimize::check()`].
@@ -272,6 +434,25 @@ impl Optimize {
         unsafe { Z3_optimize_set_params(self.ctx.z3_ctx.0, self.z3_opt, params.z3_params) };
     }
 
+    /// Return a string describing all available parameters for this
+    /// optimizer, see [`HelpEntry::parse()`](crate::HelpEntry::parse).
+    pub fn get_help(&self) -> String {
+        unsafe {
+            let help = Z3_optimize_get_help(self.ctx.z3_ctx.0, self.z3_opt);
+            CStr::from_ptr(help).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Return the parameters accepted by this optimizer.
+    pub fn get_param_descrs(&self) -> ParamDescrs {
+        unsafe {
+            ParamDescrs::wrap(
+                &self.ctx,
+                Z3_optimize_get_param_descrs(self.ctx.z3_ctx.0, self.z3_opt).unwrap(),
+            )
+        }
+    }
+
     /// Retrieve the statistics for the last [`Optimize::check()`].
     pub fn get_statistics(&self) -> Statistics {
         unsafe {
@@ -283,6 +464,159 @@ impl Optimize {
     }
 }
 
+/// An objective of an [`Optimize`] context, see [`Optimize::objective()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Objective<'a> {
+    opt: &'a Optimize,
+    index: u32,
+}
+
+impl Objective<'_> {
+    /// Returns the index of the objective.
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    /// Returns the term of the objective, as in [`Optimize::objectives()`].
+    pub fn term(&self) -> Dynamic {
+        self.opt.objectives().get(self.index as usize)
+    }
+
+    /// Returns the lower bound found for the objective by the last
+    /// [`Optimize::check()`].
+    ///
+    /// ```
+    /// # use z3::{ObjectiveBound, Optimize, SatResult};
+    /// # use z3::ast::Int;
+    /// let opt = Optimize::new();
+    /// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    /// opt.assert(&x.ge(3));
+    /// opt.minimize(&x);
+    /// opt.minimize(&y);
+    /// assert_eq!(opt.check(&[]), SatResult::Sat);
+    /// assert_eq!(opt.objective(0).unwrap().lower().unwrap().to_string(), "3");
+    /// assert_eq!(opt.objective(1).unwrap().lower(), Some(ObjectiveBound::NegativeInfinity));
+    /// ```
+    pub fn lower(&self) -> Option<ObjectiveBound> {
+        let vector = unsafe {
+            Z3_optimize_get_lower_as_vector(self.opt.ctx.z3_ctx.0, self.opt.z3_opt, self.index)?
+        };
+        ObjectiveBound::from_vector(&unsafe { AstVector::wrap(&self.opt.ctx, vector) })
+    }
+
+    /// Returns the upper bound found for the objective by the last
+    /// [`Optimize::check()`].
+    pub fn upper(&self) -> Option<ObjectiveBound> {
+        let vector = unsafe {
+            Z3_optimize_get_upper_as_vector(self.opt.ctx.z3_ctx.0, self.opt.z3_opt, self.index)?
+        };
+        ObjectiveBound::from_vector(&unsafe { AstVector::wrap(&self.opt.ctx, vector) })
+    }
+}
+
+/// A bound of an [`Objective`].
+///
+/// Z3 represents bounds as `a·∞ + b + c·ε`, where `ε` is an infinitesimal
+/// used for bounds which are only achieved in the limit, e.g. the minimum of
+/// `x` subject to `x > 0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectiveBound {
+    /// A finite bound which is attained.
+    Finite(BigRational),
+    /// The bound `value + epsilon·ε`, approached but not attained.
+    Infinitesimal {
+        value: BigRational,
+        epsilon: BigRational,
+    },
+    /// The objective is unbounded from above.
+    PositiveInfinity,
+    /// The objective is unbounded from below.
+    NegativeInfinity,
+}
+
+impl ObjectiveBound {
+    fn from_vector(vector: &AstVector) -> Option<ObjectiveBound> {
+        if vector.len() != 3 {
+            return None;
+        }
+        let coeff = |i: usize| -> Option<BigRational> {
+            let numeral = vector.get(i);
+            let s = unsafe { Z3_get_numeral_string(numeral.ctx.z3_ctx.0, numeral.z3_ast) };
+            if s.is_null() {
+                return None;
+            }
+            unsafe { CStr::from_ptr(s) }.to_str().ok()?.parse().ok()
+        };
+        let (infinity, value, epsilon) = (coeff(0)?, coeff(1)?, coeff(2)?);
+        Some(if infinity.is_positive() {
+            ObjectiveBound::PositiveInfinity
+        } else if infinity.is_negative() {
+            ObjectiveBound::NegativeInfinity
+        } else if epsilon.is_zero() {
+            ObjectiveBound::Finite(value)
+        } else {
+            ObjectiveBound::Infinitesimal { value, epsilon }
+        })
+    }
+
+    /// Returns `true` if the bound is finite and attained.
+    pub fn is_finite(&self) -> bool {
+        matches!(self, ObjectiveBound::Finite(_))
+    }
+}
+
+impl fmt::Display for ObjectiveBound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            ObjectiveBound::Finite(value) => write!(f, "{value}"),
+            ObjectiveBound::Infinitesimal { value, epsilon } => {
+                write!(f, "{value} + {epsilon}*epsilon")
+            }
+            ObjectiveBound::PositiveInfinity => write!(f, "oo"),
+            ObjectiveBound::NegativeInfinity => write!(f, "-oo"),
+        }
+    }
+}
+
+/// Iterator over the Pareto-optimal models of an [`Optimize`] context.
+///
+/// # See also:
+///
+/// - [`Optimize::pareto_iter()`]
+#[derive(Debug)]
+pub struct ParetoIter<'a> {
+    opt: &'a Optimize,
+    done: bool,
+}
+
+impl Iterator for ParetoIter<'_> {
+    type Item = Model;
+
+    fn next(&mut self) -> Option<Model> {
+        if self.done {
+            return None;
+        }
+        let model = match self.opt.check(&[]) {
+            SatResult::Sat => self.opt.get_model(),
+            SatResult::Unsat | SatResult::Unknown => None,
+        };
+        self.done = model.is_none();
+        model
+    }
+}
+
+impl std::iter::FusedIterator for ParetoIter<'_> {}
+
+pub(crate) struct OnModelState {
+    model: Model,
+    callback: Box<dyn FnMut(&Model)>,
+}
+
+unsafe extern "C" fn on_model_trampoline(state: *mut c_void) {
+    let state = unsafe { &mut *(state as *mut OnModelState) };
+    (state.callback)(&state.model);
+}
+
 impl Default for Optimize {
     fn default() -> Self {
         Self::new()
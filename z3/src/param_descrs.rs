@@ -0,0 +1,150 @@
+use std::ffi::CStr;
+use std::fmt;
+use z3_sys::*;
+
+use crate::{Context, ParamDescrs, Params, Symbol};
+
+/// The description of a single parameter in a [`ParamDescrs`] set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamDescr {
+    pub name: String,
+    pub kind: ParamKind,
+    pub documentation: String,
+}
+
+impl ParamDescrs {
+    pub(crate) unsafe fn wrap(ctx: &Context, z3_param_descrs: Z3_param_descrs) -> ParamDescrs {
+        unsafe {
+            Z3_param_descrs_inc_ref(ctx.z3_ctx.0, z3_param_descrs);
+        }
+        ParamDescrs {
+            ctx: ctx.clone(),
+            z3_param_descrs,
+        }
+    }
+
+    /// Returns the parameters of the simplifier used by
+    /// [`Ast::simplify()`](crate::ast::Ast::simplify).
+    pub fn simplifier() -> ParamDescrs {
+        let ctx = &Context::thread_local();
+        unsafe { Self::wrap(ctx, Z3_simplify_get_param_descrs(ctx.z3_ctx.0).unwrap()) }
+    }
+
+    /// Check that every parameter set in `params` is in the set, with the
+    /// right kind, and return Z3's error message otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{Params, Tactic};
+    /// let descrs = Tactic::new("simplify").get_param_descrs();
+    /// let mut params = Params::new();
+    /// params.set_bool("elim_and", true);
+    /// assert!(descrs.validate(&params).is_ok());
+    ///
+    /// params.set_bool("elim_andd", true);
+    /// assert!(descrs.validate(&params).unwrap_err().contains("elim_andd"));
+    /// ```
+    pub fn validate(&self, params: &Params) -> Result<(), String> {
+        unsafe {
+            let z3_ctx = self.ctx.z3_ctx.0;
+            Z3_set_error(z3_ctx, ErrorCode::OK);
+            Z3_params_validate(z3_ctx, params.z3_params, self.z3_param_descrs);
+            let code = Z3_get_error_code(z3_ctx);
+            if code == ErrorCode::OK {
+                return Ok(());
+            }
+            let msg = Z3_get_error_msg(z3_ctx, code);
+            Err(CStr::from_ptr(msg).to_string_lossy().into_owned())
+        }
+    }
+
+    /// Returns the number of parameters in the set.
+    pub fn len(&self) -> usize {
+        unsafe { Z3_param_descrs_size(self.ctx.z3_ctx.0, self.z3_param_descrs) as usize }
+    }
+
+    /// Returns `true` if the set describes no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the kind of the parameter `name`, or `None` if there is no
+    /// such parameter.
+    pub fn get_kind<S: Into<Symbol>>(&self, name: S) -> Option<ParamKind> {
+        self.kind_of(name.into().as_z3_symbol())
+    }
+
+    /// Returns the documentation of the parameter `name`, or `None` if there
+    /// is no such parameter.
+    pub fn get_documentation<S: Into<Symbol>>(&self, name: S) -> Option<String> {
+        self.documentation_of(name.into().as_z3_symbol())
+    }
+
+    /// Iterate over the descriptions of all parameters in the set.
+    pub fn iter(&self) -> impl Iterator<Item = ParamDescr> + '_ {
+        (0..self.len()).map(move |i| {
+            let z3_ctx = self.ctx.z3_ctx.0;
+            // Look the parameter up by the symbol Z3 returns, which need not
+            // be a string symbol.
+            let symbol =
+                unsafe { Z3_param_descrs_get_name(z3_ctx, self.z3_param_descrs, i as u32) }
+                    .unwrap();
+            let name = unsafe {
+                match Z3_get_symbol_kind(z3_ctx, symbol) {
+                    SymbolKind::String => CStr::from_ptr(Z3_get_symbol_string(z3_ctx, symbol))
+                        .to_string_lossy()
+                        .into_owned(),
+                    SymbolKind::Int => format!("k!{}", Z3_get_symbol_int(z3_ctx, symbol)),
+                }
+            };
+            ParamDescr {
+                name,
+                kind: self.kind_of(symbol).unwrap_or(ParamKind::Invalid),
+                documentation: self.documentation_of(symbol).unwrap_or_default(),
+            }
+        })
+    }
+
+    fn kind_of(&self, name: Z3_symbol) -> Option<ParamKind> {
+        let kind =
+            unsafe { Z3_param_descrs_get_kind(self.ctx.z3_ctx.0, self.z3_param_descrs, name) };
+        (kind != ParamKind::Invalid).then_some(kind)
+    }
+
+    fn documentation_of(&self, name: Z3_symbol) -> Option<String> {
+        self.kind_of(name)?;
+        unsafe {
+            let doc =
+                Z3_param_descrs_get_documentation(self.ctx.z3_ctx.0, self.z3_param_descrs, name);
+            (!doc.is_null()).then(|| CStr::from_ptr(doc).to_string_lossy().into_owned())
+        }
+    }
+}
+
+impl fmt::Display for ParamDescrs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_param_descrs_to_string(self.ctx.z3_ctx.0, self.z3_param_descrs) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{s}"),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
+impl fmt::Debug for ParamDescrs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        <Self as fmt::Display>::fmt(self, f)
+    }
+}
+
+impl Drop for ParamDescrs {
+    fn drop(&mut self) {
+        unsafe {
+            Z3_param_descrs_dec_ref(self.ctx.z3_ctx.0, self.z3_param_descrs);
+        }
+    }
+}
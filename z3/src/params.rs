@@ -4,6 +4,15 @@ use z3_sys::*;
 
 use crate::{Context, Params, Symbol};
 
+/// The value of a parameter, as given to one of the setters of [`Params`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ParamValue {
+    Bool(bool),
+    U32(u32),
+    F64(f64),
+    Symbol(Symbol),
+}
+
 impl Params {
     unsafe fn wrap(ctx: &Context, z3_params: Z3_params) -> Params {
         unsafe {
@@ -12,6 +21,7 @@ impl Params {
         Params {
             ctx: ctx.clone(),
             z3_params,
+            entries: vec![],
         }
     }
 
@@ -21,47 +31,41 @@ impl Params {
     }
 
     pub fn set_symbol<K: Into<Symbol>, V: Into<Symbol>>(&mut self, k: K, v: V) {
-        unsafe {
-            Z3_params_set_symbol(
-                self.ctx.z3_ctx.0,
-                self.z3_params,
-                k.into().as_z3_symbol(),
-                v.into().as_z3_symbol(),
-            );
-        };
+        self.set(k.into(), ParamValue::Symbol(v.into()));
     }
 
     pub fn set_bool<K: Into<Symbol>>(&mut self, k: K, v: bool) {
-        unsafe {
-            Z3_params_set_bool(
-                self.ctx.z3_ctx.0,
-                self.z3_params,
-                k.into().as_z3_symbol(),
-                v,
-            );
-        };
+        self.set(k.into(), ParamValue::Bool(v));
     }
 
     pub fn set_f64<K: Into<Symbol>>(&mut self, k: K, v: f64) {
-        unsafe {
-            Z3_params_set_double(
-                self.ctx.z3_ctx.0,
-                self.z3_params,
-                k.into().as_z3_symbol(),
-                v,
-            );
-        };
+        self.set(k.into(), ParamValue::F64(v));
     }
 
     pub fn set_u32<K: Into<Symbol>>(&mut self, k: K, v: u32) {
+        self.set(k.into(), ParamValue::U32(v));
+    }
+
+    pub(crate) fn set(&mut self, k: Symbol, v: ParamValue) {
+        let z3_ctx = self.ctx.z3_ctx.0;
+        let key = k.as_z3_symbol();
         unsafe {
-            Z3_params_set_uint(
-                self.ctx.z3_ctx.0,
-                self.z3_params,
-                k.into().as_z3_symbol(),
-                v,
-            );
-        };
+            match &v {
+                ParamValue::Bool(v) => Z3_params_set_bool(z3_ctx, self.z3_params, key, *v),
+                ParamValue::U32(v) => Z3_params_set_uint(z3_ctx, self.z3_params, key, *v),
+                ParamValue::F64(v) => Z3_params_set_double(z3_ctx, self.z3_params, key, *v),
+                ParamValue::Symbol(v) => {
+                    Z3_params_set_symbol(z3_ctx, self.z3_params, key, v.as_z3_symbol())
+                }
+            }
+        }
+        self.entries.retain(|(key, _)| *key != k);
+        self.entries.push((k, v));
+    }
+
+    /// The parameters set so far, in the order they were first set.
+    pub(crate) fn entries(&self) -> &[(Symbol, ParamValue)] {
+        &self.entries
     }
 }
 
@@ -100,6 +104,23 @@ pub fn set_global_param(k: &str, v: &str) {
     unsafe { Z3_global_param_set(ks.as_ptr(), vs.as_ptr()) };
 }
 
+/// Keep Z3 from starting threads of its own, in all contexts.
+///
+/// This turns off the parallel SAT and SMT cores, which is needed where
+/// threads are unavailable, such as on `wasm32-unknown-unknown`. Contexts
+/// are single-threaded otherwise anyway: a [`Context`] is neither `Send`
+/// nor `Sync`.
+///
+/// # See also
+///
+/// - [`set_global_param()`]
+/// - [`Solver::check_yielding()`](crate::Solver::check_yielding)
+pub fn set_single_threaded() {
+    set_global_param("parallel.enable", "false");
+    set_global_param("sat.threads", "1");
+    set_global_param("smt.threads", "1");
+}
+
 /// Restore the value of all global (and module) parameters. This command will not affect already created objects (such as tactics and solvers).
 ///
 /// # See also
@@ -0,0 +1,113 @@
+use std::ffi::CStr;
+use std::fmt;
+
+use z3_sys::*;
+
+use crate::Context;
+
+/// An error raised while parsing SMT-LIB2 input, with the location of the
+/// offending input when Z3 reports one.
+///
+/// When the input contains several errors, the location and message are
+/// those of the first one.
+///
+/// # Example
+///
+/// ```
+/// # use z3::Optimize;
+/// let opt = Optimize::new();
+/// let err = opt
+///     .from_string("(declare-const x Int)\n(assert (< y 10))")
+///     .unwrap_err();
+/// assert_eq!(err.line(), Some(2));
+/// assert_eq!(err.column(), Some(11));
+/// assert_eq!(err.message(), "unknown constant y");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    line: Option<u32>,
+    column: Option<u32>,
+    message: String,
+}
+
+impl ParseError {
+    /// Create a parse error without a location.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    /// Extract the first error from a Z3 error message, which lists errors
+    /// as `(error "line L column C: message")`.
+    pub(crate) fn from_z3_message(msg: &str) -> Self {
+        let Some(start) = msg.find("(error \"") else {
+            return Self::new(msg.trim());
+        };
+        let rest = &msg[start + "(error \"".len()..];
+        let error = rest.find("\")").map_or(rest, |end| &rest[..end]);
+        match parse_location(error) {
+            Some((line, column, message)) => Self {
+                line: Some(line),
+                column: Some(column),
+                message: message.to_string(),
+            },
+            None => Self::new(error),
+        }
+    }
+
+    /// Call `parse`, and return the error it raised in `ctx`, if any.
+    pub(crate) fn catch<T>(ctx: &Context, parse: impl FnOnce() -> T) -> Result<T, ParseError> {
+        unsafe {
+            // Not every parser resets the error code on success.
+            Z3_set_error(ctx.z3_ctx.0, ErrorCode::OK);
+            let result = parse();
+            let code = Z3_get_error_code(ctx.z3_ctx.0);
+            if code == ErrorCode::OK {
+                return Ok(result);
+            }
+            let msg = Z3_get_error_msg(ctx.z3_ctx.0, code);
+            Err(Self::from_z3_message(
+                &CStr::from_ptr(msg).to_string_lossy(),
+            ))
+        }
+    }
+
+    /// The line of the error, starting from 1, if known.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// The column of the error, if known.
+    pub fn column(&self) -> Option<u32> {
+        self.column
+    }
+
+    /// The description of the error, without its location.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Split `line L column C: message`.
+fn parse_location(error: &str) -> Option<(u32, u32, &str)> {
+    let rest = error.strip_prefix("line ")?;
+    let (line, rest) = rest.split_once(" column ")?;
+    let (column, message) = rest.split_once(": ")?;
+    Some((line.parse().ok()?, column.parse().ok()?, message))
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => {
+                write!(f, "line {line} column {column}: {}", self.message)
+            }
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
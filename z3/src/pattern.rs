@@ -3,9 +3,23 @@ use std::ffi::CStr;
 use std::fmt;
 use z3_sys::*;
 
-use crate::{Context, Pattern, ast::Ast};
+use crate::ast::{Ast, Dynamic};
+use crate::{Context, Pattern};
 
 impl Pattern {
+    pub(crate) unsafe fn wrap(ctx: &Context, z3_pattern: Z3_pattern) -> Pattern {
+        unsafe {
+            Z3_inc_ref(
+                ctx.z3_ctx.0,
+                Z3_pattern_to_ast(ctx.z3_ctx.0, z3_pattern).unwrap(),
+            );
+        }
+        Pattern {
+            ctx: ctx.clone(),
+            z3_pattern,
+        }
+    }
+
     /// Create a pattern for quantifier instantiation.
     ///
     /// Z3 uses pattern matching to instantiate quantifiers. If a
@@ -45,6 +59,19 @@ impl Pattern {
             },
         }
     }
+
+    /// Return the terms of the (multi-)pattern.
+    pub fn terms(&self) -> Vec<Dynamic> {
+        let n = unsafe { Z3_get_pattern_num_terms(self.ctx.z3_ctx.0, self.z3_pattern) };
+        (0..n)
+            .map(|i| unsafe {
+                Dynamic::wrap(
+                    &self.ctx,
+                    Z3_get_pattern(self.ctx.z3_ctx.0, self.z3_pattern, i).unwrap(),
+                )
+            })
+            .collect()
+    }
 }
 
 impl fmt::Debug for Pattern {
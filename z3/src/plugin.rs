@@ -0,0 +1,660 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::fmt;
+use std::os::raw::c_uint;
+
+use z3_sys::*;
+
+use crate::ast::{Ast, Dynamic};
+use crate::{Context, FuncDecl, Sort};
+
+/// Identifies a term of a [`TermRegistry`]. `0` is never a valid id, and is
+/// returned by the [`TermProvider`] functions on failure.
+pub type TermId = u64;
+
+/// Identifies a sort of a [`TermRegistry`]. `0` is never a valid id.
+pub type SortId = u64;
+
+/// Identifies a function declaration of a [`TermRegistry`]. `0` is never a
+/// valid id.
+pub type DeclId = u64;
+
+/// The version of the [`TermProvider`] layout. It is increased whenever
+/// functions are added to the table, and only ever by appending them.
+pub const TERM_PROVIDER_ABI_VERSION: u32 = 1;
+
+/// The built-in operators which [`TermProvider::op`] applies, by their
+/// stable numeric code.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TermOp {
+    /// Boolean negation, of one argument.
+    Not = 0,
+    /// Conjunction, of any number of arguments.
+    And = 1,
+    /// Disjunction, of any number of arguments.
+    Or = 2,
+    Implies = 3,
+    Xor = 4,
+    Eq = 5,
+    /// Pairwise disequality, of any number of arguments.
+    Distinct = 6,
+    /// If-then-else, of three arguments.
+    Ite = 7,
+    /// Arithmetic sum, of any number of arguments.
+    Add = 8,
+    /// Arithmetic difference, of any number of arguments.
+    Sub = 9,
+    /// Arithmetic product, of any number of arguments.
+    Mul = 10,
+    Div = 11,
+    Mod = 12,
+    /// Arithmetic negation, of one argument.
+    Neg = 13,
+    Lt = 14,
+    Le = 15,
+    Gt = 16,
+    Ge = 17,
+    BvAdd = 18,
+    BvSub = 19,
+    BvMul = 20,
+    BvAnd = 21,
+    BvOr = 22,
+    BvXor = 23,
+    /// Bitwise negation, of one argument.
+    BvNot = 24,
+    /// Two's complement negation, of one argument.
+    BvNeg = 25,
+    BvUlt = 26,
+    BvUle = 27,
+    BvSlt = 28,
+    BvSle = 29,
+    Concat = 30,
+    /// Array read, of an array and an index.
+    Select = 31,
+    /// Array update, of an array, an index and a value.
+    Store = 32,
+}
+
+impl TryFrom<u32> for TermOp {
+    type Error = u32;
+
+    fn try_from(code: u32) -> Result<TermOp, u32> {
+        use TermOp::*;
+        const OPS: [TermOp; 33] = [
+            Not, And, Or, Implies, Xor, Eq, Distinct, Ite, Add, Sub, Mul, Div, Mod, Neg, Lt, Le,
+            Gt, Ge, BvAdd, BvSub, BvMul, BvAnd, BvOr, BvXor, BvNot, BvNeg, BvUlt, BvUle, BvSlt,
+            BvSle, Concat, Select, Store,
+        ];
+        OPS.get(code as usize).copied().ok_or(code)
+    }
+}
+
+/// Terms of the thread-local [`Context`] referred to by plain integer ids,
+/// so that code in other languages can build them through a [`TermProvider`].
+///
+/// The registry keeps every term alive until it is released, or until the
+/// registry is dropped. The host process moves terms between the registry
+/// and the rest of this crate with [`TermRegistry::insert()`] and
+/// [`TermRegistry::get()`].
+///
+/// # Example
+///
+/// ```
+/// # use std::ffi::CString;
+/// # use z3::{SatResult, Solver, TermOp, TermRegistry};
+/// let registry = TermRegistry::new();
+/// let provider = registry.provider();
+///
+/// // What a plugin written in C would do with the table.
+/// let (reg, x) = (provider.registry, CString::new("x").unwrap());
+/// let two = CString::new("2").unwrap();
+/// let id = unsafe {
+///     let int = (provider.int_sort)(reg);
+///     let x = (provider.constant)(reg, x.as_ptr(), int);
+///     let two = (provider.numeral)(reg, two.as_ptr(), int);
+///     (provider.op)(reg, TermOp::Gt as u32, [x, two].as_ptr(), 2)
+/// };
+///
+/// let term = registry.get(id).unwrap();
+/// assert_eq!(term.to_string(), "(> x 2)");
+/// let solver = Solver::new();
+/// solver.assert(term.as_bool().unwrap());
+/// assert_eq!(solver.check(), SatResult::Sat);
+/// ```
+pub struct TermRegistry {
+    // Boxed, so that the pointer handed out in `TermProvider` stays valid
+    // when the registry moves.
+    state: Box<RefCell<RegistryState>>,
+}
+
+struct RegistryState {
+    ctx: Context,
+    terms: HashMap<TermId, Dynamic>,
+    next_term: TermId,
+    sorts: Vec<Sort>,
+    decls: Vec<FuncDecl>,
+    error: CString,
+}
+
+/// A table of `extern "C"` functions building terms in a [`TermRegistry`],
+/// handed to code in other languages, e.g. a plugin loaded by the host.
+///
+/// Every function takes [`TermProvider::registry`] as first argument.
+/// Functions creating an object return its id, or `0` on failure, in which
+/// case [`TermProvider::last_error`] describes the problem. Strings are
+/// NUL-terminated and UTF-8, and arrays are passed as a pointer and a
+/// length.
+///
+/// The table is valid as long as the registry it was obtained from, and
+/// only on the thread which created the registry.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TermProvider {
+    /// [`TERM_PROVIDER_ABI_VERSION`] at the time the table was created.
+    pub abi_version: u32,
+    /// The registry, opaque to the plugin.
+    pub registry: *mut c_void,
+    /// Returns the Boolean sort.
+    pub bool_sort: unsafe extern "C" fn(registry: *mut c_void) -> SortId,
+    /// Returns the integer sort.
+    pub int_sort: unsafe extern "C" fn(registry: *mut c_void) -> SortId,
+    /// Returns the real sort.
+    pub real_sort: unsafe extern "C" fn(registry: *mut c_void) -> SortId,
+    /// Returns the sort of bit-vectors of `width` bits.
+    pub bv_sort: unsafe extern "C" fn(registry: *mut c_void, width: u32) -> SortId,
+    /// Returns the sort of arrays from `domain` to `range`.
+    pub array_sort:
+        unsafe extern "C" fn(registry: *mut c_void, domain: SortId, range: SortId) -> SortId,
+    /// Returns the uninterpreted sort named `name`.
+    pub uninterpreted_sort:
+        unsafe extern "C" fn(registry: *mut c_void, name: *const c_char) -> SortId,
+    /// Creates a constant named `name` of sort `sort`.
+    pub constant:
+        unsafe extern "C" fn(registry: *mut c_void, name: *const c_char, sort: SortId) -> TermId,
+    /// Creates a numeral of sort `sort` from its decimal text, such as
+    /// `"-12"` or `"1/3"`.
+    pub numeral:
+        unsafe extern "C" fn(registry: *mut c_void, value: *const c_char, sort: SortId) -> TermId,
+    /// Creates the Boolean constant `value`.
+    pub boolean: unsafe extern "C" fn(registry: *mut c_void, value: bool) -> TermId,
+    /// Declares a function named `name`, from the `domain_len` sorts at
+    /// `domain` to `range`.
+    pub declare_fun: unsafe extern "C" fn(
+        registry: *mut c_void,
+        name: *const c_char,
+        domain: *const SortId,
+        domain_len: usize,
+        range: SortId,
+    ) -> DeclId,
+    /// Applies the declared function `decl` to `num_args` terms.
+    pub apply: unsafe extern "C" fn(
+        registry: *mut c_void,
+        decl: DeclId,
+        args: *const TermId,
+        num_args: usize,
+    ) -> TermId,
+    /// Applies the built-in operator of code `op`, see [`TermOp`], to
+    /// `num_args` terms.
+    pub op: unsafe extern "C" fn(
+        registry: *mut c_void,
+        op: u32,
+        args: *const TermId,
+        num_args: usize,
+    ) -> TermId,
+    /// Releases the term `term`, whose id becomes invalid. Terms built from
+    /// it remain valid.
+    pub release: unsafe extern "C" fn(registry: *mut c_void, term: TermId),
+    /// Writes the SMT-LIB text of `term` to `buf`, truncated to `len - 1`
+    /// bytes and NUL-terminated. Returns the length of the full text, so
+    /// that a larger buffer can be retried, or `0` for an invalid term.
+    pub to_string: unsafe extern "C" fn(
+        registry: *mut c_void,
+        term: TermId,
+        buf: *mut c_char,
+        len: usize,
+    ) -> usize,
+    /// Writes the message of the last failure to `buf`, like `to_string`.
+    pub last_error:
+        unsafe extern "C" fn(registry: *mut c_void, buf: *mut c_char, len: usize) -> usize,
+}
+
+impl TermRegistry {
+    /// Create an empty registry for the thread-local context.
+    pub fn new() -> TermRegistry {
+        TermRegistry {
+            state: Box::new(RefCell::new(RegistryState {
+                ctx: Context::thread_local(),
+                terms: HashMap::new(),
+                next_term: 1,
+                sorts: vec![],
+                decls: vec![],
+                error: CString::default(),
+            })),
+        }
+    }
+
+    /// Returns the table of functions through which other languages build
+    /// terms in this registry.
+    pub fn provider(&self) -> TermProvider {
+        TermProvider {
+            abi_version: TERM_PROVIDER_ABI_VERSION,
+            registry: &*self.state as *const RefCell<RegistryState> as *mut c_void,
+            bool_sort,
+            int_sort,
+            real_sort,
+            bv_sort,
+            array_sort,
+            uninterpreted_sort,
+            constant,
+            numeral,
+            boolean,
+            declare_fun,
+            apply,
+            op,
+            release,
+            to_string,
+            last_error,
+        }
+    }
+
+    /// Register `ast`, and return its id.
+    pub fn insert(&self, ast: &impl Ast) -> TermId {
+        let mut state = self.state.borrow_mut();
+        assert_eq!(ast.get_ctx(), &state.ctx, "term of another context");
+        state.insert(Dynamic::from_ast(ast))
+    }
+
+    /// Returns the term of id `id`, unless it was released.
+    pub fn get(&self, id: TermId) -> Option<Dynamic> {
+        self.state.borrow().terms.get(&id).cloned()
+    }
+
+    /// Release the term of id `id`. Returns `false` if there is none.
+    pub fn release(&self, id: TermId) -> bool {
+        self.state.borrow_mut().terms.remove(&id).is_some()
+    }
+
+    /// Returns the number of terms registered and not released.
+    pub fn len(&self) -> usize {
+        self.state.borrow().terms.len()
+    }
+
+    /// Returns `true` if no term is registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the message of the last failure of a [`TermProvider`]
+    /// function.
+    pub fn last_error(&self) -> String {
+        self.state.borrow().error.to_string_lossy().into_owned()
+    }
+}
+
+impl Default for TermRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for TermRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("TermRegistry")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl RegistryState {
+    fn insert(&mut self, term: Dynamic) -> TermId {
+        let id = self.next_term;
+        self.next_term += 1;
+        self.terms.insert(id, term);
+        id
+    }
+
+    /// Record `msg` as the last error, and return the failure id `0`.
+    fn fail(&mut self, msg: impl Into<String>) -> u64 {
+        let msg = msg.into().replace('\0', " ");
+        self.error = CString::new(msg).unwrap_or_default();
+        0
+    }
+
+    /// Run the raw Z3 call `mk` with a cleared error code, and return Z3's
+    /// error if it fails.
+    fn z3<T>(&self, mk: impl FnOnce(Z3_context) -> Option<T>) -> Result<T, String> {
+        let z3_ctx = self.ctx.z3_ctx.0;
+        unsafe { Z3_set_error(z3_ctx, ErrorCode::OK) };
+        let result = mk(z3_ctx);
+        let code = unsafe { Z3_get_error_code(z3_ctx) };
+        match result {
+            Some(result) if code == ErrorCode::OK => Ok(result),
+            _ => {
+                let msg = unsafe { CStr::from_ptr(Z3_get_error_msg(z3_ctx, code)) };
+                Err(msg.to_string_lossy().into_owned())
+            }
+        }
+    }
+
+    /// Register the term built by `mk`, or record Z3's error.
+    fn term(&mut self, mk: impl FnOnce(Z3_context) -> Option<Z3_ast>) -> TermId {
+        match self.z3(mk) {
+            Ok(z3_ast) => {
+                let term = unsafe { Dynamic::wrap(&self.ctx, z3_ast) };
+                self.insert(term)
+            }
+            Err(e) => self.fail(e),
+        }
+    }
+
+    fn sort(&mut self, sort: Sort) -> SortId {
+        self.sorts.push(sort);
+        self.sorts.len() as SortId
+    }
+
+    /// Register the sort built by `mk`, or record Z3's error.
+    fn mk_sort(&mut self, mk: impl FnOnce(Z3_context) -> Option<Z3_sort>) -> SortId {
+        match self.z3(mk) {
+            Ok(z3_sort) => {
+                let sort = unsafe { Sort::wrap(&self.ctx, z3_sort) };
+                self.sort(sort)
+            }
+            Err(e) => self.fail(e),
+        }
+    }
+
+    fn get_sort(&self, id: SortId) -> Result<Sort, String> {
+        let index = usize::try_from(id).ok().and_then(|i| i.checked_sub(1));
+        index
+            .and_then(|i| self.sorts.get(i).cloned())
+            .ok_or_else(|| format!("invalid sort id {id}"))
+    }
+
+    fn get_args(&self, ids: &[TermId]) -> Result<Vec<Z3_ast>, String> {
+        ids.iter()
+            .map(|id| {
+                self.terms
+                    .get(id)
+                    .map(|t| t.get_z3_ast())
+                    .ok_or_else(|| format!("invalid term id {id}"))
+            })
+            .collect()
+    }
+
+    fn op(&mut self, op: TermOp, args: &[Z3_ast]) -> TermId {
+        use TermOp::*;
+        let n = args.len() as c_uint;
+        let arity = match op {
+            And | Or | Distinct | Add | Sub | Mul => None,
+            Not | Neg | BvNot | BvNeg => Some(1),
+            Ite | Store => Some(3),
+            _ => Some(2),
+        };
+        if let Some(arity) = arity {
+            if args.len() != arity {
+                return self.fail(format!(
+                    "{op:?} expects {arity} arguments, got {}",
+                    args.len()
+                ));
+            }
+        } else if args.is_empty() && op != And && op != Or {
+            return self.fail(format!("{op:?} expects at least one argument"));
+        }
+        self.term(|c| unsafe {
+            match op {
+                Not => Z3_mk_not(c, args[0]),
+                And => Z3_mk_and(c, n, args.as_ptr()),
+                Or => Z3_mk_or(c, n, args.as_ptr()),
+                Implies => Z3_mk_implies(c, args[0], args[1]),
+                Xor => Z3_mk_xor(c, args[0], args[1]),
+                Eq => Z3_mk_eq(c, args[0], args[1]),
+                Distinct => Z3_mk_distinct(c, n, args.as_ptr()),
+                Ite => Z3_mk_ite(c, args[0], args[1], args[2]),
+                Add => Z3_mk_add(c, n, args.as_ptr()),
+                Sub => Z3_mk_sub(c, n, args.as_ptr()),
+                Mul => Z3_mk_mul(c, n, args.as_ptr()),
+                Div => Z3_mk_div(c, args[0], args[1]),
+                Mod => Z3_mk_mod(c, args[0], args[1]),
+                Neg => Z3_mk_unary_minus(c, args[0]),
+                Lt => Z3_mk_lt(c, args[0], args[1]),
+                Le => Z3_mk_le(c, args[0], args[1]),
+                Gt => Z3_mk_gt(c, args[0], args[1]),
+                Ge => Z3_mk_ge(c, args[0], args[1]),
+                BvAdd => Z3_mk_bvadd(c, args[0], args[1]),
+                BvSub => Z3_mk_bvsub(c, args[0], args[1]),
+                BvMul => Z3_mk_bvmul(c, args[0], args[1]),
+                BvAnd => Z3_mk_bvand(c, args[0], args[1]),
+                BvOr => Z3_mk_bvor(c, args[0], args[1]),
+                BvXor => Z3_mk_bvxor(c, args[0], args[1]),
+                BvNot => Z3_mk_bvnot(c, args[0]),
+                BvNeg => Z3_mk_bvneg(c, args[0]),
+                BvUlt => Z3_mk_bvult(c, args[0], args[1]),
+                BvUle => Z3_mk_bvule(c, args[0], args[1]),
+                BvSlt => Z3_mk_bvslt(c, args[0], args[1]),
+                BvSle => Z3_mk_bvsle(c, args[0], args[1]),
+                Concat => Z3_mk_concat(c, args[0], args[1]),
+                Select => Z3_mk_select(c, args[0], args[1]),
+                Store => Z3_mk_store(c, args[0], args[1], args[2]),
+            }
+        })
+    }
+}
+
+/// Run `f` on the state behind the `registry` pointer of a [`TermProvider`].
+unsafe fn with_state<R>(registry: *mut c_void, f: impl FnOnce(&mut RegistryState) -> R) -> R {
+    let state = unsafe { &*(registry as *const RefCell<RegistryState>) };
+    f(&mut state.borrow_mut())
+}
+
+unsafe fn str_arg<'a>(s: *const c_char) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err("null string".to_owned());
+    }
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|e| format!("invalid UTF-8: {e}"))
+}
+
+unsafe fn slice_arg<'a, T>(ptr: *const T, len: usize) -> &'a [T] {
+    if len == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(ptr, len) }
+    }
+}
+
+/// Copy `text` to the buffer `buf` of `len` bytes, like `snprintf`.
+unsafe fn write_str(text: &[u8], buf: *mut c_char, len: usize) -> usize {
+    if len > 0 && !buf.is_null() {
+        let n = text.len().min(len - 1);
+        unsafe {
+            std::ptr::copy_nonoverlapping(text.as_ptr(), buf as *mut u8, n);
+            *buf.add(n) = 0;
+        }
+    }
+    text.len()
+}
+
+unsafe extern "C" fn bool_sort(registry: *mut c_void) -> SortId {
+    unsafe { with_state(registry, |s| s.sort(Sort::bool())) }
+}
+
+unsafe extern "C" fn int_sort(registry: *mut c_void) -> SortId {
+    unsafe { with_state(registry, |s| s.sort(Sort::int())) }
+}
+
+unsafe extern "C" fn real_sort(registry: *mut c_void) -> SortId {
+    unsafe { with_state(registry, |s| s.sort(Sort::real())) }
+}
+
+unsafe extern "C" fn bv_sort(registry: *mut c_void, width: u32) -> SortId {
+    unsafe {
+        with_state(registry, |s| match width {
+            0 => s.fail("bit-vectors must have at least one bit"),
+            _ => s.mk_sort(|c| Z3_mk_bv_sort(c, width)),
+        })
+    }
+}
+
+unsafe extern "C" fn array_sort(registry: *mut c_void, domain: SortId, range: SortId) -> SortId {
+    unsafe {
+        with_state(registry, |s| {
+            match s.get_sort(domain).and_then(|d| Ok((d, s.get_sort(range)?))) {
+                Ok((domain, range)) => {
+                    s.mk_sort(|c| Z3_mk_array_sort(c, domain.z3_sort, range.z3_sort))
+                }
+                Err(e) => s.fail(e),
+            }
+        })
+    }
+}
+
+unsafe extern "C" fn uninterpreted_sort(registry: *mut c_void, name: *const c_char) -> SortId {
+    unsafe {
+        with_state(registry, |s| match str_arg(name) {
+            Ok(_) => s.mk_sort(|c| Z3_mk_uninterpreted_sort(c, Z3_mk_string_symbol(c, name)?)),
+            Err(e) => s.fail(e),
+        })
+    }
+}
+
+unsafe extern "C" fn constant(registry: *mut c_void, name: *const c_char, sort: SortId) -> TermId {
+    unsafe {
+        with_state(registry, |s| {
+            match str_arg(name).and_then(|_| s.get_sort(sort)) {
+                Ok(sort) => s.term(|c| Z3_mk_const(c, Z3_mk_string_symbol(c, name)?, sort.z3_sort)),
+                Err(e) => s.fail(e),
+            }
+        })
+    }
+}
+
+unsafe extern "C" fn numeral(registry: *mut c_void, value: *const c_char, sort: SortId) -> TermId {
+    unsafe {
+        with_state(registry, |s| match s.get_sort(sort) {
+            Ok(sort) if !value.is_null() => s.term(|c| Z3_mk_numeral(c, value, sort.z3_sort)),
+            Ok(_) => s.fail("null string"),
+            Err(e) => s.fail(e),
+        })
+    }
+}
+
+unsafe extern "C" fn boolean(registry: *mut c_void, value: bool) -> TermId {
+    unsafe {
+        with_state(registry, |s| {
+            s.term(|c| if value { Z3_mk_true(c) } else { Z3_mk_false(c) })
+        })
+    }
+}
+
+unsafe extern "C" fn declare_fun(
+    registry: *mut c_void,
+    name: *const c_char,
+    domain: *const SortId,
+    domain_len: usize,
+    range: SortId,
+) -> DeclId {
+    unsafe {
+        with_state(registry, |s| {
+            let sorts = slice_arg(domain, domain_len)
+                .iter()
+                .map(|&id| s.get_sort(id))
+                .collect::<Result<Vec<Sort>, String>>();
+            let decl = str_arg(name)
+                .and_then(|_| Ok((sorts?, s.get_sort(range)?)))
+                .and_then(|(domain, range)| {
+                    let domain: Vec<Z3_sort> = domain.iter().map(|d| d.z3_sort).collect();
+                    s.z3(|c| {
+                        Z3_mk_func_decl(
+                            c,
+                            Z3_mk_string_symbol(c, name)?,
+                            domain.len() as c_uint,
+                            domain.as_ptr(),
+                            range.z3_sort,
+                        )
+                    })
+                });
+            match decl {
+                Ok(z3_func_decl) => {
+                    let decl = FuncDecl::wrap(&s.ctx, z3_func_decl);
+                    s.decls.push(decl);
+                    s.decls.len() as DeclId
+                }
+                Err(e) => s.fail(e),
+            }
+        })
+    }
+}
+
+unsafe extern "C" fn apply(
+    registry: *mut c_void,
+    decl: DeclId,
+    args: *const TermId,
+    num_args: usize,
+) -> TermId {
+    unsafe {
+        with_state(registry, |s| {
+            let index = usize::try_from(decl).ok().and_then(|i| i.checked_sub(1));
+            let Some(z3_func_decl) = index.and_then(|i| s.decls.get(i)).map(|d| d.z3_func_decl)
+            else {
+                return s.fail(format!("invalid declaration id {decl}"));
+            };
+            match s.get_args(slice_arg(args, num_args)) {
+                Ok(args) => {
+                    s.term(|c| Z3_mk_app(c, z3_func_decl, args.len() as c_uint, args.as_ptr()))
+                }
+                Err(e) => s.fail(e),
+            }
+        })
+    }
+}
+
+unsafe extern "C" fn op(
+    registry: *mut c_void,
+    op: u32,
+    args: *const TermId,
+    num_args: usize,
+) -> TermId {
+    unsafe {
+        with_state(registry, |s| {
+            let Ok(op) = TermOp::try_from(op) else {
+                return s.fail(format!("unknown operator code {op}"));
+            };
+            match s.get_args(slice_arg(args, num_args)) {
+                Ok(args) => s.op(op, &args),
+                Err(e) => s.fail(e),
+            }
+        })
+    }
+}
+
+unsafe extern "C" fn release(registry: *mut c_void, term: TermId) {
+    unsafe {
+        with_state(registry, |s| {
+            s.terms.remove(&term);
+        })
+    }
+}
+
+unsafe extern "C" fn to_string(
+    registry: *mut c_void,
+    term: TermId,
+    buf: *mut c_char,
+    len: usize,
+) -> usize {
+    unsafe {
+        with_state(registry, |s| match s.terms.get(&term) {
+            Some(t) => write_str(t.to_string().as_bytes(), buf, len),
+            None => {
+                s.fail(format!("invalid term id {term}"));
+                write_str(b"", buf, len)
+            }
+        })
+    }
+}
+
+unsafe extern "C" fn last_error(registry: *mut c_void, buf: *mut c_char, len: usize) -> usize {
+    unsafe { with_state(registry, |s| write_str(s.error.as_bytes(), buf, len)) }
+}
@@ -0,0 +1,185 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::ast::Bool;
+use crate::{CancellationToken, Model, Params, SatResult, Solver, Synchronized, Tactic};
+
+/// A configuration of one of the solvers racing in a [`Portfolio`].
+///
+/// By default, this is the general-purpose solver of [`Solver::new()`] with
+/// the default random seed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Strategy {
+    tactic: Option<String>,
+    random_seed: Option<u32>,
+}
+
+impl Strategy {
+    /// Create a strategy using the general-purpose solver.
+    pub fn new() -> Strategy {
+        Strategy::default()
+    }
+
+    /// Use a solver built from the tactic named `name` instead, see
+    /// [`Tactic::new()`].
+    pub fn tactic(mut self, name: &str) -> Strategy {
+        self.tactic = Some(name.to_owned());
+        self
+    }
+
+    /// Set the `random_seed` parameter of the solver.
+    pub fn random_seed(mut self, seed: u32) -> Strategy {
+        self.random_seed = Some(seed);
+        self
+    }
+}
+
+/// Portfolio solving: race solvers with different strategies on the same
+/// assertions, and keep the first definite answer.
+///
+/// See [`Portfolio::check()`].
+#[derive(Debug)]
+pub enum Portfolio {}
+
+impl Portfolio {
+    /// Check the satisfiability of `assertions` with one solver per strategy,
+    /// each on its own thread and in its own [`Context`](crate::Context).
+    ///
+    /// Returns as soon as one of the solvers answers [`SatResult::Sat`] or
+    /// [`SatResult::Unsat`], after interrupting the others. If none does
+    /// within `timeout`, all solvers are interrupted and the result is
+    /// [`SatResult::Unknown`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a strategy names an invalid tactic.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use z3::{Portfolio, SatResult, Strategy};
+    /// # use z3::ast::Int;
+    /// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    /// let assertions = [x.gt(&y), y.gt(10), x.lt(20)];
+    /// let strategies = [
+    ///     Strategy::new(),
+    ///     Strategy::new().random_seed(42),
+    ///     Strategy::new().tactic("qflia"),
+    /// ];
+    /// let outcome = Portfolio::check(&assertions, &strategies, Duration::from_secs(10));
+    /// assert_eq!(outcome.result(), SatResult::Sat);
+    ///
+    /// let model = outcome.model().unwrap();
+    /// let x = model.eval(&x, true).unwrap().as_i64().unwrap();
+    /// assert!(11 < x && x < 20);
+    /// ```
+    pub fn check(
+        assertions: &[Bool],
+        strategies: &[Strategy],
+        timeout: Duration,
+    ) -> PortfolioResult {
+        for name in strategies.iter().filter_map(|s| s.tactic.as_deref()) {
+            Tactic::new(name);
+        }
+        let deadline = Instant::now() + timeout;
+        let token = CancellationToken::new();
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for (index, strategy) in strategies.iter().enumerate() {
+                let assertions = Synchronized::new(&assertions.to_vec());
+                let (sender, token) = (sender.clone(), &token);
+                scope.spawn(move || {
+                    let outcome = run(index, strategy, &assertions, token);
+                    sender.send(outcome).ok();
+                });
+            }
+            drop(sender);
+
+            let mut winner = None;
+            while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+                match receiver.recv_timeout(left) {
+                    Ok((index, result, model)) if result != SatResult::Unknown => {
+                        winner = Some((index, result, model));
+                        break;
+                    }
+                    Ok(_) => {}
+                    Err(_) => break,
+                }
+            }
+            token.cancel();
+
+            match winner {
+                Some((index, result, model)) => PortfolioResult {
+                    result,
+                    model: model.map(|m| m.recover()),
+                    strategy: Some(index),
+                },
+                None => PortfolioResult {
+                    result: SatResult::Unknown,
+                    model: None,
+                    strategy: None,
+                },
+            }
+        })
+    }
+}
+
+/// The answer of a [`Portfolio::check()`].
+#[derive(Debug)]
+pub struct PortfolioResult {
+    result: SatResult,
+    model: Option<Model>,
+    strategy: Option<usize>,
+}
+
+impl PortfolioResult {
+    /// Returns the first definite answer, or [`SatResult::Unknown`] if no
+    /// solver gave one in time.
+    pub fn result(&self) -> SatResult {
+        self.result
+    }
+
+    /// Returns the model found by the winning solver, translated into the
+    /// thread-local [`Context`](crate::Context), if the answer is [`SatResult::Sat`].
+    pub fn model(&self) -> Option<&Model> {
+        self.model.as_ref()
+    }
+
+    /// Returns the index of the strategy whose solver answered first.
+    pub fn strategy(&self) -> Option<usize> {
+        self.strategy
+    }
+}
+
+type Outcome = (usize, SatResult, Option<Synchronized<Model>>);
+
+fn run(
+    index: usize,
+    strategy: &Strategy,
+    assertions: &Synchronized<Vec<Bool>>,
+    token: &CancellationToken,
+) -> Outcome {
+    let assertions = assertions.recover();
+    let solver = match &strategy.tactic {
+        Some(name) => Tactic::new(name).solver(),
+        None => Solver::new(),
+    };
+    if let Some(seed) = strategy.random_seed {
+        let mut params = Params::new();
+        params.set_u32("random_seed", seed);
+        solver.set_params(&params);
+    }
+    for assertion in &assertions {
+        solver.assert(assertion);
+    }
+
+    let result = solver.check_cancellable(token);
+
+    let model = match result {
+        SatResult::Sat => solver.get_model().map(|m| Synchronized::new(&m)),
+        _ => None,
+    };
+    (index, result, model)
+}
@@ -57,10 +57,18 @@ impl Probe {
     ///
     /// let probe = Probe::new("is-qfbv");
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no probe named `name`, see [`Probe::list_all()`].
     pub fn new(name: &str) -> Probe {
         let ctx = &Context::thread_local();
         let probe_name = CString::new(name).unwrap();
-        unsafe { Self::wrap(ctx, Z3_mk_probe(ctx.z3_ctx.0, probe_name.as_ptr()).unwrap()) }
+        unsafe {
+            let probe = Z3_mk_probe(ctx.z3_ctx.0, probe_name.as_ptr())
+                .unwrap_or_else(|| panic!("{name} is an invalid probe"));
+            Self::wrap(ctx, probe)
+        }
     }
 
     /// Execute the probe over the goal.
@@ -71,6 +79,27 @@ impl Probe {
         unsafe { Z3_probe_apply(self.ctx.z3_ctx.0, self.z3_probe, goal.z3_goal) }
     }
 
+    /// Execute a "Boolean" probe over the goal, such as `is-qfbv` or a
+    /// comparison between probes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{Goal, Probe};
+    /// # use z3::ast::Int;
+    /// let x = Int::new_const("x");
+    /// let goal = Goal::new(false, false, false);
+    /// goal.assert(&x.gt(0));
+    ///
+    /// assert!(Probe::new("is-qflia").holds(&goal));
+    /// assert!(!Probe::new("is-qfbv").holds(&goal));
+    /// let small = Probe::new("num-consts").le(&Probe::constant(3.0));
+    /// assert!(small.holds(&goal));
+    /// ```
+    pub fn holds(&self, goal: &Goal) -> bool {
+        self.apply(goal) != 0.0
+    }
+
     /// Return a probe that always evaluates to val.
     /// ```
     /// # use z3::Probe;
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::DeclKind;
+use crate::ast::{Ast, Bool, Dynamic};
+
+/// A proof produced by Z3, as a tree of inference steps.
+///
+/// Each step applies an inference rule to the proofs of its premises, and
+/// concludes a formula. Steps may be shared between several conclusions, so
+/// the proof is a DAG; [`Proof::steps()`] lists each step once.
+///
+/// Proofs are only produced if [proof generation is enabled].
+///
+/// # Example
+///
+/// ```
+/// # use z3::{Config, DeclKind, SatResult, Solver};
+/// # use z3::ast::Bool;
+/// let mut cfg = Config::new();
+/// cfg.set_proof_generation(true);
+/// z3::set_thread_local_config(cfg);
+///
+/// let solver = Solver::new();
+/// let a = Bool::new_const("a");
+/// solver.assert(&a);
+/// solver.assert(a.not());
+/// assert_eq!(solver.check(), SatResult::Unsat);
+///
+/// let proof = solver.get_structured_proof().unwrap();
+/// assert_eq!(proof.conclusion(), Bool::from_bool(false));
+/// let asserted: Vec<Bool> = proof
+///     .steps()
+///     .iter()
+///     .filter(|step| step.rule() == DeclKind::PR_ASSERTED)
+///     .map(|step| step.conclusion())
+///     .collect();
+/// assert!(asserted.contains(&a));
+/// assert!(asserted.contains(&a.not()));
+/// ```
+///
+/// [proof generation is enabled]: crate::Config::set_proof_generation
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Proof {
+    ast: Dynamic,
+}
+
+impl Proof {
+    /// Wrap a proof term, as returned by [`Solver::get_proof()`](crate::Solver::get_proof).
+    /// Returns `None` if `ast` is not a proof term.
+    pub fn from_ast(ast: &impl Ast) -> Option<Proof> {
+        let ast = Dynamic::from_ast(ast);
+        if !ast.is_app() || !is_proof_rule(ast.decl().kind()) {
+            return None;
+        }
+        Some(Proof { ast })
+    }
+
+    /// Returns the proof term.
+    pub fn as_ast(&self) -> &Dynamic {
+        &self.ast
+    }
+
+    /// Returns the inference rule of this step.
+    pub fn rule(&self) -> DeclKind {
+        self.ast.decl().kind()
+    }
+
+    /// Returns the name of the inference rule of this step, such as `mp` for
+    /// modus ponens.
+    pub fn rule_name(&self) -> String {
+        self.ast.decl().name()
+    }
+
+    /// Returns the proofs of the premises of this step.
+    pub fn premises(&self) -> Vec<Proof> {
+        let children = self.ast.children();
+        children[..children.len().saturating_sub(1)]
+            .iter()
+            .filter_map(Proof::from_ast)
+            .collect()
+    }
+
+    /// Returns the formula this step concludes.
+    pub fn conclusion(&self) -> Bool {
+        self.ast
+            .children()
+            .last()
+            .and_then(|c| c.as_bool())
+            .unwrap_or_else(|| Bool::from_bool(true))
+    }
+
+    /// Returns every step of the proof once, each after the steps proving
+    /// its premises, ending with this step.
+    pub fn steps(&self) -> Vec<Proof> {
+        let mut steps = vec![];
+        let mut visited = HashSet::new();
+        // Iterative post-order traversal, since proofs can be deep.
+        let mut stack = vec![(self.clone(), false)];
+        while let Some((step, expanded)) = stack.pop() {
+            if expanded {
+                steps.push(step);
+                continue;
+            }
+            if !visited.insert(step.clone()) {
+                continue;
+            }
+            let premises = step.premises();
+            stack.push((step, true));
+            stack.extend(
+                premises
+                    .into_iter()
+                    .rev()
+                    .filter(|p| !visited.contains(p))
+                    .map(|p| (p, false)),
+            );
+        }
+        steps
+    }
+}
+
+fn is_proof_rule(kind: DeclKind) -> bool {
+    (DeclKind::PR_UNDEF as u32..DeclKind::RA_STORE as u32).contains(&(kind as u32))
+}
+
+impl fmt::Display for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ast)
+    }
+}
@@ -1,143 +1,291 @@
-use crate::ast::{Ast, Bool};
+//! Quantifier elimination and model-based projection.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fmt;
+
+use crate::ast::{Ast, Bool, Dynamic};
+use crate::{AstVector, Context, Goal, Model, Tactic};
 use z3_sys::*;
 
-/// Quantifier elimination facilities.
-/// 
-/// Provides methods for eliminating quantifiers from logical formulas when possible.
-pub struct QuantifierElimination;
+/// An error of [`qe_lite()`], [`model_project()`] or [`qe()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QeError {
+    /// The arguments belong to different contexts, or [`qe()`] was given a
+    /// formula of another context than the thread-local one.
+    ContextMismatch,
+    /// A variable to eliminate is not a constant.
+    NotAConstant(Dynamic),
+    /// The model given to [`model_project()`] does not satisfy the formula.
+    ModelFalsifies,
+    /// Z3 failed, with its message.
+    Z3(String),
+}
 
-impl QuantifierElimination {
-    /// Eliminate quantifiers from the given formula.
-    /// 
-    /// This function attempts to eliminate existential and universal quantifiers
-    /// from logical formulas. The elimination is sound but not necessarily complete.
-    /// 
-    /// # Parameters
-    /// - `formula`: The formula containing quantifiers to eliminate
-    /// - `eliminate_all`: If true, try to eliminate all quantifiers; if false, eliminate only outermost
-    /// 
-    /// # Returns
-    /// A quantifier-free formula equivalent to the input (if elimination succeeded),
-    /// or the original formula if elimination is not possible.
-    pub fn eliminate_quantifiers(formula: &impl Ast, eliminate_all: bool) -> Bool {
-        let ctx = formula.get_ctx();
-        unsafe {
-            Bool::wrap(
-                ctx,
-                Z3_qe_eliminate_quantifiers(
-                    ctx.z3_ctx.0,
-                    formula.get_z3_ast(),
-                    eliminate_all,
-                ).unwrap(),
-            )
+impl fmt::Display for QeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            QeError::ContextMismatch => write!(f, "terms of different contexts"),
+            QeError::NotAConstant(var) => write!(f, "{var} is not a constant"),
+            QeError::ModelFalsifies => write!(f, "the model does not satisfy the formula"),
+            QeError::Z3(message) => write!(f, "{message}"),
         }
     }
+}
 
-    /// Eliminate quantifiers using model-based quantifier elimination.
-    /// 
-    /// This is a more aggressive quantifier elimination strategy that may
-    /// produce simpler results in some cases.
-    /// 
-    /// # Parameters
-    /// - `formula`: The formula containing quantifiers to eliminate
-    /// - `model`: A model that can guide the elimination process (can be null)
-    /// - `eliminate_all`: If true, try to eliminate all quantifiers
-    pub fn eliminate_quantifiers_with_model(
-        formula: &impl Ast,
-        model: Option<&crate::Model>,
-        eliminate_all: bool,
-    ) -> Bool {
-        let ctx = formula.get_ctx();
-        let model_ptr = match model {
-            Some(m) => m.z3_model,
-            None => std::ptr::null_mut(),
-        };
-        
-        unsafe {
-            Bool::wrap(
-                ctx,
-                Z3_qe_model_project(
-                    ctx.z3_ctx.0,
-                    model_ptr,
-                    0,
-                    std::ptr::null(),
-                    formula.get_z3_ast(),
-                ).unwrap(),
-            )
+impl std::error::Error for QeError {}
+
+/// Check that `vars` are constants of the context of `formula`.
+fn check_vars(vars: &[&dyn Ast], formula: &Bool) -> Result<(), QeError> {
+    for var in vars {
+        if var.get_ctx() != formula.get_ctx() {
+            return Err(QeError::ContextMismatch);
+        }
+        if !var.is_const() {
+            return Err(QeError::NotAConstant(Dynamic::from_ast(*var)));
         }
     }
+    Ok(())
+}
 
-    /// Project variables from a formula with respect to a model.
-    /// 
-    /// This eliminates the specified variables from the formula using
-    /// information from the provided model.
-    /// 
-    /// # Parameters
-    /// - `model`: The model to use for projection
-    /// - `variables`: Array of variables to eliminate
-    /// - `formula`: The formula from which to eliminate variables
-    pub fn project_variables(
-        model: &crate::Model,
-        variables: &[&dyn Ast],
-        formula: &impl Ast,
-    ) -> Bool {
-        let ctx = formula.get_ctx();
-        let vars_z3: Vec<Z3_ast> = variables.iter().map(|v| v.get_z3_ast()).collect();
-        
-        unsafe {
-            Bool::wrap(
-                ctx,
-                Z3_qe_model_project(
-                    ctx.z3_ctx.0,
-                    model.z3_model,
-                    vars_z3.len() as u32,
-                    vars_z3.as_ptr(),
-                    formula.get_z3_ast(),
-                ).unwrap(),
-            )
+/// Call `f`, and return the error it raised in `ctx`, if any.
+fn catch(ctx: &Context, f: impl FnOnce() -> Option<Z3_ast>) -> Result<Bool, QeError> {
+    unsafe {
+        Z3_set_error(ctx.z3_ctx.0, ErrorCode::OK);
+        let result = f();
+        let code = Z3_get_error_code(ctx.z3_ctx.0);
+        match result {
+            Some(ast) if code == ErrorCode::OK => Ok(Bool::wrap(ctx, ast)),
+            _ => {
+                let msg = CStr::from_ptr(Z3_get_error_msg(ctx.z3_ctx.0, code));
+                Err(QeError::Z3(msg.to_string_lossy().into_owned()))
+            }
         }
     }
+}
 
-    /// Eliminate existential quantifiers from the formula.
-    /// 
-    /// This is equivalent to `eliminate_quantifiers` but specifically focuses
-    /// on existential quantifiers.
-    pub fn eliminate_existential_quantifiers(formula: &impl Ast) -> Bool {
-        Self::eliminate_quantifiers(formula, false)
+/// Light quantifier elimination of `vars` from `formula`, using
+/// `Z3_qe_lite`.
+///
+/// The result is implied by `formula`, and equivalent to its projection on
+/// the other constants when every variable could be eliminated. Variables
+/// which could not be eliminated remain in the result.
+///
+/// # Example
+///
+/// ```
+/// # use z3::ast::{Bool, Int};
+/// # use z3::quantifier_elimination::{QeError, qe_lite};
+/// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+/// let formula = Bool::and(&[x.eq(&y), x.gt(0)]);
+/// assert_eq!(qe_lite(&[&x], &formula), Ok(y.gt(0)));
+///
+/// let sum = &x + &y;
+/// assert_eq!(qe_lite(&[&sum], &formula), Err(QeError::NotAConstant(sum.into())));
+/// ```
+pub fn qe_lite(vars: &[&dyn Ast], formula: &Bool) -> Result<Bool, QeError> {
+    check_vars(vars, formula)?;
+    let ctx = formula.get_ctx();
+    let vector = unsafe { AstVector::wrap(ctx, Z3_mk_ast_vector(ctx.z3_ctx.0).unwrap()) };
+    for var in vars {
+        unsafe { Z3_ast_vector_push(ctx.z3_ctx.0, vector.z3_ast_vector, var.get_z3_ast()) };
     }
+    catch(ctx, || unsafe {
+        Z3_qe_lite(ctx.z3_ctx.0, vector.z3_ast_vector, formula.get_z3_ast())
+    })
+}
 
-    /// Simplify a quantified formula using quantifier elimination techniques.
-    /// 
-    /// This may not eliminate all quantifiers but will simplify the formula
-    /// as much as possible using quantifier elimination.
-    pub fn simplify_with_qe(formula: &impl Ast) -> Bool {
-        let ctx = formula.get_ctx();
-        unsafe {
-            Bool::wrap(
-                ctx,
-                Z3_qe_simplify(ctx.z3_ctx.0, formula.get_z3_ast()).unwrap(),
-            )
-        }
+/// Model-based projection of `vars` from `formula`, using
+/// `Z3_qe_model_project`.
+///
+/// The result is free of `vars`, implied by `formula`, and satisfied by
+/// `model`, which must satisfy `formula`.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{SatResult, Solver};
+/// # use z3::ast::{Bool, Int};
+/// # use z3::quantifier_elimination::model_project;
+/// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+/// let formula = Bool::and(&[x.gt(&y), x.lt(10)]);
+/// let solver = Solver::new();
+/// solver.assert(&formula);
+/// assert_eq!(solver.check(), SatResult::Sat);
+/// let model = solver.get_model().unwrap();
+///
+/// let projection = model_project(&model, &[&x], &formula).unwrap();
+/// // The projection no longer mentions `x`.
+/// let solver = Solver::new();
+/// solver.assert(&projection);
+/// solver.assert(&y.ge(9));
+/// assert_eq!(solver.check(), SatResult::Unsat);
+/// ```
+pub fn model_project(model: &Model, vars: &[&dyn Ast], formula: &Bool) -> Result<Bool, QeError> {
+    check_vars(vars, formula)?;
+    let ctx = formula.get_ctx();
+    if &model.ctx != ctx {
+        return Err(QeError::ContextMismatch);
+    }
+    if model.eval(formula, true).and_then(|v| v.as_bool()) != Some(true) {
+        return Err(QeError::ModelFalsifies);
+    }
+    let vars: Vec<Z3_app> = vars
+        .iter()
+        .map(|var| unsafe { Z3_to_app(ctx.z3_ctx.0, var.get_z3_ast()).unwrap() })
+        .collect();
+    catch(ctx, || unsafe {
+        Z3_qe_model_project(
+            ctx.z3_ctx.0,
+            model.z3_mdl,
+            vars.len() as u32,
+            vars.as_ptr(),
+            formula.get_z3_ast(),
+        )
+    })
+}
+
+/// Eliminate the quantifiers of `formula` with the `qe` tactic.
+///
+/// The result is equivalent to `formula`. It may keep quantifiers the
+/// tactic cannot eliminate, e.g. over uninterpreted functions.
+///
+/// # Example
+///
+/// ```
+/// # use z3::ast::{self, Bool, Int};
+/// # use z3::quantifier_elimination::qe;
+/// let y = Int::new_const("y");
+/// let formula = ast::exists(|x: Int| Bool::and(&[x.gt(&y), x.lt(3)]));
+/// let result = qe(&formula).unwrap();
+/// assert!(!result.is_exists());
+/// ```
+pub fn qe(formula: &Bool) -> Result<Bool, QeError> {
+    if formula.get_ctx() != &Context::thread_local() {
+        return Err(QeError::ContextMismatch);
     }
+    let goal = Goal::new(false, false, false);
+    goal.assert(formula);
+    let subgoals = Tactic::new("qe")
+        .apply(&goal, None)
+        .map_err(QeError::Z3)?
+        .list_subgoals()
+        .map(|g| Bool::and(&g.get_formulas()))
+        .collect::<Vec<_>>();
+    Ok(Bool::or(&subgoals).simplify())
 }
 
-/// Light-weight quantifier elimination.
-/// 
-/// This provides a faster but less complete quantifier elimination strategy.
-pub struct LightQuantifierElimination;
-
-impl LightQuantifierElimination {
-    /// Perform light quantifier elimination.
-    /// 
-    /// This is a faster version of quantifier elimination that may not
-    /// eliminate as many quantifiers but is more efficient.
-    pub fn eliminate(formula: &impl Ast) -> Bool {
-        let ctx = formula.get_ctx();
-        unsafe {
-            Bool::wrap(
-                ctx,
-                Z3_qe_light(ctx.z3_ctx.0, formula.get_z3_ast()).unwrap(),
-            )
+/// Basic quantifier elimination using available Z3 functions.
+#[derive(Debug)]
+pub struct QuantifierElimination;
+
+impl QuantifierElimination {
+    /// Light quantifier elimination using `Z3_qe_lite`.
+    ///
+    /// The variables which could not be eliminated are left in `vars`.
+    #[deprecated = "Please use qe_lite instead"]
+    pub fn lite(vars: &crate::AstVector, formula: &impl Ast) -> Bool {
+        lite(vars, formula)
+    }
+}
+
+/// Light quantifier elimination of `vars` from `formula`, leaving in `vars`
+/// the variables which could not be eliminated.
+fn lite(vars: &AstVector, formula: &impl Ast) -> Bool {
+    let ctx = formula.get_ctx();
+    unsafe {
+        Bool::wrap(
+            ctx,
+            Z3_qe_lite(ctx.z3_ctx.0, vars.z3_ast_vector, formula.get_z3_ast()).unwrap(),
+        )
+    }
+}
+
+/// Memoized quantifier elimination, for formulas which are eliminated
+/// repeatedly.
+///
+/// Results are cached by the formula and the eliminated variables. Z3
+/// shares structurally equal terms, so an identical formula built again
+/// hits the cache. The cache belongs to the thread-local context: formulas
+/// of other contexts are eliminated without caching.
+///
+/// # Example
+///
+/// ```
+/// # use z3::AstVector;
+/// # use z3::ast::{Bool, Int};
+/// # use z3::quantifier_elimination::QeCache;
+/// let qe = QeCache::new();
+/// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+/// let formula = Bool::and(&[x.eq(&y), x.gt(0)]);
+/// let result = qe.lite(&AstVector::from_slice(&[&x]), &formula);
+/// assert_eq!(result, y.gt(0));
+/// assert_eq!(qe.num_cached(), 1);
+///
+/// // The same formula, built again.
+/// let formula = Bool::and(&[x.eq(&y), x.gt(0)]);
+/// assert_eq!(qe.lite(&AstVector::from_slice(&[&x]), &formula), result);
+/// assert_eq!(qe.num_cached(), 1);
+/// ```
+#[derive(Debug)]
+pub struct QeCache {
+    ctx: Context,
+    lite: RefCell<HashMap<QeKey, QeResult>>,
+}
+
+/// The variables and formula of an elimination.
+type QeKey = (Vec<Dynamic>, Bool);
+
+/// The result and remaining variables of an elimination.
+type QeResult = (Bool, Vec<Dynamic>);
+
+impl QeCache {
+    /// Create an empty cache for the thread-local context.
+    pub fn new() -> QeCache {
+        QeCache {
+            ctx: Context::thread_local(),
+            lite: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Light quantifier elimination of `vars` from `formula`, as
+    /// [`qe_lite()`], computed once per formula and variables.
+    ///
+    /// This leaves in `vars` the variables which were not eliminated, also
+    /// when the result is cached.
+    pub fn lite(&self, vars: &AstVector, formula: &Bool) -> Bool {
+        if formula.get_ctx() != &self.ctx {
+            return lite(vars, formula);
+        }
+        let key = (vars.to_vec(), formula.clone());
+        if let Some((result, remaining)) = self.lite.borrow().get(&key) {
+            vars.clear();
+            for var in remaining {
+                vars.push(var);
+            }
+            return result.clone();
         }
+        let result = lite(vars, formula);
+        self.lite
+            .borrow_mut()
+            .insert(key, (result.clone(), vars.to_vec()));
+        result
+    }
+
+    /// Returns the number of cached results.
+    pub fn num_cached(&self) -> usize {
+        self.lite.borrow().len()
+    }
+
+    /// Forget every cached result.
+    pub fn clear(&self) {
+        self.lite.borrow_mut().clear();
     }
-}
\ No newline at end of file
+}
+
+impl Default for QeCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
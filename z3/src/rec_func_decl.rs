@@ -27,7 +27,7 @@ impl RecFuncDecl {
 
         let domain: Vec<_> = domain.iter().map(|s| s.z3_sort).collect();
 
-        unsafe {
+        let decl = unsafe {
             Self::wrap(
                 ctx,
                 Z3_mk_rec_func_decl(
@@ -39,7 +39,9 @@ impl RecFuncDecl {
                 )
                 .unwrap(),
             )
-        }
+        };
+        crate::declarations::record_decl(ctx, decl.z3_func_decl);
+        decl
     }
 
     /// Adds the body to a recursive function.
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use z3_sys::DeclKind;
+
+use crate::ast::{Ast, Bool, Dynamic, Float, Real};
+
+/// The roundoff error of a floating-point expression: its value, the value
+/// of the same expression evaluated over the reals without rounding, and
+/// the difference of the two.
+///
+/// The ideal expression applies the real counterpart of each floating-point
+/// operation to the same inputs, so `error()` bounds the error accumulated
+/// by rounding. Both values are only meaningful when every intermediate
+/// result is finite, which [`Roundoff::finite()`] expresses.
+///
+/// # Example
+///
+/// ```
+/// # use z3::Roundoff;
+/// # use z3::ast::{Ast, Float, RoundingMode};
+/// let rne = RoundingMode::round_nearest_ties_to_even();
+/// let sum = Float::from_f64(0.1).add_with_rounding_mode(Float::from_f64(0.2), &rne);
+/// let roundoff = Roundoff::new(&sum).unwrap();
+/// assert_eq!(roundoff.finite().simplify().as_bool(), Some(true));
+///
+/// // 0.1 + 0.2 is rounded up to the next double.
+/// let error = roundoff.error().simplify();
+/// assert!(error.as_f64_exact().unwrap() > 0.0);
+/// assert_eq!(
+///     (roundoff.actual() - roundoff.ideal()).simplify(),
+///     error
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Roundoff {
+    actual: Real,
+    ideal: Real,
+    finite: Bool,
+}
+
+impl Roundoff {
+    /// Build the ideal real-valued counterpart of `expr`.
+    ///
+    /// Supports the arithmetic operations, negation, absolute value, fused
+    /// multiply-add, square root and conversions between sorts. Constants
+    /// and numerals are the inputs. Returns `None` if `expr` contains any
+    /// other floating-point operation, such as `fp.rem` or `fp.min`.
+    pub fn new(expr: &Float) -> Option<Roundoff> {
+        let mut builder = Builder {
+            ideal: HashMap::new(),
+            finite: vec![],
+        };
+        let ideal = builder.ideal(expr)?;
+        let (actual, _) = expr.to_real_exact();
+        Some(Roundoff {
+            actual,
+            ideal,
+            finite: Bool::and(&builder.finite),
+        })
+    }
+
+    /// Returns the value of the floating-point expression, as a real.
+    pub fn actual(&self) -> &Real {
+        &self.actual
+    }
+
+    /// Returns the value of the expression evaluated without rounding.
+    pub fn ideal(&self) -> &Real {
+        &self.ideal
+    }
+
+    /// Returns the absolute roundoff error, `|actual - ideal|`.
+    pub fn error(&self) -> Real {
+        let diff = &self.actual - &self.ideal;
+        diff.ge(Real::from_rational(0, 1)).ite(&diff, &-&diff)
+    }
+
+    /// Returns the condition that the expression and all its intermediate
+    /// results are neither NaN nor infinite.
+    pub fn finite(&self) -> &Bool {
+        &self.finite
+    }
+}
+
+struct Builder {
+    ideal: HashMap<Dynamic, Real>,
+    finite: Vec<Bool>,
+}
+
+impl Builder {
+    fn ideal(&mut self, expr: &Float) -> Option<Real> {
+        let key = Dynamic::from_ast(expr);
+        if let Some(ideal) = self.ideal.get(&key) {
+            return Some(ideal.clone());
+        }
+        let (real, finite) = expr.to_real_exact();
+        self.finite.push(finite);
+
+        let args = expr.children();
+        let float = |i: usize| args.get(i).and_then(|a| a.as_float());
+        let ideal = match expr.decl().kind() {
+            DeclKind::UNINTERPRETED if args.is_empty() => real,
+            DeclKind::FPA_NUM | DeclKind::FPA_PLUS_ZERO | DeclKind::FPA_MINUS_ZERO => {
+                real.simplify()
+            }
+            DeclKind::FPA_ADD => self.ideal(&float(1)?)? + self.ideal(&float(2)?)?,
+            DeclKind::FPA_SUB => self.ideal(&float(1)?)? - self.ideal(&float(2)?)?,
+            DeclKind::FPA_MUL => self.ideal(&float(1)?)? * self.ideal(&float(2)?)?,
+            DeclKind::FPA_DIV => self.ideal(&float(1)?)? / self.ideal(&float(2)?)?,
+            DeclKind::FPA_NEG => -self.ideal(&float(0)?)?,
+            DeclKind::FPA_ABS => {
+                let x = self.ideal(&float(0)?)?;
+                x.ge(Real::from_rational(0, 1)).ite(&x, &-&x)
+            }
+            DeclKind::FPA_FMA => {
+                self.ideal(&float(1)?)? * self.ideal(&float(2)?)? + self.ideal(&float(3)?)?
+            }
+            DeclKind::FPA_SQRT => self.ideal(&float(1)?)?.power(Real::from_rational(1, 2)),
+            // Conversions from another float, or from a real, with a rounding mode.
+            DeclKind::FPA_TO_FP if args.len() == 2 => match float(1) {
+                Some(x) => self.ideal(&x)?,
+                None => args[1].as_real()?,
+            },
+            _ => return None,
+        };
+        self.ideal.insert(key, ideal.clone());
+        Some(ideal)
+    }
+}
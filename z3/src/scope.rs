@@ -0,0 +1,82 @@
+use std::ops::Deref;
+
+use crate::{Optimize, Solver};
+
+/// A backtracking point of a [`Solver`], popped when the guard is dropped.
+///
+/// The guard dereferences to the solver, so assertions and checks go
+/// through it. Since it pops on drop, also while unwinding from a panic,
+/// assertions made in the scope cannot leak out of it.
+///
+/// Created by [`Solver::push_scoped()`].
+///
+/// # Example
+///
+/// ```
+/// # use z3::{SatResult, Solver};
+/// # use z3::ast::Int;
+/// let solver = Solver::new();
+/// let x = Int::new_const("x");
+/// solver.assert(x.gt(0));
+/// {
+///     let scope = solver.push_scoped();
+///     scope.assert(x.lt(0));
+///     assert_eq!(scope.check(), SatResult::Unsat);
+/// }
+/// assert_eq!(solver.check(), SatResult::Sat);
+/// ```
+#[derive(Debug)]
+pub struct SolverScope<'a> {
+    solver: &'a Solver,
+}
+
+impl<'a> SolverScope<'a> {
+    pub(crate) fn new(solver: &'a Solver) -> SolverScope<'a> {
+        solver.push();
+        SolverScope { solver }
+    }
+}
+
+impl Deref for SolverScope<'_> {
+    type Target = Solver;
+
+    fn deref(&self) -> &Solver {
+        self.solver
+    }
+}
+
+impl Drop for SolverScope<'_> {
+    fn drop(&mut self) {
+        self.solver.pop(1);
+    }
+}
+
+/// A backtracking point of an [`Optimize`], popped when the guard is
+/// dropped.
+///
+/// Created by [`Optimize::push_scoped()`]. See [`SolverScope`].
+#[derive(Debug)]
+pub struct OptimizeScope<'a> {
+    optimize: &'a Optimize,
+}
+
+impl<'a> OptimizeScope<'a> {
+    pub(crate) fn new(optimize: &'a Optimize) -> OptimizeScope<'a> {
+        optimize.push();
+        OptimizeScope { optimize }
+    }
+}
+
+impl Deref for OptimizeScope<'_> {
+    type Target = Optimize;
+
+    fn deref(&self) -> &Optimize {
+        self.optimize
+    }
+}
+
+impl Drop for OptimizeScope<'_> {
+    fn drop(&mut self) {
+        self.optimize.pop();
+    }
+}
@@ -0,0 +1,298 @@
+//! A JSON-RPC service solving SMT-LIB problems, enabled by the `server`
+//! feature.
+//!
+//! The service speaks [JSON-RPC 2.0](https://www.jsonrpc.org/specification)
+//! with one message per line. It offers two methods, whose `params` object
+//! holds the problem in the `smtlib` field, and optionally a timeout in
+//! milliseconds in the `timeout_ms` field:
+//!
+//! - `solve` checks the satisfiability of the assertions. While the check
+//!   runs, the statistics gathered so far are sent periodically as
+//!   `statistics` notifications, whose `params` hold the `id` of the request
+//!   and the `statistics`.
+//! - `optimize` checks the assertions and optimizes the objectives declared
+//!   by `minimize` and `maximize` commands, and answers with the values of
+//!   the objectives in `objectives`.
+//!
+//! Both answer with the `result` (`sat`, `unsat` or `unknown`), the `model`
+//! if satisfiable, the `reason_unknown` otherwise, and the final
+//! `statistics`. Each request gets its own [`Context`](crate::Context), so
+//! requests do not affect each other.
+//!
+//! # Example
+//!
+//! ```
+//! # use z3::server::Server;
+//! let server = Server::new();
+//! let request = r#"{"jsonrpc": "2.0", "id": 1, "method": "solve",
+//!     "params": {"smtlib": "(declare-const x Int) (assert (> x 2))"}}"#;
+//! let response = server.handle(&request.replace('\n', ""), |_| {}).unwrap();
+//!
+//! let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+//! assert_eq!(response["id"], 1);
+//! assert_eq!(response["result"]["result"], "sat");
+//! assert!(response["result"]["model"].as_str().unwrap().contains("x"));
+//! ```
+
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::net::{TcpListener, ToSocketAddrs};
+use std::ops::ControlFlow;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+
+use crate::{Config, Optimize, SatResult, Solver, Statistics, with_z3_config};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The SMT-LIB input could not be parsed.
+const SMTLIB_ERROR: i64 = -32000;
+
+/// A JSON-RPC service solving SMT-LIB problems, see the
+/// [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Server {
+    timeout: Duration,
+    rlimit: u32,
+}
+
+/// The error object of a JSON-RPC response.
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> RpcError {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl Server {
+    /// Create a server giving each request at most one minute.
+    pub fn new() -> Server {
+        Server {
+            timeout: Duration::from_secs(60),
+            rlimit: 100_000,
+        }
+    }
+
+    /// Set the time a request may take at most. Requests asking for a
+    /// longer `timeout_ms` get this instead.
+    pub fn timeout(mut self, timeout: Duration) -> Server {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the resource units checked by `solve` before the statistics are
+    /// sent for the first time. The interval doubles after each
+    /// notification, see [`Solver::check_yielding()`].
+    pub fn statistics_interval(mut self, rlimit: u32) -> Server {
+        self.rlimit = rlimit;
+        self
+    }
+
+    /// Handle the request `request`, passing the notifications it produces
+    /// to `notify` as they come, and return the response.
+    ///
+    /// Returns `None` if the request is a notification, i.e. has no `id`,
+    /// which gets no response.
+    pub fn handle(&self, request: &str, mut notify: impl FnMut(String)) -> Option<String> {
+        let request: Value = match serde_json::from_str(request) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(&Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        let id = request.get("id").cloned();
+        let method = request.get("method").and_then(Value::as_str);
+        let (Some(method), Some("2.0")) = (method, request["jsonrpc"].as_str()) else {
+            let id = id.unwrap_or(Value::Null);
+            return Some(error_response(
+                &id,
+                INVALID_REQUEST,
+                "not a JSON-RPC 2.0 request",
+            ));
+        };
+        let params = request.get("params").cloned().unwrap_or(json!({}));
+
+        let (sender, receiver) = mpsc::channel();
+        let result = thread::scope(|scope| {
+            let worker = scope.spawn(move || self.dispatch(method, &params, &sender));
+            for statistics in receiver {
+                let params = json!({ "id": id, "statistics": statistics });
+                notify(notification("statistics", params));
+            }
+            worker.join().unwrap()
+        });
+
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+            Err(e) => error_response(&id, e.code, &e.message),
+        })
+    }
+
+    /// Handle the requests read from `input`, one per line, writing the
+    /// notifications and responses to `output`, one per line, until the
+    /// end of the input.
+    pub fn serve(&self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut failure = Ok(());
+            let response = self.handle(&line, |message| {
+                if failure.is_ok() {
+                    failure = writeln!(output, "{message}").and_then(|()| output.flush());
+                }
+            });
+            failure?;
+            if let Some(response) = response {
+                writeln!(output, "{response}")?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Accept TCP connections on `addr`, serving each on its own thread
+    /// like [`Server::serve()`]. Only returns if accepting fails.
+    pub fn listen(&self, addr: impl ToSocketAddrs) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        loop {
+            let (stream, peer) = listener.accept()?;
+            let server = *self;
+            thread::spawn(move || {
+                let input = BufReader::new(stream.try_clone()?);
+                let result = server.serve(input, BufWriter::new(stream));
+                if let Err(e) = &result {
+                    log::debug!("connection from {peer} failed: {e}");
+                }
+                result
+            });
+        }
+    }
+
+    fn dispatch(
+        &self,
+        method: &str,
+        params: &Value,
+        progress: &mpsc::Sender<Value>,
+    ) -> Result<Value, RpcError> {
+        let Some(smtlib) = params.get("smtlib").and_then(Value::as_str) else {
+            return Err(RpcError::new(
+                INVALID_PARAMS,
+                "missing string parameter `smtlib`",
+            ));
+        };
+        let timeout = match params.get("timeout_ms") {
+            None => self.timeout,
+            Some(ms) => match ms.as_u64() {
+                Some(ms) => self.timeout.min(Duration::from_millis(ms)),
+                None => return Err(RpcError::new(INVALID_PARAMS, "invalid `timeout_ms`")),
+            },
+        };
+        let mut cfg = Config::new();
+        cfg.set_model_generation(true);
+        cfg.set_timeout_msec(timeout.as_millis().try_into().unwrap_or(u64::MAX));
+        match method {
+            "solve" => with_z3_config(&cfg, || self.solve(smtlib, timeout, progress)),
+            "optimize" => with_z3_config(&cfg, || optimize(smtlib)),
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method `{method}`"),
+            )),
+        }
+    }
+
+    fn solve(
+        &self,
+        smtlib: &str,
+        timeout: Duration,
+        progress: &mpsc::Sender<Value>,
+    ) -> Result<Value, RpcError> {
+        let solver = Solver::new();
+        solver
+            .from_string(smtlib)
+            .map_err(|e| RpcError::new(SMTLIB_ERROR, e.to_string()))?;
+        let deadline = Instant::now() + timeout;
+        let result = solver.check_yielding(self.rlimit, |_| {
+            progress.send(statistics(&solver.get_statistics())).ok();
+            if Instant::now() < deadline {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        });
+        Ok(json!({
+            "result": result.to_string(),
+            "model": solver.get_model().filter(|_| result == SatResult::Sat).map(|m| m.to_string()),
+            "reason_unknown": match result {
+                SatResult::Unknown => solver.get_reason_unknown(),
+                _ => None,
+            },
+            "statistics": statistics(&solver.get_statistics()),
+        }))
+    }
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn optimize(smtlib: &str) -> Result<Value, RpcError> {
+    let optimize = Optimize::new();
+    optimize
+        .from_string(smtlib)
+        .map_err(|e| RpcError::new(SMTLIB_ERROR, e.to_string()))?;
+    let result = optimize.check(&[]);
+    let model = optimize.get_model().filter(|_| result == SatResult::Sat);
+    let objectives: Option<Vec<String>> = model.as_ref().map(|model| {
+        optimize
+            .get_objectives()
+            .iter()
+            .map(|o| {
+                model
+                    .eval(o, true)
+                    .map_or_else(|| o.to_string(), |v| v.to_string())
+            })
+            .collect()
+    });
+    Ok(json!({
+        "result": result.to_string(),
+        "model": model.map(|m| m.to_string()),
+        "objectives": objectives,
+        "reason_unknown": match result {
+            SatResult::Unknown => optimize.get_reason_unknown(),
+            _ => None,
+        },
+        "statistics": statistics(&optimize.get_statistics()),
+    }))
+}
+
+fn statistics(statistics: &Statistics) -> Value {
+    serde_json::to_value(statistics).unwrap_or(Value::Null)
+}
+
+fn notification(method: &str, params: Value) -> String {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params }).to_string()
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+    .to_string()
+}
@@ -0,0 +1,180 @@
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::str::Utf8Error;
+use z3_sys::*;
+
+use crate::{Context, ParamDescrs, Params, Solver};
+
+/// Incremental preprocessing of the assertions of a [`Solver`], enabled by
+/// the `z3_4_12_0` feature.
+///
+/// Unlike a [`Tactic`](crate::Tactic), which rewrites a whole goal at once,
+/// a simplifier keeps preprocessing as assertions are added and scopes are
+/// pushed and popped. Simplifiers are chained with
+/// [`Simplifier::and_then()`] and attached with [`Solver::add_simplifier()`].
+///
+/// # Example
+///
+/// ```
+/// # use z3::{Params, SatResult, Simplifier, Solver};
+/// # use z3::ast::Int;
+/// let mut params = Params::new();
+/// params.set_bool("som", true);
+/// let pipeline = Simplifier::new("solve-eqs")
+///     .and_then(&Simplifier::new("simplify").using_params(&params));
+/// let solver = Solver::new().add_simplifier(&pipeline).unwrap();
+///
+/// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+/// solver.assert(x.eq(&y + 1));
+/// solver.assert(y.gt(2));
+/// assert_eq!(solver.check(), SatResult::Sat);
+/// let x = solver.get_model().unwrap().eval(&x, true).unwrap();
+/// assert!(x.as_i64().unwrap() > 3);
+/// ```
+pub struct Simplifier {
+    ctx: Context,
+    z3_simplifier: Z3_simplifier,
+}
+
+impl Simplifier {
+    /// Iterate through the valid simplifier names.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::Simplifier;
+    /// let simplifiers: Vec<_> = Simplifier::list_all().into_iter().filter_map(|r| r.ok()).collect();
+    /// assert!(simplifiers.contains(&"solve-eqs".to_string()));
+    /// ```
+    pub fn list_all() -> Vec<Result<String, Utf8Error>> {
+        let ctx = &Context::thread_local();
+        let p = unsafe { Z3_get_num_simplifiers(ctx.z3_ctx.0) };
+        (0..p)
+            .map(move |n| {
+                let t = unsafe { Z3_get_simplifier_name(ctx.z3_ctx.0, n) };
+                unsafe { CStr::from_ptr(t) }.to_str().map(String::from)
+            })
+            .collect()
+    }
+
+    /// Return a string containing a description of the simplifier with
+    /// the given `name`.
+    pub fn describe(name: &str) -> Result<String, Utf8Error> {
+        let ctx = &Context::thread_local();
+        let simplifier_name = CString::new(name).unwrap();
+        unsafe {
+            CStr::from_ptr(Z3_simplifier_get_descr(
+                ctx.z3_ctx.0,
+                simplifier_name.as_ptr(),
+            ))
+            .to_str()
+            .map(|s| s.to_string())
+        }
+    }
+
+    unsafe fn wrap(ctx: &Context, z3_simplifier: Z3_simplifier) -> Simplifier {
+        unsafe {
+            Z3_simplifier_inc_ref(ctx.z3_ctx.0, z3_simplifier);
+        }
+        Simplifier {
+            ctx: ctx.clone(),
+            z3_simplifier,
+        }
+    }
+
+    /// Create a simplifier by name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid simplifier, see
+    /// [`Simplifier::list_all()`].
+    pub fn new(name: &str) -> Simplifier {
+        let ctx = &Context::thread_local();
+        let simplifier_name = CString::new(name).unwrap();
+        unsafe {
+            let simplifier = Z3_mk_simplifier(ctx.z3_ctx.0, simplifier_name.as_ptr())
+                .unwrap_or_else(|| panic!("{name} is an invalid simplifier"));
+            Self::wrap(ctx, simplifier)
+        }
+    }
+
+    /// Return a simplifier that applies the current simplifier and then
+    /// `then_simplifier`.
+    pub fn and_then(&self, then_simplifier: &Simplifier) -> Simplifier {
+        unsafe {
+            Self::wrap(
+                &self.ctx,
+                Z3_simplifier_and_then(
+                    self.ctx.z3_ctx.0,
+                    self.z3_simplifier,
+                    then_simplifier.z3_simplifier,
+                )
+                .unwrap(),
+            )
+        }
+    }
+
+    /// Return a simplifier that applies the current simplifier using the
+    /// given set of parameters.
+    pub fn using_params(&self, params: &Params) -> Simplifier {
+        unsafe {
+            Self::wrap(
+                &self.ctx,
+                Z3_simplifier_using_params(self.ctx.z3_ctx.0, self.z3_simplifier, params.z3_params)
+                    .unwrap(),
+            )
+        }
+    }
+
+    /// Return a string describing the parameters of the simplifier, see
+    /// [`HelpEntry::parse()`](crate::HelpEntry::parse).
+    pub fn get_help(&self) -> String {
+        unsafe {
+            let help = Z3_simplifier_get_help(self.ctx.z3_ctx.0, self.z3_simplifier);
+            CStr::from_ptr(help).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Return the parameters accepted by the simplifier.
+    pub fn get_param_descrs(&self) -> ParamDescrs {
+        unsafe {
+            ParamDescrs::wrap(
+                &self.ctx,
+                Z3_simplifier_get_param_descrs(self.ctx.z3_ctx.0, self.z3_simplifier).unwrap(),
+            )
+        }
+    }
+}
+
+impl Solver {
+    /// Return a solver preprocessing its assertions with `simplifier`
+    /// before solving them like this solver, or `None` if this solver does
+    /// not support simplifiers.
+    pub fn add_simplifier(&self, simplifier: &Simplifier) -> Option<Solver> {
+        unsafe {
+            let solver =
+                Z3_solver_add_simplifier(self.ctx.z3_ctx.0, self.z3_slv, simplifier.z3_simplifier)?;
+            Some(Solver::wrap(&self.ctx, solver))
+        }
+    }
+}
+
+impl Clone for Simplifier {
+    fn clone(&self) -> Self {
+        unsafe { Self::wrap(&self.ctx, self.z3_simplifier) }
+    }
+}
+
+impl fmt::Debug for Simplifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.get_help())
+    }
+}
+
+impl Drop for Simplifier {
+    fn drop(&mut self) {
+        unsafe {
+            Z3_simplifier_dec_ref(self.ctx.z3_ctx.0, self.z3_simplifier);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::ffi::{CStr, CString};
+
+use z3_sys::*;
+
+use crate::{Config, Context, ParseError};
+
+/// An interactive SMT-LIB2 session, evaluating commands one at a time.
+///
+/// The session keeps the declarations, assertion scopes, options and solver
+/// of the commands evaluated so far, like the Z3 command line in interactive
+/// mode. Each session has its own [`Context`], so that sessions do not see
+/// each other's declarations.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{Smt2Response, Smt2Session};
+/// let session = Smt2Session::new();
+/// assert!(session.eval("(declare-const x Int) (assert (> x 2))").is_empty());
+/// assert_eq!(session.eval("(check-sat)"), [Smt2Response::Sat]);
+/// assert_eq!(
+///     session.eval("(push) (assert (< x 0)) (check-sat) (pop)"),
+///     [Smt2Response::Unsat]
+/// );
+///
+/// match &session.eval("(assert (> y 0))")[..] {
+///     [Smt2Response::Error(err)] => assert_eq!(err.message(), "unknown constant y"),
+///     other => panic!("unexpected response {other:?}"),
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Smt2Session {
+    ctx: Context,
+}
+
+/// The response to a command evaluated by an [`Smt2Session`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Smt2Response {
+    /// `success`, printed for every successful command when the option
+    /// `:print-success` is set.
+    Success,
+    /// `sat`, from `check-sat`.
+    Sat,
+    /// `unsat`, from `check-sat`.
+    Unsat,
+    /// `unknown`, from `check-sat`.
+    Unknown,
+    /// `unsupported`, for unknown commands and options.
+    Unsupported,
+    /// An error, with its location in the evaluated string if known.
+    Error(ParseError),
+    /// Any other output, such as models, values or `echo` output, as printed.
+    Output(String),
+}
+
+impl Smt2Session {
+    /// Create a session with the default configuration.
+    pub fn new() -> Smt2Session {
+        Smt2Session::with_config(&Config::new())
+    }
+
+    /// Create a session with the configuration `cfg`.
+    pub fn with_config(cfg: &Config) -> Smt2Session {
+        Smt2Session {
+            ctx: Context::new(cfg),
+        }
+    }
+
+    /// Returns the context of the session.
+    pub fn get_context(&self) -> &Context {
+        &self.ctx
+    }
+
+    /// Evaluate one or more SMT-LIB2 commands, and return the responses
+    /// they printed, in order.
+    ///
+    /// Commands which succeed silently add no response. The locations of
+    /// errors are relative to `commands`.
+    pub fn eval(&self, commands: &str) -> Vec<Smt2Response> {
+        let commands = match CString::new(commands) {
+            Ok(commands) => commands,
+            Err(e) => return vec![Smt2Response::Error(ParseError::new(e.to_string()))],
+        };
+        let output = unsafe {
+            let output = Z3_eval_smtlib2_string(self.ctx.z3_ctx.0, commands.as_ptr());
+            if output.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(output).to_string_lossy().into_owned()
+            }
+        };
+        split_responses(&output)
+            .into_iter()
+            .map(|response| match response.as_str() {
+                "success" => Smt2Response::Success,
+                "sat" => Smt2Response::Sat,
+                "unsat" => Smt2Response::Unsat,
+                "unknown" => Smt2Response::Unknown,
+                "unsupported" => Smt2Response::Unsupported,
+                _ if response.starts_with("(error ") => {
+                    Smt2Response::Error(ParseError::from_z3_message(&response))
+                }
+                _ => Smt2Response::Output(response),
+            })
+            .collect()
+    }
+}
+
+impl Default for Smt2Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Split the output of Z3 into responses: runs of lines with balanced
+/// parentheses. Comment lines are dropped.
+fn split_responses(output: &str) -> Vec<String> {
+    let mut responses = vec![];
+    let mut current = String::new();
+    let mut depth = 0i32;
+    for line in output.lines() {
+        if current.is_empty() && (line.trim().is_empty() || line.starts_with(';')) {
+            continue;
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+        depth += paren_depth(line);
+        if depth <= 0 {
+            responses.push(std::mem::take(&mut current));
+            depth = 0;
+        }
+    }
+    if !current.is_empty() {
+        responses.push(current);
+    }
+    responses
+}
+
+/// The change of parenthesis nesting over `line`, ignoring parentheses in
+/// string literals and quoted symbols.
+fn paren_depth(line: &str) -> i32 {
+    let mut depth = 0;
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (None, '"' | '|') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
@@ -1,13 +1,25 @@
 use log::debug;
 use std::borrow::Borrow;
+use std::collections::HashSet;
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::iter::FusedIterator;
+use std::ops::ControlFlow;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
 use z3_sys::*;
 
-use crate::ast::Bool;
+use crate::ast::{Bool, Dynamic};
+use crate::user_propagator::{PropagatorState, UserPropagator};
 use crate::{
-    Context, Model, Params, SatResult, Solver, Statistics, Symbol, Translate, ast, ast::Ast,
+    CancellationToken, Context, DependencyGraph, Dimacs, FuncDecl, Goal, Logic, Model, ParamDescrs,
+    Params, ParseError, Proof, SatResult, Solver, SolverScope, Sort, Statistics, Symbol, Tactic,
+    Translate, ast, ast::Ast,
 };
 use std::ops::AddAssign;
 
@@ -19,6 +31,13 @@ impl Solver {
         Solver {
             ctx: ctx.clone(),
             z3_slv,
+            propagator: std::cell::RefCell::new(None),
+            #[cfg(feature = "z3_4_12_0")]
+            on_clause: std::cell::RefCell::new(None),
+            tracked: std::cell::RefCell::new(vec![]),
+            next_track_id: std::cell::Cell::new(0),
+            logic: std::cell::Cell::new(None),
+            params: std::cell::RefCell::new(vec![]),
         }
     }
 
@@ -49,13 +68,30 @@ impl Solver {
         unsafe { Self::wrap(ctx, Z3_mk_solver(ctx.z3_ctx.0).unwrap()) }
     }
 
+    /// Create a new incremental solver that runs Z3's SMT core directly,
+    /// without the preprocessing tactics of [`Solver::new()`].
+    ///
+    /// This is the solver user propagators require.
+    pub fn new_simple() -> Solver {
+        let ctx = &Context::thread_local();
+        unsafe { Self::wrap(ctx, Z3_mk_simple_solver(ctx.z3_ctx.0).unwrap()) }
+    }
+
     /// Parse an SMT-LIB2 string with assertions, soft constraints and optimization objectives.
     /// Add the parsed constraints and objectives to the solver.
-    pub fn from_string<T: Into<Vec<u8>>>(&self, source_string: T) {
-        let source_cstring = CString::new(source_string).unwrap();
-        unsafe {
+    ///
+    /// Returns the location and description of the first error if the
+    /// string cannot be parsed. Use [`Solver::validate()`] to check the
+    /// parsed assertions against the logic declared with
+    /// [`Solver::set_logic()`].
+    pub fn from_string<T: Into<Vec<u8>>>(&self, source_string: T) -> Result<(), ParseError> {
+        let source_cstring =
+            CString::new(source_string).map_err(|e| ParseError::new(e.to_string()))?;
+        ParseError::catch(&self.ctx, || unsafe {
             Z3_solver_from_string(self.ctx.z3_ctx.0, self.z3_slv, source_cstring.as_ptr());
-        }
+        })?;
+        crate::declarations::record_asts(&self.ctx, &self.get_assertions());
+        Ok(())
     }
 
     /// Create a new solver customized for the given logic.
@@ -119,9 +155,152 @@ impl Solver {
         unsafe { Z3_solver_assert_and_track(self.ctx.z3_ctx.0, self.z3_slv, ast.z3_ast, p.z3_ast) };
     }
 
+    /// Assert a constraint into the solver, and track it in the unsat core
+    /// with a fresh answer literal, identified by the returned [`TrackId`].
+    ///
+    /// [`Solver::get_unsat_core_ids()`] maps the unsat core back to these
+    /// identifiers, so that the answer literals never have to be managed by
+    /// hand.
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let solver = Solver::new();
+    /// let x = Int::new_const("x");
+    /// let positive = solver.assert_and_track_auto(x.gt(0));
+    /// let small = solver.assert_and_track_auto(x.lt(10));
+    /// let negative = solver.assert_and_track_auto(x.lt(0));
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    ///
+    /// let core = solver.get_unsat_core_ids();
+    /// assert!(core.contains(&positive) && core.contains(&negative));
+    /// assert!(!core.contains(&small));
+    /// ```
+    pub fn assert_and_track_auto<T: Into<Bool>>(&self, ast: T) -> TrackId {
+        self.track(ast.into(), None)
+    }
+
+    /// Assert a constraint into the solver, and track it in the unsat core
+    /// under `name`.
+    ///
+    /// [`Solver::unsat_core_names()`] reports the names of the constraints
+    /// in the unsat core, e.g. to map them back to the source they were
+    /// generated from. Several constraints may share a name.
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let solver = Solver::new();
+    /// let x = Int::new_const("x");
+    /// solver.assert_named("loop-inv-3", x.gt(0));
+    /// solver.assert_named("bound", x.lt(10));
+    /// solver.assert_named("guard-7", x.lt(0));
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// assert_eq!(solver.unsat_core_names(), ["loop-inv-3", "guard-7"]);
+    /// ```
+    pub fn assert_named<T: Into<Bool>>(&self, name: &str, ast: T) {
+        self.track(ast.into(), Some(name.to_owned()));
+    }
+
+    fn track(&self, ast: Bool, name: Option<String>) -> TrackId {
+        let literal = Bool::fresh_const(name.as_deref().unwrap_or("track"));
+        self.assert_and_track(&ast, &literal);
+        let id = TrackId(self.next_track_id.get());
+        self.next_track_id.set(id.0 + 1);
+        self.tracked.borrow_mut().push(Tracked {
+            id,
+            constraint: ast,
+            literal,
+            name,
+            scope: unsafe { Z3_solver_get_num_scopes(self.ctx.z3_ctx.0, self.z3_slv) },
+        });
+        id
+    }
+
+    /// Return the constraint identified by `id`, or `None` if it was removed
+    /// by [`Solver::pop()`] or [`Solver::reset()`].
+    pub fn tracked_constraint(&self, id: TrackId) -> Option<Bool> {
+        self.tracked
+            .borrow()
+            .iter()
+            .find(|t| t.id == id)
+            .map(|t| t.constraint.clone())
+    }
+
+    /// Declare the logic of the constraints asserted into the solver, so
+    /// that [`Solver::try_assert()`] rejects constraints outside of it and
+    /// [`Solver::validate()`] reports them, e.g. to keep the generated
+    /// problems within a benchmark suite.
+    ///
+    /// This only validates the constraints. To also use a solver
+    /// specialized for the logic, create it with
+    /// [`Solver::new_for_logic()`].
+    ///
+    /// ```
+    /// # use z3::{Logic, Solver};
+    /// # use z3::ast::Int;
+    /// let solver = Solver::new_for_logic(Logic::QF_LIA).unwrap();
+    /// solver.set_logic(Logic::QF_LIA);
+    /// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    /// assert!(solver.try_assert(x.lt(&y)).is_ok());
+    /// assert!(solver.try_assert((&x * &y).eq(2)).is_err());
+    /// assert_eq!(solver.get_logic(), Some(Logic::QF_LIA));
+    /// assert!(solver.validate().is_ok());
+    /// ```
+    pub fn set_logic(&self, logic: Logic) {
+        self.logic.set(Some(logic));
+    }
+
+    /// Return the logic declared with [`Solver::set_logic()`].
+    pub fn get_logic(&self) -> Option<Logic> {
+        self.logic.get()
+    }
+
+    /// Assert a constraint into the solver, unless it does not belong to
+    /// the logic declared with [`Solver::set_logic()`].
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::assert()`]
+    /// - [`Solver::validate()`]
+    pub fn try_assert<T: Borrow<Bool>>(&self, ast: T) -> Result<(), String> {
+        let ast = ast.borrow();
+        self.check_logic(ast)?;
+        self.assert(ast);
+        Ok(())
+    }
+
+    /// Check that every assertion of the solver, including those added by
+    /// [`Solver::assert()`] and [`Solver::from_string()`], belongs to the
+    /// logic declared with [`Solver::set_logic()`].
+    ///
+    /// Returns an error describing the first assertion outside of it.
+    pub fn validate(&self) -> Result<(), String> {
+        self.get_assertions()
+            .iter()
+            .try_for_each(|ast| self.check_logic(ast))
+    }
+
+    fn check_logic(&self, ast: &Bool) -> Result<(), String> {
+        match self.logic.get() {
+            Some(logic) => {
+                let used = ast.detect_logic();
+                if logic.includes(&used) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{ast} is in {used}, which is not included in the logic {logic} of the solver"
+                    ))
+                }
+            }
+            None => Ok(()),
+        }
+    }
+
     /// Remove all assertions from the solver.
     pub fn reset(&self) {
         unsafe { Z3_solver_reset(self.ctx.z3_ctx.0, self.z3_slv) };
+        self.tracked.borrow_mut().clear();
     }
 
     /// Check whether the assertions in a given solver are consistent or not.
@@ -178,6 +357,313 @@ impl Solver {
         }
     }
 
+    /// Check whether the assertions in the solver are consistent, giving up
+    /// with [`SatResult::Unknown`] after `timeout`.
+    ///
+    /// The check is interrupted from a timer thread, so this also bounds
+    /// tactics and theories which do not poll the `timeout` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let x = Int::new_const("x");
+    /// let solver = Solver::new();
+    /// solver.assert(x.gt(0));
+    /// assert_eq!(solver.check_with_timeout(Duration::from_secs(10)), SatResult::Sat);
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::check_cancellable()`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn check_with_timeout(&self, timeout: Duration) -> SatResult {
+        let token = CancellationToken::new();
+        let (done, timer) = mpsc::channel::<()>();
+        thread::scope(|scope| {
+            let canceller = token.clone();
+            scope.spawn(move || {
+                if timer.recv_timeout(timeout) == Err(mpsc::RecvTimeoutError::Timeout) {
+                    canceller.cancel();
+                }
+            });
+            let result = self.check_cancellable(&token);
+            drop(done);
+            result
+        })
+    }
+
+    /// Check whether the assertions in the solver are consistent, unless
+    /// `token` is cancelled from another thread.
+    ///
+    /// Returns [`SatResult::Unknown`] if the check is interrupted, and also
+    /// without checking if the token was cancelled before.
+    ///
+    /// # See also:
+    ///
+    /// - [`CancellationToken`]
+    /// - [`Solver::check_with_timeout()`]
+    pub fn check_cancellable(&self, token: &CancellationToken) -> SatResult {
+        token
+            .run(self.ctx.z3_ctx.0, || self.check())
+            .unwrap_or(SatResult::Unknown)
+    }
+
+    /// Check whether the assertions in the solver are consistent, giving up
+    /// with [`SatResult::Unknown`] after consuming `rlimit` resource units.
+    ///
+    /// Unlike a timeout, the resource limit does not depend on the speed or
+    /// load of the machine, so the result is reproducible. Returns the result
+    /// together with the number of units consumed by the check.
+    ///
+    /// This sets the `rlimit` parameter of the solver for the duration of
+    /// the check. Afterwards, it is set back to the value given with
+    /// [`Solver::set_params()`], or to `0` (no limit).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let x = Int::new_const("x");
+    /// let solver = Solver::new();
+    /// solver.assert((&x * &x * &x).eq(&x + 24));
+    ///
+    /// let (result, _) = solver.check_with_rlimit(10);
+    /// assert_eq!(result, SatResult::Unknown);
+    /// assert_eq!(
+    ///     solver.get_reason_unknown().as_deref(),
+    ///     Some("max. resource limit exceeded")
+    /// );
+    ///
+    /// let (result, used) = solver.check_with_rlimit(1_000_000);
+    /// assert_eq!(result, SatResult::Sat);
+    /// assert!(0 < used && used <= 1_000_000);
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`Statistics::rlimit_count()`]
+    pub fn check_with_rlimit(&self, rlimit: u32) -> (SatResult, u64) {
+        let count = || self.get_statistics().rlimit_count().unwrap_or(0);
+        let before = count();
+        let mut params = Params::new();
+        params.set_u32("rlimit", rlimit);
+        self.apply_params(&params);
+        let result = self.check();
+        params.set_u32("rlimit", 0);
+        self.restore_params(params);
+        (result, count().saturating_sub(before))
+    }
+
+    /// Check whether the assertions in the solver are consistent, calling
+    /// `on_yield` periodically so that the caller keeps control during long
+    /// checks.
+    ///
+    /// This needs no threads, so it also works where they are unavailable,
+    /// such as on `wasm32-unknown-unknown`, where a browser would otherwise
+    /// freeze until the check returns. The check runs in slices of
+    /// [resource units](Solver::check_with_rlimit), starting with `rlimit`
+    /// units and doubling with each slice. After each slice which ran out of
+    /// units, `on_yield` is called with the units consumed so far, and
+    /// returns [`ControlFlow::Break`] to give up with [`SatResult::Unknown`].
+    ///
+    /// Each slice starts the check anew, but since the slices double, the
+    /// total work is at most about twice that of a single check.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::ops::ControlFlow;
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let x = Int::new_const("x");
+    /// let solver = Solver::new();
+    /// solver.assert((&x * &x * &x).eq(&x + 24));
+    ///
+    /// let mut yields = 0;
+    /// let result = solver.check_yielding(10, |_| {
+    ///     yields += 1;
+    ///     ControlFlow::Continue(())
+    /// });
+    /// assert_eq!(result, SatResult::Sat);
+    /// assert!(yields > 0);
+    ///
+    /// assert_eq!(solver.check_yielding(10, |_| ControlFlow::Break(())), SatResult::Unknown);
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::check_with_rlimit()`]
+    pub fn check_yielding(
+        &self,
+        rlimit: u32,
+        mut on_yield: impl FnMut(u64) -> ControlFlow<()>,
+    ) -> SatResult {
+        let mut slice = rlimit.max(1);
+        let mut consumed = 0;
+        loop {
+            let (result, used) = self.check_with_rlimit(slice);
+            consumed += used;
+            if result != SatResult::Unknown
+                || self.get_reason_unknown().as_deref() != Some("max. resource limit exceeded")
+            {
+                return result;
+            }
+            if on_yield(consumed).is_break() {
+                return SatResult::Unknown;
+            }
+            slice = slice.saturating_mul(2);
+        }
+    }
+
+    /// Check a quantified problem with increasing instantiation limits,
+    /// stopping at the first definite answer or when `timeout` has elapsed.
+    ///
+    /// For each bound `k` of `bounds`, in order, the check may instantiate
+    /// quantifiers at most `k` times (`smt.qi.max_instances`) and run at most
+    /// `k` rounds of model-based quantifier instantiation
+    /// (`smt.mbqi.max_iterations`). Each step is bounded by the time left.
+    /// Afterwards, both parameters are set back to the values given with
+    /// [`Solver::set_params()`], or to their defaults.
+    ///
+    /// Returns one step per attempted bound, so that the bounds at which the
+    /// solver gave up show how far it got. The result of the last step is
+    /// the answer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use z3::{FuncDecl, SatResult, Solver, Sort};
+    /// # use z3::ast::{forall_const, Int};
+    /// // p(0), p(x) => p(x + 1) and not p(20) need many instantiations.
+    /// let p = FuncDecl::new("p", &[&Sort::int()], &Sort::bool());
+    /// let at = |x: &Int| p.apply(&[x]).as_bool().unwrap();
+    /// let x = Int::new_const("x");
+    /// let solver = Solver::new();
+    /// solver.assert(forall_const(&[&x], &[], &at(&x).implies(at(&(&x + 1)))));
+    /// solver.assert(at(&Int::from_i64(0)));
+    /// solver.assert(at(&Int::from_i64(20)).not());
+    ///
+    /// let steps = solver.check_deepening([1, 10, 100, 1000], Duration::from_secs(30));
+    /// assert_eq!(steps[0].result, SatResult::Unknown);
+    /// assert_eq!(steps.last().unwrap().result, SatResult::Unsat);
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn check_deepening(
+        &self,
+        bounds: impl IntoIterator<Item = u32>,
+        timeout: Duration,
+    ) -> Vec<DeepeningStep> {
+        let deadline = Instant::now() + timeout;
+        let mut params = Params::new();
+        let mut steps = vec![];
+        for bound in bounds {
+            let Some(left) = deadline.checked_duration_since(Instant::now()) else {
+                break;
+            };
+            params.set_u32("smt.qi.max_instances", bound);
+            params.set_u32("smt.mbqi.max_iterations", bound);
+            self.apply_params(&params);
+            let start = Instant::now();
+            let result = self.check_with_timeout(left);
+            steps.push(DeepeningStep {
+                bound,
+                result,
+                reason_unknown: match result {
+                    SatResult::Unknown => self.get_reason_unknown(),
+                    _ => None,
+                },
+                elapsed: start.elapsed(),
+            });
+            if result != SatResult::Unknown {
+                break;
+            }
+        }
+        params.set_u32("smt.qi.max_instances", u32::MAX);
+        params.set_u32("smt.mbqi.max_iterations", 1000);
+        self.restore_params(params);
+        steps
+    }
+
+    /// Check which of the `obligations` are valid under the current
+    /// assertions, which act as their shared context.
+    ///
+    /// Rather than one check per obligation, each check asks for a model
+    /// falsifying any of the obligations still pending. If there is none,
+    /// they are all valid at once; otherwise the obligations the model
+    /// falsifies are invalid, with the model as counterexample, and the
+    /// others are checked again. When most obligations hold, as for the
+    /// verification conditions of a program, this takes few checks.
+    ///
+    /// If a check returns [`SatResult::Unknown`], the pending obligations
+    /// are [`Verdict::Unknown`]. The assertions of the solver are left
+    /// unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{Solver, Verdict};
+    /// # use z3::ast::Int;
+    /// let x = Int::new_const("x");
+    /// let solver = Solver::new();
+    /// solver.assert(x.gt(10));
+    ///
+    /// let verdicts = solver.check_obligations(&[x.gt(5), x.gt(20), x.ne(0)]);
+    /// assert!(matches!(verdicts[0], Verdict::Valid));
+    /// let Verdict::Invalid(counterexample) = &verdicts[1] else { panic!() };
+    /// assert!(counterexample.eval(&x, true).unwrap().as_i64().unwrap() <= 20);
+    /// assert!(matches!(verdicts[2], Verdict::Valid));
+    /// ```
+    pub fn check_obligations(&self, obligations: &[Bool]) -> Vec<Verdict> {
+        let mut verdicts: Vec<Option<Verdict>> = obligations.iter().map(|_| None).collect();
+        let mut pending: Vec<usize> = (0..obligations.len()).collect();
+        self.push();
+        while !pending.is_empty() {
+            let guard = Bool::fresh_const("obligations");
+            let negated: Vec<Bool> = pending.iter().map(|&i| obligations[i].not()).collect();
+            self.assert(guard.implies(Bool::or(&negated)));
+            match self.check_assumptions(&[guard]) {
+                SatResult::Unsat => {
+                    for &i in &pending {
+                        verdicts[i] = Some(Verdict::Valid);
+                    }
+                    pending.clear();
+                }
+                SatResult::Sat => {
+                    let model = self.get_model().unwrap();
+                    let before = pending.len();
+                    pending.retain(|&i| {
+                        let holds = model.eval(&obligations[i], true).and_then(|v| v.as_bool());
+                        if holds == Some(false) {
+                            verdicts[i] = Some(Verdict::Invalid(model.clone()));
+                        }
+                        holds != Some(false)
+                    });
+                    // The model may not decide the obligations, e.g. under
+                    // quantifiers.
+                    if pending.len() == before {
+                        for &i in &pending {
+                            verdicts[i] = Some(Verdict::Unknown);
+                        }
+                        pending.clear();
+                    }
+                }
+                SatResult::Unknown => {
+                    for &i in &pending {
+                        verdicts[i] = Some(Verdict::Unknown);
+                    }
+                    pending.clear();
+                }
+            }
+        }
+        self.pop(1);
+        verdicts.into_iter().map(Option::unwrap).collect()
+    }
+
     // Return a vector of assumptions in the solver.
     pub fn get_assertions(&self) -> Vec<ast::Bool> {
         let z3_vec = unsafe { Z3_solver_get_assertions(self.ctx.z3_ctx.0, self.z3_slv) }.unwrap();
@@ -190,6 +676,174 @@ impl Solver {
             .collect()
     }
 
+    /// Return the literals the solver has established at the base level,
+    /// modulo model conversion.
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::get_non_units()`]
+    /// - [`Solver::get_trail()`]
+    pub fn get_units(&self) -> Vec<ast::Bool> {
+        let z3_vec = unsafe { Z3_solver_get_units(self.ctx.z3_ctx.0, self.z3_slv) };
+        self.bools_of(z3_vec)
+    }
+
+    /// Return the formulas of the solver state which are not units, such as
+    /// the clauses of the SAT solver.
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::get_units()`]
+    pub fn get_non_units(&self) -> Vec<ast::Bool> {
+        let z3_vec = unsafe { Z3_solver_get_non_units(self.ctx.z3_ctx.0, self.z3_slv) };
+        self.bools_of(z3_vec)
+    }
+
+    /// Return the literals assigned by the last check, modulo model
+    /// conversion, in order of decision level.
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::get_levels()`]
+    pub fn get_trail(&self) -> Vec<ast::Bool> {
+        let z3_vec = unsafe { Z3_solver_get_trail(self.ctx.z3_ctx.0, self.z3_slv) };
+        self.bools_of(z3_vec)
+    }
+
+    /// Return the decision level of each of `literals` (Boolean variables or
+    /// their negations), such as those of [`Solver::get_trail()`].
+    ///
+    /// This is only meaningful right after a check, before the model is
+    /// retrieved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Bool;
+    /// let solver = Solver::new();
+    /// let (a, b) = (Bool::new_const("a"), Bool::new_const("b"));
+    /// solver.assert(&a);
+    /// solver.assert(Bool::or(&[a.not(), b.clone()]));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let trail = solver.get_trail();
+    /// assert_eq!(solver.get_levels(&trail).len(), trail.len());
+    /// ```
+    pub fn get_levels(&self, literals: &[ast::Bool]) -> Vec<u32> {
+        let mut levels = vec![0; literals.len()];
+        unsafe {
+            let z3_vec = Z3_mk_ast_vector(self.ctx.z3_ctx.0).unwrap();
+            Z3_ast_vector_inc_ref(self.ctx.z3_ctx.0, z3_vec);
+            for l in literals {
+                Z3_ast_vector_push(self.ctx.z3_ctx.0, z3_vec, l.z3_ast);
+            }
+            Z3_solver_get_levels(
+                self.ctx.z3_ctx.0,
+                self.z3_slv,
+                z3_vec,
+                levels.len().try_into().unwrap(),
+                levels.as_mut_ptr(),
+            );
+            Z3_ast_vector_dec_ref(self.ctx.z3_ctx.0, z3_vec);
+        }
+        levels
+    }
+
+    fn bools_of(&self, z3_vec: Option<Z3_ast_vector>) -> Vec<ast::Bool> {
+        let Some(z3_vec) = z3_vec else {
+            return vec![];
+        };
+        unsafe {
+            Z3_ast_vector_inc_ref(self.ctx.z3_ctx.0, z3_vec);
+            let bools = (0..Z3_ast_vector_size(self.ctx.z3_ctx.0, z3_vec))
+                .map(|i| {
+                    let z3_ast = Z3_ast_vector_get(self.ctx.z3_ctx.0, z3_vec, i).unwrap();
+                    ast::Bool::wrap(&self.ctx, z3_ast)
+                })
+                .collect();
+            Z3_ast_vector_dec_ref(self.ctx.z3_ctx.0, z3_vec);
+            bools
+        }
+    }
+
+    /// Check that `model` satisfies the assertions of the solver, by
+    /// evaluating each of them under `model` with model completion.
+    ///
+    /// Returns the assertions that do not evaluate to `true`, in the order
+    /// they were asserted. This is a cheap sanity check of a model returned
+    /// by the solver, or extracted or built by hand.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let solver = Solver::new();
+    /// let x = Int::new_const("x");
+    /// solver.assert(x.gt(0));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// let model = solver.get_model().unwrap();
+    /// assert_eq!(solver.validate_model(&model), Ok(()));
+    ///
+    /// solver.assert(x.lt(0));
+    /// assert_eq!(solver.validate_model(&model), Err(vec![x.lt(0)]));
+    /// ```
+    pub fn validate_model(&self, model: &Model) -> Result<(), Vec<Bool>> {
+        let violated: Vec<Bool> = self
+            .get_assertions()
+            .into_iter()
+            .filter(|a| model.eval(a, true).and_then(|v| v.as_bool()) != Some(true))
+            .collect();
+        if violated.is_empty() {
+            Ok(())
+        } else {
+            Err(violated)
+        }
+    }
+
+    /// Return the graph connecting the assertions of the solver which share
+    /// uninterpreted constants or functions.
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::split_independent()`]
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        DependencyGraph::new(self.get_assertions())
+    }
+
+    /// Split the assertions of the solver into independent subproblems, one
+    /// new solver per connected component of [`Solver::dependency_graph()`].
+    ///
+    /// The conjunction of the assertions is satisfiable iff every returned
+    /// solver is satisfiable, so the solvers can be checked concurrently
+    /// (e.g. after [`Translate`]ing them to other contexts). Parameters set
+    /// on this solver are not copied. Their verdicts can be recombined with
+    /// [`SatResult::combine()`].
+    pub fn split_independent(&self) -> Vec<Solver> {
+        let graph = self.dependency_graph();
+        graph
+            .components()
+            .into_iter()
+            .map(|component| {
+                let solver =
+                    unsafe { Solver::wrap(&self.ctx, Z3_mk_solver(self.ctx.z3_ctx.0).unwrap()) };
+                for node in component {
+                    solver.assert(&graph.assertions()[node]);
+                }
+                solver
+            })
+            .collect()
+    }
+
+    /// Check the independent subproblems of [`Solver::split_independent()`]
+    /// one after the other, stopping at the first unsatisfiable one.
+    ///
+    /// Note that no model is available from this solver afterwards.
+    pub fn check_independent(&self) -> SatResult {
+        SatResult::combine(self.split_independent().iter().map(Solver::check))
+    }
+
     /// Return a subset of the assumptions provided to either the last
     ///
     /// * [`Solver::check_assumptions`] call, or
@@ -231,7 +885,69 @@ impl Solver {
         unsat_core
     }
 
+    /// Return the identifiers of the constraints asserted with
+    /// [`Solver::assert_and_track_auto()`] that are in the unsat core of
+    /// the last check, in increasing order.
+    ///
+    /// Other elements of the core, such as assumptions or literals passed
+    /// to [`Solver::assert_and_track()`], are left out.
+    pub fn get_unsat_core_ids(&self) -> Vec<TrackId> {
+        let core: HashSet<Bool> = self.get_unsat_core().into_iter().collect();
+        self.tracked
+            .borrow()
+            .iter()
+            .filter(|t| t.name.is_none() && core.contains(&t.literal))
+            .map(|t| t.id)
+            .collect()
+    }
+
+    /// Return the names of the constraints asserted with
+    /// [`Solver::assert_named()`] that are in the unsat core of the last
+    /// check, in assertion order and without duplicates.
+    ///
+    /// Other elements of the core are left out.
+    pub fn unsat_core_names(&self) -> Vec<String> {
+        let core: HashSet<Bool> = self.get_unsat_core().into_iter().collect();
+        let mut names: Vec<String> = vec![];
+        for t in self.tracked.borrow().iter() {
+            match &t.name {
+                Some(name) if core.contains(&t.literal) && !names.contains(name) => {
+                    names.push(name.clone());
+                }
+                _ => {}
+            }
+        }
+        names
+    }
+
     /// Retrieve consequences from the solver given a set of assumptions.
+    ///
+    /// Returns the literals over `variables` which are implied by the
+    /// assertions and `assumptions`, each as an implication from the
+    /// assumptions it depends on. Returns an empty vector if the assertions
+    /// and assumptions are unsatisfiable or the check is inconclusive.
+    ///
+    /// Without assumptions, this computes the backbone of the assertions over
+    /// `variables`: the literals true in every model. The consequences are
+    /// not returned in any particular order.
+    ///
+    /// # Example
+    /// ```
+    /// # use z3::Solver;
+    /// # use z3::ast::Bool;
+    /// let solver = Solver::new();
+    /// let (a, b, c) = (Bool::new_const("a"), Bool::new_const("b"), Bool::new_const("c"));
+    /// solver.assert(a.implies(&b));
+    /// solver.assert(Bool::or(&[&a, &c.not()]));
+    /// solver.assert(&c);
+    ///
+    /// let backbone = solver.get_consequences(&[], &[a, b, c.clone()]);
+    /// let mut backbone: Vec<String> = backbone.iter().map(|l| l.to_string()).collect();
+    /// backbone.sort();
+    /// assert_eq!(backbone, ["(=> true a)", "(=> true b)", "(=> true c)"]);
+    ///
+    /// assert!(solver.get_consequences(&[c.not()], &[c]).is_empty());
+    /// ```
     pub fn get_consequences(
         &self,
         assumptions: &[ast::Bool],
@@ -291,6 +1007,39 @@ impl Solver {
     /// - [`Solver::push()`]
     pub fn pop(&self, n: u32) {
         unsafe { Z3_solver_pop(self.ctx.z3_ctx.0, self.z3_slv, n) };
+        let scopes = unsafe { Z3_solver_get_num_scopes(self.ctx.z3_ctx.0, self.z3_slv) };
+        self.tracked.borrow_mut().retain(|t| t.scope <= scopes);
+    }
+
+    /// Create a backtracking point, which is popped when the returned guard
+    /// is dropped.
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::scope()`]
+    pub fn push_scoped(&self) -> SolverScope<'_> {
+        SolverScope::new(self)
+    }
+
+    /// Run `f` in a new backtracking point, which is popped afterwards,
+    /// also if `f` panics.
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Bool;
+    /// let solver = Solver::new();
+    /// let a = Bool::new_const("a");
+    /// solver.assert(&a);
+    /// let result = solver.scope(|s| {
+    ///     s.assert(a.not());
+    ///     s.check()
+    /// });
+    /// assert_eq!(result, SatResult::Unsat);
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// ```
+    pub fn scope<R>(&self, f: impl FnOnce(&Solver) -> R) -> R {
+        let scope = self.push_scoped();
+        f(&scope)
     }
 
     /// Retrieve the model for the last [`Solver::check()`]
@@ -311,6 +1060,90 @@ impl Solver {
         Model::of_solver(self)
     }
 
+    /// Find a model of the current assertions in which as few of `terms` as
+    /// possible take a value different from the one they have in
+    /// `reference`, e.g. to repair a configuration after a constraint was
+    /// added.
+    ///
+    /// Returns the result of the check, [`SatResult::Unsat`] or
+    /// [`SatResult::Unknown`], if there is no model. Terms which `reference`
+    /// does not interpret are compared to the value it gives them by model
+    /// completion.
+    ///
+    /// The number of changed terms is bisected with [`Solver::check()`] on
+    /// the solver itself, bounding it inside a [`Solver::push()`] scope, so
+    /// that constraints tracked with [`Solver::assert_and_track()`] are
+    /// enforced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let vars: Vec<Int> = (0..4).map(|i| Int::new_const(format!("x{i}"))).collect();
+    /// let solver = Solver::new();
+    /// for x in &vars {
+    ///     solver.assert(x.ge(0));
+    /// }
+    /// solver.push();
+    /// solver.assert(vars[0].eq(5));
+    /// solver.assert(vars[1].eq(5));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// let reference = solver.get_model().unwrap();
+    /// solver.pop(1);
+    ///
+    /// // The new constraint forces one of x0 and x1 to change.
+    /// solver.assert((&vars[0] + &vars[1]).le(8));
+    /// let terms: Vec<&dyn z3::ast::Ast> = vars.iter().map(|x| x as _).collect();
+    /// let model = solver.nearest_model(&reference, &terms).unwrap();
+    /// let changed = vars
+    ///     .iter()
+    ///     .filter(|x| model.eval(*x, true) != reference.eval(*x, true))
+    ///     .count();
+    /// assert_eq!(changed, 1);
+    ///
+    /// solver.assert(vars[2].lt(0));
+    /// assert_eq!(solver.nearest_model(&reference, &terms).unwrap_err(), SatResult::Unsat);
+    /// ```
+    pub fn nearest_model(&self, reference: &Model, terms: &[&dyn Ast]) -> Result<Model, SatResult> {
+        let changed: Vec<Bool> = terms
+            .iter()
+            .filter_map(|term| {
+                let term = Dynamic::from_ast(*term);
+                let value = reference.eval(&term, true)?;
+                Some(term.eq(&value).not())
+            })
+            .collect();
+        let count_changed = |model: &Model| {
+            changed
+                .iter()
+                .filter(|c| model.eval(*c, true).and_then(|c| c.as_bool()) != Some(false))
+                .count() as u32
+        };
+        let mut best = match self.check() {
+            SatResult::Sat => self.get_model().ok_or(SatResult::Unknown)?,
+            result => return Err(result),
+        };
+        let (mut low, mut high) = (0, count_changed(&best));
+        while low < high {
+            let mid = low + (high - low) / 2;
+            self.push();
+            self.assert(ast::atmost(&changed, mid));
+            let result = self.check();
+            let model = self.get_model();
+            self.pop(1);
+            match result {
+                SatResult::Sat => {
+                    best = model.ok_or(SatResult::Unknown)?;
+                    high = count_changed(&best).min(mid);
+                }
+                SatResult::Unsat => low = mid + 1,
+                SatResult::Unknown => return Err(SatResult::Unknown),
+            }
+        }
+        Ok(best)
+    }
+
     /// Retrieve the proof for the last [`Solver::check()`]
     /// or [`Solver::check_assumptions()`].
     ///
@@ -331,6 +1164,15 @@ impl Solver {
         Some(unsafe { ast::Dynamic::wrap(&self.ctx, m) })
     }
 
+    /// Retrieve the proof for the last [`Solver::check()`] or
+    /// [`Solver::check_assumptions()`] as a [`Proof`], whose inference
+    /// steps can be walked.
+    ///
+    /// Returns `None` under the same conditions as [`Solver::get_proof()`].
+    pub fn get_structured_proof(&self) -> Option<Proof> {
+        Proof::from_ast(&self.get_proof()?)
+    }
+
     /// Return a brief justification for an "unknown" result (i.e.,
     /// [`SatResult::Unknown`]) for the commands [`Solver::check()`]
     /// and [`Solver::check_assumptions()`].
@@ -347,9 +1189,50 @@ impl Solver {
 
     /// Set the current solver using the given parameters.
     pub fn set_params(&self, params: &Params) {
+        self.apply_params(params);
+        let mut set = self.params.borrow_mut();
+        for (k, v) in params.entries() {
+            set.retain(|(key, _)| key != k);
+            set.push((k.clone(), v.clone()));
+        }
+    }
+
+    /// Set parameters temporarily, without recording them for
+    /// [`Solver::restore_params()`].
+    fn apply_params(&self, params: &Params) {
         unsafe { Z3_solver_set_params(self.ctx.z3_ctx.0, self.z3_slv, params.z3_params) };
     }
 
+    /// Set the parameters of `defaults` back to the values given with
+    /// [`Solver::set_params()`], or to those of `defaults` if there are none.
+    fn restore_params(&self, mut defaults: Params) {
+        for (k, v) in self.params.borrow().iter() {
+            if defaults.entries().iter().any(|(key, _)| key == k) {
+                defaults.set(k.clone(), v.clone());
+            }
+        }
+        self.apply_params(&defaults);
+    }
+
+    /// Return a string describing all available parameters for this solver,
+    /// see [`HelpEntry::parse()`](crate::HelpEntry::parse).
+    pub fn get_help(&self) -> String {
+        unsafe {
+            let help = Z3_solver_get_help(self.ctx.z3_ctx.0, self.z3_slv);
+            CStr::from_ptr(help).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Return the parameters accepted by this solver.
+    pub fn get_param_descrs(&self) -> ParamDescrs {
+        unsafe {
+            ParamDescrs::wrap(
+                &self.ctx,
+                Z3_solver_get_param_descrs(self.ctx.z3_ctx.0, self.z3_slv).unwrap(),
+            )
+        }
+    }
+
     /// Retrieve the statistics for the last [`Solver::check()`].
     pub fn get_statistics(&self) -> Statistics {
         unsafe {
@@ -360,6 +1243,198 @@ impl Solver {
         }
     }
 
+    /// Attach a [`UserPropagator`] to the solver, notified of the
+    /// assignments to the terms registered with
+    /// [`Solver::propagate_register()`].
+    ///
+    /// Only solvers created by [`Solver::new_simple()`] support user
+    /// propagators; other solvers return Z3's error message. The propagator
+    /// is not carried over to copies of the solver made by [`Clone`] or
+    /// [`Translate`], which solve without it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a user propagator is already attached.
+    pub fn set_user_propagator(
+        &self,
+        propagator: impl UserPropagator + 'static,
+    ) -> Result<(), String> {
+        let mut slot = self.propagator.borrow_mut();
+        assert!(slot.is_none(), "Solver already has a user propagator");
+        let mut state = Box::new(PropagatorState::new(&self.ctx, Box::new(propagator)));
+        // The state is kept in the solver, which drops it after the Z3 solver.
+        unsafe { state.install(self.z3_slv)? };
+        *slot = Some(state);
+        Ok(())
+    }
+
+    /// Register `term` with the user propagator, which is then notified when
+    /// the term is assigned a value or is found equal or distinct to another
+    /// registered term.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no user propagator is attached.
+    pub fn propagate_register(&self, term: &impl Ast) {
+        self.propagator
+            .borrow_mut()
+            .as_mut()
+            .expect("Solver has no user propagator")
+            .register(self.z3_slv, Dynamic::from_ast(term));
+    }
+
+    /// Declare a function whose applications are registered with the user
+    /// propagator as the solver creates them, reporting each to
+    /// [`UserPropagator::created()`].
+    ///
+    /// The function is otherwise uninterpreted: the propagator defines it
+    /// through the consequences it propagates.
+    #[cfg(feature = "z3_4_8_15")]
+    pub fn propagate_declare<S: Into<Symbol>>(
+        &self,
+        name: S,
+        domain: &[&Sort],
+        range: &Sort,
+    ) -> FuncDecl {
+        let domain: Vec<_> = domain.iter().map(|s| s.z3_sort).collect();
+        let decl = unsafe {
+            FuncDecl::wrap(
+                &self.ctx,
+                Z3_solver_propagate_declare(
+                    self.ctx.z3_ctx.0,
+                    name.into().as_z3_symbol(),
+                    domain.len().try_into().unwrap(),
+                    domain.as_ptr(),
+                    range.z3_sort,
+                )
+                .unwrap(),
+            )
+        };
+        crate::declarations::record_decl(&self.ctx, decl.z3_func_decl);
+        decl
+    }
+
+    /// Hint that `var` should take the value `value`, which the solver then
+    /// tries first when it branches on `var`, e.g. to seed the search with
+    /// the model of a previous, slightly different problem.
+    ///
+    /// The hint does not constrain `var`: it is dropped if it conflicts with
+    /// the assertions. A [`UserPropagator`] steers the search on its terms
+    /// with [`PropagatorCallback::next_split()`](crate::PropagatorCallback::next_split)
+    /// instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let x = Int::new_const("x");
+    /// let solver = Solver::new();
+    /// solver.assert(x.gt(0));
+    /// solver.set_initial_value(&x, &Int::from_i64(42));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// let x = solver.get_model().unwrap().eval(&x, true).unwrap();
+    /// assert!(x.as_i64().unwrap() > 0);
+    /// ```
+    #[cfg(feature = "z3_4_13_1")]
+    pub fn set_initial_value<T: Ast>(&self, var: &T, value: &T) {
+        unsafe {
+            Z3_solver_set_initial_value(
+                self.ctx.z3_ctx.0,
+                self.z3_slv,
+                var.get_z3_ast(),
+                value.get_z3_ast(),
+            );
+        }
+    }
+
+    /// Return the terms the solver considers equal to `term` after the last
+    /// check, starting with `term` itself.
+    ///
+    /// Only terms the solver has internalized are reported; for other terms
+    /// the class is just `term`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::{Ast, Int};
+    /// let (x, y, z) = (Int::new_const("x"), Int::new_const("y"), Int::new_const("z"));
+    /// let solver = Solver::new_simple();
+    /// solver.assert(x.eq(&y));
+    /// solver.assert(z.gt(&x));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let class: Vec<String> = solver.congruence_class(&x).map(|t| t.to_string()).collect();
+    /// assert_eq!(class[0], "x");
+    /// assert!(class.contains(&"y".to_string()));
+    /// assert!(!class.contains(&"z".to_string()));
+    /// ```
+    #[cfg(feature = "z3_4_12_0")]
+    pub fn congruence_class(&self, term: &impl Ast) -> impl Iterator<Item = Dynamic> {
+        let start = Dynamic::from_ast(term);
+        let mut class = vec![start.clone()];
+        let mut current = start.get_z3_ast();
+        while let Some(next) =
+            unsafe { Z3_solver_congruence_next(self.ctx.z3_ctx.0, self.z3_slv, current) }
+        {
+            let next = unsafe { Dynamic::wrap(&self.ctx, next) };
+            // The class is a cycle through `term`.
+            if next == start {
+                break;
+            }
+            current = next.get_z3_ast();
+            class.push(next);
+        }
+        class.into_iter()
+    }
+
+    /// Register a callback invoked with each clause the solver infers,
+    /// replacing any callback registered before, e.g. to stream the clauses
+    /// to a DRAT proof file or a proof checker.
+    ///
+    /// The callback receives the proof hint justifying the clause, if any,
+    /// and the literals of the clause. The terms are only valid during the
+    /// call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::cell::RefCell;
+    /// # use std::rc::Rc;
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Bool;
+    /// let solver = Solver::new_simple();
+    /// let clauses = Rc::new(RefCell::new(vec![]));
+    /// let sink = clauses.clone();
+    /// solver.on_clause(move |_hint, literals| {
+    ///     sink.borrow_mut().push(literals.len());
+    /// });
+    ///
+    /// let (a, b) = (Bool::new_const("a"), Bool::new_const("b"));
+    /// for (a, b) in [(a.clone(), b.clone()), (a.not(), b.clone()), (a.clone(), b.not()), (a.not(), b.not())] {
+    ///     solver.assert(Bool::or(&[&a, &b]));
+    /// }
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// assert!(!clauses.borrow().is_empty());
+    /// ```
+    #[cfg(feature = "z3_4_12_0")]
+    pub fn on_clause(&self, callback: impl FnMut(Option<&Dynamic>, &[Bool]) + 'static) {
+        let mut state = Box::new(OnClauseState {
+            ctx: self.ctx.clone(),
+            callback: Box::new(callback),
+        });
+        unsafe {
+            Z3_solver_register_on_clause(
+                self.ctx.z3_ctx.0,
+                self.z3_slv,
+                &mut *state as *mut OnClauseState as *mut std::ffi::c_void,
+                Some(on_clause_trampoline),
+            );
+        }
+        *self.on_clause.borrow_mut() = Some(state);
+    }
+
     pub fn to_smt2(&self) -> String {
         let name = CString::new("benchmark generated from rust API").unwrap();
         let logic = CString::new("").unwrap();
@@ -397,6 +1472,129 @@ impl Solver {
             .unwrap_or_else(String::new)
     }
 
+    /// Serialize the current assertions as an SMT-LIB2 benchmark, the same
+    /// as [`Solver::to_smt2()`].
+    ///
+    /// The benchmark declares the sorts, constants and functions the
+    /// assertions use, followed by the assertions and a `(check-sat)`
+    /// command, so that it can be read back with [`Solver::from_string()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::Solver;
+    /// # use z3::ast::Int;
+    /// let x = Int::new_const("x");
+    /// let solver = Solver::new();
+    /// solver.assert(x.gt(1));
+    /// solver.assert(x.lt(3));
+    ///
+    /// let benchmark = solver.to_smtlib2();
+    /// assert!(benchmark.contains("(declare-fun x () Int)"));
+    /// assert!(benchmark.ends_with("(check-sat)\n"));
+    ///
+    /// let copy = Solver::new();
+    /// copy.from_string(benchmark).unwrap();
+    /// assert_eq!(copy.get_assertions(), solver.get_assertions());
+    /// ```
+    pub fn to_smtlib2(&self) -> String {
+        self.to_smt2()
+    }
+
+    /// Serialize the current assertions as an SMT-LIB2 benchmark of
+    /// declarations, assertions and a `(check-sat)` command, binding with
+    /// `let` every subterm of at least `min_size` nodes which occurs more
+    /// than once in an assertion.
+    ///
+    /// Printed without sharing, a term built by repeated squaring doubles in
+    /// size at each step, so a small threshold keeps benchmarks of highly
+    /// shared terms linear in the size of their DAG.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::Solver;
+    /// # use z3::ast::Int;
+    /// let x = Int::new_const("x");
+    /// let mut term = x.clone();
+    /// for i in 0..20 {
+    ///     term = &term * &term + i;
+    /// }
+    /// let solver = Solver::new();
+    /// solver.assert(term.gt(3));
+    ///
+    /// let benchmark = solver.to_smtlib2_with_lets(2);
+    /// assert!(benchmark.len() < 2000);
+    ///
+    /// let copy = Solver::new();
+    /// copy.from_string(benchmark).unwrap();
+    /// assert_eq!(copy.get_assertions(), solver.get_assertions());
+    /// ```
+    pub fn to_smtlib2_with_lets(&self, min_size: usize) -> String {
+        let text = self.to_string();
+        let declarations = match text.find("\n(assert") {
+            Some(end) => &text[..=end],
+            None if text.starts_with("(assert") => "",
+            None => &text,
+        };
+        let mut prefix = String::from("?x");
+        while declarations.contains(&prefix) {
+            prefix.insert(0, '?');
+        }
+        let mut benchmark = declarations.to_owned();
+        for assertion in self.get_assertions() {
+            benchmark.push_str(&crate::let_binding::assert_with_lets(
+                &assertion, min_size, &prefix,
+            ));
+        }
+        benchmark.push_str("(check-sat)\n");
+        benchmark
+    }
+
+    /// Export the assertions of the solver in the DIMACS format, so that
+    /// Boolean and bit-vector problems can be handed to external SAT solvers
+    /// or proof checkers.
+    ///
+    /// The assertions are copied into a [`Goal`] and put in conjunctive
+    /// normal form by the `simplify`, `bit-blast` and `tseitin-cnf` tactics;
+    /// see [`Goal::to_dimacs()`] for the numbering of the variables, starting
+    /// with the atoms of `first`. Unsatisfiable assertions may simplify to
+    /// the empty clause.
+    ///
+    /// Returns an error if a tactic fails, the preprocessing splits the
+    /// assertions into several subgoals, or a term of `first` is not an
+    /// atom.
+    ///
+    /// # Example
+    /// ```
+    /// # use z3::Solver;
+    /// # use z3::ast::{Ast, Bool, BV};
+    /// let x = BV::new_const("x", 4);
+    /// let p = Bool::new_const("p");
+    /// let solver = Solver::new();
+    /// solver.assert(&p.iff(x.bvugt(BV::from_u64(9, 4))));
+    /// solver.assert(&x.bvurem(BV::from_u64(3, 4)).eq(BV::from_u64(0, 4)));
+    ///
+    /// let dimacs = solver.to_dimacs(&[&p]).unwrap();
+    /// assert_eq!(dimacs.literal(&p), Some(1));
+    /// assert!(dimacs.to_string().starts_with("p cnf "));
+    /// ```
+    pub fn to_dimacs(&self, first: &[&Bool]) -> Result<Dimacs, String> {
+        let goal = Goal::new(false, false, false);
+        for assertion in self.get_assertions() {
+            goal.assert(&assertion);
+        }
+        let mut subgoals = Tactic::new("simplify")
+            .and_then(&Tactic::new("bit-blast"))
+            .and_then(&Tactic::new("tseitin-cnf"))
+            .apply(&goal, None)?
+            .list_subgoals();
+        match (subgoals.next(), subgoals.next()) {
+            (Some(cnf), None) => cnf.to_dimacs(first),
+            _ => Err("the assertions do not reduce to a single goal".to_owned()),
+        }
+    }
+
     /// Iterates over models for the given [`Solvable`] from the current state of a [`Solver`].
     ///
     /// The iterator terminates if the [`Solver`] returns `UNSAT` or `UNKNOWN`, as well as if model
@@ -522,6 +1720,77 @@ impl Solver {
         .fuse()
     }
 
+    /// Iterates over the models of the solver which differ in the values of
+    /// `terms` (All-SAT).
+    ///
+    /// After each model, a clause blocking the values of `terms` in it is
+    /// asserted, so every model yields a new combination of values. Values
+    /// are taken with model completion. Arrays whose value is the graph of
+    /// an auxiliary function of the model are blocked by their contents, as
+    /// the function is not known to the solver. With no `terms`, only one
+    /// model is produced.
+    ///
+    /// Like [`Solver::solutions()`], this iterates over a clone of the
+    /// solver, and stops at the first `Unsat` or `Unknown` result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::Solver;
+    /// # use z3::ast::{Bool, Int};
+    /// let solver = Solver::new();
+    /// let (x, flag) = (Int::new_const("x"), Bool::new_const("flag"));
+    /// solver.assert(x.ge(0));
+    /// solver.assert(x.lt(3));
+    ///
+    /// let models: Vec<_> = solver.models(&[&x]).collect();
+    /// assert_eq!(models.len(), 3);
+    /// assert_eq!(solver.models(&[&x, &flag]).count(), 6);
+    /// ```
+    pub fn models(&self, terms: &[&dyn Ast]) -> impl FusedIterator<Item = Model> + use<> {
+        self.models_with_functions(terms, &[])
+    }
+
+    /// Iterates over the models of the solver which differ in the values of
+    /// `terms` or in the interpretations of `functions`, like
+    /// [`Solver::models()`].
+    ///
+    /// A function is blocked by its whole graph, given by its
+    /// [array](FuncDecl::as_array_term), rather than by its values on
+    /// particular arguments.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{FuncDecl, Solver, Sort};
+    /// # use z3::ast::Bool;
+    /// let f = FuncDecl::new("f", &[&Sort::bool()], &Sort::bool());
+    /// let solver = Solver::new();
+    /// solver.assert(f.apply(&[&Bool::from_bool(true)]).as_bool().unwrap());
+    ///
+    /// // f(false) is free: f is the identity or the constant true.
+    /// assert_eq!(solver.models_with_functions(&[], &[&f]).count(), 2);
+    /// ```
+    pub fn models_with_functions(
+        &self,
+        terms: &[&dyn Ast],
+        functions: &[&FuncDecl],
+    ) -> impl FusedIterator<Item = Model> + use<> {
+        let functions = functions.iter().map(|f| match f.as_array_term() {
+            Some(graph) => Dynamic::from_ast(&graph),
+            None => f.apply(&[]),
+        });
+        ModelIterator {
+            solver: self.clone(),
+            terms: terms
+                .iter()
+                .map(|t| Dynamic::from_ast(*t))
+                .chain(functions)
+                .collect(),
+            done: false,
+        }
+    }
+
     /// Consume the current [`Solver`] and iterate over solutions to the given [`Solvable`].
     ///
     /// # Example
@@ -593,6 +1862,123 @@ impl Solver {
     }
 }
 
+impl SatResult {
+    /// Combine the verdicts of independent subproblems into the verdict of
+    /// their conjunction.
+    ///
+    /// The result is `Unsat` if any verdict is `Unsat`, otherwise `Unknown`
+    /// if any verdict is `Unknown`, and `Sat` if all verdicts are `Sat`
+    /// (including when there are none). Stops consuming `results` at the
+    /// first `Unsat`.
+    ///
+    /// ```
+    /// # use z3::SatResult;
+    /// use SatResult::*;
+    /// assert_eq!(SatResult::combine([Sat, Unknown, Sat]), Unknown);
+    /// assert_eq!(SatResult::combine([Sat, Unknown, Unsat]), Unsat);
+    /// assert_eq!(SatResult::combine([]), Sat);
+    /// ```
+    pub fn combine(results: impl IntoIterator<Item = SatResult>) -> SatResult {
+        let mut combined = SatResult::Sat;
+        for result in results {
+            match result {
+                SatResult::Unsat => return SatResult::Unsat,
+                SatResult::Unknown => combined = SatResult::Unknown,
+                SatResult::Sat => {}
+            }
+        }
+        combined
+    }
+}
+
+/// Prints the result as in SMT-LIB: `sat`, `unsat` or `unknown`.
+impl fmt::Display for SatResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.write_str(match self {
+            SatResult::Sat => "sat",
+            SatResult::Unsat => "unsat",
+            SatResult::Unknown => "unknown",
+        })
+    }
+}
+
+/// The verdict on one obligation of [`Solver::check_obligations()`].
+#[derive(Debug)]
+pub enum Verdict {
+    /// The obligation holds in every model of the assertions.
+    Valid,
+    /// The obligation is false in this model of the assertions.
+    Invalid(Model),
+    /// The solver could not decide the obligation, because it gave up or
+    /// its model does not evaluate the obligation.
+    Unknown,
+}
+
+/// One attempt of [`Solver::check_deepening()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeepeningStep {
+    /// The instantiation limit of the attempt.
+    pub bound: u32,
+    pub result: SatResult,
+    /// Why the solver gave up, if the result is [`SatResult::Unknown`].
+    pub reason_unknown: Option<String>,
+    pub elapsed: Duration,
+}
+
+/// Identifies a constraint asserted with [`Solver::assert_and_track_auto()`].
+///
+/// Identifiers are numbered from 0 in assertion order, per solver, among
+/// the constraints tracked by [`Solver::assert_and_track_auto()`] and
+/// [`Solver::assert_named()`]. They are never given out again, so the
+/// identifier of a constraint removed by [`Solver::pop()`] or
+/// [`Solver::reset()`] no longer resolves, see
+/// [`Solver::tracked_constraint()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TrackId(usize);
+
+impl TrackId {
+    /// Returns the number of the identifier, which counts the constraints
+    /// tracked by its solver before this one, including removed ones.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// A constraint tracked with a fresh answer literal, see
+/// [`Solver::assert_and_track_auto()`] and [`Solver::assert_named()`].
+pub(crate) struct Tracked {
+    id: TrackId,
+    constraint: Bool,
+    literal: Bool,
+    name: Option<String>,
+    // The number of backtracking points when the constraint was asserted.
+    scope: u32,
+}
+
+#[cfg(feature = "z3_4_12_0")]
+pub(crate) struct OnClauseState {
+    ctx: Context,
+    callback: Box<dyn FnMut(Option<&Dynamic>, &[Bool])>,
+}
+
+#[cfg(feature = "z3_4_12_0")]
+unsafe extern "C" fn on_clause_trampoline(
+    state: *mut std::ffi::c_void,
+    proof_hint: Option<Z3_ast>,
+    literals: Z3_ast_vector,
+) {
+    let state = unsafe { &mut *(state as *mut OnClauseState) };
+    let ctx = &state.ctx;
+    let hint = proof_hint.map(|h| unsafe { Dynamic::wrap(ctx, h) });
+    let literals: Vec<Bool> = unsafe {
+        (0..Z3_ast_vector_size(ctx.z3_ctx.0, literals))
+            .filter_map(|i| Z3_ast_vector_get(ctx.z3_ctx.0, literals, i))
+            .map(|l| Bool::wrap(ctx, l))
+            .collect()
+    };
+    (state.callback)(hint.as_ref(), &literals);
+}
+
 impl Default for Solver {
     fn default() -> Self {
         Self::new()
@@ -622,6 +2008,75 @@ impl<T: Solvable> Iterator for SolverIterator<T> {
     }
 }
 
+struct ModelIterator {
+    solver: Solver,
+    terms: Vec<Dynamic>,
+    done: bool,
+}
+
+impl Iterator for ModelIterator {
+    type Item = Model;
+
+    fn next(&mut self) -> Option<Model> {
+        if self.done || self.solver.check() != SatResult::Sat {
+            self.done = true;
+            return None;
+        }
+        let model = self.solver.get_model();
+        let blocking: Option<Vec<Bool>> = model.as_ref().and_then(|model| {
+            self.terms
+                .iter()
+                .map(|t| {
+                    let value = model.eval(t, true)?;
+                    Some(t.eq(without_model_functions(model, value)).not())
+                })
+                .collect()
+        });
+        match blocking {
+            Some(blocking) if !blocking.is_empty() => self.solver.assert(Bool::or(&blocking)),
+            _ => self.done = true,
+        }
+        model
+    }
+}
+
+impl FusedIterator for ModelIterator {}
+
+/// Replace an array value given as the graph of a function of `model` by a
+/// lambda with the interpretation of the function.
+fn without_model_functions(model: &Model, value: Dynamic) -> Dynamic {
+    let ctx = value.get_ctx();
+    let f = unsafe {
+        if !Z3_is_as_array(ctx.z3_ctx.0, value.get_z3_ast()) {
+            return value;
+        }
+        match Z3_get_as_array_func_decl(ctx.z3_ctx.0, value.get_z3_ast()) {
+            Some(f) => FuncDecl::wrap(ctx, f),
+            None => return value,
+        }
+    };
+    let Some(interp) = model.get_func_interp(&f) else {
+        return value;
+    };
+    let vars: Vec<Dynamic> = (0..f.arity())
+        .map(|i| unsafe {
+            let domain = Z3_get_domain(ctx.z3_ctx.0, f.z3_func_decl, i as u32).unwrap();
+            Dynamic::fresh_const("x", &Sort::wrap(ctx, domain))
+        })
+        .collect();
+    let otherwise = without_model_functions(model, interp.get_else());
+    let body = interp.get_entries().iter().fold(otherwise, |body, entry| {
+        let args: Vec<Bool> = vars
+            .iter()
+            .zip(entry.get_args())
+            .map(|(var, arg)| var.eq(arg))
+            .collect();
+        Bool::and(&args).ite(&without_model_functions(model, entry.get_value()), &body)
+    });
+    let bounds: Vec<&dyn Ast> = vars.iter().map(|v| v as &dyn Ast).collect();
+    Dynamic::from_ast(&ast::lambda_const(&bounds, &body))
+}
+
 impl fmt::Display for Solver {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         let p = unsafe { Z3_solver_to_string(self.ctx.z3_ctx.0, self.z3_slv) };
@@ -655,14 +2110,69 @@ impl Clone for Solver {
     }
 }
 
+/// Translates the solver with its assertions, parameters and tracked
+/// constraints into another context. A user propagator is not carried
+/// over.
+///
+/// Combined with [`Synchronized`](crate::Synchronized), this moves a solver
+/// to another thread, e.g. to explore several cases in parallel:
+///
+/// ```
+/// # use z3::{PrepareSynchronized, SatResult, Solver};
+/// # use z3::ast::Int;
+/// let x = Int::new_const("x");
+/// let solver = Solver::new();
+/// solver.assert(x.gt(0));
+///
+/// let shared = solver.synchronized();
+/// let results: Vec<SatResult> = [5, -5]
+///     .map(|bound| {
+///         let shared = shared.clone();
+///         std::thread::spawn(move || {
+///             // `recover` translates into the context of this thread.
+///             let solver = shared.recover();
+///             solver.assert(Int::new_const("x").lt(bound));
+///             solver.check()
+///         })
+///     })
+///     .into_iter()
+///     .map(|handle| handle.join().unwrap())
+///     .collect();
+/// assert_eq!(results, [SatResult::Sat, SatResult::Unsat]);
+/// ```
 unsafe impl Translate for Solver {
     fn translate(&self, dest: &Context) -> Solver {
-        unsafe {
+        let solver = unsafe {
             Solver::wrap(
                 dest,
                 Z3_solver_translate(self.ctx.z3_ctx.0, self.z3_slv, dest.z3_ctx.0).unwrap(),
             )
-        }
+        };
+        // Z3 does not translate terms into their own context.
+        let translate = |term: &Bool| {
+            if self.ctx == *dest {
+                term.clone()
+            } else {
+                term.translate(dest)
+            }
+        };
+        *solver.tracked.borrow_mut() = self
+            .tracked
+            .borrow()
+            .iter()
+            .map(|t| Tracked {
+                id: t.id,
+                constraint: translate(&t.constraint),
+                literal: translate(&t.literal),
+                name: t.name.clone(),
+                // The copy has all assertions at its base level.
+                scope: 0,
+            })
+            .collect();
+        solver.next_track_id.set(self.next_track_id.get());
+        solver.logic.set(self.logic.get());
+        *solver.params.borrow_mut() = self.params.borrow().clone();
+        solver
     }
 }
 
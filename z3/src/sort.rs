@@ -4,6 +4,7 @@ use std::fmt;
 use std::ptr::NonNull;
 use z3_sys::*;
 
+use crate::ast::{Ast, Dynamic};
 use crate::{Context, FuncDecl, Sort, SortDiffers, Symbol};
 
 impl Sort {
@@ -24,12 +25,14 @@ impl Sort {
     pub fn uninterpreted(name: Symbol) -> Sort {
         let ctx = &Context::thread_local();
 
-        unsafe {
+        let sort = unsafe {
             Self::wrap(
                 ctx,
                 Z3_mk_uninterpreted_sort(ctx.z3_ctx.0, name.as_z3_symbol()).unwrap(),
             )
-        }
+        };
+        crate::declarations::record_sort(ctx, sort.z3_sort);
+        sort
     }
 
     pub fn bool() -> Sort {
@@ -92,6 +95,58 @@ impl Sort {
         }
     }
 
+    /// Create a finite domain sort with `size` elements, named `name`.
+    ///
+    /// Finite domain sorts are mainly useful for relations solved by the
+    /// Datalog engine of [`Fixedpoint`](crate::Fixedpoint).
+    ///
+    /// # Examples
+    /// ```
+    /// # use z3::{Sort, SortKind};
+    /// let s = Sort::finite_domain("S", 10);
+    /// assert_eq!(s.kind(), SortKind::FiniteDomain);
+    /// assert_eq!(s.finite_domain_size(), Some(10));
+    /// assert!(s.finite_domain_value(9).is_some());
+    /// assert!(s.finite_domain_value(10).is_none());
+    /// assert_eq!(Sort::int().finite_domain_size(), None);
+    /// ```
+    pub fn finite_domain<S: Into<Symbol>>(name: S, size: u64) -> Sort {
+        let ctx = &Context::thread_local();
+        let sort = unsafe {
+            Self::wrap(
+                ctx,
+                Z3_mk_finite_domain_sort(ctx.z3_ctx.0, name.into().as_z3_symbol(), size).unwrap(),
+            )
+        };
+        crate::declarations::record_sort(ctx, sort.z3_sort);
+        sort
+    }
+
+    /// Returns `Some(n)` where `n` is the number of elements if the sort is a
+    /// finite domain sort and `None` otherwise.
+    pub fn finite_domain_size(&self) -> Option<u64> {
+        if self.kind() != SortKind::FiniteDomain {
+            return None;
+        }
+        let mut size = 0;
+        unsafe { Z3_get_finite_domain_sort_size(self.ctx.z3_ctx.0, self.z3_sort, &mut size) }
+            .then_some(size)
+    }
+
+    /// Returns the element with index `value` of a finite domain sort.
+    ///
+    /// Returns `None` if the sort is not a finite domain sort or `value` is
+    /// not smaller than its size.
+    pub fn finite_domain_value(&self, value: u64) -> Option<Dynamic> {
+        if value >= self.finite_domain_size()? {
+            return None;
+        }
+        unsafe {
+            let ast = Z3_mk_unsigned_int64(self.ctx.z3_ctx.0, value, self.z3_sort)?;
+            Some(Dynamic::wrap(&self.ctx, ast))
+        }
+    }
+
     pub fn array(domain: &Sort, range: &Sort) -> Sort {
         let ctx = &Context::thread_local();
 
@@ -170,6 +225,7 @@ impl Sort {
                 .unwrap(),
             )
         };
+        crate::declarations::record_sort(ctx, sort.z3_sort);
 
         // increase ref counts
         for i in &enum_consts {
@@ -346,7 +402,21 @@ impl Drop for Sort {
 
 impl SortDiffers {
     pub fn new(left: Sort, right: Sort) -> Self {
-        Self { left, right }
+        Self {
+            left,
+            right,
+            operation: None,
+        }
+    }
+
+    /// Create a `SortDiffers` for the operation `operation` whose arguments
+    /// were expected to share a sort.
+    pub fn in_operation(operation: &'static str, left: Sort, right: Sort) -> Self {
+        Self {
+            left,
+            right,
+            operation: Some(operation),
+        }
     }
 
     pub fn left(&self) -> &Sort {
@@ -356,14 +426,28 @@ impl SortDiffers {
     pub fn right(&self) -> &Sort {
         &self.right
     }
+
+    /// The name of the operation whose arguments did not match, if known.
+    pub fn operation(&self) -> Option<&'static str> {
+        self.operation
+    }
 }
 
 impl fmt::Display for SortDiffers {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(
-            f,
-            "Can not compare nodes, Sort does not match.  Nodes contain types {} and {}",
-            self.left, self.right
-        )
+        match self.operation {
+            Some(op) => write!(
+                f,
+                "Can not apply `{}`, Sort does not match.  Arguments contain types {} and {}",
+                op, self.left, self.right
+            ),
+            None => write!(
+                f,
+                "Can not compare nodes, Sort does not match.  Nodes contain types {} and {}",
+                self.left, self.right
+            ),
+        }
     }
 }
+
+impl std::error::Error for SortDiffers {}
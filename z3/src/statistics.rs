@@ -28,6 +28,37 @@ pub struct StatisticsEntry {
     pub value: StatisticsValue,
 }
 
+impl StatisticsEntry {
+    /// Returns the key as a column name: lowercase, with runs of characters
+    /// other than letters and digits replaced by `_`, so `rlimit count`
+    /// becomes `rlimit_count`.
+    pub fn column(&self) -> String {
+        column_name(&self.key)
+    }
+}
+
+impl fmt::Display for StatisticsValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            StatisticsValue::UInt(v) => write!(f, "{v}"),
+            StatisticsValue::Double(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+fn column_name(key: &str) -> String {
+    let mut column = String::with_capacity(key.len());
+    for c in key.chars() {
+        if c.is_ascii_alphanumeric() {
+            column.push(c.to_ascii_lowercase());
+        } else if !column.is_empty() && !column.ends_with('_') {
+            column.push('_');
+        }
+    }
+    column.truncate(column.trim_end_matches('_').len());
+    column
+}
+
 impl Statistics {
     /// Wrap a raw [`Z3_stats`], managing refcounts.
     pub(crate) unsafe fn wrap(ctx: &Context, z3_stats: Z3_stats) -> Statistics {
@@ -77,6 +108,20 @@ impl Statistics {
         }
     }
 
+    /// Get the resource units consumed so far, the `rlimit count` entry.
+    ///
+    /// The count is shared by all solvers of a [`Context`] and only grows,
+    /// which makes it a deterministic, machine independent measure of work.
+    /// The difference of the counts before and after a check is the
+    /// consumption of the check, see
+    /// [`Solver::check_with_rlimit()`](crate::Solver::check_with_rlimit).
+    pub fn rlimit_count(&self) -> Option<u64> {
+        match self.value("rlimit count")? {
+            StatisticsValue::UInt(count) => Some(count.into()),
+            StatisticsValue::Double(count) => Some(count as u64),
+        }
+    }
+
     /// Iterate over all of the entries in this set of statistics.
     pub fn entries(&self) -> impl Iterator<Item = StatisticsEntry> + '_ {
         let p = unsafe { Z3_stats_size(self.ctx.z3_ctx.0, self.z3_stats) };
@@ -88,6 +133,69 @@ impl Statistics {
             }
         })
     }
+
+    /// Serialize the statistics as a flat JSON object, mapping the column
+    /// name of each entry (see [`StatisticsEntry::column()`]) to its value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let solver = Solver::new();
+    /// solver.assert(Int::new_const("x").gt(1));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let json = solver.get_statistics().to_json();
+    /// assert!(json.starts_with('{') && json.contains("\"rlimit_count\":"));
+    /// ```
+    pub fn to_json(&self) -> String {
+        let fields: Vec<String> = self
+            .entries()
+            .map(|e| match e.value {
+                StatisticsValue::Double(v) if !v.is_finite() => format!("\"{}\":null", e.column()),
+                _ => format!("\"{}\":{}", e.column(), e.value),
+            })
+            .collect();
+        format!("{{{}}}", fields.join(","))
+    }
+
+    /// Return the values of the given `columns` as a CSV row, without a
+    /// trailing newline. Columns missing from these statistics are left
+    /// empty.
+    ///
+    /// Which entries Z3 reports depends on the problem, so the columns are
+    /// chosen by the caller to get the same layout for every run, and
+    /// `columns.join(",")` is the header.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{SatResult, Solver};
+    /// # use z3::ast::Int;
+    /// let solver = Solver::new();
+    /// solver.assert(Int::new_const("x").gt(1));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let row = solver.get_statistics().to_csv_row(&["rlimit_count", "no_such_entry"]);
+    /// let cells: Vec<&str> = row.split(',').collect();
+    /// assert!(cells[0].parse::<u64>().is_ok());
+    /// assert_eq!(cells[1], "");
+    /// ```
+    pub fn to_csv_row(&self, columns: &[&str]) -> String {
+        let entries: Vec<StatisticsEntry> = self.entries().collect();
+        columns
+            .iter()
+            .map(|column| {
+                entries
+                    .iter()
+                    .find(|e| e.column() == *column)
+                    .map(|e| e.value.to_string())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
 }
 
 impl Clone for Statistics {
@@ -115,6 +223,24 @@ impl fmt::Debug for Statistics {
     }
 }
 
+/// Serializes as a map from the column name of each entry (see
+/// [`StatisticsEntry::column()`]) to its value.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Statistics {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        for e in self.entries() {
+            match e.value {
+                StatisticsValue::UInt(v) => map.serialize_entry(&e.column(), &v)?,
+                StatisticsValue::Double(v) => map.serialize_entry(&e.column(), &v)?,
+            }
+        }
+        map.end()
+    }
+}
+
 impl Drop for Statistics {
     fn drop(&mut self) {
         unsafe {
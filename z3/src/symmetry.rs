@@ -0,0 +1,76 @@
+//! Symmetry-breaking constraints.
+//!
+//! When entities of a model are interchangeable, such as identical machines
+//! in a scheduling problem, every solution has one symmetric copy per
+//! permutation of the entities, and the solver may waste its time refuting
+//! all of them. Ordering the entities lexicographically by their variables
+//! keeps one representative of each class of solutions.
+
+use z3_sys::SortKind;
+
+use crate::ast::{Ast, Bool, Dynamic};
+
+/// Returns the constraint that `a` is lexicographically smaller than or
+/// equal to `b`.
+///
+/// Booleans are ordered with `false` before `true`, bit-vectors as unsigned
+/// numbers. The encoding is linear in the length of the sequences.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` have different lengths, or if they contain values
+/// which are not Booleans, integers, reals or bit-vectors.
+pub fn lex_le<T: Ast>(a: &[T], b: &[T]) -> Bool {
+    assert_eq!(a.len(), b.len(), "sequences of different lengths");
+    a.iter()
+        .zip(b)
+        .rev()
+        .fold(Bool::from_bool(true), |rest, (a, b)| {
+            let (a, b) = (Dynamic::from_ast(a), Dynamic::from_ast(b));
+            Bool::or(&[lt(&a, &b), Bool::and(&[a.eq(&b), rest])])
+        })
+}
+
+/// Returns lexicographic symmetry-breaking constraints for interchangeable
+/// `rows`: each row is lexicographically smaller than or equal to the next.
+///
+/// Each row holds the variables of one entity, in the same order for all
+/// entities. Interchangeable single constants are rows of length one.
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`lex_le()`].
+///
+/// # Example
+///
+/// ```
+/// # use z3::{SatResult, Solver};
+/// # use z3::ast::Int;
+/// # use z3::symmetry::lex_leader;
+/// // Three identical machines, each with the start times of two jobs.
+/// let machines: Vec<Vec<Int>> = (0..3)
+///     .map(|m| (0..2).map(|j| Int::new_const(format!("start_{m}_{j}"))).collect())
+///     .collect();
+/// let solver = Solver::new();
+/// for constraint in lex_leader(&machines) {
+///     solver.assert(&constraint);
+/// }
+/// solver.assert(machines[0][0].eq(5));
+/// solver.assert(machines[1][0].eq(3));
+/// assert_eq!(solver.check(), SatResult::Unsat);
+/// ```
+pub fn lex_leader<T: Ast>(rows: &[impl AsRef<[T]>]) -> Vec<Bool> {
+    rows.windows(2)
+        .map(|pair| lex_le(pair[0].as_ref(), pair[1].as_ref()))
+        .collect()
+}
+
+fn lt(a: &Dynamic, b: &Dynamic) -> Bool {
+    match a.sort_kind() {
+        SortKind::Bool => Bool::and(&[a.as_bool().unwrap().not(), b.as_bool().unwrap()]),
+        SortKind::Int => a.as_int().unwrap().lt(b.as_int().unwrap()),
+        SortKind::Real => a.as_real().unwrap().lt(b.as_real().unwrap()),
+        SortKind::BV => a.as_bv().unwrap().bvult(b.as_bv().unwrap()),
+        _ => panic!("cannot order values of sort {}", a.get_sort()),
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+
+use z3_sys::*;
+
+use crate::ast::{Ast, Bool, Dynamic};
+use crate::{FormulaTemplate, FuncDecl, SatResult, Solver, Sort};
+
+/// A grammar of terms built from leaves (variables and constants) and
+/// operators, for syntax-guided enumeration.
+///
+/// [`Grammar::synthesize()`] enumerates the terms of the grammar by
+/// increasing size, and returns the first one satisfying a specification.
+/// Terms that simplify to a term already enumerated are skipped, so each
+/// size only contains new behaviors.
+///
+/// # Example
+///
+/// ```
+/// # use z3::Grammar;
+/// # use z3::ast::{Ast, Int};
+/// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+/// let mut grammar = Grammar::new();
+/// grammar
+///     .leaf(&x)
+///     .leaf(&y)
+///     .leaf(&Int::from_i64(1))
+///     .operator(&(&x + &y).decl())
+///     .operator(&(&x * &y).decl());
+///
+/// // Find f(x, y) with f(x, y) = 2 * x + 1 for all x and y.
+/// let f = Int::new_const("f");
+/// let spec = f.eq(Int::from_i64(2) * &x + 1);
+/// let f = grammar.synthesize(&spec, &f, 6).unwrap();
+/// assert_eq!(f.to_string(), "(+ x x 1)");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Grammar {
+    leaves: Vec<Dynamic>,
+    operators: Vec<FuncDecl>,
+}
+
+impl Grammar {
+    /// Create a grammar without leaves or operators.
+    pub fn new() -> Grammar {
+        Grammar::default()
+    }
+
+    /// Add a leaf, a term of size 1, usually a variable or a constant.
+    pub fn leaf(&mut self, term: &impl Ast) -> &mut Grammar {
+        self.leaves.push(Dynamic::from_ast(term));
+        self
+    }
+
+    /// Add an operator, whose applications to terms of its domain sorts
+    /// are terms of its range sort.
+    ///
+    /// The declarations of built-in operators can be taken from a sample
+    /// term with [`Ast::decl()`].
+    pub fn operator(&mut self, op: &FuncDecl) -> &mut Grammar {
+        self.operators.push(op.clone());
+        self
+    }
+
+    /// Returns the terms of sort `sort` and of size 1 to `max_size`, by
+    /// increasing size. The size of a term is its number of leaves and
+    /// operators.
+    pub fn terms(&self, sort: &Sort, max_size: usize) -> Vec<Dynamic> {
+        let mut terms = vec![];
+        self.enumerate(max_size, |term| {
+            if term.get_sort() == *sort {
+                terms.push(term.clone());
+            }
+            false
+        });
+        terms
+    }
+
+    /// Returns the smallest term of size at most `max_size` which, put in
+    /// place of the constant `hole`, makes `spec` valid for all values of
+    /// its other constants.
+    ///
+    /// Candidates are only enumerated for the sort of `hole`. Returns `None`
+    /// if no candidate up to `max_size` is proven correct.
+    pub fn synthesize(&self, spec: &Bool, hole: &impl Ast, max_size: usize) -> Option<Dynamic> {
+        let sort = hole.get_sort();
+        let spec = FormulaTemplate::new(spec, &[hole]);
+        let solver = Solver::new();
+        let mut found = None;
+        self.enumerate(max_size, |term| {
+            if term.get_sort() != sort {
+                return false;
+            }
+            solver.push();
+            solver.assert(spec.apply(&[term]).not());
+            let correct = solver.check() == SatResult::Unsat;
+            solver.pop(1);
+            if correct {
+                found = Some(term.clone());
+            }
+            correct
+        });
+        found
+    }
+
+    /// Enumerate the terms of size 1 to `max_size` by increasing size,
+    /// calling `visit` on each until it returns `true`.
+    fn enumerate(&self, max_size: usize, mut visit: impl FnMut(&Dynamic) -> bool) {
+        // `by_size[n - 1]` lists the terms of size `n`.
+        let mut by_size: Vec<Vec<Dynamic>> = vec![];
+        let mut seen = HashSet::new();
+        for size in 1..=max_size {
+            let candidates = if size == 1 {
+                self.leaves.clone()
+            } else {
+                self.operators
+                    .iter()
+                    .flat_map(|op| applications(op, size - 1, &by_size))
+                    .collect()
+            };
+            let mut terms = vec![];
+            for term in candidates {
+                if !seen.insert(term.simplify()) {
+                    continue;
+                }
+                if visit(&term) {
+                    return;
+                }
+                terms.push(term);
+            }
+            by_size.push(terms);
+        }
+    }
+}
+
+/// The applications of `op` to argument terms of `by_size` whose sizes sum
+/// to `size`.
+fn applications(op: &FuncDecl, size: usize, by_size: &[Vec<Dynamic>]) -> Vec<Dynamic> {
+    let ctx = &op.ctx;
+    let domain: Vec<Sort> = (0..op.arity())
+        .map(|i| unsafe {
+            Sort::wrap(
+                ctx,
+                Z3_get_domain(ctx.z3_ctx.0, op.z3_func_decl, i as u32).unwrap(),
+            )
+        })
+        .collect();
+    if domain.is_empty() || size < domain.len() {
+        return vec![];
+    }
+    let mut apps = vec![];
+    let mut args = vec![];
+    extend_args(op, &domain, size, by_size, &mut args, &mut apps);
+    apps
+}
+
+/// Complete the arguments `args` of `op` with terms whose sizes sum to
+/// `size`, collecting the applications into `apps`.
+fn extend_args(
+    op: &FuncDecl,
+    domain: &[Sort],
+    size: usize,
+    by_size: &[Vec<Dynamic>],
+    args: &mut Vec<Dynamic>,
+    apps: &mut Vec<Dynamic>,
+) {
+    let i = args.len();
+    if i == domain.len() {
+        if size == 0 {
+            let args: Vec<&dyn Ast> = args.iter().map(|a| a as &dyn Ast).collect();
+            apps.push(op.apply(&args));
+        }
+        return;
+    }
+    // Leave at least size 1 for each remaining argument.
+    let remaining = domain.len() - i - 1;
+    for arg_size in 1..=size.saturating_sub(remaining) {
+        for arg in &by_size[arg_size - 1] {
+            if arg.get_sort() == domain[i] {
+                args.push(arg.clone());
+                extend_args(op, domain, size - arg_size, by_size, args, apps);
+                args.pop();
+            }
+        }
+    }
+}
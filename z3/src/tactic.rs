@@ -7,7 +7,7 @@ use std::str::Utf8Error;
 use std::time::Duration;
 use z3_sys::*;
 
-use crate::{ApplyResult, Context, Goal, Params, Probe, Solver, Tactic};
+use crate::{ApplyResult, Context, Goal, Model, ParamDescrs, Params, Probe, Solver, Tactic};
 
 impl ApplyResult {
     unsafe fn wrap(ctx: &Context, z3_apply_result: Z3_apply_result) -> ApplyResult {
@@ -30,6 +30,68 @@ impl ApplyResult {
             )
         })
     }
+
+    /// Return the number of subgoals.
+    pub fn num_subgoals(&self) -> usize {
+        unsafe {
+            Z3_apply_result_get_num_subgoals(self.ctx.z3_ctx.0, self.z3_apply_result) as usize
+        }
+    }
+
+    /// Return the `i`-th subgoal, or `None` if there are not that many.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{Goal, Tactic};
+    /// # use z3::ast::{Bool, Int};
+    /// let x = Int::new_const("x");
+    /// let goal = Goal::new(false, false, false);
+    /// goal.assert(&Bool::or(&[x.lt(0), x.gt(10)]));
+    ///
+    /// let result = Tactic::new("split-clause").apply(&goal, None).unwrap();
+    /// assert_eq!(result.num_subgoals(), 2);
+    /// assert_eq!(result.subgoal(1).unwrap().get_formulas(), [x.gt(10)]);
+    /// assert!(result.subgoal(2).is_none());
+    /// ```
+    pub fn subgoal(&self, i: usize) -> Option<Goal> {
+        if i >= self.num_subgoals() {
+            return None;
+        }
+        unsafe {
+            let goal =
+                Z3_apply_result_get_subgoal(self.ctx.z3_ctx.0, self.z3_apply_result, i as c_uint)?;
+            Some(Goal::wrap(&self.ctx, goal))
+        }
+    }
+
+    /// Convert a model of the `i`-th subgoal into a model of the goal the
+    /// tactic was applied to, or return `None` if there are not that many
+    /// subgoals or the model cannot be converted.
+    ///
+    /// See [`Goal::convert_model()`].
+    pub fn convert_model(&self, i: usize, model: &Model) -> Option<Model> {
+        self.subgoal(i)?.convert_model(model)
+    }
+}
+
+impl Clone for ApplyResult {
+    fn clone(&self) -> Self {
+        unsafe { Self::wrap(&self.ctx, self.z3_apply_result) }
+    }
+}
+
+impl fmt::Display for ApplyResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_apply_result_to_string(self.ctx.z3_ctx.0, self.z3_apply_result) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{s}"),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
 }
 
 impl Drop for ApplyResult {
@@ -61,6 +123,28 @@ impl Tactic {
             .collect()
     }
 
+    /// Return a string containing a description of the tactic with
+    /// the given `name`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::Tactic;
+    /// for name in Tactic::list_all().into_iter().filter_map(|r| r.ok()) {
+    ///     assert!(Tactic::describe(&name).is_ok());
+    /// }
+    /// assert!(Tactic::describe("bit-blast").unwrap().contains("bit-vector"));
+    /// ```
+    pub fn describe(name: &str) -> Result<String, Utf8Error> {
+        let ctx = &Context::thread_local();
+        let tactic_name = CString::new(name).unwrap();
+        unsafe {
+            CStr::from_ptr(Z3_tactic_get_descr(ctx.z3_ctx.0, tactic_name.as_ptr()))
+                .to_str()
+                .map(|s| s.to_string())
+        }
+    }
+
     unsafe fn wrap(ctx: &Context, z3_tactic: Z3_tactic) -> Tactic {
         unsafe {
             Z3_tactic_inc_ref(ctx.z3_ctx.0, z3_tactic);
@@ -83,6 +167,7 @@ impl Tactic {
     /// # See also
     ///
     /// - [`Tactic::list_all()`]
+    /// - [`Tactic::describe()`]
     pub fn new(name: &str) -> Tactic {
         let ctx = &Context::thread_local();
         let tactic_name = CString::new(name).unwrap();
@@ -154,6 +239,73 @@ impl Tactic {
         }
     }
 
+    /// Return a tactic that applies the current tactic to a given goal and
+    /// then `then_tactic` to every subgoal produced by it, processing the
+    /// subgoals in parallel.
+    pub fn par_and_then(&self, then_tactic: &Tactic) -> Tactic {
+        unsafe {
+            Self::wrap(
+                &self.ctx,
+                Z3_tactic_par_and_then(self.ctx.z3_ctx.0, self.z3_tactic, then_tactic.z3_tactic)
+                    .unwrap(),
+            )
+        }
+    }
+
+    /// Return a tactic that applies the current tactic using the given
+    /// parameters, e.g. to configure a tactic inside a combinator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{Goal, Params, Tactic};
+    /// # use z3::ast::Int;
+    /// let x = Int::new_const("x");
+    /// let goal = Goal::new(false, false, false);
+    /// goal.assert(&(&x + 1).gt(&x * 2));
+    ///
+    /// let mut params = Params::new();
+    /// params.set_bool("arith_lhs", true);
+    /// let simplify = Tactic::new("simplify").using_params(&params);
+    /// let result = Tactic::create_skip().and_then(&simplify).apply(&goal, None).unwrap();
+    /// assert_eq!(result.subgoal(0).unwrap().get_formulas().len(), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `params` contains a parameter which the tactic does not
+    /// accept, or of the wrong kind. Use [`Tactic::try_using_params()`] to
+    /// handle this as an error instead.
+    pub fn using_params(&self, params: &Params) -> Tactic {
+        self.try_using_params(params)
+            .unwrap_or_else(|message| panic!("invalid parameters for the tactic: {message}"))
+    }
+
+    /// Like [`Tactic::using_params()`], but return the error message of
+    /// [`ParamDescrs::validate()`] if the tactic does not accept `params`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use z3::{Params, Tactic};
+    /// let mut params = Params::new();
+    /// params.set_bool("arith_lhs", true);
+    /// assert!(Tactic::new("simplify").try_using_params(&params).is_ok());
+    ///
+    /// params.set_bool("arith_lhss", true);
+    /// let error = Tactic::new("simplify").try_using_params(&params).unwrap_err();
+    /// assert!(error.contains("arith_lhss"));
+    /// ```
+    pub fn try_using_params(&self, params: &Params) -> Result<Tactic, String> {
+        self.get_param_descrs().validate(params)?;
+        unsafe {
+            let z3_tactic =
+                Z3_tactic_using_params(self.ctx.z3_ctx.0, self.z3_tactic, params.z3_params)
+                    .ok_or("invalid parameters for the tactic")?;
+            Ok(Self::wrap(&self.ctx, z3_tactic))
+        }
+    }
+
     /// Return a tactic that applies self to a given goal if the probe `p` evaluates to true,
     /// and `t` if `p` evaluates to false.
     pub fn probe_or_else(&self, p: &Probe, t: &Tactic) -> Tactic {
@@ -225,6 +377,23 @@ impl Tactic {
         }
     }
 
+    /// Return a string describing the parameters of the tactic, see
+    /// [`HelpEntry::parse()`](crate::HelpEntry::parse).
+    pub fn get_help(&self) -> String {
+        self.to_string()
+    }
+
+    /// Return the parameters accepted by the tactic, to check [`Params`]
+    /// with [`ParamDescrs::validate()`] before applying it.
+    pub fn get_param_descrs(&self) -> ParamDescrs {
+        unsafe {
+            ParamDescrs::wrap(
+                &self.ctx,
+                Z3_tactic_get_param_descrs(self.ctx.z3_ctx.0, self.z3_tactic).unwrap(),
+            )
+        }
+    }
+
     /// Create a new solver that is implemented using the given tactic.
     ///
     /// # Example
@@ -0,0 +1,183 @@
+use z3_sys::*;
+
+use crate::ast::{Ast, Bool, Dynamic};
+use crate::logic::walk;
+
+/// A formula with designated placeholder constants, which can be
+/// instantiated with concrete terms many times.
+///
+/// The placeholders are abstracted once when the template is created, so that
+/// each instantiation is a single pass over the formula that does not need to
+/// match the placeholders again.
+///
+/// # Example
+///
+/// ```
+/// # use z3::FormulaTemplate;
+/// # use z3::ast::{Ast, Int};
+/// let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+/// let axiom = FormulaTemplate::new(&(&x + &y).gt(&x), &[&x, &y]);
+///
+/// let a = Int::new_const("a");
+/// let one = Int::from_i64(1);
+/// assert_eq!(axiom.instantiate(&[(&x, &a), (&y, &one)]).to_string(), "(> (+ a 1) a)");
+/// assert_eq!(axiom.apply(&[&one, &a]).to_string(), "(> (+ 1 a) 1)");
+/// ```
+#[derive(Debug, Clone)]
+pub struct FormulaTemplate {
+    formula: Bool,
+    placeholders: Vec<Dynamic>,
+    /// `formula` with the i-th placeholder replaced by the free variable `i`,
+    /// if the formula has no binders of its own.
+    abstracted: Option<Bool>,
+}
+
+impl FormulaTemplate {
+    /// Create a template from `formula`, whose occurrences of the constants
+    /// `placeholders` are to be replaced on instantiation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a placeholder occurs twice.
+    pub fn new(formula: &Bool, placeholders: &[&dyn Ast]) -> FormulaTemplate {
+        let placeholders: Vec<Dynamic> =
+            placeholders.iter().map(|&p| Dynamic::from_ast(p)).collect();
+        for (i, p) in placeholders.iter().enumerate() {
+            assert!(
+                !placeholders[..i].contains(p),
+                "Placeholder {p} occurs twice"
+            );
+        }
+        FormulaTemplate {
+            abstracted: abstract_placeholders(formula, &placeholders),
+            formula: formula.clone(),
+            placeholders,
+        }
+    }
+
+    /// Returns the formula the template was created from.
+    pub fn formula(&self) -> &Bool {
+        &self.formula
+    }
+
+    /// Returns the placeholders, in order.
+    pub fn placeholders(&self) -> &[Dynamic] {
+        &self.placeholders
+    }
+
+    /// Replace the placeholders by terms, given in the order of
+    /// [`FormulaTemplate::placeholders()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of terms does not match the number of
+    /// placeholders, or if a term does not have the sort of its placeholder.
+    pub fn apply(&self, terms: &[&dyn Ast]) -> Bool {
+        assert_eq!(
+            terms.len(),
+            self.placeholders.len(),
+            "Template expects {} terms",
+            self.placeholders.len()
+        );
+        for (term, p) in terms.iter().zip(&self.placeholders) {
+            assert!(
+                term.get_sort() == p.get_sort(),
+                "Placeholder {p} of sort {} cannot be replaced by a term of sort {}",
+                p.get_sort(),
+                term.get_sort()
+            );
+        }
+        let ctx = self.formula.get_ctx();
+        let tos: Vec<Z3_ast> = terms.iter().map(|t| t.get_z3_ast()).collect();
+        unsafe {
+            match &self.abstracted {
+                Some(abstracted) => Bool::wrap(
+                    ctx,
+                    Z3_substitute_vars(
+                        ctx.z3_ctx.0,
+                        abstracted.get_z3_ast(),
+                        tos.len() as u32,
+                        tos.as_ptr(),
+                    )
+                    .unwrap(),
+                ),
+                None => {
+                    let froms: Vec<Z3_ast> =
+                        self.placeholders.iter().map(|p| p.get_z3_ast()).collect();
+                    Bool::wrap(
+                        ctx,
+                        Z3_substitute(
+                            ctx.z3_ctx.0,
+                            self.formula.get_z3_ast(),
+                            froms.len() as u32,
+                            froms.as_ptr(),
+                            tos.as_ptr(),
+                        )
+                        .unwrap(),
+                    )
+                }
+            }
+        }
+    }
+
+    /// Replace each placeholder `p` of the `(p, term)` pairs by `term`.
+    /// Placeholders without a pair are kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pair does not start with a placeholder, or if a term does
+    /// not have the sort of its placeholder.
+    pub fn instantiate(&self, bindings: &[(&dyn Ast, &dyn Ast)]) -> Bool {
+        let mut terms: Vec<&dyn Ast> = self.placeholders.iter().map(|p| p as &dyn Ast).collect();
+        for &(placeholder, term) in bindings {
+            let placeholder = Dynamic::from_ast(placeholder);
+            let i = self
+                .placeholders
+                .iter()
+                .position(|p| *p == placeholder)
+                .unwrap_or_else(|| panic!("{placeholder} is not a placeholder of the template"));
+            terms[i] = term;
+        }
+        self.apply(&terms)
+    }
+}
+
+/// Replace the i-th placeholder in `formula` by the free variable `i`, unless
+/// the formula contains binders or free variables itself.
+fn abstract_placeholders(formula: &Bool, placeholders: &[Dynamic]) -> Option<Bool> {
+    let ctx = formula.get_ctx();
+    let z3_ctx = ctx.z3_ctx.0;
+    let mut closed = true;
+    walk(ctx, formula.get_z3_ast(), |_, kind| {
+        closed = kind != AstKind::Quantifier && kind != AstKind::Var;
+        closed
+    });
+    if !closed {
+        return None;
+    }
+    let vars: Vec<Dynamic> = placeholders
+        .iter()
+        .enumerate()
+        .map(|(i, p)| unsafe {
+            Dynamic::wrap(
+                ctx,
+                Z3_mk_bound(z3_ctx, i as u32, p.get_sort().z3_sort).unwrap(),
+            )
+        })
+        .collect();
+    let froms: Vec<Z3_ast> = placeholders.iter().map(|p| p.get_z3_ast()).collect();
+    let tos: Vec<Z3_ast> = vars.iter().map(|v| v.get_z3_ast()).collect();
+    unsafe {
+        Some(Bool::wrap(
+            ctx,
+            Z3_substitute(
+                z3_ctx,
+                formula.get_z3_ast(),
+                froms.len() as u32,
+                froms.as_ptr(),
+                tos.as_ptr(),
+            )
+            .unwrap(),
+        ))
+    }
+}
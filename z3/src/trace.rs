@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Model;
+use crate::ast::Dynamic;
+
+/// A step-indexed counterexample trace extracted from a [`Model`].
+///
+/// Bounded model checking unrolls a transition system into one copy of each
+/// state variable per step. Given the per-step mapping from state variable
+/// names to their unrolled constants, [`Trace::from_model()`] evaluates every
+/// copy in the model and collects the values into a table which can be
+/// inspected, printed, or (with the `serde` feature) serialized.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{SatResult, Solver, Trace};
+/// # use z3::ast::Int;
+/// let solver = Solver::new();
+/// let x: Vec<Int> = (0..3).map(|i| Int::new_const(format!("x_{i}"))).collect();
+/// solver.assert(x[0].eq(0));
+/// solver.assert(x[1].eq(&x[0] + 1));
+/// solver.assert(x[2].eq(&x[1] + 1));
+/// assert_eq!(solver.check(), SatResult::Sat);
+///
+/// let model = solver.get_model().unwrap();
+/// let trace = Trace::from_model(&model, x.iter().map(|x_i| [("x", x_i)]));
+/// assert_eq!(trace.len(), 3);
+/// assert_eq!(trace.value(2, "x").and_then(|v| v.as_int()).and_then(|v| v.as_i64()), Some(2));
+/// assert_eq!(trace.to_string(), "step 0: x = 0\nstep 1: x = 1\nstep 2: x = 2\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Trace {
+    variables: Vec<String>,
+    steps: Vec<Vec<Option<Dynamic>>>,
+}
+
+impl Trace {
+    /// Build a trace by evaluating, for every step, the unrolled copy of
+    /// each state variable in `model`.
+    ///
+    /// `steps` yields one map per step from the name of a state variable to
+    /// the term representing it at that step. Variables are ordered by their
+    /// first appearance. A variable that is missing from a step has no value
+    /// at that step.
+    pub fn from_model<S, M, K, A>(model: &Model, steps: S) -> Trace
+    where
+        S: IntoIterator<Item = M>,
+        M: IntoIterator<Item = (K, A)>,
+        K: Into<String>,
+        A: Into<Dynamic>,
+    {
+        let mut variables: Vec<String> = vec![];
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut rows: Vec<Vec<(usize, Dynamic)>> = vec![];
+        for step in steps {
+            let mut row = vec![];
+            for (name, ast) in step {
+                let name = name.into();
+                let idx = *index.entry(name.clone()).or_insert_with(|| {
+                    variables.push(name);
+                    variables.len() - 1
+                });
+                if let Some(value) = model.eval(&ast.into(), true) {
+                    row.push((idx, value));
+                }
+            }
+            rows.push(row);
+        }
+        let steps = rows
+            .into_iter()
+            .map(|row| {
+                let mut values = vec![None; variables.len()];
+                for (idx, value) in row {
+                    values[idx] = Some(value);
+                }
+                values
+            })
+            .collect();
+        Trace { variables, steps }
+    }
+
+    /// Returns the number of steps in the trace.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns `true` if the trace has no steps.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Returns the names of the state variables, in column order.
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    /// Returns the value of the state variable `name` at `step`.
+    pub fn value(&self, step: usize, name: &str) -> Option<&Dynamic> {
+        let idx = self.variables.iter().position(|v| v == name)?;
+        self.steps.get(step)?.get(idx)?.as_ref()
+    }
+
+    /// Iterate over the values of the state variables at `step`.
+    ///
+    /// Variables without a value at this step are skipped.
+    pub fn step(&self, step: usize) -> impl Iterator<Item = (&str, &Dynamic)> + '_ {
+        self.steps
+            .get(step)
+            .into_iter()
+            .flat_map(|row| row.iter().zip(&self.variables))
+            .filter_map(|(value, name)| value.as_ref().map(|v| (name.as_str(), v)))
+    }
+
+    /// Iterate over the values taken by the state variable `name`, one per step.
+    pub fn history<'a>(&'a self, name: &str) -> impl Iterator<Item = Option<&'a Dynamic>> + 'a {
+        let idx = self.variables.iter().position(|v| v == name);
+        self.steps
+            .iter()
+            .map(move |row| idx.and_then(|i| row[i].as_ref()))
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        for i in 0..self.len() {
+            write!(f, "step {i}:")?;
+            for (n, (name, value)) in self.step(i).enumerate() {
+                let sep = if n == 0 { " " } else { ", " };
+                write!(f, "{sep}{name} = {value}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serializes as a sequence of steps, each a map from state variable names
+/// to the SMT-LIB representation of their values.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Trace {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{SerializeMap, SerializeSeq};
+
+        struct Step<'a>(&'a Trace, usize);
+
+        impl serde::Serialize for Step<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut map = serializer.serialize_map(None)?;
+                for (name, value) in self.0.step(self.1) {
+                    map.serialize_entry(name, &value.to_string())?;
+                }
+                map.end()
+            }
+        }
+
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for i in 0..self.len() {
+            seq.serialize_element(&Step(self, i))?;
+        }
+        seq.end()
+    }
+}
@@ -0,0 +1,132 @@
+//! Transformations of goals.
+
+use std::collections::HashMap;
+
+use z3_sys::DeclKind;
+
+use crate::ast::{Ast, Bool, Dynamic};
+use crate::{FuncDecl, Goal};
+
+/// The result of [`ackermannize()`]: a goal without uninterpreted function
+/// applications, and the constants which replaced them.
+#[derive(Debug, Clone)]
+pub struct Ackermannization {
+    goal: Goal,
+    /// Each replaced application with its constant.
+    replacements: Vec<(Dynamic, Dynamic)>,
+}
+
+impl Ackermannization {
+    /// Returns the transformed goal.
+    pub fn goal(&self) -> &Goal {
+        &self.goal
+    }
+
+    /// Returns the replaced applications, as they occur in the original
+    /// goal, with the constants replacing them, innermost applications
+    /// first.
+    ///
+    /// In a model of the transformed goal, the value of a constant is the
+    /// value of its application.
+    pub fn replacements(&self) -> &[(Dynamic, Dynamic)] {
+        &self.replacements
+    }
+}
+
+/// Replace the applications of uninterpreted functions in `goal` by fresh
+/// constants, and add the congruence constraints between them.
+///
+/// For every two applications `f(a1, ..., an)` and `f(b1, ..., bn)`
+/// replaced by `c` and `d`, the transformed goal contains
+/// `a1 = b1 && ... && an = bn => c = d`, so it is equisatisfiable with
+/// `goal`. Once the functions are gone, a problem over uninterpreted
+/// functions and bit-vectors becomes a pure bit-vector problem.
+///
+/// The number of constraints is quadratic in the number of applications
+/// of each function. Applications below quantifiers are left in place.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{FuncDecl, Goal, SatResult, Solver, Sort};
+/// # use z3::ast::{Ast, BV};
+/// # use z3::transform::ackermannize;
+/// let f = FuncDecl::new("f", &[&Sort::bitvector(8)], &Sort::bitvector(8));
+/// let (x, y) = (BV::new_const("x", 8), BV::new_const("y", 8));
+/// let goal = Goal::new(true, false, false);
+/// goal.assert(&x.eq(&y));
+/// goal.assert(&f.apply(&[&x]).eq(f.apply(&[&y])).not());
+///
+/// let ackermannized = ackermannize(&goal);
+/// assert_eq!(ackermannized.replacements().len(), 2);
+///
+/// let solver = Solver::new();
+/// for formula in ackermannized.goal().get_formulas() {
+///     solver.assert(&formula);
+/// }
+/// assert_eq!(solver.check(), SatResult::Unsat);
+/// ```
+pub fn ackermannize(goal: &Goal) -> Ackermannization {
+    let mut builder = Builder {
+        replaced: HashMap::new(),
+        replacements: vec![],
+        applications: vec![],
+    };
+    let formulas: Vec<Bool> = goal
+        .get_formulas()
+        .iter()
+        .map(|f| builder.rewrite(&Dynamic::from_ast(f)).as_bool().unwrap())
+        .collect();
+
+    let result = Goal::new(true, false, false);
+    for formula in &formulas {
+        result.assert(formula);
+    }
+    for (i, (f, args, c)) in builder.applications.iter().enumerate() {
+        for (g, other_args, d) in &builder.applications[i + 1..] {
+            if f != g {
+                continue;
+            }
+            let equal: Vec<Bool> = args.iter().zip(other_args).map(|(a, b)| a.eq(b)).collect();
+            result.assert(&Bool::and(&equal).implies(c.eq(d)));
+        }
+    }
+    Ackermannization {
+        goal: result,
+        replacements: builder.replacements,
+    }
+}
+
+struct Builder {
+    /// The rewritten form of each visited term.
+    replaced: HashMap<Dynamic, Dynamic>,
+    replacements: Vec<(Dynamic, Dynamic)>,
+    /// Each replaced application with its function, rewritten arguments and
+    /// constant.
+    applications: Vec<(FuncDecl, Vec<Dynamic>, Dynamic)>,
+}
+
+impl Builder {
+    fn rewrite(&mut self, term: &Dynamic) -> Dynamic {
+        if let Some(rewritten) = self.replaced.get(term) {
+            return rewritten.clone();
+        }
+        let rewritten = if term.is_app() && term.num_children() > 0 {
+            let decl = term.decl();
+            let args: Vec<Dynamic> = term.children().iter().map(|a| self.rewrite(a)).collect();
+            if decl.kind() == DeclKind::UNINTERPRETED {
+                let constant = Dynamic::fresh_const(&decl.name(), &term.get_sort());
+                self.replacements.push((term.clone(), constant.clone()));
+                self.applications.push((decl, args, constant.clone()));
+                constant
+            } else {
+                let args: Vec<&dyn Ast> = args.iter().map(|a| a as &dyn Ast).collect();
+                decl.apply(&args)
+            }
+        } else {
+            term.clone()
+        };
+        self.replaced.insert(term.clone(), rewritten.clone());
+        rewritten
+    }
+}
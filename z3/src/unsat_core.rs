@@ -0,0 +1,67 @@
+//! Utilities for unsat cores.
+
+use z3_sys::*;
+
+use crate::ast::Bool;
+use crate::{SatResult, Solver};
+
+/// Shrink an unsat `core` of `solver` to a locally minimal one: removing
+/// any single literal of the result makes the assertions of `solver`
+/// satisfiable.
+///
+/// The literals of `core` are checked as assumptions, as returned by
+/// [`Solver::get_unsat_core()`] for both [`Solver::check_assumptions()`]
+/// and [`Solver::assert_and_track()`]. Each literal is dropped in turn if
+/// the rest still is unsat, and the remaining candidates are narrowed to
+/// the core of that check. Literals whose removal gives `unknown` are
+/// kept, so the result is unsat whenever `core` is.
+///
+/// The checks run on a new solver with the assertions of `solver`, which
+/// is left untouched.
+///
+/// # Example
+///
+/// ```
+/// # use z3::{SatResult, Solver};
+/// # use z3::ast::{Bool, Int};
+/// let solver = Solver::new();
+/// let x = Int::new_const("x");
+/// let lits = [x.gt(0), x.gt(1), x.lt(5), x.lt(1)];
+/// assert_eq!(solver.check_assumptions(&lits), SatResult::Unsat);
+///
+/// let core = z3::unsat_core::minimize(&solver, &solver.get_unsat_core());
+/// assert_eq!(core.len(), 2);
+/// assert!(core.contains(&lits[3]));
+/// ```
+pub fn minimize(solver: &Solver, core: &[Bool]) -> Vec<Bool> {
+    // Z3 assumes the literals of `assert_and_track()` in every check of
+    // `solver`, so they could not be dropped there.
+    let assertions = solver.get_assertions();
+    let ctx = solver.get_context();
+    let solver = unsafe { Solver::wrap(ctx, Z3_mk_solver(ctx.z3_ctx.0).unwrap()) };
+    for assertion in &assertions {
+        solver.assert(assertion);
+    }
+    let mut core = core.to_vec();
+    let mut i = 0;
+    while i < core.len() {
+        let candidate: Vec<Bool> = core
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, lit)| lit.clone())
+            .collect();
+        if solver.check_assumptions(&candidate) == SatResult::Unsat {
+            // The new core may drop more than the removed literal. The
+            // literals before `i` are necessary, so they stay in place.
+            let reduced = solver.get_unsat_core();
+            core = candidate
+                .into_iter()
+                .filter(|lit| reduced.contains(lit))
+                .collect();
+        } else {
+            i += 1;
+        }
+    }
+    core
+}
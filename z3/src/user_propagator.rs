@@ -0,0 +1,466 @@
+#[cfg(feature = "z3_4_8_15")]
+use std::cell::RefCell;
+#[cfg(not(feature = "z3_4_8_15"))]
+use std::collections::HashMap;
+#[cfg(feature = "z3_4_8_15")]
+use std::collections::HashSet;
+use std::ffi::{CStr, c_uint, c_void};
+use z3_sys::*;
+
+use crate::Context;
+use crate::ast::{Ast, Bool, Dynamic};
+
+/// A custom theory, integrated into the search of a [`Solver`](crate::Solver).
+///
+/// The solver notifies the propagator of the values and equalities it
+/// assigns to the terms registered with
+/// [`Solver::propagate_register()`](crate::Solver::propagate_register), and
+/// the propagator may respond with consequences or conflicts through the
+/// [`PropagatorCallback`] it is passed. All methods default to doing nothing.
+///
+/// With the `z3_4_8_15` feature, enabled by default, this binds the user
+/// propagator API of Z3 4.8.15 and later; without it, the API of Z3 4.8.12
+/// to 4.8.14, which lacks the [`created()`](UserPropagator::created)
+/// callback. The [`decide()`](UserPropagator::decide) callback requires the
+/// `z3_4_12_0` feature.
+///
+/// The propagator must keep track of its state across the decision levels of
+/// the search: [`push()`](UserPropagator::push) opens a new level and
+/// [`pop()`](UserPropagator::pop) backtracks over levels, undoing every
+/// assignment reported in between.
+///
+/// # Example
+///
+/// A propagator enforcing that at most one of its Boolean terms is true:
+///
+/// ```
+/// # use z3::{PropagatorCallback, SatResult, Solver, UserPropagator};
+/// # use z3::ast::{Bool, Dynamic};
+/// #[derive(Default)]
+/// struct AtMostOne {
+///     trues: Vec<Dynamic>,
+///     levels: Vec<usize>,
+/// }
+///
+/// impl UserPropagator for AtMostOne {
+///     fn push(&mut self) {
+///         self.levels.push(self.trues.len());
+///     }
+///
+///     fn pop(&mut self, num_scopes: u32) {
+///         let len = self.levels.len() - num_scopes as usize;
+///         self.trues.truncate(self.levels[len]);
+///         self.levels.truncate(len);
+///     }
+///
+///     fn fixed(&mut self, cb: &PropagatorCallback, term: &Dynamic, value: &Dynamic) {
+///         if value.as_bool().and_then(|b| b.as_bool()) == Some(true) {
+///             self.trues.push(term.clone());
+///             if let [a, b, ..] = &self.trues[..] {
+///                 assert!(cb.conflict(&[a, b], &[]));
+///             }
+///         }
+///     }
+/// }
+///
+/// let solver = Solver::new_simple();
+/// solver.set_user_propagator(AtMostOne::default()).unwrap();
+/// let xs: Vec<Bool> = (0..3).map(|i| Bool::new_const(format!("x{i}"))).collect();
+/// for x in &xs {
+///     solver.propagate_register(x);
+/// }
+/// solver.assert(Bool::or(&[&xs[0], &xs[1]]));
+/// assert_eq!(solver.check(), SatResult::Sat);
+/// solver.assert(Bool::or(&[&xs[1], &xs[2]]));
+/// solver.assert(xs[1].not());
+/// assert_eq!(solver.check(), SatResult::Unsat);
+/// ```
+pub trait UserPropagator {
+    /// A new decision level is opened.
+    fn push(&mut self) {}
+
+    /// Backtrack over the last `num_scopes` decision levels.
+    fn pop(&mut self, num_scopes: u32) {
+        let _ = num_scopes;
+    }
+
+    /// The registered `term` is assigned `value`.
+    fn fixed(&mut self, cb: &PropagatorCallback, term: &Dynamic, value: &Dynamic) {
+        let _ = (cb, term, value);
+    }
+
+    /// The registered terms `x` and `y` are equal.
+    fn eq(&mut self, cb: &PropagatorCallback, x: &Dynamic, y: &Dynamic) {
+        let _ = (cb, x, y);
+    }
+
+    /// The registered terms `x` and `y` are distinct.
+    fn diseq(&mut self, cb: &PropagatorCallback, x: &Dynamic, y: &Dynamic) {
+        let _ = (cb, x, y);
+    }
+
+    /// The solver found an assignment satisfying all other constraints.
+    ///
+    /// Propagating a consequence or conflict makes the search continue,
+    /// otherwise the assignment is accepted.
+    fn final_check(&mut self, cb: &PropagatorCallback) {
+        let _ = cb;
+    }
+
+    /// The solver created `term`, an application of a function declared
+    /// with [`Solver::propagate_declare()`](crate::Solver::propagate_declare),
+    /// which is registered with the propagator.
+    #[cfg(feature = "z3_4_8_15")]
+    fn created(&mut self, cb: &PropagatorCallback, term: &Dynamic) {
+        let _ = (cb, term);
+    }
+
+    /// The solver branches on bit `idx` of the registered `term`, `0` for a
+    /// Boolean, assigning it `phase`.
+    ///
+    /// Call [`PropagatorCallback::next_split()`] to branch differently.
+    #[cfg(feature = "z3_4_12_0")]
+    fn decide(&mut self, cb: &PropagatorCallback, term: &Dynamic, idx: u32, phase: bool) {
+        let _ = (cb, term, idx, phase);
+    }
+}
+
+/// Propagates consequences from within the callbacks of a
+/// [`UserPropagator`].
+///
+/// Consequences are justified by the values of registered terms and the
+/// equalities between them reported to the propagator.
+#[derive(Debug)]
+pub struct PropagatorCallback<'a> {
+    ctx: &'a Context,
+    cb: Z3_solver_callback,
+    registry: &'a Registry,
+}
+
+impl PropagatorCallback<'_> {
+    /// Propagate `consequence`, which follows from the current values of the
+    /// registered terms `fixed` and the equalities `eqs` between registered
+    /// terms.
+    ///
+    /// Returns `false`, without propagating, if a term of `fixed` or `eqs`
+    /// is not registered.
+    pub fn propagate(
+        &self,
+        fixed: &[&Dynamic],
+        eqs: &[(&Dynamic, &Dynamic)],
+        consequence: &Bool,
+    ) -> bool {
+        let handles = || -> Option<_> {
+            let handle = |t: &Dynamic| self.registry.handle(t);
+            let fixed: Vec<Handle> = fixed.iter().map(|t| handle(t)).collect::<Option<_>>()?;
+            let lhs: Vec<Handle> = eqs.iter().map(|(x, _)| handle(x)).collect::<Option<_>>()?;
+            let rhs: Vec<Handle> = eqs.iter().map(|(_, y)| handle(y)).collect::<Option<_>>()?;
+            Some((fixed, lhs, rhs))
+        };
+        let Some((fixed, lhs, rhs)) = handles() else {
+            return false;
+        };
+        unsafe {
+            Z3_solver_propagate_consequence(
+                self.ctx.z3_ctx.0,
+                self.cb,
+                fixed.len() as c_uint,
+                fixed.as_ptr(),
+                eqs.len() as c_uint,
+                lhs.as_ptr(),
+                rhs.as_ptr(),
+                consequence.get_z3_ast(),
+            );
+        }
+        true
+    }
+
+    /// Report that the current values of `fixed` and the equalities `eqs`
+    /// are inconsistent.
+    ///
+    /// Returns `false`, without reporting, if a term of `fixed` or `eqs` is
+    /// not registered.
+    pub fn conflict(&self, fixed: &[&Dynamic], eqs: &[(&Dynamic, &Dynamic)]) -> bool {
+        self.propagate(fixed, eqs, &Bool::from_bool(false))
+    }
+
+    /// Register `term`, e.g. a subterm of a term reported to the propagator,
+    /// for the rest of the search.
+    #[cfg(feature = "z3_4_8_15")]
+    pub fn register(&self, term: &impl Ast) {
+        unsafe {
+            Z3_solver_propagate_register_cb(self.ctx.z3_ctx.0, self.cb, term.get_z3_ast());
+        }
+        self.registry.insert(Dynamic::from_ast(term));
+    }
+
+    /// Make the solver branch next on bit `idx` of the registered `term`,
+    /// `0` for a Boolean, assigning it `phase`, or a value of its choice for
+    /// `None`, e.g. from [`UserPropagator::decide()`].
+    ///
+    /// Returns `false` if the solver rejects the split.
+    #[cfg(feature = "z3_4_12_0")]
+    pub fn next_split(&self, term: &impl Ast, idx: u32, phase: Option<bool>) -> bool {
+        let phase = match phase {
+            Some(true) => Z3_L_TRUE,
+            Some(false) => Z3_L_FALSE,
+            None => Z3_L_UNDEF,
+        };
+        unsafe { Z3_solver_next_split(self.ctx.z3_ctx.0, self.cb, term.get_z3_ast(), idx, phase) }
+    }
+}
+
+/// How Z3 identifies registered terms in callbacks: by id up to Z3 4.8.14,
+/// by the terms themselves since Z3 4.8.15.
+#[cfg(not(feature = "z3_4_8_15"))]
+type Handle = c_uint;
+#[cfg(feature = "z3_4_8_15")]
+type Handle = Z3_ast;
+
+/// The terms registered with the propagator.
+#[derive(Debug, Default)]
+struct Registry {
+    #[cfg(not(feature = "z3_4_8_15"))]
+    terms: HashMap<c_uint, Dynamic>,
+    #[cfg(not(feature = "z3_4_8_15"))]
+    ids: HashMap<Dynamic, c_uint>,
+    // Terms are also registered from within callbacks.
+    #[cfg(feature = "z3_4_8_15")]
+    terms: RefCell<HashSet<Dynamic>>,
+}
+
+#[cfg(not(feature = "z3_4_8_15"))]
+impl Registry {
+    fn insert(&mut self, term: Dynamic, id: c_uint) {
+        self.terms.insert(id, term.clone());
+        self.ids.insert(term, id);
+    }
+
+    fn handle(&self, term: &Dynamic) -> Option<Handle> {
+        self.ids.get(term).copied()
+    }
+
+    fn term(&self, _ctx: &Context, id: Handle) -> Option<Dynamic> {
+        self.terms.get(&id).cloned()
+    }
+}
+
+#[cfg(feature = "z3_4_8_15")]
+impl Registry {
+    fn insert(&self, term: Dynamic) {
+        self.terms.borrow_mut().insert(term);
+    }
+
+    fn handle(&self, term: &Dynamic) -> Option<Handle> {
+        self.terms
+            .borrow()
+            .contains(term)
+            .then(|| term.get_z3_ast())
+    }
+
+    fn term(&self, ctx: &Context, t: Handle) -> Option<Dynamic> {
+        Some(unsafe { Dynamic::wrap(ctx, t) })
+    }
+}
+
+pub(crate) struct PropagatorState {
+    ctx: Context,
+    propagator: Box<dyn UserPropagator>,
+    registry: Registry,
+}
+
+impl PropagatorState {
+    pub(crate) fn new(ctx: &Context, propagator: Box<dyn UserPropagator>) -> PropagatorState {
+        PropagatorState {
+            ctx: ctx.clone(),
+            propagator,
+            registry: Registry::default(),
+        }
+    }
+
+    /// Register `term` with the solver `z3_slv` this state was installed in.
+    pub(crate) fn register(&mut self, z3_slv: Z3_solver, term: Dynamic) {
+        let z3_ctx = self.ctx.z3_ctx.0;
+        #[cfg(not(feature = "z3_4_8_15"))]
+        {
+            let id = unsafe { Z3_solver_propagate_register(z3_ctx, z3_slv, term.get_z3_ast()) };
+            self.registry.insert(term, id);
+        }
+        #[cfg(feature = "z3_4_8_15")]
+        {
+            unsafe { Z3_solver_propagate_register(z3_ctx, z3_slv, term.get_z3_ast()) };
+            self.registry.insert(term);
+        }
+    }
+
+    /// Install the callbacks of this state in the solver `z3_slv`, or return
+    /// Z3's error message if the solver does not support user propagators.
+    ///
+    /// # Safety
+    ///
+    /// The state must outlive the solver, and must not move.
+    pub(crate) unsafe fn install(&mut self, z3_slv: Z3_solver) -> Result<(), String> {
+        let z3_ctx = self.ctx.z3_ctx.0;
+        unsafe {
+            Z3_set_error(z3_ctx, ErrorCode::OK);
+            Z3_solver_propagate_init(
+                z3_ctx,
+                z3_slv,
+                self as *mut PropagatorState as *mut c_void,
+                Some(push_trampoline),
+                Some(pop_trampoline),
+                Some(fresh_trampoline),
+            );
+            let code = Z3_get_error_code(z3_ctx);
+            if code != ErrorCode::OK {
+                let msg = Z3_get_error_msg(z3_ctx, code);
+                return Err(CStr::from_ptr(msg).to_string_lossy().into_owned());
+            }
+            Z3_solver_propagate_fixed(z3_ctx, z3_slv, Some(fixed_trampoline));
+            Z3_solver_propagate_eq(z3_ctx, z3_slv, Some(eq_trampoline));
+            Z3_solver_propagate_diseq(z3_ctx, z3_slv, Some(diseq_trampoline));
+            Z3_solver_propagate_final(z3_ctx, z3_slv, Some(final_trampoline));
+            #[cfg(feature = "z3_4_8_15")]
+            Z3_solver_propagate_created(z3_ctx, z3_slv, Some(created_trampoline));
+            #[cfg(feature = "z3_4_12_0")]
+            Z3_solver_propagate_decide(z3_ctx, z3_slv, Some(decide_trampoline));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the state passed as user context, or `None` for the inert
+/// propagators of translated solvers.
+unsafe fn state<'a>(ctx: *mut c_void) -> Option<&'a mut PropagatorState> {
+    unsafe { (ctx as *mut PropagatorState).as_mut() }
+}
+
+#[cfg(not(feature = "z3_4_8_15"))]
+unsafe extern "C" fn push_trampoline(ctx: *mut c_void) {
+    if let Some(state) = unsafe { state(ctx) } {
+        state.propagator.push();
+    }
+}
+
+#[cfg(feature = "z3_4_8_15")]
+unsafe extern "C" fn push_trampoline(ctx: *mut c_void, _cb: Z3_solver_callback) {
+    if let Some(state) = unsafe { state(ctx) } {
+        state.propagator.push();
+    }
+}
+
+#[cfg(not(feature = "z3_4_8_15"))]
+unsafe extern "C" fn pop_trampoline(ctx: *mut c_void, num_scopes: c_uint) {
+    if let Some(state) = unsafe { state(ctx) } {
+        state.propagator.pop(num_scopes);
+    }
+}
+
+#[cfg(feature = "z3_4_8_15")]
+unsafe extern "C" fn pop_trampoline(ctx: *mut c_void, _cb: Z3_solver_callback, num_scopes: c_uint) {
+    if let Some(state) = unsafe { state(ctx) } {
+        state.propagator.pop(num_scopes);
+    }
+}
+
+unsafe extern "C" fn fresh_trampoline(_ctx: *mut c_void, _new_context: Z3_context) -> *mut c_void {
+    // The propagator is not carried over to copies of the solver.
+    std::ptr::null_mut()
+}
+
+unsafe extern "C" fn fixed_trampoline(
+    ctx: *mut c_void,
+    cb: Z3_solver_callback,
+    term: Handle,
+    value: Z3_ast,
+) {
+    if let Some(state) = unsafe { state(ctx) } {
+        let value = unsafe { Dynamic::wrap(&state.ctx, value) };
+        let cb = PropagatorCallback {
+            ctx: &state.ctx,
+            cb,
+            registry: &state.registry,
+        };
+        if let Some(term) = state.registry.term(&state.ctx, term) {
+            state.propagator.fixed(&cb, &term, &value);
+        }
+    }
+}
+
+unsafe extern "C" fn eq_trampoline(ctx: *mut c_void, cb: Z3_solver_callback, x: Handle, y: Handle) {
+    if let Some(state) = unsafe { state(ctx) } {
+        let cb = PropagatorCallback {
+            ctx: &state.ctx,
+            cb,
+            registry: &state.registry,
+        };
+        let registry = &state.registry;
+        if let (Some(x), Some(y)) = (registry.term(&state.ctx, x), registry.term(&state.ctx, y)) {
+            state.propagator.eq(&cb, &x, &y);
+        }
+    }
+}
+
+unsafe extern "C" fn diseq_trampoline(
+    ctx: *mut c_void,
+    cb: Z3_solver_callback,
+    x: Handle,
+    y: Handle,
+) {
+    if let Some(state) = unsafe { state(ctx) } {
+        let cb = PropagatorCallback {
+            ctx: &state.ctx,
+            cb,
+            registry: &state.registry,
+        };
+        let registry = &state.registry;
+        if let (Some(x), Some(y)) = (registry.term(&state.ctx, x), registry.term(&state.ctx, y)) {
+            state.propagator.diseq(&cb, &x, &y);
+        }
+    }
+}
+
+unsafe extern "C" fn final_trampoline(ctx: *mut c_void, cb: Z3_solver_callback) {
+    if let Some(state) = unsafe { state(ctx) } {
+        let cb = PropagatorCallback {
+            ctx: &state.ctx,
+            cb,
+            registry: &state.registry,
+        };
+        state.propagator.final_check(&cb);
+    }
+}
+
+#[cfg(feature = "z3_4_8_15")]
+unsafe extern "C" fn created_trampoline(ctx: *mut c_void, cb: Z3_solver_callback, term: Z3_ast) {
+    if let Some(state) = unsafe { state(ctx) } {
+        let term = unsafe { Dynamic::wrap(&state.ctx, term) };
+        // Z3 registers the applications of declared functions itself.
+        state.registry.insert(term.clone());
+        let cb = PropagatorCallback {
+            ctx: &state.ctx,
+            cb,
+            registry: &state.registry,
+        };
+        state.propagator.created(&cb, &term);
+    }
+}
+
+#[cfg(feature = "z3_4_12_0")]
+unsafe extern "C" fn decide_trampoline(
+    ctx: *mut c_void,
+    cb: Z3_solver_callback,
+    term: Z3_ast,
+    idx: c_uint,
+    phase: bool,
+) {
+    if let Some(state) = unsafe { state(ctx) } {
+        let term = unsafe { Dynamic::wrap(&state.ctx, term) };
+        let cb = PropagatorCallback {
+            ctx: &state.ctx,
+            cb,
+            registry: &state.registry,
+        };
+        state.propagator.decide(&cb, &term, idx, phase);
+    }
+}
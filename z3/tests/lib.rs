@@ -1,6 +1,6 @@
 use log::info;
 use std::convert::TryInto;
-use std::ops::Add;
+use std::ops::{Add, ControlFlow};
 use std::time::Duration;
 use z3::ast::{Array, Ast, BV, Bool, Int, atleast, atmost};
 use z3::*;
@@ -240,7 +240,7 @@ fn test_solver_new_from_smtlib2() {
 (assert (=(+( -(* 2 x) (* 2 y)) (* 4 z)) -2))
 "#;
     let solver = Solver::new();
-    solver.from_string(problem);
+    solver.from_string(problem).unwrap();
     assert_eq!(solver.check(), SatResult::Sat);
 }
 
@@ -252,7 +252,7 @@ fn test_solver_to_smtlib2() {
     solver1.assert(t1.eq(&t2));
     let s1_smt2 = solver1.to_smt2();
     let solver2 = Solver::new();
-    solver2.from_string(s1_smt2);
+    solver2.from_string(s1_smt2).unwrap();
     assert_eq!(solver2.check(), solver1.check());
 }
 
@@ -397,6 +397,34 @@ fn test_substitution() {
     assert!(x_plus_y.substitute(substitutions) == x_plus_z);
 }
 
+#[test]
+fn test_substitute_bound_vars() {
+    let v0 = ast::Dynamic::bound_var(0, &Sort::int()).as_int().unwrap();
+    let v1 = ast::Dynamic::bound_var(1, &Sort::int()).as_int().unwrap();
+    let t = (&v0 - &v1).gt(0);
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    assert_eq!(t.substitute_bound_vars(&[&x, &y]), (&x - &y).gt(0));
+    assert_eq!(t.substitute_bound_vars(&[&y, &x]), (&y - &x).gt(0));
+}
+
+#[cfg(feature = "z3_4_12_0")]
+#[test]
+fn test_substitute_funcs() {
+    let f = FuncDecl::new("f", &[&Sort::int(), &Sort::int()], &Sort::int());
+    let g = FuncDecl::new("g", &[&Sort::int()], &Sort::bool());
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    let f_xy = f.apply(&[&x, &y]).as_int().unwrap();
+    let t = g.apply(&[&f_xy]).as_bool().unwrap() & f_xy.gt(1);
+
+    // f(a, b) := a - b, g(a) := a > 0
+    let v0 = ast::Dynamic::bound_var(0, &Sort::int()).as_int().unwrap();
+    let v1 = ast::Dynamic::bound_var(1, &Sort::int()).as_int().unwrap();
+    let f_body = &v0 - &v1;
+    let g_body = v0.gt(0);
+    let u = t.substitute_funcs(&[(&f, &f_body), (&g, &g_body)]);
+    assert_eq!(u, (&x - &y).gt(0) & (&x - &y).gt(1));
+}
+
 #[test]
 fn test_real_cmp() {
     let solver = Solver::new();
@@ -663,7 +691,7 @@ fn test_optimize_new_from_smtlib2() {
 (assert (=(+( -(* 2 x) (* 2 y)) (* 4 z)) -2))
 "#;
     let optimize = Optimize::new();
-    optimize.from_string(problem);
+    optimize.from_string(problem).unwrap();
     assert_eq!(optimize.check(&[]), SatResult::Sat);
 }
 
@@ -1649,12 +1677,12 @@ fn iterate_all_solutions() {
     assert!(
         solutions
             == vec![
-                "[b = 1, a = 2]".to_string(),
-                "[b = 2, a = 4]".to_string(),
-                "[b = 1, a = 3]".to_string(),
-                "[b = 2, a = 5]".to_string(),
-                "[b = 1, a = 4]".to_string(),
-                "[b = 1, a = 5]".to_string()
+                "[a = 2, b = 1]".to_string(),
+                "[a = 4, b = 2]".to_string(),
+                "[a = 3, b = 1]".to_string(),
+                "[a = 5, b = 2]".to_string(),
+                "[a = 4, b = 1]".to_string(),
+                "[a = 5, b = 1]".to_string()
             ]
             .into_iter()
             .collect()
@@ -1815,3 +1843,3282 @@ fn test_compare_trait_resolution() {
     assert!(!test_bool.ne(a.eq(&b)).is_const());
     assert!(!test_bool.ne(a.ne(&b)).is_const());
 }
+
+#[test]
+fn test_lazy_model_caches_values() {
+    let solver = Solver::new();
+    let x = Int::new_const("x");
+    let y = Int::new_const("y");
+    solver.assert(x.eq(3));
+    solver.assert(y.eq(&x + 1));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let model = LazyModel::new(solver.get_model().unwrap(), true);
+    assert!(!model.is_cached(&y));
+    assert_eq!(model.get(&y).unwrap().as_i64(), Some(4));
+    assert!(model.is_cached(&y));
+    assert!(!model.is_cached(&x));
+    assert_eq!(model.get(&y).unwrap().as_i64(), Some(4));
+    assert_eq!(model.num_cached(), 1);
+
+    model.clear_cache();
+    assert_eq!(model.num_cached(), 0);
+}
+
+#[test]
+fn test_fixedpoint_get_rules_and_assertions() {
+    let fp = Fixedpoint::new();
+    let queries = fp
+        .from_string(
+            "(declare-rel reach (Int))
+             (declare-var x Int)
+             (rule (reach 0))
+             (rule (=> (and (reach x) (< x 10)) (reach (+ x 1))))
+             (declare-const y Int)
+             (assert (> y 0))",
+        )
+        .unwrap();
+    assert!(queries.is_empty());
+
+    let rules = fp.get_rules();
+    assert_eq!(rules.len(), 2);
+    assert!((&rules).into_iter().all(|r| r.as_bool().is_some()));
+    assert_eq!(fp.get_assertions().len(), 1);
+}
+
+#[test]
+fn test_trace_from_bmc_unrolling() {
+    let solver = Solver::new();
+    let steps: Vec<(Int, Bool)> = (0..3)
+        .map(|i| {
+            (
+                Int::new_const(format!("cnt_{i}")),
+                Bool::new_const(format!("flag_{i}")),
+            )
+        })
+        .collect();
+    solver.assert(steps[0].0.eq(5));
+    solver.assert(&steps[0].1);
+    for w in steps.windows(2) {
+        solver.assert(w[1].0.eq(&w[0].0 * 2));
+        solver.assert(w[1].1.eq(w[0].1.not()));
+    }
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+
+    let trace = Trace::from_model(
+        &model,
+        steps.iter().map(|(cnt, flag)| {
+            [
+                ("cnt", ast::Dynamic::from(cnt)),
+                ("flag", ast::Dynamic::from(flag)),
+            ]
+        }),
+    );
+    assert_eq!(trace.len(), 3);
+    assert_eq!(trace.variables(), ["cnt", "flag"]);
+    let cnts: Vec<i64> = trace
+        .history("cnt")
+        .map(|v| v.unwrap().as_int().unwrap().as_i64().unwrap())
+        .collect();
+    assert_eq!(cnts, [5, 10, 20]);
+    assert_eq!(
+        trace.value(1, "flag").unwrap().as_bool().unwrap().as_bool(),
+        Some(false)
+    );
+    assert!(trace.value(3, "cnt").is_none());
+    assert_eq!(
+        trace.to_string(),
+        "step 0: cnt = 5, flag = true\nstep 1: cnt = 10, flag = false\nstep 2: cnt = 20, flag = true\n"
+    );
+
+    #[cfg(feature = "serde")]
+    assert_eq!(
+        serde_json::to_string(&trace).unwrap(),
+        r#"[{"cnt":"5","flag":"true"},{"cnt":"10","flag":"false"},{"cnt":"20","flag":"true"}]"#
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "Can not apply `bvadd`, Sort does not match.  Arguments contain types (_ BitVec 8) and (_ BitVec 16)"
+)]
+fn test_sort_mismatch_names_operation() {
+    let a = BV::new_const("a", 8);
+    let b = BV::new_const("b", 16);
+    let _ = a.bvadd(&b);
+}
+
+#[test]
+fn test_sort_mismatch_try_variants() {
+    let a = BV::new_const("a", 8);
+    let b = BV::new_const("b", 16);
+    let err = a.try_bvadd(&b).unwrap_err();
+    assert_eq!(err.operation(), Some("bvadd"));
+    assert!(a.try_bvadd(&a).is_ok());
+
+    let rm = ast::RoundingMode::round_nearest_ties_to_even();
+    let x = ast::Float::new_const_float32("x");
+    let y = ast::Float::new_const_double("y");
+    assert!(rm.try_add(&x, &y).is_err());
+    assert!(rm.try_add(&x, &x).is_ok());
+}
+
+#[test]
+#[should_panic(
+    expected = "Can not apply `select`, Sort does not match.  Arguments contain types Int and Bool"
+)]
+fn test_sort_mismatch_array_select() {
+    let arr = Array::new_const("arr", &Sort::int(), &Sort::int());
+    let _ = arr.select(&Bool::new_const("b"));
+}
+
+#[test]
+fn test_sort_differs_operation() {
+    let x = ast::Dynamic::from(Int::new_const("x"));
+    let y = ast::Dynamic::from(Bool::new_const("y"));
+    let err = x.safe_eq(&y).unwrap_err();
+    assert_eq!(err.operation(), Some("eq"));
+    assert_eq!(
+        err.to_string(),
+        "Can not apply `eq`, Sort does not match.  Arguments contain types Int and Bool"
+    );
+
+    // concatenation of differently sized bitvectors is still allowed
+    let wide = BV::new_const("a", 8).concat(BV::new_const("b", 16));
+    assert_eq!(wide.get_size(), 24);
+}
+
+#[test]
+fn test_fixedpoint_spacer_counterexample_trace() {
+    let fp = Fixedpoint::new();
+    let mut params = Params::new();
+    params.set_symbol("engine", "spacer");
+    fp.set_params(&params);
+
+    let int = Sort::int();
+    let inv = FuncDecl::new("inv", &[&int], &Sort::bool());
+    let err = FuncDecl::new("err", &[], &Sort::bool());
+    fp.register_relation(&inv);
+    fp.register_relation(&err);
+
+    let x = Int::new_const("x");
+    let inv_x = inv.apply(&[&x]).as_bool().unwrap();
+    let inv_x1 = inv.apply(&[&(&x + 1)]).as_bool().unwrap();
+    let err_app = err.apply(&[]).as_bool().unwrap();
+    let rule = |body: Bool| ast::forall_const(&[&x], &[], &body);
+    fp.add_rule(&rule(x.eq(0).implies(&inv_x)), Some("init"));
+    fp.add_rule(&rule((&inv_x & x.lt(5)).implies(&inv_x1)), Some("next"));
+    fp.add_rule(&rule((&inv_x & x.eq(5)).implies(&err_app)), Some("error"));
+
+    assert_eq!(fp.query(&err_app), SatResult::Sat);
+    assert_eq!(fp.get_rules_along_trace().len(), 7);
+
+    let derivation = fp.get_derivation().unwrap();
+    let names: Vec<&str> = derivation.steps().iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["error", "next", "next", "next", "next", "next", "init"]
+    );
+    assert!(derivation.to_string().starts_with("error: "));
+}
+
+#[test]
+fn test_fixedpoint_lemma_callbacks() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let fp = Fixedpoint::new();
+    let mut params = Params::new();
+    params.set_symbol("engine", "spacer");
+    params.set_bool("spacer.p3.share_lemmas", true);
+    params.set_bool("spacer.p3.share_invariants", true);
+    fp.set_params(&params);
+
+    let lemmas = Rc::new(RefCell::new(vec![]));
+    let unfolds = Rc::new(RefCell::new(0));
+    let (lemma_sink, unfold_sink) = (lemmas.clone(), unfolds.clone());
+    fp.add_callbacks(
+        FixedpointCallbacks::new()
+            .on_lemma(move |lemma, level| lemma_sink.borrow_mut().push((lemma.clone(), level)))
+            .on_unfold(move || *unfold_sink.borrow_mut() += 1),
+    );
+    fp.from_string(
+        "(declare-rel inv (Int))
+         (declare-rel err ())
+         (declare-var x Int)
+         (rule (=> (= x 0) (inv x)))
+         (rule (=> (and (inv x) (< x 10)) (inv (+ x 1))))
+         (rule (=> (and (inv x) (< x 0)) err))",
+    )
+    .unwrap();
+    let err = FuncDecl::new("err", &[], &Sort::bool());
+    assert_eq!(
+        fp.query(&err.apply(&[]).as_bool().unwrap()),
+        SatResult::Unsat
+    );
+
+    assert!(!lemmas.borrow().is_empty());
+    assert!(*unfolds.borrow() > 0);
+}
+
+#[test]
+fn test_as_array_round_trip() {
+    let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    let arr = f.as_array_term().unwrap();
+    assert!(arr.is_as_array());
+    assert_eq!(arr.to_func_decl().unwrap().name(), "f");
+    assert!(
+        FuncDecl::new("c", &[], &Sort::int())
+            .as_array_term()
+            .is_none()
+    );
+
+    let solver = Solver::new();
+    let x = Int::new_const("x");
+    solver.assert(f.apply(&[&x]).as_int().unwrap().eq(&x + 1));
+    let a = Array::new_const("a", &Sort::int(), &Sort::int());
+    solver.assert(a.eq(&arr));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert!(!Array::fresh_const("b", &Sort::int(), &Sort::int()).is_as_array());
+    // The function behind the array term is interpreted by the model.
+    let g = arr.to_func_decl().unwrap();
+    assert_eq!(g, f);
+    assert!(model.get_func_interp(&g).is_some());
+}
+
+#[test]
+fn test_fixedpoint_spacer_config() {
+    let config = SpacerConfig::new()
+        .max_level(50)
+        .push_pob(true)
+        .gpdr(false)
+        .quantified_lemmas(false)
+        .random_seed(7);
+    let params = config.to_params().to_string();
+    assert!(params.contains("engine spacer"));
+    assert!(params.contains("spacer.max_level 50"));
+    assert!(!params.contains("share_lemmas"));
+
+    let fp = Fixedpoint::new();
+    fp.set_spacer_config(&config);
+    fp.from_string(
+        "(declare-rel inv (Int))
+         (declare-rel err ())
+         (declare-var x Int)
+         (rule (=> (= x 0) (inv x)))
+         (rule (=> (and (inv x) (< x 10)) (inv (+ x 1))))
+         (rule (=> (and (inv x) (< x 0)) err))",
+    )
+    .unwrap();
+    let err = FuncDecl::new("err", &[], &Sort::bool());
+    assert_eq!(
+        fp.query(&err.apply(&[]).as_bool().unwrap()),
+        SatResult::Unsat
+    );
+}
+
+#[test]
+fn test_fixedpoint_invariants() {
+    let fp = Fixedpoint::new();
+    fp.set_spacer_config(&SpacerConfig::new());
+    fp.from_string(
+        "(declare-rel inv (Int))
+         (declare-rel err ())
+         (declare-var x Int)
+         (rule (=> (= x 0) (inv x)))
+         (rule (=> (and (inv x) (< x 10)) (inv (+ x 1))))
+         (rule (=> (and (inv x) (< x 0)) err))",
+    )
+    .unwrap();
+    let inv = FuncDecl::new("inv", &[&Sort::int()], &Sort::bool());
+    let err = FuncDecl::new("err", &[], &Sort::bool());
+    fp.register_relation(&inv);
+    fp.register_relation(&inv);
+    assert_eq!(
+        fp.query(&err.apply(&[]).as_bool().unwrap()),
+        SatResult::Unsat
+    );
+
+    let invariants = fp.get_invariants();
+    assert_eq!(invariants.len(), 1);
+    let body = &invariants[&inv];
+    assert_ne!(body.as_bool(), Some(true));
+    assert_ne!(body.as_bool(), Some(false));
+}
+
+#[test]
+fn test_fixedpoint_relation_algebra() {
+    let fp = Fixedpoint::new();
+    let mut params = Params::new();
+    params.set_symbol("engine", "datalog");
+    fp.set_params(&params);
+
+    let s = Sort::bitvector(4);
+    let a = FuncDecl::new("a", &[&s], &Sort::bool());
+    let b = FuncDecl::new("b", &[&s], &Sort::bool());
+    let edge = FuncDecl::new("edge", &[&s, &s], &Sort::bool());
+    let ab = FuncDecl::new("ab", &[&s], &Sort::bool());
+    let small = FuncDecl::new("small", &[&s], &Sort::bool());
+    let target = FuncDecl::new("target", &[&s], &Sort::bool());
+    let joined = FuncDecl::new("joined", &[&s, &s, &s], &Sort::bool());
+    for r in [&a, &b, &edge] {
+        fp.register_relation(r);
+    }
+    fp.add_fact(&a, &[1]);
+    fp.add_fact(&b, &[9]);
+    fp.add_fact(&edge, &[1, 2]);
+    fp.add_fact(&edge, &[9, 3]);
+
+    fp.add_union(&ab, &[&a, &b]);
+    fp.add_filter(&small, &ab, |xs| xs[0].as_bv().unwrap().bvult(5));
+    fp.add_projection(&target, &edge, &[1]);
+    fp.add_join(&joined, &small, &edge, &[(0, 0)]);
+
+    let holds = |r: &FuncDecl, args: &[u64]| {
+        let args: Vec<BV> = args.iter().map(|&v| BV::from_u64(v, 4)).collect();
+        let args: Vec<&dyn Ast> = args.iter().map(|a| a as &dyn Ast).collect();
+        fp.query(&r.apply(&args).as_bool().unwrap())
+    };
+    assert_eq!(holds(&ab, &[9]), SatResult::Sat);
+    assert_eq!(holds(&ab, &[2]), SatResult::Unsat);
+    assert_eq!(holds(&small, &[1]), SatResult::Sat);
+    assert_eq!(holds(&small, &[9]), SatResult::Unsat);
+    assert_eq!(holds(&target, &[3]), SatResult::Sat);
+    assert_eq!(holds(&target, &[1]), SatResult::Unsat);
+    assert_eq!(holds(&joined, &[1, 1, 2]), SatResult::Sat);
+    assert_eq!(holds(&joined, &[9, 9, 3]), SatResult::Unsat);
+}
+
+#[test]
+fn test_fixedpoint_query_from_lvl() {
+    let fp = Fixedpoint::new();
+    fp.set_spacer_config(&SpacerConfig::new());
+    fp.from_string(
+        "(declare-rel reach (Int))
+         (declare-rel err ())
+         (declare-var x Int)
+         (rule (=> (= x 0) (reach x)))
+         (rule (=> (and (reach x) (< x 5)) (reach (+ x 1))))
+         (rule (=> (and (reach x) (= x 3)) err))",
+    )
+    .unwrap();
+    let err = FuncDecl::new("err", &[], &Sort::bool());
+    let err = err.apply(&[]).as_bool().unwrap();
+    assert_eq!(fp.query_from_lvl(&err, 2), SatResult::Sat);
+    assert!(fp.get_answer().is_some());
+}
+
+#[test]
+fn test_solver_dependency_graph() {
+    let solver = Solver::new();
+    let (x, y, z) = (
+        Int::new_const("x"),
+        Int::new_const("y"),
+        Int::new_const("z"),
+    );
+    let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    let b = Int::new_const("b");
+    solver.assert(x.gt(0));
+    solver.assert(z.lt(0));
+    solver.assert(f.apply(&[&y]).as_int().unwrap().eq(&x));
+    solver.assert(ast::forall_const(
+        &[&b],
+        &[],
+        &f.apply(&[&b]).as_int().unwrap().ge(0),
+    ));
+    solver.assert(Int::from_i64(1).lt(2));
+
+    let graph = solver.dependency_graph();
+    assert_eq!(graph.len(), 5);
+    assert_eq!(graph.symbols(2).len(), 3);
+    assert_eq!(graph.symbols(3).len(), 1);
+    assert_eq!(graph.edges().collect::<Vec<_>>(), vec![(0, 2), (2, 3)]);
+    assert_eq!(graph.components(), vec![vec![0, 2, 3], vec![1], vec![4]]);
+
+    let parts = solver.split_independent();
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts[0].get_assertions().len(), 3);
+    assert!(parts.iter().all(|s| s.check() == SatResult::Sat));
+}
+
+#[test]
+fn test_datalog_dsl() {
+    use z3::datalog::{Relation, Rule, Term};
+
+    let fp = Fixedpoint::new();
+    let mut params = Params::new();
+    params.set_symbol("engine", "datalog");
+    fp.set_params(&params);
+
+    let node = Sort::bitvector(4);
+    let edge = Relation::new("edge", &[&node, &node]);
+    let reach = Relation::new("reach", &[&node]);
+    let n = |v: u64| Term::value(BV::from_u64(v, 4));
+    for (a, b) in [(0, 1), (1, 2), (2, 7), (7, 8)] {
+        Rule::fact(edge.atom([n(a), n(b)])).add_to(&fp);
+    }
+    Rule::fact(reach.atom([n(0)])).add_to(&fp);
+    Rule::new(reach.atom(["y"]))
+        .when(reach.atom(["x"]))
+        .when(edge.atom(["x", "y"]))
+        .constraint(|vars| vars["y"].as_bv().unwrap().bvult(5))
+        .name("step")
+        .add_to(&fp);
+
+    assert_eq!(reach.atom([n(2)]).query(&fp), SatResult::Sat);
+    assert_eq!(reach.atom([n(7)]).query(&fp), SatResult::Unsat);
+    assert_eq!(reach.atom(["z"]).query(&fp), SatResult::Sat);
+}
+
+#[test]
+#[should_panic(expected = "Variable x is used with sorts")]
+fn test_datalog_dsl_sort_mismatch() {
+    use z3::datalog::{Relation, Rule};
+
+    let p = Relation::new("p", &[&Sort::int()]);
+    let q = Relation::new("q", &[&Sort::bool()]);
+    Rule::new(p.atom(["x"])).when(q.atom(["x"])).to_bool();
+}
+
+#[test]
+fn test_finite_domain_datalog() {
+    use z3::datalog::{Relation, Term};
+
+    let fp = Fixedpoint::new();
+    let mut params = Params::new();
+    params.set_symbol("engine", "datalog");
+    fp.set_params(&params);
+
+    let s = Sort::finite_domain("S", 8);
+    assert_eq!(s.finite_domain_size(), Some(8));
+    let r = Relation::new("r", &[&s, &s]);
+    fp.register_relation(r.decl());
+    fp.add_fact(r.decl(), &[1, 5]);
+
+    let v = |i| Term::value(s.finite_domain_value(i).unwrap());
+    assert_eq!(r.atom([v(1), v(5)]).query(&fp), SatResult::Sat);
+    assert_eq!(r.atom([v(5), v(1)]).query(&fp), SatResult::Unsat);
+}
+
+#[test]
+fn test_solver_check_independent() {
+    let solver = Solver::new();
+    let xs: Vec<Int> = (0..4).map(|i| Int::new_const(format!("x{i}"))).collect();
+    for x in &xs {
+        solver.assert(x.gt(0));
+        solver.assert(x.lt(10));
+    }
+    solver.assert(xs[0].lt(&xs[1]));
+    assert_eq!(solver.split_independent().len(), 3);
+    assert_eq!(solver.check_independent(), SatResult::Sat);
+
+    solver.assert(xs[3].gt(20));
+    let verdicts: Vec<SatResult> = solver
+        .split_independent()
+        .iter()
+        .map(Solver::check)
+        .collect();
+    assert_eq!(
+        verdicts,
+        vec![SatResult::Sat, SatResult::Sat, SatResult::Unsat]
+    );
+    assert_eq!(SatResult::combine(verdicts), SatResult::Unsat);
+    assert_eq!(solver.check_independent(), SatResult::Unsat);
+}
+
+#[test]
+fn test_fixedpoint_param_descrs() {
+    let fp = Fixedpoint::new();
+    let descrs = fp.get_param_descrs();
+    assert!(!descrs.is_empty());
+    assert_eq!(descrs.iter().count(), descrs.len());
+
+    let max_level = descrs
+        .iter()
+        .find(|d| d.name == "spacer.max_level")
+        .unwrap();
+    assert_eq!(max_level.kind, ParamKind::UInt);
+    assert!(max_level.documentation.contains("level"));
+    assert_eq!(descrs.get_kind("spacer.q3"), Some(ParamKind::Bool));
+    assert_eq!(descrs.get_documentation("no_such_option"), None);
+}
+
+#[test]
+fn test_model_summary() {
+    let solver = Solver::new();
+    let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    let x = Int::new_const("x");
+    solver.assert(x.eq(3));
+    solver.assert(f.apply(&[&Int::from_i64(1)]).as_int().unwrap().eq(10));
+    solver.assert(f.apply(&[&Int::from_i64(2)]).as_int().unwrap().eq(20));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+
+    let summary = model.summary(10).to_string();
+    assert!(summary.contains("x -> 3\n"));
+    assert!(summary.contains("f -> function with "));
+    assert!(!summary.contains("more"));
+    assert_eq!(model.summary(1).to_string().lines().count(), 2);
+    assert_eq!(model.summary(0).to_string(), "... 2 more\n");
+}
+
+#[test]
+fn test_model_dump() {
+    let solver = Solver::new();
+    let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    let x = Int::new_const("x");
+    solver.assert(x.eq(3));
+    solver.assert(f.apply(&[&Int::from_i64(1)]).as_int().unwrap().eq(10));
+    solver.assert(f.apply(&[&Int::from_i64(2)]).as_int().unwrap().eq(20));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+
+    let dump = model.dump();
+    assert_eq!(dump, model.to_string());
+    assert!(dump.contains("(define-fun x () Int"));
+    let f_interp = &dump[dump.find("(define-fun f ").unwrap()..];
+    assert!(f_interp.contains("10"));
+    assert!(f_interp.contains("20"));
+}
+
+#[test]
+fn test_optimize_scheduling_push_pop() {
+    // Two jobs of length 3 and 2 on one machine, minimizing the makespan.
+    let opt = Optimize::new();
+    let (s1, s2) = (Int::new_const("s1"), Int::new_const("s2"));
+    let end = Int::new_const("end");
+    opt.assert(&s1.ge(0));
+    opt.assert(&s2.ge(0));
+    opt.assert(&Bool::or(&[(&s1 + 3).le(&s2), (&s2 + 2).le(&s1)]));
+    opt.assert(&end.ge(&s1 + 3));
+    opt.assert(&end.ge(&s2 + 2));
+    opt.assert_soft(&s2.eq(0), 1, None);
+    opt.minimize(&end);
+
+    assert_eq!(opt.check(&[]), SatResult::Sat);
+    let model = opt.get_model().unwrap();
+    assert_eq!(model.eval(&end, true).unwrap().as_i64(), Some(5));
+    assert_eq!(model.eval(&s2, true).unwrap().as_i64(), Some(0));
+
+    opt.push();
+    opt.assert(&s1.ge(4));
+    assert_eq!(opt.check(&[]), SatResult::Sat);
+    let model = opt.get_model().unwrap();
+    assert_eq!(model.eval(&end, true).unwrap().as_i64(), Some(7));
+    opt.pop();
+
+    assert_eq!(opt.check(&[]), SatResult::Sat);
+    let model = opt.get_model().unwrap();
+    assert_eq!(model.eval(&end, true).unwrap().as_i64(), Some(5));
+}
+
+#[test]
+fn test_fixedpoint_answer_tuples() {
+    let fp = Fixedpoint::new();
+    let mut params = Params::new();
+    params.set_symbol("engine", "datalog");
+    fp.set_params(&params);
+
+    let s = Sort::finite_domain("S", 8);
+    let edge = FuncDecl::new("edge", &[&s, &s], &Sort::bool());
+    let mark = FuncDecl::new("mark", &[&s], &Sort::bool());
+    fp.register_relation(&edge);
+    fp.register_relation(&mark);
+    for (a, b) in [(1, 2), (2, 3), (5, 3)] {
+        fp.add_fact(&edge, &[a, b]);
+    }
+    fp.add_fact(&mark, &[4]);
+
+    let v = |i| s.finite_domain_value(i).unwrap();
+    assert_eq!(fp.query_relations(&[&edge]), SatResult::Sat);
+    let tuples = fp.get_answer_tuples(&[&edge]).unwrap();
+    let mut edges = tuples[&edge].clone();
+    edges.sort_by_key(|t| t.iter().map(|v| v.to_string()).collect::<Vec<_>>());
+    assert_eq!(
+        edges,
+        vec![vec![v(1), v(2)], vec![v(2), v(3)], vec![v(5), v(3)]]
+    );
+
+    assert_eq!(fp.query_relations(&[&edge, &mark]), SatResult::Sat);
+    let tuples = fp.get_answer_tuples(&[&edge, &mark]).unwrap();
+    assert_eq!(tuples[&edge].len(), 3);
+    assert_eq!(tuples[&mark], vec![vec![v(4)]]);
+}
+
+#[test]
+fn test_detect_logic() {
+    let x = Int::new_const("x");
+    let y = Int::new_const("y");
+    let r = ast::Real::new_const("r");
+    let a = Array::new_const("a", &Sort::bitvector(8), &Sort::bitvector(8));
+    let bv = BV::new_const("bv", 8);
+    let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+
+    let lia = x.lt(&y);
+    assert!(lia.is_quantifier_free());
+    assert_eq!(lia.detect_logic().to_string(), "QF_LIA");
+    assert_eq!((&x * 3).lt(&y).detect_logic().to_string(), "QF_LIA");
+    assert_eq!((&x * &y).lt(2).detect_logic().to_string(), "QF_NIA");
+    assert_eq!(
+        r.gt(ast::Real::from_rational(0, 1))
+            .detect_logic()
+            .to_string(),
+        "QF_LRA"
+    );
+    assert_eq!(
+        a.select(&bv)
+            .as_bv()
+            .unwrap()
+            .eq(&bv)
+            .detect_logic()
+            .to_string(),
+        "QF_ABV"
+    );
+    assert_eq!(
+        f.apply(&[&x])
+            .as_int()
+            .unwrap()
+            .eq(&x)
+            .detect_logic()
+            .to_string(),
+        "QF_UFLIA"
+    );
+    assert_eq!(Bool::new_const("p").detect_logic().to_string(), "QF_UF");
+
+    let b = ast::Real::new_const("b");
+    let quantified = ast::forall_const(&[&b], &[], &(&b * &b).ge(&r));
+    assert!(!quantified.is_quantifier_free());
+    let logic = quantified.detect_logic();
+    assert!(logic.quantifiers && logic.nonlinear);
+    assert_eq!(logic.to_string(), "NRA");
+    assert!(logic.includes(&r.gt(ast::Real::from_rational(0, 1)).detect_logic()));
+    assert!(!logic.includes(&lia.detect_logic()));
+}
+
+#[test]
+fn test_numeral_formatter() {
+    let ctx = Context::thread_local();
+    let x = BV::new_const("x", 8);
+    let t = x
+        .extract(3, 0)
+        .bvadd(BV::from_u64(10, 4))
+        .bvult(BV::from_u64(3, 4));
+    let n = Int::new_const("n");
+    let u = (&n + 1).gt(&n);
+    assert_eq!(t.to_string(), format!("{t:?}"));
+    let before = u.to_string();
+
+    ctx.set_numeral_formatter(|v| {
+        let bv = v.as_bv()?;
+        Some(format!("0x{:x}", bv.as_u64()?))
+    });
+    assert_eq!(t.to_string(), "(bvult (bvadd ((_ extract 3 0) x) 0xa) 0x3)");
+    // Debug output and numerals the hook declines keep Z3's rendering.
+    assert_ne!(format!("{t:?}"), t.to_string());
+    assert_eq!(u.to_string(), "(> (+ n 1) n)");
+
+    // Shared subterms are bound once, so deep DAGs print in linear size.
+    let mut dag = x.bvadd(BV::from_u64(1, 8));
+    for _ in 0..1000 {
+        dag = dag.bvmul(&dag);
+    }
+    let printed = dag.to_string();
+    assert!(printed.len() < 100_000, "{}", printed.len());
+    assert!(printed.starts_with("(let ((a!1 (bvadd x 0x1))) (let ((a!2 (bvmul a!1 a!1)))"));
+
+    // A hook printing terms itself gets Z3's rendering.
+    ctx.set_numeral_formatter(|v| Some(format!("[{v}]")));
+    assert_eq!(
+        t.to_string(),
+        "(bvult (bvadd ((_ extract 3 0) x) [#xa]) [#x3])"
+    );
+
+    ctx.clear_numeral_formatter();
+    assert_eq!(t.to_string(), format!("{t:?}"));
+    assert_eq!(u.to_string(), before);
+}
+
+#[test]
+fn test_model_get_from_model() {
+    let pair = DatatypeBuilder::new("Pair")
+        .variant(
+            "mk-pair",
+            vec![
+                ("name", DatatypeAccessor::Sort(Sort::string())),
+                ("score", DatatypeAccessor::Sort(Sort::real())),
+            ],
+        )
+        .finish();
+    let p = ast::Datatype::new_const("p", &pair.sort);
+    let xs = ast::Seq::new_const("xs", &Sort::bitvector(8));
+    let f = ast::Float::new_const_double("f");
+    let solver = Solver::new();
+    let alice = pair.variants[0]
+        .constructor
+        .apply(&[&ast::String::from("alice"), &ast::Real::from_rational(5, 2)]);
+    solver.assert(p.eq(alice.as_datatype().unwrap()));
+    let elems = [3, 200].map(|v| ast::Seq::unit(&ast::BV::from_u64(v, 8)));
+    solver.assert(xs.eq(ast::Seq::concat(&[&elems[0], &elems[1]])));
+    solver.assert(f.eq(ast::Float::from_f64(0.25)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+
+    assert_eq!(
+        model.get::<(String, f64)>(&p),
+        Some(("alice".to_string(), 2.5))
+    );
+    assert_eq!(model.get::<Vec<u64>>(&xs), Some(vec![3, 200]));
+    assert_eq!(model.get::<f64>(&f), Some(0.25));
+    assert_eq!(model.get::<(String, f64, bool)>(&p), None);
+    assert_eq!(model.get::<Vec<bool>>(&xs), None);
+    assert_eq!(model.get::<String>(&f), None);
+}
+
+#[test]
+fn test_ast_vector_remove_swap_truncate() {
+    let xs: Vec<ast::Int> = (0..5).map(ast::Int::from_i64).collect();
+    let v = AstVector::from_slice(&xs.iter().collect::<Vec<_>>());
+    let names = |v: &AstVector| v.to_vec().iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(v.remove(1).to_string(), "1");
+    assert_eq!(names(&v), ["0", "2", "3", "4"]);
+    v.swap(0, 3);
+    assert_eq!(names(&v), ["4", "2", "3", "0"]);
+    v.truncate(10);
+    assert_eq!(v.len(), 4);
+    v.truncate(2);
+    assert_eq!(names(&v), ["4", "2"]);
+    assert_eq!(v.remove(1).to_string(), "2");
+    v.clear();
+    assert!(v.is_empty());
+}
+
+#[test]
+fn test_formula_template() {
+    let x = Int::new_const("x");
+    let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    let monotone = FormulaTemplate::new(&f.apply(&[&x]).as_int().unwrap().ge(&x), &[&x]);
+
+    let solver = Solver::new();
+    for i in 0..1000 {
+        solver.assert(monotone.apply(&[&Int::from_i64(i)]));
+    }
+    let fx = f.apply(&[&Int::from_i64(999)]).as_int().unwrap();
+    solver.assert(fx.lt(999));
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    // Templates with binders of their own are instantiated by substitution.
+    let y = Int::new_const("y");
+    let c = Int::new_const("c");
+    let quantified = FormulaTemplate::new(&ast::forall_const(&[&y], &[], &(&y * &y).ge(&c)), &[&c]);
+    let instance = quantified.instantiate(&[(&c, &Int::from_i64(0))]);
+    assert_eq!(instance, ast::forall_const(&[&y], &[], &(&y * &y).ge(0)));
+    assert_eq!(quantified.instantiate(&[]), *quantified.formula());
+}
+
+#[test]
+fn test_set_thread_local_config() {
+    std::thread::spawn(|| {
+        let before = Context::thread_local();
+        let mut cfg = Config::new();
+        cfg.set_proof_generation(true);
+        set_thread_local_config(cfg);
+        // An existing thread-local context is replaced.
+        assert_ne!(Context::thread_local(), before);
+        assert_eq!(Context::thread_local(), Context::thread_local());
+
+        let solver = Solver::new();
+        let x = Int::new_const("x");
+        solver.assert(x.gt(0));
+        solver.assert(x.lt(0));
+        assert_eq!(solver.check(), SatResult::Unsat);
+        assert!(solver.get_proof().is_some());
+    })
+    .join()
+    .unwrap();
+
+    // Other threads keep the default configuration.
+    let solver = Solver::new();
+    solver.assert(Bool::from_bool(false));
+    assert_eq!(solver.check(), SatResult::Unsat);
+    assert!(solver.get_proof().is_none());
+}
+
+#[test]
+fn test_goal_to_dimacs_numbered_first() {
+    let x = BV::new_const("x", 3);
+    let bits: Vec<Bool> = (0..3).map(|i| Bool::new_const(format!("bit{i}"))).collect();
+    let goal = Goal::new(false, false, false);
+    for (i, bit) in bits.iter().enumerate() {
+        goal.assert(&bit.iff(x.extract(i as u32, i as u32).eq(BV::from_u64(1, 1))));
+    }
+    goal.assert(&x.bvugt(BV::from_u64(4, 3)));
+    let unused = Bool::new_const("unused");
+
+    let cnf = Tactic::new("simplify")
+        .and_then(&Tactic::new("bit-blast"))
+        .and_then(&Tactic::new("tseitin-cnf"))
+        .apply(&goal, None)
+        .unwrap()
+        .list_subgoals()
+        .next()
+        .unwrap();
+    let first: Vec<&Bool> = bits.iter().chain([&unused]).collect();
+    let dimacs = cnf.to_dimacs(&first).unwrap();
+    for (i, bit) in bits.iter().enumerate() {
+        let var = i as i32 + 1;
+        assert_eq!(dimacs.literal(bit), Some(var));
+        assert_eq!(dimacs.atom(var as u32), Some(bit));
+        assert!(
+            dimacs
+                .clauses()
+                .iter()
+                .any(|c| c.contains(&var) || c.contains(&-var))
+        );
+    }
+    assert_eq!(dimacs.literal(&unused.not()), Some(-4));
+    assert_eq!(dimacs.atom(0), None);
+
+    // x > 4 forces the top bit in every model of the clauses.
+    let solver = Solver::new();
+    for clause in dimacs.clauses() {
+        let lits: Vec<Bool> = clause
+            .iter()
+            .map(|&l| {
+                let atom = dimacs.atom(l.unsigned_abs()).unwrap();
+                if l > 0 { atom.clone() } else { atom.not() }
+            })
+            .collect();
+        solver.assert(Bool::or(&lits.iter().collect::<Vec<_>>()));
+    }
+    solver.assert(bits[2].not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let text = dimacs.to_string();
+    assert!(text.starts_with(&format!(
+        "p cnf {} {}\n",
+        dimacs.num_vars(),
+        dimacs.clauses().len()
+    )));
+    assert!(text.contains("c 1 bit0\n"));
+
+    let not_cnf = Goal::new(false, false, false);
+    not_cnf.assert(&Bool::or(&[&bits[0], &Bool::and(&[&bits[1], &bits[2]])]));
+    assert!(not_cnf.to_dimacs(&[]).is_err());
+    assert!(cnf.to_dimacs(&[&bits[0].not()]).is_err());
+
+    // Negated constants are false or true literals.
+    let constants = Goal::new(false, false, false);
+    let (t, f) = (Bool::from_bool(true), Bool::from_bool(false));
+    constants.assert(&Bool::or(&[&bits[0], &t.not()]));
+    constants.assert(&Bool::or(&[&bits[1], &f.not()]));
+    let dimacs = constants.to_dimacs(&[]).unwrap();
+    assert_eq!(dimacs.clauses(), [vec![1]]);
+}
+
+#[test]
+fn test_user_propagator() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use z3::ast::Dynamic;
+
+    #[derive(Default)]
+    struct Log {
+        fixed: Vec<(String, String)>,
+        finals: usize,
+        rejected_unregistered: bool,
+    }
+
+    // Forbids registered terms from being equal, and records assignments.
+    struct Distinct(Rc<RefCell<Log>>);
+
+    impl UserPropagator for Distinct {
+        fn fixed(&mut self, _: &PropagatorCallback, term: &Dynamic, value: &Dynamic) {
+            self.0
+                .borrow_mut()
+                .fixed
+                .push((term.to_string(), value.to_string()));
+        }
+
+        fn eq(&mut self, cb: &PropagatorCallback, x: &Dynamic, y: &Dynamic) {
+            cb.conflict(&[], &[(x, y)]);
+        }
+
+        fn final_check(&mut self, cb: &PropagatorCallback) {
+            let mut log = self.0.borrow_mut();
+            log.finals += 1;
+            let unregistered = Dynamic::from_ast(&Bool::new_const("unregistered"));
+            log.rejected_unregistered = !cb.conflict(&[&unregistered], &[]);
+        }
+    }
+
+    let log = Rc::new(RefCell::new(Log::default()));
+    assert!(
+        Solver::new()
+            .set_user_propagator(Distinct(log.clone()))
+            .is_err()
+    );
+    let solver = Solver::new_simple();
+    solver.set_user_propagator(Distinct(log.clone())).unwrap();
+    let x = BV::new_const("x", 8);
+    let y = BV::new_const("y", 8);
+    let b = Bool::new_const("b");
+    solver.propagate_register(&x);
+    solver.propagate_register(&y);
+    solver.propagate_register(&b);
+
+    solver.assert(b.implies(x.bvult(&y)));
+    solver.assert(&b);
+    assert_eq!(solver.check(), SatResult::Sat);
+    assert!(log.borrow().finals > 0);
+    assert!(log.borrow().rejected_unregistered);
+    assert!(
+        log.borrow()
+            .fixed
+            .contains(&("b".to_string(), "true".to_string()))
+    );
+
+    // The propagator rejects the equality the solver derives.
+    let solver = Solver::new_simple();
+    solver.set_user_propagator(Distinct(log.clone())).unwrap();
+    solver.propagate_register(&x);
+    solver.propagate_register(&y);
+    solver.assert(x.bvadd(BV::from_u64(1, 8)).eq(y.bvadd(BV::from_u64(1, 8))));
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[cfg(feature = "z3_4_8_15")]
+#[test]
+fn test_user_propagator_declare() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use z3::ast::Dynamic;
+
+    #[derive(Default)]
+    struct Log {
+        created: Vec<String>,
+        fixed: Vec<String>,
+    }
+
+    // Interprets the declared function as the constant 7, and registers
+    // the arguments of its applications.
+    struct Seven(Rc<RefCell<Log>>);
+
+    impl UserPropagator for Seven {
+        fn created(&mut self, cb: &PropagatorCallback, term: &Dynamic) {
+            self.0.borrow_mut().created.push(term.to_string());
+            for arg in term.children() {
+                cb.register(&arg);
+            }
+        }
+
+        fn fixed(&mut self, cb: &PropagatorCallback, term: &Dynamic, value: &Dynamic) {
+            self.0.borrow_mut().fixed.push(term.to_string());
+            let is_app = term.decl().name() == "f";
+            if is_app && value.as_bv().and_then(|v| v.as_u64()) != Some(7) {
+                assert!(cb.conflict(&[term], &[]));
+            }
+        }
+    }
+
+    let log = Rc::new(RefCell::new(Log::default()));
+    let solver = Solver::new_simple();
+    solver.set_user_propagator(Seven(log.clone())).unwrap();
+    let bv8 = Sort::bitvector(8);
+    let f = solver.propagate_declare("f", &[&bv8], &bv8);
+    let x = BV::new_const("x", 8);
+    let fx = f.apply(&[&x]).as_bv().unwrap();
+
+    solver.assert(fx.bvugt(BV::from_u64(5, 8)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    assert_eq!(log.borrow().created, ["(f x)"]);
+    assert!(log.borrow().fixed.contains(&"x".to_string()));
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(&fx, true).unwrap().as_u64(), Some(7));
+
+    solver.assert(fx.eq(BV::from_u64(7, 8)).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[cfg(feature = "z3_4_12_0")]
+#[test]
+fn test_user_propagator_next_split() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use z3::ast::Dynamic;
+
+    // Redirects every split to make `a` false.
+    struct PreferNotA(Bool, Rc<RefCell<usize>>);
+
+    impl UserPropagator for PreferNotA {
+        fn decide(&mut self, cb: &PropagatorCallback, _: &Dynamic, _: u32, _: bool) {
+            *self.1.borrow_mut() += 1;
+            cb.next_split(&self.0, 0, Some(false));
+        }
+    }
+
+    let (a, b) = (Bool::new_const("a"), Bool::new_const("b"));
+    let decisions = Rc::new(RefCell::new(0));
+    let solver = Solver::new_simple();
+    solver
+        .set_user_propagator(PreferNotA(a.clone(), decisions.clone()))
+        .unwrap();
+    solver.propagate_register(&a);
+    solver.propagate_register(&b);
+    solver.assert(Bool::or(&[&a, &b]));
+    assert_eq!(solver.check(), SatResult::Sat);
+    assert!(*decisions.borrow() > 0);
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(&a, true).unwrap().as_bool(), Some(false));
+}
+
+#[test]
+fn test_grammar_synthesize() {
+    let x = BV::new_const("x", 8);
+    let mut grammar = Grammar::new();
+    grammar
+        .leaf(&x)
+        .leaf(&BV::from_u64(1, 8))
+        .operator(&x.bvand(&x).decl())
+        .operator(&x.bvsub(&x).decl());
+
+    // Sizes 1 and 3 only, since both operators are binary.
+    let terms = grammar.terms(&Sort::bitvector(8), 3);
+    assert_eq!(
+        terms[..2],
+        [ast::Dynamic::from_ast(&x), BV::from_u64(1, 8).into()]
+    );
+    assert!(terms[2..].iter().all(|t| t.num_children() == 2));
+    assert!(grammar.terms(&Sort::bool(), 3).is_empty());
+
+    // Clear the lowest set bit of x.
+    let hole = BV::new_const("hole", 8);
+    let spec = hole.eq(x.bvand(x.bvsub(1)));
+    let found = grammar.synthesize(&spec, &hole, 5).unwrap();
+    let solver = Solver::new();
+    solver.assert(found.eq(x.bvand(x.bvsub(1))).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let spec = hole.eq(x.bvmul(3));
+    assert_eq!(grammar.synthesize(&spec, &hole, 5), None);
+}
+
+#[test]
+fn test_bv_resize_to() {
+    let x = BV::new_const("x", 8);
+    assert_eq!(x.resize_to(8, true), x);
+    assert_eq!(x.resize_to(12, true).get_size(), 12);
+    assert_eq!(x.resize_to(3, false).get_size(), 3);
+
+    // Signed truncation keeps the value iff it is in -8..8.
+    let (y, fits) = x.resize_to_checked(4, true);
+    let solver = Solver::new();
+    solver.assert(&fits);
+    solver.assert(x.bvsge(8));
+    assert_eq!(solver.check(), SatResult::Unsat);
+    solver.reset();
+    solver.assert(&fits);
+    solver.assert(x.eq(BV::from_i64(-8, 8)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(&y, true).unwrap(), BV::from_i64(-8, 4));
+
+    let (_, fits) = x.resize_to_checked(16, false);
+    assert_eq!(fits.simplify().as_bool(), Some(true));
+
+    let (a, b) = BV::common_width(&BV::from_i64(-1, 4), &x, true);
+    assert_eq!((a.get_size(), b.get_size()), (8, 8));
+    assert_eq!(a.simplify(), BV::from_i64(-1, 8));
+    assert_eq!(b, x);
+}
+
+#[test]
+fn test_mod_arithmetic() {
+    type F13 = Mod<13>;
+    assert_eq!(F13::modulus(), 13);
+    assert_eq!(F13::from_u64(30).as_u64(), Some(4));
+    assert_eq!((-F13::from_u64(4)).as_u64(), Some(9));
+    assert_eq!((F13::from_u64(4) - F13::from_u64(6)).as_u64(), Some(11));
+    assert_eq!(F13::from_u64(2).pow(12).as_u64(), Some(1));
+    assert_eq!(Mod::<8>::from_u64(3).pow(0).as_u64(), Some(1));
+
+    // The square roots of 10 modulo 13 are 6 and 7.
+    let x = F13::new_const("x");
+    let solver = Solver::new();
+    solver.assert(x.pow(2).eq(&F13::from_u64(10)));
+    solver.assert(x.eq(&F13::from_u64(6)).not());
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(x.value(), true).unwrap().as_u64(), Some(7));
+
+    // Symbolic inverses exist exactly for values coprime to the modulus.
+    let y = Mod::<12>::new_const("y");
+    let (inv, exists) = y.inverse();
+    let solver = Solver::new();
+    solver.assert(&exists);
+    solver.assert((&y * &inv).eq(&Mod::from_u64(1)).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+    solver.reset();
+    solver.assert(&exists);
+    solver.assert(y.eq(&Mod::from_u64(9)));
+    assert_eq!(solver.check(), SatResult::Unsat);
+    solver.reset();
+    solver.assert(&exists);
+    solver.assert(y.eq(&Mod::from_u64(5)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(inv.value(), true).unwrap().as_u64(), Some(5));
+}
+
+#[test]
+fn test_structured_proof() {
+    std::thread::spawn(|| {
+        let mut cfg = Config::new();
+        cfg.set_proof_generation(true);
+        set_thread_local_config(cfg);
+
+        let solver = Solver::new();
+        let x = Int::new_const("x");
+        let p = Bool::new_const("p");
+        solver.assert(p.implies(x.gt(2)));
+        solver.assert(&p);
+        solver.assert(x.lt(1));
+        assert_eq!(solver.check(), SatResult::Unsat);
+
+        let proof = solver.get_structured_proof().unwrap();
+        assert_eq!(proof.conclusion(), Bool::from_bool(false));
+        assert_eq!(Proof::from_ast(proof.as_ast()), Some(proof.clone()));
+        assert_eq!(Proof::from_ast(&x), None);
+
+        let steps = proof.steps();
+        assert_eq!(steps.last(), Some(&proof));
+        for (i, step) in steps.iter().enumerate() {
+            // Each step is listed once, after its premises.
+            assert!(!steps[..i].contains(step));
+            for premise in step.premises() {
+                assert!(steps[..i].contains(&premise));
+            }
+        }
+        let asserted: Vec<Proof> = steps
+            .into_iter()
+            .filter(|s| s.rule() == DeclKind::PR_ASSERTED)
+            .collect();
+        assert!(asserted.iter().all(|s| s.premises().is_empty()));
+        assert!(asserted.iter().any(|s| s.conclusion() == p));
+        assert_eq!(asserted[0].rule_name(), "asserted");
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_parse_error_location() {
+    let solver = Solver::new();
+    let err = solver
+        .from_string("(declare-const x Int)\n(assert (> x 1))\n  (assert (< x y))")
+        .unwrap_err();
+    assert_eq!((err.line(), err.column()), (Some(3), Some(15)));
+    assert_eq!(err.message(), "unknown constant y");
+    assert_eq!(err.to_string(), "line 3 column 15: unknown constant y");
+    solver
+        .from_string("(declare-const z Int)\n(assert (> z 1))")
+        .unwrap();
+
+    let err = Optimize::new()
+        .from_file("/nonexistent/problem.smt2")
+        .unwrap_err();
+    assert_eq!((err.line(), err.column()), (None, None));
+    assert!(err.message().contains("/nonexistent/problem.smt2"));
+
+    // The fixedpoint parser reports errors without their location.
+    assert!(
+        Fixedpoint::new()
+            .from_string("(declare-rel p (Int))\n(rule (p z))")
+            .is_err()
+    );
+    assert!(Solver::new().from_string("(assert \0)").is_err());
+}
+
+#[test]
+fn test_assert_and_track_auto() {
+    let solver = Solver::new();
+    let (a, b) = (Bool::new_const("a"), Bool::new_const("b"));
+    let ids = [
+        solver.assert_and_track_auto(&a),
+        solver.assert_and_track_auto(Bool::or(&[&a, &b])),
+        solver.assert_and_track_auto(a.not()),
+    ];
+    assert_eq!(ids.map(|id| id.index()), [0, 1, 2]);
+    // Assumptions are not reported as tracked constraints.
+    assert_eq!(
+        solver.check_assumptions(std::slice::from_ref(&b)),
+        SatResult::Unsat
+    );
+    assert_eq!(solver.get_unsat_core_ids(), [ids[0], ids[2]]);
+    assert_eq!(solver.get_unsat_core().len(), 2);
+
+    let copy = solver.clone();
+    assert_eq!(copy.check(), SatResult::Unsat);
+    assert_eq!(copy.get_unsat_core_ids(), [ids[0], ids[2]]);
+
+    let solver = Solver::new();
+    solver.assert_and_track_auto(&a);
+    assert_eq!(solver.check(), SatResult::Sat);
+    assert!(solver.get_unsat_core_ids().is_empty());
+}
+
+#[test]
+fn test_smt2_session() {
+    let session = Smt2Session::new();
+    assert_eq!(
+        session.eval("(set-option :print-success true)"),
+        [Smt2Response::Success]
+    );
+    assert_eq!(
+        session.eval("(declare-const x Int)\n(assert (= x 3))\n(check-sat)"),
+        [
+            Smt2Response::Success,
+            Smt2Response::Success,
+            Smt2Response::Sat
+        ]
+    );
+    assert_eq!(
+        session.eval("(get-value (x))"),
+        [Smt2Response::Output("((x 3))".to_string())]
+    );
+    assert_eq!(session.eval("(foo)")[0], Smt2Response::Unsupported);
+    match &session.eval("(check-sat)\n(assert (= z 1))")[..] {
+        [Smt2Response::Sat, Smt2Response::Error(err)] => {
+            assert_eq!((err.line(), err.column()), (Some(2), Some(11)));
+        }
+        other => panic!("unexpected responses {other:?}"),
+    }
+    assert_eq!(
+        session.eval("(echo \"a (b\")"),
+        [Smt2Response::Output("a (b".to_string())]
+    );
+
+    // Sessions do not share declarations.
+    let other = Smt2Session::new();
+    assert!(matches!(
+        other.eval("(assert (= x 1))")[..],
+        [Smt2Response::Error(_)]
+    ));
+    assert!(matches!(other.eval("\0")[..], [Smt2Response::Error(_)]));
+}
+
+#[test]
+fn test_minimize_core() {
+    let solver = Solver::new();
+    let x = Int::new_const("x");
+    let (p, q, r, s) = (
+        Bool::new_const("p"),
+        Bool::new_const("q"),
+        Bool::new_const("r"),
+        Bool::new_const("s"),
+    );
+    solver.assert_and_track(x.gt(10), &p);
+    solver.assert_and_track(x.gt(0), &q);
+    solver.assert_and_track(x.lt(5), &r);
+    solver.assert_and_track(x.lt(20), &s);
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let core = z3::unsat_core::minimize(&solver, &[p.clone(), q, r.clone(), s]);
+    assert_eq!(core, [p, r]);
+    assert!(z3::unsat_core::minimize(&solver, &[]).is_empty());
+}
+
+#[test]
+fn test_validate_model() {
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    let solver = Solver::new();
+    solver.assert(x.gt(&y));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert_eq!(solver.validate_model(&model), Ok(()));
+
+    // A model of other constraints, which leaves `y` to model completion.
+    let other = Solver::new();
+    other.assert(x.eq(-1));
+    assert_eq!(other.check(), SatResult::Sat);
+    let model = other.get_model().unwrap();
+    solver.assert(x.ge(0));
+    solver.assert(y.lt(5));
+    assert_eq!(solver.validate_model(&model), Err(vec![x.gt(&y), x.ge(0)]));
+}
+
+#[test]
+fn test_solver_trail_and_levels() {
+    let solver = Solver::new_simple();
+    let (a, b, c) = (
+        Bool::new_const("a"),
+        Bool::new_const("b"),
+        Bool::new_const("c"),
+    );
+    solver.assert(&a);
+    solver.assert(Bool::or(&[a.not(), b.clone()]));
+    solver.assert(Bool::or(&[b.not(), c.clone(), c.not()]));
+    solver.assert(Bool::or(&[c.clone(), Bool::new_const("d")]));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let trail = solver.get_trail();
+    let levels = solver.get_levels(&trail);
+    assert_eq!(trail[..2], [a.clone(), b.clone()]);
+    assert_eq!(levels[..2], [0, 0]);
+    assert!(levels.is_sorted());
+
+    let units = solver.get_units();
+    assert!(units.contains(&a) && units.contains(&b));
+    assert!(!solver.get_non_units().is_empty());
+    assert!(solver.get_levels(&[]).is_empty());
+}
+
+#[test]
+fn test_qe_cache() {
+    use z3::quantifier_elimination::{QeCache, qe_lite};
+
+    let qe = QeCache::new();
+    let (x, y, z) = (
+        Int::new_const("x"),
+        Int::new_const("y"),
+        Int::new_const("z"),
+    );
+    let formula = Bool::and(&[x.eq(&y), y.gt(3), z.lt(&y)]);
+    let vars = || AstVector::from_slice(&[&x, &z]);
+
+    let expected = qe_lite(&[&x, &z], &formula).unwrap();
+    let expected_vars = vars();
+    assert_eq!(qe.lite(&expected_vars, &formula), expected);
+    for _ in 0..2 {
+        let remaining = vars();
+        assert_eq!(qe.lite(&remaining, &formula), expected);
+        assert_eq!(remaining.to_vec(), expected_vars.to_vec());
+    }
+    assert_eq!(qe.num_cached(), 1);
+
+    // Other variables are another entry.
+    let by_y = AstVector::from_slice(&[&y]);
+    assert_eq!(qe.lite(&by_y, &formula), qe_lite(&[&y], &formula).unwrap());
+    assert_eq!(qe.num_cached(), 2);
+
+    qe.clear();
+    assert_eq!(qe.num_cached(), 0);
+}
+
+#[test]
+fn test_float_real_exact_conversion() {
+    use z3::ast::{Float, Real, RoundingMode};
+
+    let x = Float::new_const_double("x");
+    let (real, finite) = x.to_real_exact();
+    let solver = Solver::new();
+    solver.assert(&finite);
+    solver.assert(x.eq(Float::from_f64(0.375)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let value = model.eval(&x, true).unwrap();
+    assert_eq!(value.as_big_rational(), BigRational::from_float(0.375));
+    assert_eq!(model.eval(&real, true).unwrap().as_f64_exact(), Some(0.375));
+
+    // The real value of the infinities is not constrained.
+    let solver = Solver::new();
+    solver.assert(x.is_infinite());
+    solver.assert(finite);
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    assert_eq!(Float::nan64().as_big_rational(), None);
+    assert_eq!(x.as_big_rational(), None);
+    assert_eq!(Real::new_const("r").as_big_rational(), None);
+
+    let big = BigRational::new(BigInt::from(10).pow(30u32), 7.into());
+    assert_eq!(Real::from_big_rational(&big).as_big_rational(), Some(big));
+
+    let rtn = RoundingMode::round_towards_negative();
+    let tenth = Real::from_rational(1, 10).to_float(&Sort::float32(), &rtn);
+    let tenth = tenth.simplify().as_big_rational().unwrap();
+    assert!(tenth < BigRational::new(1.into(), 10.into()));
+    assert_eq!(Some(tenth), BigRational::from_float(0.1f32.next_down()));
+}
+
+#[test]
+fn test_solver_scope_guard() {
+    let solver = Solver::new();
+    let x = Int::new_const("x");
+    solver.assert(x.gt(0));
+    {
+        let outer = solver.push_scoped();
+        outer.assert(x.lt(10));
+        {
+            let inner = outer.push_scoped();
+            inner.assert(x.gt(20));
+            assert_eq!(inner.check(), SatResult::Unsat);
+        }
+        assert_eq!(outer.check(), SatResult::Sat);
+        assert_eq!(outer.get_assertions().len(), 2);
+    }
+    assert_eq!(solver.get_assertions().len(), 1);
+
+    // The scope is popped when unwinding from a panic.
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        solver.scope(|s| {
+            s.assert(x.lt(0));
+            panic!("in scope");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(solver.get_assertions().len(), 1);
+    assert_eq!(solver.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_roundoff_error() {
+    use z3::ast::{Float, Real, RoundingMode};
+
+    let rne = RoundingMode::round_nearest_ties_to_even();
+    let (x, y) = (Float::new_const_double("x"), Float::new_const_double("y"));
+    let expr = x
+        .mul_with_rounding_mode(&y, &rne)
+        .add_with_rounding_mode(&x, &rne);
+    let roundoff = Roundoff::new(&expr).unwrap();
+    assert_eq!(
+        roundoff.ideal().simplify(),
+        (x.to_real() * y.to_real() + x.to_real()).simplify()
+    );
+
+    // 0.1 * 3 + 0.1 happens to round to 4 * 0.1, but 0.1 * 0.7 + 0.1 does not.
+    for (y_value, rounded) in [(3.0, false), (0.7, true)] {
+        let solver = Solver::new();
+        solver.assert(roundoff.finite());
+        solver.assert(x.eq(Float::from_f64(0.1)));
+        solver.assert(y.eq(Float::from_f64(y_value)));
+        solver.assert(roundoff.error().gt(Real::from_rational(0, 1)));
+        let expected = if rounded {
+            SatResult::Sat
+        } else {
+            SatResult::Unsat
+        };
+        assert_eq!(solver.check(), expected);
+    }
+
+    // Exact operations have no error.
+    let exact = Float::from_f64(1.5).add_with_rounding_mode(Float::from_f64(0.25), &rne);
+    let roundoff = Roundoff::new(&exact).unwrap();
+    assert_eq!(roundoff.error().simplify().as_f64_exact(), Some(0.0));
+
+    // A real rounded to a float is an input with its exact value.
+    let third = Real::from_rational(1, 3).to_float(&Sort::float32(), &rne);
+    let roundoff = Roundoff::new(&third.sqrt_with_rounding_mode(&rne)).unwrap();
+    assert_eq!(
+        roundoff.ideal().simplify().to_string(),
+        "(root-obj (+ (* 3 (^ x 2)) (- 1)) 2)"
+    );
+
+    assert!(Roundoff::new(&x.rem(&y)).is_none());
+}
+
+#[test]
+fn test_solver_models() {
+    let solver = Solver::new();
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    solver.assert(x.ge(0));
+    solver.assert(x.lt(4));
+    solver.assert(y.eq(&x * 2));
+    let mut values: Vec<i64> = solver
+        .models(&[&x])
+        .map(|m| m.eval(&x, true).unwrap().as_i64().unwrap())
+        .collect();
+    values.sort();
+    assert_eq!(values, [0, 1, 2, 3]);
+    // The solver itself is unchanged.
+    assert_eq!(solver.get_assertions().len(), 3);
+
+    // There are four arrays, or functions, from Bool to Bool.
+    let a = Array::new_const("a", &Sort::bool(), &Sort::bool());
+    let solver = Solver::new();
+    solver.assert(Bool::from_bool(true));
+    assert_eq!(solver.models(&[&a]).count(), 4);
+    let f = FuncDecl::new("f", &[&Sort::bool()], &Sort::bool());
+    let f_true = f.apply(&[&Bool::from_bool(true)]);
+    let f_false = f.apply(&[&Bool::from_bool(false)]);
+    assert_eq!(solver.models(&[&f_true, &f_false]).count(), 4);
+    let c = FuncDecl::new("c", &[], &Sort::bool());
+    let (ft, ff) = (f_true.as_bool().unwrap(), f_false.as_bool().unwrap());
+    let solver_f = Solver::new();
+    solver_f.assert(Bool::or(&[&ft, &ff]));
+    solver_f.assert(c.apply(&[]).as_bool().unwrap().xor(&ft));
+    assert_eq!(solver_f.models_with_functions(&[], &[&f]).count(), 3);
+    assert_eq!(solver_f.models_with_functions(&[], &[&f, &c]).count(), 3);
+    assert_eq!(solver_f.models_with_functions(&[], &[&c]).count(), 2);
+
+    assert_eq!(solver.models(&[]).count(), 1);
+    solver.assert(Bool::from_bool(false));
+    assert_eq!(solver.models(&[&a]).count(), 0);
+}
+
+#[test]
+fn test_generalize() {
+    let f = FuncDecl::new("f", &[&Sort::int(), &Sort::int()], &Sort::int());
+    let (x, y, z) = (
+        Int::new_const("x"),
+        Int::new_const("y"),
+        Int::new_const("z"),
+    );
+    let a = f.apply(&[&x, &(&x + &z)]);
+    let b = f.apply(&[&y, &(&y + &z)]);
+    let g = generalize(&a, &b).unwrap();
+    assert_eq!(g.placeholders().len(), 1);
+    let apply = |subst: Vec<(ast::Dynamic, ast::Dynamic)>| {
+        let subst: Vec<_> = subst.iter().map(|(p, t)| (p, t)).collect();
+        g.pattern().substitute(&subst)
+    };
+    assert_eq!(apply(g.left()), a);
+    assert_eq!(apply(g.right()), b);
+
+    // Different pairs get different placeholders.
+    let g = generalize(&f.apply(&[&x, &y]), &f.apply(&[&y, &x])).unwrap();
+    assert_eq!(g.placeholders().len(), 2);
+
+    // Different heads generalize to a single placeholder.
+    let g = generalize(&(&x + &y), &(&x * &y)).unwrap();
+    assert_eq!(g.placeholders(), [g.pattern().clone()]);
+
+    assert_eq!(generalize(&a, &a).unwrap().pattern(), &a);
+    assert!(generalize(&x, &Bool::new_const("b")).is_none());
+
+    // Shared subterms are generalized once.
+    let (mut a, mut b) = (x.clone(), y.clone());
+    for _ in 0..100 {
+        a = &a * &a;
+        b = &b * &b;
+    }
+    let g = generalize(&a, &b).unwrap();
+    assert_eq!(g.left(), [(g.placeholders()[0].clone(), x.into())]);
+}
+
+#[test]
+fn test_bmc_incremental_deepening() {
+    // Two counters: `a` doubles, `b` increments; bad once `a == b + 5`.
+    let (a, b) = (Int::new_const("a"), Int::new_const("b"));
+    let (a2, b2) = (Int::new_const("a'"), Int::new_const("b'"));
+    let init = a.eq(1) & b.eq(0);
+    let trans = a2.eq(&a * 2) & b2.eq(&b + 1);
+    let mut bmc = Bmc::new(&[&a, &b], &[&a2, &b2], &init, &trans, &a.eq(&b + 5));
+    assert_eq!(bmc.depth(), 0);
+
+    assert_eq!(bmc.check(2), SatResult::Unsat);
+    assert_eq!(bmc.depth(), 2);
+    // The init, and per step one guarded bad-state condition, one transition
+    // (except for step 0) and one retired activation literal for step 2.
+    assert_eq!(bmc.solver().get_assertions().len(), 1 + 3 + 2 + 1);
+
+    // Deepening only adds the new steps.
+    bmc.unroll(4);
+    assert_eq!(bmc.solver().get_assertions().len(), 1 + 5 + 4 + 1);
+    bmc.unroll(3);
+    assert_eq!(bmc.depth(), 4);
+
+    // 1, 2, 4, 8, 16 against 0, 1, 2, 3, 4.
+    assert_eq!(bmc.find(10), Some(3));
+    let model = bmc.solver().get_model().unwrap();
+    let trace = bmc.trace(&model);
+    assert_eq!(trace.variables(), ["a", "b"]);
+    let a_values: Vec<i64> = trace
+        .history("a")
+        .map(|v| v.unwrap().as_int().unwrap().as_i64().unwrap())
+        .collect();
+    assert_eq!(a_values, [1, 2, 4, 8, 16]);
+    assert_eq!(
+        model
+            .eval(&bmc.state(3).unwrap()[1], true)
+            .unwrap()
+            .as_int()
+            .unwrap()
+            .as_i64(),
+        Some(3)
+    );
+    assert!(bmc.activation(5).is_none());
+
+    // A property that never fails.
+    let mut bmc = Bmc::new(&[&a, &b], &[&a2, &b2], &init, &trans, &a.le(&b));
+    assert_eq!(bmc.find(8), None);
+    assert_eq!(bmc.depth(), 8);
+    assert_eq!(bmc.check(8), SatResult::Unsat);
+}
+
+#[test]
+fn test_portfolio() {
+    use std::time::Duration;
+
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    let strategies = [
+        Strategy::new().random_seed(1),
+        Strategy::new().tactic("qflia").random_seed(2),
+        Strategy::new().tactic("smt"),
+    ];
+
+    let sat = [x.gt(&y), y.ge(3), (&x + &y).eq(9)];
+    let outcome = Portfolio::check(&sat, &strategies, Duration::from_secs(30));
+    assert_eq!(outcome.result(), SatResult::Sat);
+    assert!(outcome.strategy().unwrap() < strategies.len());
+    let model = outcome.model().unwrap();
+    for assertion in &sat {
+        assert_eq!(model.eval(assertion, true).unwrap().as_bool(), Some(true));
+    }
+
+    let unsat = [x.gt(&y), y.gt(&x)];
+    let outcome = Portfolio::check(&unsat, &strategies, Duration::from_secs(30));
+    assert_eq!(outcome.result(), SatResult::Unsat);
+    assert!(outcome.model().is_none());
+
+    // Without strategies, or without time, there is no answer.
+    let outcome = Portfolio::check(&sat, &[], Duration::from_secs(30));
+    assert_eq!(outcome.result(), SatResult::Unknown);
+    assert_eq!(outcome.strategy(), None);
+    let outcome = Portfolio::check(&sat, &strategies, Duration::ZERO);
+    assert_eq!(outcome.result(), SatResult::Unknown);
+}
+
+#[test]
+fn test_portfolio_interrupts_losers() {
+    use std::time::{Duration, Instant};
+
+    // A hard nonlinear problem that no solver finishes quickly.
+    let vars: Vec<Int> = (0..4).map(|i| Int::new_const(format!("v{i}"))).collect();
+    let cube = |v: &Int| v * v * v;
+    let hard = [
+        (cube(&vars[0]) + cube(&vars[1]) + cube(&vars[2])).eq(cube(&vars[3]) + 33),
+        vars[3].gt(1000),
+    ];
+    let start = Instant::now();
+    let outcome = Portfolio::check(
+        &hard,
+        &[Strategy::new(), Strategy::new().random_seed(7)],
+        Duration::from_millis(200),
+    );
+    assert_eq!(outcome.result(), SatResult::Unknown);
+    assert!(start.elapsed() < Duration::from_secs(20));
+}
+
+#[test]
+fn test_check_with_timeout_and_cancellation() {
+    use std::time::{Duration, Instant};
+
+    let vars: Vec<Int> = (0..4).map(|i| Int::new_const(format!("v{i}"))).collect();
+    let cube = |v: &Int| v * v * v;
+    let solver = Solver::new();
+    solver.assert((cube(&vars[0]) + cube(&vars[1]) + cube(&vars[2])).eq(cube(&vars[3]) + 33));
+    solver.assert(vars[3].gt(1000));
+
+    let start = Instant::now();
+    assert_eq!(
+        solver.check_with_timeout(Duration::from_millis(100)),
+        SatResult::Unknown
+    );
+    assert!(start.elapsed() < Duration::from_secs(20));
+    assert_eq!(solver.get_reason_unknown().as_deref(), Some("interrupted"));
+
+    // The timer does not outlive a quick check.
+    let easy = Solver::new();
+    easy.assert(vars[0].gt(3));
+    assert_eq!(
+        easy.check_with_timeout(Duration::from_secs(3600)),
+        SatResult::Sat
+    );
+
+    let token = CancellationToken::new();
+    assert!(!token.is_cancelled());
+    let canceller = token.clone();
+    let handle = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        canceller.cancel();
+    });
+    assert_eq!(solver.check_cancellable(&token), SatResult::Unknown);
+    handle.join().unwrap();
+    assert!(token.is_cancelled());
+
+    // A cancelled token stops later checks before they start.
+    assert_eq!(easy.check_cancellable(&token), SatResult::Unknown);
+    assert_eq!(
+        easy.check_cancellable(&CancellationToken::new()),
+        SatResult::Sat
+    );
+}
+
+#[test]
+fn test_model_diff() {
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    let b = Bool::new_const("b");
+    let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    let fx = f.apply(&[&x]);
+
+    let solver = Solver::new();
+    solver.assert(x.eq(2) & y.gt(0) & fx.as_int().unwrap().eq(&y));
+    let models: Vec<Model> = [(1, true), (1, false), (5, true)]
+        .iter()
+        .map(|&(y_value, b_value)| {
+            solver.scope(|s| {
+                s.assert(y.eq(y_value));
+                s.assert(b.eq(Bool::from_bool(b_value)));
+                assert_eq!(s.check(), SatResult::Sat);
+                s.get_model().unwrap()
+            })
+        })
+        .collect();
+    let terms: [&dyn Ast; 4] = [&x, &y, &b, &fx];
+
+    assert!(models[0].diff(&models[0], &terms).is_empty());
+
+    let diff = models[0].diff(&models[1], &terms);
+    assert_eq!(diff.len(), 1);
+    assert_eq!(diff[0].0, ast::Dynamic::from_ast(&b));
+    assert_eq!(diff[0].1.as_bool().unwrap().as_bool(), Some(true));
+    assert_eq!(diff[0].2.as_bool().unwrap().as_bool(), Some(false));
+
+    // Compound terms are compared by value, in the order given.
+    let diff = models[2].diff(&models[0], &terms);
+    let changed: Vec<String> = diff.iter().map(|(t, _, _)| t.to_string()).collect();
+    assert_eq!(changed, ["y", "(f x)"]);
+    assert_eq!(diff[1].1.as_int().unwrap().as_i64(), Some(5));
+    assert_eq!(diff[1].2.as_int().unwrap().as_i64(), Some(1));
+}
+
+#[test]
+fn test_check_with_rlimit() {
+    let vars: Vec<Int> = (0..4).map(|i| Int::new_const(format!("v{i}"))).collect();
+    let cube = |v: &Int| v * v * v;
+    let hard = Solver::new();
+    hard.assert((cube(&vars[0]) + cube(&vars[1]) + cube(&vars[2])).eq(cube(&vars[3]) + 33));
+    hard.assert(vars[3].gt(1000));
+
+    let start = hard.get_statistics().rlimit_count().unwrap_or(0);
+    let (result, used) = hard.check_with_rlimit(50_000);
+    assert_eq!(result, SatResult::Unknown);
+    assert!(used >= 50_000);
+    assert_eq!(hard.get_statistics().rlimit_count(), Some(start + used));
+
+    // The limit only applies to the one check: each check gets a fresh
+    // budget, and afterwards the solver is unlimited again.
+    let (result, used_again) = hard.check_with_rlimit(50_000);
+    assert_eq!(result, SatResult::Unknown);
+    assert!(used_again >= 50_000);
+
+    let easy = Solver::new();
+    easy.assert(vars[0].gt(3) & vars[1].lt(&vars[0]));
+    let (result, used) = easy.check_with_rlimit(1_000_000);
+    assert_eq!(result, SatResult::Sat);
+    assert!(used > 0 && used < 1_000_000);
+
+    // A limit the caller set is restored afterwards.
+    let mut params = Params::new();
+    params.set_u32("rlimit", 50_000);
+    hard.set_params(&params);
+    assert_eq!(hard.check_with_rlimit(100_000).0, SatResult::Unknown);
+    assert_eq!(hard.check(), SatResult::Unknown);
+    assert_eq!(
+        hard.get_reason_unknown().as_deref(),
+        Some("max. resource limit exceeded")
+    );
+}
+
+#[test]
+fn test_model_sorted_display() {
+    let g = FuncDecl::new("g", &[&Sort::int(), &Sort::bool()], &Sort::int());
+    let (z, a) = (Int::new_const("z"), Int::new_const("a"));
+    let (q, p) = (Bool::new_const("q"), Bool::new_const("p"));
+    let arr = ast::Array::new_const("arr", &Sort::int(), &Sort::bool());
+
+    let solver = Solver::new();
+    solver.assert(z.eq(10) & a.eq(-2) & &p & q.not());
+    solver.assert(g.apply(&[&z, &p]).as_int().unwrap().eq(7));
+    solver.assert(g.apply(&[&a, &q]).as_int().unwrap().eq(8));
+    solver.assert(g.apply(&[&z, &q]).as_int().unwrap().eq(9));
+    solver.assert(arr.select(&z).as_bool().unwrap());
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let sorted = model.sorted().to_string();
+    assert_eq!(sorted, model.sorted().to_string());
+
+    // Groups by sort name, constants by name within each group.
+    let lines: Vec<&str> = sorted.lines().collect();
+    let group = |sort: &str| lines.iter().position(|l| *l == format!("; {sort}"));
+    let (array, bool, int) = (
+        group("(Array Int Bool)").unwrap(),
+        group("Bool").unwrap(),
+        group("Int").unwrap(),
+    );
+    assert!(array < bool && bool < int);
+    assert!(lines[array + 1].starts_with("arr ->"));
+    assert_eq!(lines[bool + 1..bool + 3], ["p -> true", "q -> false"]);
+    assert_eq!(lines[int + 1..int + 3], ["a -> (- 2)", "z -> 10"]);
+
+    // Functions are tabulated with the entries sorted by their arguments.
+    let header = lines
+        .iter()
+        .position(|l| *l == "g : Int Bool -> Int")
+        .unwrap();
+    let rows: Vec<&str> = lines[header + 1..]
+        .iter()
+        .take_while(|l| l.starts_with("  ") && !l.starts_with("  else"))
+        .copied()
+        .collect();
+    assert!(rows.windows(2).all(|w| w[0] < w[1]));
+    let else_line = lines[header + rows.len() + 1];
+    assert!(else_line.starts_with("  else -> "));
+    for (args, value) in [("(- 2), false", 8), ("10, false", 9), ("10, true", 7)] {
+        let row = format!("  {args} -> {value}");
+        assert!(rows.contains(&row.as_str()) || else_line == format!("  else -> {value}"));
+    }
+}
+
+#[test]
+fn test_context_declarations() {
+    let ctx = Context::thread_local();
+    let _before = Int::new_const("before");
+    assert!(ctx.declarations().is_none());
+    ctx.enable_declarations();
+
+    let x = Int::new_const("x");
+    let _x_again = Int::new_const("x");
+    let _fresh = Bool::fresh_const("t");
+    let s = Sort::uninterpreted(Symbol::from("S"));
+    let f = FuncDecl::new("f", &[&s, &Sort::int()], &Sort::bool());
+    let (color, _, _) = Sort::enumeration(
+        Symbol::from("Color"),
+        &[Symbol::from("red"), Symbol::from("green")],
+    );
+    // Terms built from declarations do not add any.
+    let _sum = &x + 1;
+
+    let solver = Solver::new();
+    solver
+        .from_string(
+            "(declare-sort T 0) (declare-fun g (T) Int) (declare-const c T) \
+             (assert (> (g c) 0))",
+        )
+        .unwrap();
+
+    let decls = ctx.declarations().unwrap();
+    let names: Vec<String> = decls.func_decls().iter().map(|d| d.name()).collect();
+    // Fresh constants are not recorded.
+    assert_eq!(names.len(), 4);
+    assert_eq!(names[0], "x");
+    assert_eq!(names[1], "f");
+    assert_eq!(decls.func_decls()[1], f);
+    let mut parsed = names[2..].to_vec();
+    parsed.sort();
+    assert_eq!(parsed, ["c", "g"]);
+
+    let sorts: Vec<String> = decls.sorts().iter().map(|s| s.to_string()).collect();
+    assert_eq!(sorts, ["S", "Color", "T"]);
+    assert_eq!(decls.sorts()[..2], [s, color]);
+
+    assert!(decls.contains("g") && decls.contains("T") && !decls.contains("before"));
+    assert_eq!(decls.fresh_name("y"), "y");
+    assert_eq!(decls.fresh_name("x"), "x0");
+
+    ctx.disable_declarations();
+    assert!(ctx.declarations().is_none());
+    let _y = Int::new_const("y");
+    ctx.enable_declarations();
+    assert!(ctx.declarations().unwrap().func_decls().is_empty());
+    ctx.disable_declarations();
+
+    // A context is released with its registry.
+    with_z3_config(&Config::new(), || {
+        Context::thread_local().enable_declarations();
+        Int::new_const("z");
+        let decls = Context::thread_local().declarations().unwrap();
+        assert_eq!(decls.func_decls()[0].name(), "z");
+    });
+}
+
+#[test]
+fn test_solver_to_dimacs() {
+    let x = BV::new_const("x", 3);
+    let bits: Vec<Bool> = (0..3).map(|i| Bool::new_const(format!("bit{i}"))).collect();
+    let solver = Solver::new();
+    for (i, bit) in bits.iter().enumerate() {
+        solver.assert(bit.iff(x.extract(i as u32, i as u32).eq(BV::from_u64(1, 1))));
+    }
+    solver.assert(x.bvugt(BV::from_u64(5, 3)));
+    let first: Vec<&Bool> = bits.iter().collect();
+    let dimacs = solver.to_dimacs(&first).unwrap();
+    for (i, bit) in bits.iter().enumerate() {
+        assert_eq!(dimacs.literal(bit), Some(i as i32 + 1));
+    }
+
+    let clauses = |dimacs: &z3::Dimacs| {
+        let sat = Solver::new();
+        for clause in dimacs.clauses() {
+            let lits: Vec<Bool> = clause
+                .iter()
+                .map(|&l| {
+                    let atom = dimacs.atom(l.unsigned_abs()).unwrap();
+                    if l > 0 { atom.clone() } else { atom.not() }
+                })
+                .collect();
+            sat.assert(Bool::or(&lits));
+        }
+        sat
+    };
+
+    // The clauses only allow x = 6 and x = 7.
+    let sat = clauses(&dimacs);
+    assert_eq!(sat.check(), SatResult::Sat);
+    sat.assert(bits[0].not());
+    sat.assert(bits[1].not());
+    assert_eq!(sat.check(), SatResult::Unsat);
+
+    let unsat = Solver::new();
+    unsat.assert(x.bvult(BV::from_u64(2, 3)));
+    unsat.assert(x.bvugt(BV::from_u64(4, 3)));
+    let dimacs = unsat.to_dimacs(&[]).unwrap();
+    assert_eq!(clauses(&dimacs).check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_func_decl_assert_injective() {
+    let f = FuncDecl::new("f", &[&Sort::int(), &Sort::bool()], &Sort::int());
+    let solver = Solver::new();
+    let inverses = f.assert_injective(&solver);
+    assert_eq!(inverses.len(), 2);
+    assert!(inverses.iter().all(|inv| inv.arity() == 1));
+    assert_eq!(inverses[0].range(), SortKind::Int);
+    assert_eq!(inverses[1].range(), SortKind::Bool);
+
+    let (a, b) = (Int::new_const("a"), Int::new_const("b"));
+    let (p, q) = (Bool::new_const("p"), Bool::new_const("q"));
+    let fap = f.apply(&[&a, &p]);
+    assert_eq!(
+        solver.check_assumptions(&[fap.eq(f.apply(&[&b, &p])), a.eq(&b).not()]),
+        SatResult::Unsat
+    );
+    assert_eq!(
+        solver.check_assumptions(&[fap.eq(f.apply(&[&a, &q])), p.iff(&q).not()]),
+        SatResult::Unsat
+    );
+    let c = FuncDecl::new("c", &[], &Sort::int());
+    assert!(c.assert_injective(&solver).is_empty());
+}
+
+#[test]
+fn test_solver_to_smtlib2_round_trip() {
+    let (color, colors, _) = Sort::enumeration("Color".into(), &["red".into(), "green".into()]);
+    let u = Sort::uninterpreted("U".into());
+    let f = FuncDecl::new("f", &[&u], &Sort::int());
+    let (a, c) = (
+        ast::Dynamic::new_const("a", &u),
+        ast::Dynamic::new_const("c", &color),
+    );
+    let x = Int::new_const("x");
+    let solver = Solver::new();
+    solver.assert(f.apply(&[&a]).as_int().unwrap().gt(3));
+    solver.assert(c.eq(colors[1].apply(&[])));
+    solver.assert(ast::forall_const(&[&x], &[], &x.gt(&x).not()));
+
+    let benchmark = solver.to_smtlib2();
+    assert!(benchmark.contains("(declare-sort U 0)"));
+    assert!(benchmark.contains("(declare-datatypes ((Color 0))"));
+    assert!(!benchmark.contains("(let "));
+
+    let copy = Solver::new();
+    copy.from_string(benchmark.as_str()).unwrap();
+    assert_eq!(copy.get_assertions(), solver.get_assertions());
+    assert_eq!(copy.check(), SatResult::Sat);
+
+    assert!(Solver::new().to_smtlib2().ends_with("(check-sat)\n"));
+}
+
+#[test]
+fn test_ackermannize() {
+    let f = FuncDecl::new("f", &[&Sort::bitvector(4)], &Sort::bitvector(4));
+    let p = FuncDecl::new("p", &[&Sort::bitvector(4), &Sort::bool()], &Sort::bool());
+    let (x, y) = (BV::new_const("x", 4), BV::new_const("y", 4));
+    let b = Bool::new_const("b");
+    let ffx = f.apply(&[&f.apply(&[&x])]);
+    let goal = Goal::new(true, false, false);
+    goal.assert(&ffx.eq(&y).not());
+    goal.assert(&p.apply(&[&ffx, &b]).as_bool().unwrap());
+    goal.assert(&ast::forall_const(&[&x], &[], &f.apply(&[&x]).eq(&x)));
+
+    let ackermannized = z3::transform::ackermannize(&goal);
+    let replaced: Vec<String> = ackermannized
+        .replacements()
+        .iter()
+        .map(|(app, _)| app.to_string())
+        .collect();
+    assert_eq!(replaced, ["(f x)", "(f (f x))", "(p (f (f x)) b)"]);
+    let formulas = ackermannized.goal().get_formulas();
+    // The rewritten goal, the quantifier and one congruence constraint.
+    assert_eq!(formulas.len(), 4);
+    assert!(formulas[2].to_string().contains("forall"));
+    assert!(formulas[..2].iter().all(|f| !f.to_string().contains("(f ")));
+
+    let solver = Tactic::new("qfbv").solver();
+    solver.assert(&formulas[0]);
+    solver.assert(&formulas[1]);
+    solver.assert(&formulas[3]);
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    // f(x) = x forces f(f(x)) = x by congruence.
+    let (fx, ffx_const) = (
+        &ackermannized.replacements()[0].1,
+        &ackermannized.replacements()[1].1,
+    );
+    solver.assert(fx.eq(&x));
+    solver.assert(ffx_const.eq(&x).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_assert_named() {
+    let solver = Solver::new();
+    let (a, b, c) = (
+        Bool::new_const("a"),
+        Bool::new_const("b"),
+        Bool::new_const("c"),
+    );
+    solver.assert_named("main.rs:10", &a);
+    solver.assert_named("main.rs:12", a.implies(&b));
+    solver.assert_named("main.rs:12", b.implies(&c));
+    solver.assert_named("unused", Bool::or(&[&a, &c]));
+    solver.assert_and_track_auto(&a);
+    assert_eq!(solver.check_assumptions(&[c.not()]), SatResult::Unsat);
+    assert_eq!(solver.unsat_core_names(), ["main.rs:10", "main.rs:12"]);
+
+    let copy = solver.clone();
+    assert_eq!(copy.check_assumptions(&[c.not()]), SatResult::Unsat);
+    assert_eq!(copy.unsat_core_names(), ["main.rs:10", "main.rs:12"]);
+
+    assert_eq!(solver.check(), SatResult::Sat);
+    assert!(solver.unsat_core_names().is_empty());
+
+    // Constraints popped or reset are forgotten, and their ids not reused.
+    solver.push();
+    solver.assert_named("scoped", a.not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+    assert_eq!(solver.unsat_core_names(), ["scoped"]);
+    let scoped = solver.assert_and_track_auto(&c);
+    assert_eq!(solver.tracked_constraint(scoped), Some(c.clone()));
+    solver.pop(1);
+    assert_eq!(solver.tracked_constraint(scoped), None);
+    let id = solver.assert_and_track_auto(b.not());
+    assert_eq!(id.index(), 7);
+    assert_eq!(solver.tracked_constraint(id), Some(b.not()));
+    solver.reset();
+    assert_eq!(solver.tracked_constraint(id), None);
+    assert_eq!(solver.assert_and_track_auto(&a).index(), 8);
+}
+
+#[test]
+fn test_symmetry_lex_leader() {
+    let solver = Solver::new();
+    let (a, b) = (
+        [BV::new_const("a0", 4), BV::new_const("a1", 4)],
+        [BV::new_const("b0", 4), BV::new_const("b1", 4)],
+    );
+    solver.assert(z3::symmetry::lex_le(&a, &b));
+    // Equal sequences, and a smaller first element, are allowed.
+    for (a0, a1, b0, b1, result) in [
+        (1, 2, 1, 2, SatResult::Sat),
+        (1, 9, 2, 0, SatResult::Sat),
+        (1, 3, 1, 2, SatResult::Unsat),
+        // Bit-vectors are compared as unsigned numbers.
+        (15, 0, 1, 0, SatResult::Unsat),
+    ] {
+        let values = [
+            a[0].eq(BV::from_u64(a0, 4)),
+            a[1].eq(BV::from_u64(a1, 4)),
+            b[0].eq(BV::from_u64(b0, 4)),
+            b[1].eq(BV::from_u64(b1, 4)),
+        ];
+        assert_eq!(solver.check_assumptions(&values), result);
+    }
+
+    // Interchangeable Booleans: only the sorted assignments are left.
+    let flags: Vec<[Bool; 1]> = (0..3)
+        .map(|i| [Bool::new_const(format!("flag{i}"))])
+        .collect();
+    let constraints = z3::symmetry::lex_leader(&flags);
+    assert_eq!(constraints.len(), 2);
+    let solver = Solver::new();
+    for constraint in &constraints {
+        solver.assert(constraint);
+    }
+    solver.assert(flags[0][0].clone());
+    solver.assert(flags[2][0].not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    assert!(z3::symmetry::lex_leader::<Int>(&[[Int::new_const("x")]]).is_empty());
+}
+
+#[test]
+fn test_bounded_int_encodings() {
+    use z3::encoding::{OneHotEncoding, OrderEncoding};
+
+    let x = Int::new_const("x");
+    let order = OrderEncoding::new(&x, -2, 2);
+    assert_eq!(order.literals().len(), 4);
+    assert_eq!(order.ge(-2).as_bool(), Some(true));
+    assert_eq!(order.ge(3).as_bool(), Some(false));
+    assert_eq!(order.le(2).as_bool(), Some(true));
+    assert_eq!(order.ge(-1), order.literals()[0]);
+
+    let solver = Solver::new();
+    for constraint in order.constraints() {
+        solver.assert(constraint);
+    }
+    // The literals follow the value of x, and the other way around.
+    for k in -2..=2 {
+        assert_eq!(solver.check_assumptions(&[x.eq(k)]), SatResult::Sat);
+        let model = solver.get_model().unwrap();
+        for (i, literal) in order.literals().iter().enumerate() {
+            let expected = k >= -1 + i as i64;
+            assert_eq!(model.eval(literal, true).unwrap().as_bool(), Some(expected));
+        }
+    }
+    assert_eq!(solver.check_assumptions(&[x.eq(3)]), SatResult::Unsat);
+    assert_eq!(
+        solver.check_assumptions(&[order.ge(1), order.le(0)]),
+        SatResult::Unsat
+    );
+
+    let y = Int::new_const("y");
+    let one_hot = OneHotEncoding::new(&y, 5, 7);
+    assert_eq!(one_hot.literals().len(), 3);
+    assert_eq!(one_hot.eq(4).as_bool(), Some(false));
+    assert_eq!(one_hot.eq(8).as_bool(), Some(false));
+    let solver = Solver::new();
+    for constraint in one_hot.constraints() {
+        solver.assert(constraint);
+    }
+    for k in 5..=7 {
+        assert_eq!(solver.check_assumptions(&[y.eq(k)]), SatResult::Sat);
+        let model = solver.get_model().unwrap();
+        for (literal, value) in one_hot.literals().iter().zip(5..) {
+            assert_eq!(
+                model.eval(literal, true).unwrap().as_bool(),
+                Some(value == k)
+            );
+        }
+    }
+    assert_eq!(
+        solver.check_assumptions(&[one_hot.eq(5), one_hot.eq(6)]),
+        SatResult::Unsat
+    );
+    assert_eq!(solver.check_assumptions(&[y.eq(8)]), SatResult::Unsat);
+}
+
+#[test]
+fn test_tactic_combinators_and_apply_result() {
+    let x = Int::new_const("x");
+    let goal = Goal::new(false, false, false);
+    goal.assert(&Bool::or(&[x.lt(0), x.gt(10)]));
+    goal.assert(&(&x + 1).le(&x * 2));
+
+    let result = Tactic::new("split-clause").apply(&goal, None).unwrap();
+    let copy = result.clone();
+    drop(result);
+    assert_eq!(copy.num_subgoals(), 2);
+    assert!(copy.to_string().contains("(goals"));
+    assert!(copy.subgoal(2).is_none());
+    let subgoals: Vec<String> = copy.list_subgoals().map(|g| g.to_string()).collect();
+    assert_eq!(subgoals.len(), 2);
+
+    let split_then_simplify = Tactic::new("split-clause").par_and_then(&Tactic::new("simplify"));
+    assert_eq!(
+        split_then_simplify
+            .apply(&goal, None)
+            .unwrap()
+            .num_subgoals(),
+        2
+    );
+
+    let simplified = |tactic: &Tactic| {
+        tactic
+            .apply(&goal, None)
+            .unwrap()
+            .subgoal(0)
+            .unwrap()
+            .to_string()
+    };
+    let mut params = Params::new();
+    params.set_bool("arith_lhs", true);
+    let simplify = Tactic::new("simplify");
+    assert_ne!(
+        simplified(&simplify),
+        simplified(&simplify.using_params(&params))
+    );
+}
+
+#[test]
+fn test_help_entries() {
+    let entries = HelpEntry::parse(
+        " timeout (unsigned int) timeout in milliseconds (default: 4294967295)\n\
+         fail_if_inconclusive (bool) (default: true) fail if found unsat (sat) for under (over) approximated goal.\n\
+         engine (symbol) Select: auto-config, datalog (default: auto-config)\n\
+         weights (vector) weights of the objectives\n\
+         \n\
+         not an option\n",
+    );
+    assert_eq!(
+        entries,
+        [
+            HelpEntry {
+                name: "timeout".into(),
+                kind: ParamKind::UInt,
+                default: Some("4294967295".into()),
+                doc: "timeout in milliseconds".into(),
+            },
+            HelpEntry {
+                name: "fail_if_inconclusive".into(),
+                kind: ParamKind::Bool,
+                default: Some("true".into()),
+                doc: "fail if found unsat (sat) for under (over) approximated goal.".into(),
+            },
+            HelpEntry {
+                name: "engine".into(),
+                kind: ParamKind::Symbol,
+                default: Some("auto-config".into()),
+                doc: "Select: auto-config, datalog".into(),
+            },
+            HelpEntry {
+                name: "weights".into(),
+                kind: ParamKind::Other,
+                default: None,
+                doc: "weights of the objectives".into(),
+            },
+        ]
+    );
+
+    let has = |help: &str, name: &str, kind: ParamKind| {
+        HelpEntry::parse(help)
+            .iter()
+            .any(|e| e.name == name && e.kind == kind)
+    };
+    assert!(has(&Solver::new().get_help(), "timeout", ParamKind::UInt));
+    assert!(has(
+        &Optimize::new().get_help(),
+        "priority",
+        ParamKind::Symbol
+    ));
+    assert!(has(
+        &Fixedpoint::new().get_help(),
+        "engine",
+        ParamKind::Symbol
+    ));
+    assert!(
+        HelpEntry::simplifier()
+            .iter()
+            .any(|e| e.name == "som" && e.default.as_deref() == Some("false"))
+    );
+}
+
+#[test]
+fn test_logic_from_str() {
+    for name in [
+        "QF_LIA", "QF_NRA", "QF_AUFBV", "QF_ABV", "AUFLIRA", "QF_BVFP", "QF_S", "UFNIA", "LRA",
+        "QF_UFDT",
+    ] {
+        assert_eq!(name.parse::<LogicFeatures>().unwrap().to_string(), name);
+        assert_eq!(name.parse::<Logic>().unwrap().to_string(), name);
+    }
+    let idl: LogicFeatures = "QF_IDL".parse().unwrap();
+    assert_eq!(idl, "QF_LIA".parse().unwrap());
+    assert_eq!(Logic::QF_IDL.features(), Logic::QF_LIA.features());
+    assert_eq!(
+        "QF_AX".parse::<LogicFeatures>().unwrap().to_string(),
+        "QF_A"
+    );
+    assert!(Logic::ALL.includes(&"AUFBVFPDTSNIRA".parse().unwrap()));
+    for name in ["", "QF_", "QF_LIAX", "XYZ", "QF_BVA"] {
+        assert!(name.parse::<LogicFeatures>().is_err(), "{name}");
+        assert!(name.parse::<Logic>().is_err(), "{name}");
+    }
+    assert!("QF_BVNIA".parse::<LogicFeatures>().is_ok());
+    assert!("QF_BVNIA".parse::<Logic>().is_err());
+    for logic in Logic::list_all() {
+        assert_eq!(logic.name().parse::<Logic>(), Ok(*logic));
+        logic.features();
+    }
+}
+
+#[test]
+fn test_solver_set_logic_rejects_formulas_outside_it() {
+    let solver = Solver::new();
+    solver.set_logic(Logic::QF_LIA);
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    solver.try_assert(x.lt(&y)).unwrap();
+    let copy = solver.clone();
+    assert_eq!(copy.get_logic(), solver.get_logic());
+    let err = copy.try_assert((&x * &y).eq(2)).unwrap_err();
+    assert!(err.contains("not included in the logic QF_LIA"), "{err}");
+    assert_eq!(copy.get_assertions().len(), 1);
+    assert!(copy.validate().is_ok());
+
+    copy.from_string("(declare-const z Real) (assert (> z 0.0))")
+        .unwrap();
+    assert!(copy.validate().is_err());
+    assert!(solver.validate().is_ok());
+}
+
+#[test]
+#[should_panic(expected = "no-such-probe is an invalid probe")]
+fn test_probe_invalid_name() {
+    Probe::new("no-such-probe");
+}
+
+#[test]
+fn test_tactic_describe() {
+    let names: Vec<String> = Tactic::list_all().into_iter().map(|x| x.unwrap()).collect();
+    assert!(names.iter().any(|n| n == "ackermannize_bv"));
+    assert_eq!(
+        "A tactic for performing full Ackermannization on bv instances.",
+        Tactic::describe("ackermannize_bv").unwrap(),
+    );
+}
+
+#[test]
+fn test_check_deepening() {
+    let p = FuncDecl::new("p", &[&Sort::int()], &Sort::bool());
+    let at = |x: &Int| p.apply(&[x]).as_bool().unwrap();
+    let x = Int::new_const("x");
+    let solver = Solver::new();
+    solver.assert(ast::forall_const(
+        &[&x],
+        &[],
+        &at(&x).implies(at(&(&x + 1))),
+    ));
+    solver.assert(at(&Int::from_i64(0)));
+    solver.assert(at(&Int::from_i64(20)).not());
+
+    let steps = solver.check_deepening([1, 2, 4, 1000, 2000], Duration::from_secs(60));
+    let bounds: Vec<u32> = steps.iter().map(|s| s.bound).collect();
+    assert_eq!(bounds, [1, 2, 4, 1000]);
+    for step in &steps[..3] {
+        assert_eq!(step.result, SatResult::Unknown);
+        assert!(
+            step.reason_unknown
+                .as_deref()
+                .unwrap()
+                .contains("incomplete quantifiers")
+        );
+    }
+    assert_eq!(steps[3].result, SatResult::Unsat);
+    assert_eq!(steps[3].reason_unknown, None);
+
+    // The limits are lifted afterwards.
+    assert_eq!(solver.check(), SatResult::Unsat);
+    assert!(
+        solver
+            .check_deepening([], Duration::from_secs(1))
+            .is_empty()
+    );
+
+    // Limits the caller set are restored.
+    let mut params = Params::new();
+    params.set_u32("smt.qi.max_instances", 2);
+    params.set_u32("smt.mbqi.max_iterations", 2);
+    solver.set_params(&params);
+    let steps = solver.check_deepening([1000], Duration::from_secs(60));
+    assert_eq!(steps[0].result, SatResult::Unsat);
+    assert_eq!(solver.check(), SatResult::Unknown);
+}
+
+#[test]
+fn test_apply_result_convert_model() {
+    let (x, y, z) = (
+        Int::new_const("x"),
+        Int::new_const("y"),
+        Int::new_const("z"),
+    );
+    let goal = Goal::new(true, false, false);
+    goal.assert(&x.eq(&y + &z));
+    goal.assert(&y.eq(&z * 2));
+    goal.assert(&x.gt(7));
+
+    let result = Tactic::new("solve-eqs").apply(&goal, None).unwrap();
+    let subgoal = result.subgoal(0).unwrap();
+    assert_eq!(subgoal.get_size(), 1);
+    let solver = Solver::new();
+    for formula in subgoal.get_formulas() {
+        solver.assert(&formula);
+    }
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+
+    let converted = result.convert_model(0, &model).unwrap();
+    for formula in goal.get_formulas() {
+        assert_eq!(
+            converted.eval(&formula, true).unwrap().as_bool(),
+            Some(true)
+        );
+    }
+    assert!(result.convert_model(1, &model).is_none());
+}
+
+#[test]
+fn test_farkas_certificate() {
+    let (x, y, z) = (
+        Int::new_const("x"),
+        Int::new_const("y"),
+        Int::new_const("z"),
+    );
+    // 2x + 3y = 12 and x - y > 10 force y < -1.6, while y >= -1.
+    let constraints = [
+        (&x * 2 + &y * 3).eq(12),
+        (&x - &y).gt(10),
+        y.ge(-1),
+        z.le(&x),
+    ];
+    let solver = Solver::new();
+    assert_eq!(solver.check_assumptions(&constraints), SatResult::Unsat);
+    let certificate = farkas_certificate(&constraints).unwrap();
+    let used: Vec<Bool> = certificate
+        .multipliers()
+        .iter()
+        .map(|(c, _)| c.clone())
+        .collect();
+    assert_eq!(used, constraints[..3]);
+    assert!(certificate.bound() < 0);
+    assert!(
+        certificate
+            .to_string()
+            .ends_with(&format!("sum: 0 <= {}", certificate.bound()))
+    );
+
+    // Infeasible only over the integers.
+    assert!(farkas_certificate(&[(&x * 2).eq(1)]).is_none());
+    // Not a linear comparison.
+    assert!(farkas_certificate(&[x.eq(1) | y.eq(2)]).is_none());
+}
+
+#[test]
+fn test_flatten_ite_cases() {
+    let (p, q) = (Bool::new_const("p"), Bool::new_const("q"));
+    let x = Int::new_const("x");
+    let abs = x.lt(0).ite(&-&x, &x);
+    let clamped = p.ite(&q.ite(&Int::from_i64(0), &abs), &Int::from_i64(10));
+    let formula = clamped.ge(&abs);
+
+    let flattened = formula.flatten_ite_cases(8).unwrap();
+    let solver = Solver::new();
+    solver.assert(flattened.iff(&formula).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+    // No ite is left, and the shared condition `x < 0` splits once per
+    // branch of `p` and `q`: (p, q), (p, !q) and (!p) each split on it.
+    assert_eq!(flattened.num_children(), 6);
+    assert!(!flattened.to_string().contains("ite"));
+    assert!(formula.flatten_ite_cases(5).is_none());
+
+    assert_eq!(x.gt(1).flatten_ite_cases(1), Some(x.gt(1)));
+}
+
+#[test]
+fn test_goal_introspection_after_bit_blasting() {
+    let (x, y) = (BV::new_const("x", 4), BV::new_const("y", 4));
+    let goal = Goal::new(false, false, false);
+    goal.assert(&x.bvadd(&y).eq(BV::from_u64(3, 4)));
+    goal.assert(&x.bvugt(&y));
+    assert_eq!(goal.get_depth(), 0);
+
+    let cnf = Tactic::new("simplify")
+        .and_then(&Tactic::new("bit-blast"))
+        .and_then(&Tactic::new("tseitin-cnf"))
+        .apply(&goal, None)
+        .unwrap()
+        .subgoal(0)
+        .unwrap();
+    assert!(cnf.get_depth() > 0);
+    assert!(!cnf.is_inconsistent());
+    assert!(!cnf.is_decided_sat() && !cnf.is_decided_unsat());
+    assert_eq!(cnf.get_formulas().len(), cnf.get_size() as usize);
+
+    let dimacs = cnf.to_dimacs_string(true);
+    let header: Vec<usize> = dimacs
+        .lines()
+        .find_map(|l| l.strip_prefix("p cnf "))
+        .unwrap()
+        .split_whitespace()
+        .map(|n| n.parse().unwrap())
+        .collect();
+    assert_eq!(header[1], cnf.get_size() as usize);
+    assert!(dimacs.lines().any(|l| l.starts_with("c ")));
+    assert!(
+        !cnf.to_dimacs_string(false)
+            .lines()
+            .any(|l| l.starts_with("c "))
+    );
+
+    let expected = cnf.to_dimacs_string(false);
+    let cnf = cnf.synchronized();
+    with_z3_config(&Config::new(), move || {
+        assert_eq!(cnf.recover().to_dimacs_string(false), expected);
+    });
+}
+
+#[test]
+fn test_solver_to_smtlib2_with_lets() {
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    let shared = (&x + &y) * (&x - &y);
+    let solver = Solver::new();
+    solver.assert((&shared + &shared).gt(&shared));
+    solver.assert(x.lt(&y));
+
+    let benchmark = solver.to_smtlib2_with_lets(7);
+    assert_eq!(benchmark.matches("(let ").count(), 1);
+    assert_eq!(benchmark.matches("(* (+ x y) (- x y))").count(), 1);
+    assert!(benchmark.contains("(declare-fun x () Int)"));
+    assert!(benchmark.ends_with("(check-sat)\n"));
+    let copy = Solver::new();
+    copy.from_string(benchmark).unwrap();
+    assert_eq!(copy.get_assertions(), solver.get_assertions());
+
+    // Subterms smaller than the threshold are not bound.
+    assert!(!solver.to_smtlib2_with_lets(8).contains("(let "));
+
+    // Bound variables do not clash with constants of the same name.
+    let clash = Int::new_const("?x0");
+    solver.assert(clash.eq(&shared * &shared));
+    let copy = Solver::new();
+    copy.from_string(solver.to_smtlib2_with_lets(1)).unwrap();
+    assert_eq!(copy.get_assertions(), solver.get_assertions());
+}
+
+#[test]
+fn test_solver_nearest_model() {
+    let options: Vec<Bool> = (0..5)
+        .map(|i| Bool::new_const(format!("option{i}")))
+        .collect();
+    let solver = Solver::new();
+    solver.push();
+    for option in &options {
+        solver.assert(option);
+    }
+    assert_eq!(solver.check(), SatResult::Sat);
+    let reference = solver.get_model().unwrap();
+    solver.pop(1);
+
+    solver.assert(atmost(&options[..4], 2));
+    solver.assert_and_track(options[4].implies(options[0].not()), &Bool::new_const("p"));
+    let terms: Vec<&dyn Ast> = options.iter().map(|o| o as &dyn Ast).collect();
+    let model = solver.nearest_model(&reference, &terms).unwrap();
+    let values: Vec<bool> = options
+        .iter()
+        .map(|o| model.eval(o, true).unwrap().as_bool().unwrap())
+        .collect();
+    assert_eq!(values.iter().filter(|v| !**v).count(), 2);
+    assert!(!values[0] && values[4]);
+
+    solver.assert(Bool::from_bool(false));
+    assert_eq!(
+        solver.nearest_model(&reference, &terms).unwrap_err(),
+        SatResult::Unsat
+    );
+}
+
+#[test]
+fn test_param_descrs_of_components() {
+    let tactic = Tactic::new("simplify");
+    let descrs = tactic.get_param_descrs();
+    assert_eq!(descrs.get_kind("max_steps"), Some(ParamKind::UInt));
+    assert!(descrs.iter().any(|d| d.name == "elim_and"));
+
+    let mut params = Params::new();
+    params.set_u32("max_steps", 10);
+    assert!(descrs.validate(&params).is_ok());
+    // A known parameter of the wrong kind.
+    params.set_bool("max_steps", true);
+    assert!(descrs.validate(&params).is_err());
+    // The error does not leak into later calls.
+    assert!(descrs.validate(&Params::new()).is_ok());
+
+    assert_eq!(
+        ParamDescrs::simplifier().get_kind("elim_and"),
+        Some(ParamKind::Bool)
+    );
+    assert!(!Solver::new().get_param_descrs().is_empty());
+    assert!(!Optimize::new().get_param_descrs().is_empty());
+}
+
+#[test]
+fn test_simplify_with_params() {
+    let x = BV::new_const("x", 8);
+    let t = x.bvmul(BV::from_u64(2, 8)).bvadd(BV::from_u64(0, 8));
+    // With no parameters set, the simplifier behaves as by default.
+    assert_eq!(t.simplify_with_params(&Params::new()), t.simplify());
+
+    let mut params = Params::new();
+    params.set_bool("mul2concat", true);
+    assert!(ParamDescrs::simplifier().validate(&params).is_ok());
+    let simplified = t.simplify_with_params(&params);
+    assert_ne!(simplified, t.simplify());
+    assert!(simplified.to_string().contains("concat"));
+}
+
+#[test]
+#[should_panic(expected = "invalid simplifier parameters")]
+fn test_simplify_with_invalid_params() {
+    let mut params = Params::new();
+    params.set_bool("no_such_param", true);
+    Bool::new_const("a").simplify_with_params(&params);
+}
+
+#[test]
+fn test_try_simplify_with_params() {
+    let mut params = Params::new();
+    params.set_bool("no_such_param", true);
+    let error = Bool::new_const("a")
+        .try_simplify_with_params(&params)
+        .unwrap_err();
+    assert!(!error.is_empty());
+
+    let t = Int::new_const("x") + 0;
+    assert_eq!(t.try_simplify_with_params(&Params::new()), Ok(t.simplify()));
+}
+
+#[test]
+fn test_quantifier_attributes() {
+    let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    let g = FuncDecl::new("g", &[&Sort::int()], &Sort::int());
+    let x = Int::new_const("x");
+    let (f_x, g_x) = (
+        f.apply(&[&x]).as_int().unwrap(),
+        g.apply(&[&x]).as_int().unwrap(),
+    );
+    let multi = Pattern::new(&[&f_x, &g_x]);
+    let forall =
+        ast::quantifier_const(true, 3, "fg", "sk_fg", &[&x], &[&multi], &[], &f_x.gt(&g_x));
+    assert!(forall.is_forall() && !forall.is_exists());
+    assert_eq!(forall.quantifier_weight(), Some(3));
+    let patterns = forall.quantifier_patterns();
+    assert_eq!(patterns.len(), 1);
+    let heads: Vec<FuncDecl> = patterns[0].terms().iter().map(|t| t.decl()).collect();
+    assert_eq!(heads, [f.clone(), g.clone()]);
+    assert!(forall.to_string().contains(":qid fg"));
+
+    // The multi-pattern fires only on terms where both f and g occur.
+    let solver = Solver::new();
+    solver.assert(&forall);
+    let five = Int::from_i64(5);
+    solver.assert(f.apply(&[&five]).as_int().unwrap().le(0));
+    solver.assert(g.apply(&[&five]).as_int().unwrap().ge(0));
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let exists = ast::quantifier_const(false, 0, "e", "sk_e", &[&x], &[], &[&f_x], &f_x.eq(&x));
+    assert!(exists.is_exists());
+    assert!(exists.quantifier_patterns().is_empty());
+    let [no_pattern] = &exists.quantifier_no_patterns()[..] else {
+        panic!()
+    };
+    // Bound constants are replaced by de Bruijn indices.
+    assert_eq!(no_pattern.decl(), f);
+    assert_eq!(no_pattern.nth_child(0).unwrap().kind(), AstKind::Var);
+
+    assert_eq!(x.gt(0).quantifier_weight(), None);
+    assert!(!x.gt(0).is_forall());
+}
+
+#[test]
+fn test_decl_kinds() {
+    fn kinds(t: &ast::Dynamic, out: &mut Vec<Option<DeclKind>>) {
+        out.push(t.decl_kind());
+        for child in t.children() {
+            kinds(&child, out);
+        }
+    }
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    let s = ast::String::new_const("s");
+    let t = Bool::and(&[
+        (&x * 2).le(&y),
+        ast::forall_const(&[&x], &[], &x.ge(&y)),
+        s.str_lt("b"),
+    ]);
+    let mut out = vec![];
+    kinds(&ast::Dynamic::from_ast(&t), &mut out);
+    use DeclKind::*;
+    let expected = [
+        Some(AND),
+        Some(LE),
+        Some(MUL),
+        Some(UNINTERPRETED),
+        Some(ANUM),
+        Some(UNINTERPRETED),
+        // Quantifiers are not applications, and have no children.
+        None,
+        Some(STRING_LT),
+        Some(UNINTERPRETED),
+    ];
+    assert_eq!(out[..expected.len()], expected);
+}
+
+#[test]
+fn test_solver_check_obligations() {
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    let solver = Solver::new();
+    solver.assert(x.ge(0));
+    solver.assert(y.eq(&x * 2));
+
+    let mut obligations: Vec<Bool> = (0..20).map(|k| (&y + k).ge(k)).collect();
+    obligations.push(y.gt(&x));
+    obligations.push((&y % 2).eq(0));
+    obligations.push(y.lt(100));
+    let verdicts = solver.check_obligations(&obligations);
+    assert_eq!(verdicts.len(), obligations.len());
+    assert!(verdicts[..20].iter().all(|v| matches!(v, Verdict::Valid)));
+    // y > x fails for x = 0, and y < 100 fails for large x.
+    let Verdict::Invalid(model) = &verdicts[20] else {
+        panic!()
+    };
+    assert_eq!(model.eval(&x, true).unwrap().as_i64(), Some(0));
+    assert!(matches!(verdicts[21], Verdict::Valid));
+    let Verdict::Invalid(model) = &verdicts[22] else {
+        panic!()
+    };
+    assert!(model.eval(&y, true).unwrap().as_i64().unwrap() >= 100);
+
+    // The context is unchanged.
+    assert_eq!(solver.get_assertions().len(), 2);
+    assert!(solver.check_obligations(&[]).is_empty());
+}
+
+#[test]
+fn test_tactic_param_descrs_of_named_tactics() {
+    for name in Tactic::list_all().into_iter().filter_map(|n| n.ok()) {
+        let descrs = Tactic::new(&name).get_param_descrs();
+        assert_eq!(descrs.iter().count(), descrs.len(), "{name}");
+    }
+
+    let combined = Tactic::new("simplify").and_then(&Tactic::new("solve-eqs"));
+    let descrs = combined.get_param_descrs();
+    assert_eq!(descrs.get_kind("arith_lhs"), Some(ParamKind::Bool));
+    assert_eq!(descrs.get_kind("theory_solver"), Some(ParamKind::Bool));
+
+    let mut params = Params::new();
+    params.set_bool("arith_lhs", true);
+    params.set_u32("max_steps", 1000);
+    assert!(descrs.validate(&params).is_ok());
+    let _ = combined.using_params(&params);
+}
+
+#[test]
+#[should_panic(expected = "unknown parameter 'arith_lhss'")]
+fn test_tactic_using_params_rejects_unknown_parameter() {
+    let mut params = Params::new();
+    params.set_bool("arith_lhss", true);
+    let _ = Tactic::new("simplify").using_params(&params);
+}
+
+#[test]
+fn test_tactic_try_using_params() {
+    let mut params = Params::new();
+    params.set_u32("arith_lhs", 1);
+    let error = Tactic::new("simplify")
+        .try_using_params(&params)
+        .unwrap_err();
+    assert!(error.contains("arith_lhs"), "{error}");
+
+    let mut params = Params::new();
+    params.set_bool("arith_lhs", true);
+    assert!(Tactic::new("simplify").try_using_params(&params).is_ok());
+}
+
+#[test]
+fn test_statistics_json_and_csv() {
+    let column = |key: &str| {
+        StatisticsEntry {
+            key: key.into(),
+            value: StatisticsValue::UInt(0),
+        }
+        .column()
+    };
+    assert_eq!(column("rlimit count"), "rlimit_count");
+    assert_eq!(column("sat mk clause 2ary"), "sat_mk_clause_2ary");
+    assert_eq!(column(" arith-lower  (x) "), "arith_lower_x");
+
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    let solver = Solver::new();
+    solver.assert((&x * &y).gt(10));
+    solver.assert(x.lt(&y));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let stats = solver.get_statistics();
+
+    let json: serde_json::Value = serde_json::from_str(&stats.to_json()).unwrap();
+    let object = json.as_object().unwrap();
+    assert_eq!(object.len(), stats.entries().count());
+    for entry in stats.entries() {
+        let value = &object[&entry.column()];
+        match entry.value {
+            StatisticsValue::UInt(v) => assert_eq!(value.as_u64(), Some(v.into())),
+            StatisticsValue::Double(v) => assert_eq!(value.as_f64(), Some(v)),
+        }
+    }
+    #[cfg(feature = "serde")]
+    assert_eq!(serde_json::to_value(&stats).unwrap(), json);
+
+    let row = stats.to_csv_row(&["missing", "rlimit_count", "max_memory"]);
+    let cells: Vec<&str> = row.split(',').collect();
+    assert_eq!(cells.len(), 3);
+    assert_eq!(cells[0], "");
+    assert_eq!(cells[1].parse::<u64>().ok(), stats.rlimit_count(),);
+    assert!(cells[2].parse::<f64>().is_ok());
+}
+
+#[test]
+fn test_context_arena() {
+    let owned = {
+        let arena = ContextArena::new();
+        assert!(arena.is_empty());
+        let int = Sort::int();
+        let xs: Vec<_> = (0..50)
+            .map(|i| arena.constant(format!("a{i}"), &int))
+            .collect();
+        let (zero, one) = (arena.int(0), arena.int(1));
+        let bits: Vec<_> = xs
+            .iter()
+            .map(|x| arena.or(&[x.eq(zero), x.eq(one)]))
+            .collect();
+        let sum = arena.add(&xs);
+        let parity = arena.constant("parity", &Sort::bool());
+        let formula = arena.and(&[
+            arena.and(&bits),
+            sum.eq(arena.int(25)),
+            parity.ite(xs[0] - xs[1], xs[1] * arena.int(2)).ge(one),
+            !arena.from_bool(false),
+        ]);
+        assert_eq!(arena.import(&Int::new_const("a0")).to_string(), "a0");
+        assert!(arena.len() > 100);
+        formula.to_dynamic().as_bool().unwrap()
+    };
+
+    let solver = Solver::new();
+    solver.assert(&owned);
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let value = |name: &str| model.eval(&Int::new_const(name), true).unwrap().as_i64();
+    assert_eq!(
+        (0..50).filter_map(|i| value(&format!("a{i}"))).sum::<i64>(),
+        25
+    );
+}
+
+#[test]
+#[should_panic(expected = "invalid term")]
+fn test_context_arena_rejects_sort_mismatch() {
+    let arena = ContextArena::new();
+    let _ = arena.int(1).eq(arena.from_bool(true));
+}
+
+#[test]
+fn test_closure_quantifiers() {
+    let f = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    let apply = |x: &Int| f.apply(&[x]).as_int().unwrap();
+
+    // Nested binders each get their own bound variables.
+    let total = ast::forall(|x: Int| ast::exists(|y: Int| apply(&y).gt(apply(&x))));
+    assert!(total.is_forall());
+
+    let x = Int::new_const("x");
+    let explicit = ast::forall_const(&[&x], &[], &apply(&x).ge(0));
+    let closure = ast::forall(|x: Int| apply(&x).ge(0));
+    let solver = Solver::new();
+    solver.assert(explicit.eq(&closure).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let solver = Solver::new();
+    solver.assert(ast::forall(|p: Bool, q: Bool| {
+        Bool::and(&[&p, &q]).implies(&p)
+    }));
+    solver.assert(ast::exists(|r: ast::Real, s: ast::String| {
+        Bool::and(&[r.gt(ast::Real::from_rational(1, 2)), s.str_gt("ab")])
+    }));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let max = ast::lambda(|x: Int, y: Int| x.ge(&y).ite(&x, &y));
+    let value = max
+        .select_n(&[&Int::from_i64(3), &Int::from_i64(7)])
+        .simplify();
+    assert_eq!(value.as_int().unwrap().as_i64(), Some(7));
+}
+
+#[test]
+fn test_ast_vector_for_each_ref() {
+    let x = Int::new_const("x");
+    let p = Bool::new_const("p");
+    let vector = AstVector::from_slice(&[&x, &(&x + 1)]);
+    vector.push(&p);
+
+    let mut seen = vec![];
+    let mut kept = None;
+    vector.for_each_ref(|a| {
+        seen.push((a.to_string(), a.kind(), a.sort_kind()));
+        if a.sort_kind() == SortKind::Bool {
+            kept = Some(a.to_dynamic());
+        }
+    });
+    assert_eq!(
+        seen,
+        [
+            ("x".to_string(), AstKind::App, SortKind::Int),
+            ("(+ x 1)".to_string(), AstKind::App, SortKind::Int),
+            ("p".to_string(), AstKind::App, SortKind::Bool),
+        ]
+    );
+    drop(vector);
+    assert_eq!(kept.unwrap().as_bool(), Some(p));
+}
+
+#[test]
+#[should_panic(expected = "AstVector modified during for_each_ref")]
+fn test_ast_vector_for_each_ref_rejects_modification() {
+    let x = Int::new_const("x");
+    let vector = AstVector::from_slice(&[&x]);
+    vector.for_each_ref(|_| vector.clear());
+}
+
+#[test]
+fn test_qe_lite_model_project_and_qe() {
+    use z3::quantifier_elimination::{QeError, model_project, qe, qe_lite};
+
+    let (x, y, z) = (
+        Int::new_const("x"),
+        Int::new_const("y"),
+        Int::new_const("z"),
+    );
+    let formula = Bool::and(&[x.eq(&y + 1), x.lt(&z)]);
+    assert_eq!(qe_lite(&[&x], &formula), Ok((Int::from_i64(1) + &y).lt(&z)));
+
+    let solver = Solver::new();
+    solver.assert(&formula);
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let projection = model_project(&model, &[&x, &y], &formula).unwrap();
+    assert_eq!(model.eval(&projection, true).unwrap().as_bool(), Some(true));
+    let solver = Solver::new();
+    solver.assert(&formula);
+    solver.assert(projection.not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let falsifying = Bool::and(&[x.eq(&y), x.ne(&y)]);
+    assert_eq!(
+        model_project(&model, &[&x], &falsifying),
+        Err(QeError::ModelFalsifies)
+    );
+
+    // exists x. y < x < z, i.e. y + 1 < z.
+    let quantified = ast::exists(|x: Int| Bool::and(&[y.lt(&x), x.lt(&z)]));
+    let eliminated = qe(&quantified).unwrap();
+    let solver = Solver::new();
+    solver.assert(eliminated.eq((&y + 1).lt(&z)).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let sum = &x + &y;
+    assert_eq!(
+        qe_lite(&[&sum], &formula),
+        Err(QeError::NotAConstant(sum.into()))
+    );
+}
+
+#[test]
+fn test_model_iter_is_sorted() {
+    let f_int = FuncDecl::new("f", &[&Sort::int()], &Sort::int());
+    let f_bool = FuncDecl::new("f", &[&Sort::bool()], &Sort::int());
+    let solver = Solver::new();
+    let names = ["x10", "b", "x2", "a", "c"];
+    for (i, name) in names.iter().enumerate() {
+        let x = Int::new_const(*name);
+        solver.assert(x.eq(i as i64));
+        solver.assert(f_int.apply(&[&x]).as_int().unwrap().eq(&x));
+    }
+    solver.assert(
+        f_bool
+            .apply(&[&Bool::from_bool(true)])
+            .as_int()
+            .unwrap()
+            .eq(7),
+    );
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+
+    let iter = model.iter();
+    assert_eq!(iter.len(), 7);
+    let decls: Vec<String> = iter.map(|decl| decl.to_string()).collect();
+    assert_eq!(
+        decls[..5],
+        ["a", "b", "c", "x10", "x2"].map(|n| format!("(declare-fun {n} () Int)"))
+    );
+    assert_eq!(
+        decls[5..],
+        ["(declare-fun f (Bool) Int)", "(declare-fun f (Int) Int)"]
+    );
+
+    let summary = model.summary(2).to_string();
+    assert!(summary.starts_with("a -> 3\nb -> 1\n"));
+}
+
+#[test]
+fn test_check_yielding() {
+    let vars: Vec<Int> = (0..4).map(|i| Int::new_const(format!("v{i}"))).collect();
+    let cube = |v: &Int| v * v * v;
+    let hard = Solver::new();
+    hard.assert((cube(&vars[0]) + cube(&vars[1]) + cube(&vars[2])).eq(cube(&vars[3]) + 33));
+    hard.assert(vars[3].gt(1000));
+
+    let mut consumed = vec![];
+    let result = hard.check_yielding(1000, |used| {
+        consumed.push(used);
+        if consumed.len() < 4 {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
+        }
+    });
+    assert_eq!(result, SatResult::Unknown);
+    assert_eq!(consumed.len(), 4);
+    // The slices double, so each yield comes after more units than the last.
+    assert!(consumed.windows(2).all(|w| w[0] < w[1]));
+    assert!(consumed[3] >= 1000 + 2000 + 4000 + 8000);
+
+    let easy = Solver::new();
+    easy.assert(vars[0].gt(3) & vars[1].lt(&vars[0]));
+    let result = easy.check_yielding(1_000_000, |_| panic!("no yield expected"));
+    assert_eq!(result, SatResult::Sat);
+}
+
+#[test]
+fn test_set_single_threaded() {
+    z3::set_single_threaded();
+    assert_eq!(
+        z3::get_global_param("parallel.enable").as_deref(),
+        Some("false")
+    );
+    let x = Int::new_const("x");
+    let solver = Solver::new();
+    solver.assert(x.gt(2));
+    assert_eq!(solver.check(), SatResult::Sat);
+    z3::reset_all_global_params();
+}
+
+#[test]
+fn test_term_registry() {
+    use std::ffi::{CStr, CString};
+    let registry = TermRegistry::new();
+    let provider = registry.provider();
+    assert_eq!(provider.abi_version, TERM_PROVIDER_ABI_VERSION);
+    let reg = provider.registry;
+    let (f, x) = (CString::new("f").unwrap(), CString::new("x").unwrap());
+    let mut buf = [0 as std::ffi::c_char; 64];
+    unsafe {
+        let bv8 = (provider.bv_sort)(reg, 8);
+        let x = (provider.constant)(reg, x.as_ptr(), bv8);
+        let f = (provider.declare_fun)(reg, f.as_ptr(), [bv8].as_ptr(), 1, bv8);
+        let fx = (provider.apply)(reg, f, [x].as_ptr(), 1);
+        let sum = (provider.op)(reg, TermOp::BvAdd as u32, [fx, x].as_ptr(), 2);
+        assert_ne!(sum, 0);
+
+        let len = (provider.to_string)(reg, sum, buf.as_mut_ptr(), buf.len());
+        let text = CStr::from_ptr(buf.as_ptr()).to_str().unwrap();
+        assert_eq!(text, "(bvadd (f x) x)");
+        assert_eq!(len, text.len());
+        // Truncated text reports the full length.
+        assert_eq!((provider.to_string)(reg, sum, buf.as_mut_ptr(), 4), len);
+        assert_eq!(CStr::from_ptr(buf.as_ptr()).to_str().unwrap(), "(bv");
+
+        // Mixing sorts fails, with Z3's message.
+        let int = (provider.int_sort)(reg);
+        let one = CString::new("1").unwrap();
+        let one = (provider.numeral)(reg, one.as_ptr(), int);
+        assert_eq!(
+            (provider.op)(reg, TermOp::Eq as u32, [x, one].as_ptr(), 2),
+            0
+        );
+        assert!(!registry.last_error().is_empty());
+
+        assert_eq!((provider.op)(reg, 1000, [x].as_ptr(), 1), 0);
+        (provider.last_error)(reg, buf.as_mut_ptr(), buf.len());
+        assert_eq!(
+            CStr::from_ptr(buf.as_ptr()).to_str().unwrap(),
+            "unknown operator code 1000"
+        );
+        assert_eq!((provider.op)(reg, TermOp::Not as u32, [].as_ptr(), 0), 0);
+        assert_eq!(registry.last_error(), "Not expects 1 arguments, got 0");
+
+        assert!(registry.get(x).is_some());
+        (provider.release)(reg, x);
+        assert!(registry.get(x).is_none());
+        assert_eq!((provider.apply)(reg, f, [x].as_ptr(), 1), 0);
+        assert_eq!(registry.last_error(), format!("invalid term id {x}"));
+
+        let sum = registry.get(sum).unwrap().as_bv().unwrap();
+        let y = BV::new_const("y", 8);
+        let id = registry.insert(&sum.bvadd(&y));
+        assert_eq!(
+            registry.get(id).unwrap().to_string(),
+            "(bvadd (bvadd (f x) x) y)"
+        );
+    }
+    assert_eq!(registry.len(), 4);
+}
+
+#[cfg(feature = "server")]
+#[test]
+fn test_server() {
+    use serde_json::{Value, json};
+    use z3::server::Server;
+
+    let requests = [
+        json!({"jsonrpc": "2.0", "id": 1, "method": "solve", "params": {
+            "smtlib": "(declare-const x Int) (declare-const y Int) \
+                       (assert (= (+ (* x x x) (* y y y)) 1729)) (assert (> x 1)) (assert (> y x))",
+        }}),
+        json!({"jsonrpc": "2.0", "id": "opt", "method": "optimize", "params": {
+            "smtlib": "(declare-const x Int) (assert (< x 10)) (maximize x)",
+        }}),
+        json!({"jsonrpc": "2.0", "id": 3, "method": "solve", "params": {
+            "smtlib": "(assert (> x 1))",
+        }}),
+        json!({"jsonrpc": "2.0", "id": 4, "method": "prove", "params": {"smtlib": ""}}),
+        json!({"jsonrpc": "2.0", "method": "solve", "params": {"smtlib": ""}}),
+    ];
+    let mut input: Vec<String> = requests.iter().map(Value::to_string).collect();
+    input.push("{not json".to_owned());
+    let input = input.join("\n");
+
+    let mut output = vec![];
+    let server = Server::new().statistics_interval(1);
+    server.serve(input.as_bytes(), &mut output).unwrap();
+    let messages: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+
+    let (notifications, responses): (Vec<&Value>, Vec<&Value>) =
+        messages.iter().partition(|m| m.get("method").is_some());
+    assert!(!notifications.is_empty());
+    for n in &notifications {
+        assert_eq!(n["method"], "statistics");
+        assert_eq!(n["params"]["id"], 1);
+        assert!(n["params"]["statistics"]["rlimit_count"].is_u64());
+    }
+
+    // The notification (without id) gets no response.
+    assert_eq!(responses.len(), 5);
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(responses[0]["result"]["result"], "sat");
+    assert!(responses[0]["result"]["model"].is_string());
+    assert_eq!(responses[1]["id"], "opt");
+    assert_eq!(responses[1]["result"]["objectives"], json!(["9"]));
+    assert_eq!(responses[2]["error"]["code"], -32000);
+    assert_eq!(responses[3]["error"]["code"], -32601);
+    assert_eq!(responses[4]["error"]["code"], -32700);
+    assert_eq!(responses[4]["id"], Value::Null);
+}
+
+#[test]
+fn test_bench() {
+    use z3::bench::{Bench, Setup};
+
+    let dir = std::env::temp_dir().join(format!("z3-bench-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::write(dir.join("a.smt2"), "(declare-const x Int) (assert (> x 2))").unwrap();
+    std::fs::write(
+        dir.join("nested/b.smt2"),
+        "(declare-const x Int) (assert (> x 2)) (assert (< x 1))",
+    )
+    .unwrap();
+    std::fs::write(dir.join("c.smt2"), "(assert (> y 2))").unwrap();
+    std::fs::write(dir.join("notes.txt"), "not a problem").unwrap();
+    let bench = Bench::load(&dir);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let bench = bench.unwrap().setup(Setup::new("default")).setup(
+        Setup::new("seeded")
+            .u32_param("random_seed", 3)
+            .timeout(Duration::from_secs(10)),
+    );
+    let files: Vec<_> = bench
+        .problems()
+        .iter()
+        .map(|p| p.path().strip_prefix(&dir).unwrap())
+        .collect();
+    assert_eq!(
+        files,
+        ["a.smt2", "c.smt2", "nested/b.smt2"].map(std::path::Path::new)
+    );
+
+    let report = bench.run();
+    let results: Vec<_> = report
+        .records()
+        .iter()
+        .map(|r| (r.setup.as_str(), r.result))
+        .collect();
+    assert_eq!(
+        results,
+        [
+            ("default", SatResult::Sat),
+            ("seeded", SatResult::Sat),
+            ("default", SatResult::Unknown),
+            ("seeded", SatResult::Unknown),
+            ("default", SatResult::Unsat),
+            ("seeded", SatResult::Unsat),
+        ]
+    );
+    assert!(
+        report.records()[2]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("unknown constant y")
+    );
+    assert!(report.records()[0].rlimit_count.is_some());
+    assert!(report.conflicts().is_empty());
+
+    let csv = report.to_csv();
+    assert_eq!(csv.lines().count(), 7);
+    assert!(csv.lines().nth(3).unwrap().contains(",seeded,unknown,"));
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json[4]["result"], "unsat");
+    assert_eq!(json[4]["setup"], "default");
+    assert_eq!(json[4]["error"], serde_json::Value::Null);
+    assert!(
+        json[2]["error"]
+            .as_str()
+            .unwrap()
+            .contains("unknown constant y")
+    );
+
+    let bench = Bench::from_problems(bench.problems()[..1].to_vec())
+        .setup(Setup::new("typo").tactic("qflai"));
+    let report = bench.run();
+    let record = &report.records()[0];
+    assert_eq!(record.result, SatResult::Unknown);
+    assert_eq!(record.error.as_deref(), Some("qflai is an invalid tactic"));
+}
+
+#[cfg(feature = "z3_4_12_0")]
+#[test]
+fn test_simplifier() {
+    let names: Vec<String> = Simplifier::list_all()
+        .into_iter()
+        .filter_map(|r| r.ok())
+        .collect();
+    assert!(names.contains(&"propagate-values".to_string()));
+    assert!(!Simplifier::describe("propagate-values").unwrap().is_empty());
+
+    let simplifier = Simplifier::new("propagate-values").and_then(&Simplifier::new("solve-eqs"));
+    assert!(!simplifier.get_help().is_empty());
+    let solver = Solver::new().add_simplifier(&simplifier).unwrap();
+    let (x, y) = (Int::new_const("x"), Int::new_const("y"));
+    solver.assert(x.eq(&y));
+    solver.push();
+    solver.assert(y.eq(3));
+    solver.assert(x.gt(3));
+    assert_eq!(solver.check(), SatResult::Unsat);
+    solver.pop(1);
+    solver.assert(x.gt(3));
+    assert_eq!(solver.check(), SatResult::Sat);
+}
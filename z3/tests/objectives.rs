@@ -146,3 +146,228 @@ fn test_optimize_assert_soft_and_get_objectives() {
         );
     }
 }
+
+#[test]
+fn test_optimize_objectives_ast_vector() {
+    let opt = Optimize::new();
+    let x = ast::Int::new_const("x");
+    let y = ast::Int::new_const("y");
+    opt.assert(&x.le(10));
+    opt.assert(&y.ge(0));
+    opt.minimize(&y);
+    opt.maximize(&x);
+    opt.assert_soft(&x.lt(5), 1, Some("g".into()));
+
+    let objectives = opt.objectives();
+    assert_eq!(objectives.len(), 3);
+    assert_eq!(objectives.get(0).to_string(), "y");
+    assert_eq!(objectives.get(1).to_string(), "(- x)");
+    assert_eq!(objectives.get(2).get_sort(), Sort::real());
+    assert_eq!(opt.get_objectives().len(), 3);
+}
+
+#[test]
+fn test_optimize_pareto_iter() {
+    let mut opt = Optimize::new();
+    let x = ast::Int::new_const("x");
+    let y = ast::Int::new_const("y");
+    opt.assert(&x.ge(0));
+    opt.assert(&y.ge(0));
+    opt.assert(&(&x + &y).le(3));
+    opt.maximize(&x);
+    opt.maximize(&y);
+
+    let mut front: Vec<(i64, i64)> = opt
+        .pareto_iter()
+        .map(|m| {
+            let x = m.eval(&x, true).unwrap().as_i64().unwrap();
+            let y = m.eval(&y, true).unwrap().as_i64().unwrap();
+            (x, y)
+        })
+        .collect();
+    front.sort();
+    assert_eq!(front, vec![(0, 3), (1, 2), (2, 1), (3, 0)]);
+}
+
+#[test]
+fn test_optimize_objective_bounds() {
+    use num::BigRational;
+
+    let opt = Optimize::new();
+    let x = ast::Int::new_const("x");
+    let y = ast::Int::new_const("y");
+    opt.assert(&x.le(7));
+    opt.maximize(&x);
+    opt.maximize(&y);
+    assert_eq!(opt.check(&[]), SatResult::Sat);
+
+    let bounded = opt.objective(0).unwrap();
+    assert_eq!(bounded.index(), 0);
+    assert_eq!(
+        bounded.upper(),
+        Some(ObjectiveBound::Finite(BigRational::from_integer(7.into())))
+    );
+    assert!(bounded.upper().unwrap().is_finite());
+    let unbounded = opt.objective(1).unwrap();
+    assert_eq!(unbounded.upper(), Some(ObjectiveBound::PositiveInfinity));
+    assert_eq!(unbounded.upper().unwrap().to_string(), "oo");
+    assert!(opt.objective(2).is_none());
+}
+
+#[test]
+fn test_max_sat_groups() {
+    let xs: Vec<ast::Bool> = (0..4)
+        .map(|i| ast::Bool::new_const(format!("x{i}")))
+        .collect();
+    let mut max_sat = MaxSat::new();
+    // At most one of the variables may be true.
+    for i in 0..xs.len() {
+        for j in i + 1..xs.len() {
+            max_sat.add_hard(&ast::Bool::and(&[&xs[i], &xs[j]]).not());
+        }
+    }
+    let weights = [2, 5, 3, 1];
+    for (x, w) in xs.iter().zip(weights) {
+        max_sat.add_soft(x, w, "pick");
+    }
+    let tie_break = max_sat.add_soft(&xs[0].not(), (1, 2), "extra");
+
+    assert_eq!(max_sat.check(), SatResult::Sat);
+    let solution = max_sat.get_solution().unwrap();
+    assert_eq!(solution.violated(), &[0, 2, 3]);
+    assert!(!solution.is_violated(tie_break));
+    assert_eq!(solution.penalty().to_string(), "6");
+    let penalties = solution.group_penalties();
+    assert_eq!(penalties["pick"].to_string(), "6");
+    assert_eq!(penalties["extra"].to_string(), "0");
+    assert_eq!(
+        solution.model().eval(&xs[1], true).unwrap().as_bool(),
+        Some(true)
+    );
+    assert_eq!(
+        max_sat.soft_clauses()[tie_break].weight().to_string(),
+        "1/2"
+    );
+
+    max_sat.add_hard(&xs[1].not());
+    max_sat.add_hard(&xs[2].not());
+    assert_eq!(max_sat.check(), SatResult::Sat);
+    let solution = max_sat.get_solution().unwrap();
+    assert_eq!(solution.violated(), &[1, 2, 3, 4]);
+    assert_eq!(solution.group_penalties()["extra"].to_string(), "1/2");
+}
+
+#[test]
+#[should_panic(expected = "zero denominator")]
+fn test_max_sat_zero_denominator() {
+    let mut max_sat = MaxSat::new();
+    max_sat.add_soft(&ast::Bool::new_const("x"), (1, 0), "g");
+}
+
+#[test]
+fn test_optimize_from_file_round_trip() {
+    let opt = Optimize::new();
+    let x = ast::Int::new_const("x");
+    let y = ast::Int::new_const("y");
+    opt.assert(&(&x + &y).le(10));
+    opt.assert(&x.ge(0));
+    opt.assert(&y.ge(0));
+    opt.assert_soft(&x.ge(8), 2, None);
+    opt.maximize(&(&y * 2));
+
+    let path = std::env::temp_dir().join(format!("z3-opt-{}.smt2", std::process::id()));
+    std::fs::write(&path, opt.to_string()).unwrap();
+    let loaded = Optimize::new();
+    loaded.from_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.check(&[]), SatResult::Sat);
+    let model = loaded.get_model().unwrap();
+    // The soft constraint comes first in lexicographic order.
+    assert_eq!(model.eval(&x, true).unwrap().as_i64(), Some(8));
+    assert_eq!(model.eval(&y, true).unwrap().as_i64(), Some(2));
+    assert_eq!(loaded.get_objectives().len(), opt.get_objectives().len());
+
+    assert!(loaded.from_file("/nonexistent/problem.smt2").is_err());
+}
+
+#[test]
+fn test_optimize_unsat_core_with_objectives() {
+    let opt = Optimize::new();
+    let x = ast::Int::new_const("x");
+    let y = ast::Int::new_const("y");
+    opt.assert(&(&x + &y).le(10));
+    opt.assert(&y.ge(0));
+    opt.maximize(&x);
+    opt.assert_soft(&y.ge(3), 1, None);
+
+    let big_x = x.ge(8);
+    let big_y = y.ge(5);
+    let small = x.le(100);
+    assert_eq!(
+        opt.check(&[big_x.clone(), big_y.clone(), small.clone()]),
+        SatResult::Unsat
+    );
+    let core = opt.get_unsat_core();
+    assert!(core.contains(&big_x));
+    assert!(core.contains(&big_y));
+    assert!(!core.contains(&small));
+
+    // Dropping an assumption from the core makes the instance feasible again.
+    assert_eq!(opt.check(&[big_x, small]), SatResult::Sat);
+    assert!(opt.get_unsat_core().is_empty());
+    let model = opt.get_model().unwrap();
+    assert_eq!(model.eval(&x, true).unwrap().as_i64(), Some(10));
+}
+
+#[test]
+fn test_optimize_on_model_sees_improving_models() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let opt = Optimize::new();
+    let xs: Vec<ast::Bool> = (0..6)
+        .map(|i| ast::Bool::new_const(format!("x{i}")))
+        .collect();
+    for pair in xs.windows(2) {
+        opt.assert(&ast::Bool::or(&[&pair[0], &pair[1]]));
+        opt.assert_soft(&ast::Bool::and(&[&pair[0], &pair[1]]).not(), 1, None);
+    }
+    let penalties = Rc::new(RefCell::new(vec![]));
+    let sink = penalties.clone();
+    let soft: Vec<ast::Bool> = xs
+        .windows(2)
+        .map(|pair| ast::Bool::and(&[&pair[0], &pair[1]]).not())
+        .collect();
+    opt.set_on_model(move |model| {
+        let violated = soft
+            .iter()
+            .filter(|s| model.eval(*s, true).unwrap().as_bool() == Some(false))
+            .count();
+        sink.borrow_mut().push(violated);
+    });
+
+    assert_eq!(opt.check(&[]), SatResult::Sat);
+    let penalties = penalties.borrow();
+    assert!(!penalties.is_empty());
+    assert!(penalties.windows(2).all(|w| w[1] <= w[0]));
+    assert_eq!(penalties.last(), Some(&0));
+}
+
+#[test]
+fn test_optimize_scope_guard() {
+    let opt = Optimize::new();
+    let x = ast::Int::new_const("x");
+    opt.assert(&x.le(10));
+    opt.maximize(&x);
+    let best = opt.scope(|o| {
+        o.assert(&x.le(5));
+        assert_eq!(o.check(&[]), SatResult::Sat);
+        o.get_model().unwrap().eval(&x, true).unwrap().as_i64()
+    });
+    assert_eq!(best, Some(5));
+
+    assert_eq!(opt.check(&[]), SatResult::Sat);
+    let model = opt.get_model().unwrap();
+    assert_eq!(model.eval(&x, true).unwrap().as_i64(), Some(10));
+}
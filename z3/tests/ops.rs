@@ -381,8 +381,8 @@ fn test_real_approx() {
     assert_eq!(res.approx(26), "1.41421356237309504880168872");
     assert_eq!(res.approx(27), "1.414213562373095048801688724");
     assert_eq!(res.approx(28), "1.4142135623730950488016887242");
-    assert_eq!(res.approx_f64(), res.approx(32).parse().unwrap());
-    assert_ne!(res.approx_f64(), res.approx(16).parse().unwrap());
+    assert_eq!(res.approx_f64(), res.approx(32).parse::<f64>().unwrap());
+    assert_ne!(res.approx_f64(), res.approx(16).parse::<f64>().unwrap());
 }
 
 #[test]